@@ -1,14 +1,33 @@
+#[cfg(any(feature = "wayland", feature = "x11"))]
+mod autostart;
 mod dbus_menu;
+mod desktop_entry;
 mod dispatcher;
 #[cfg(any(feature = "wayland", feature = "x11"))]
 mod file_clipboard;
+#[cfg(any(feature = "wayland", feature = "x11"))]
+mod global_shortcuts;
 mod headless;
+#[cfg(any(feature = "wayland", feature = "x11"))]
+mod inhibit;
 mod keyboard;
+mod launcher_entry;
+#[cfg(any(feature = "wayland", feature = "x11"))]
+mod notification;
 mod platform;
+mod power;
+#[cfg(any(feature = "wayland", feature = "x11"))]
+mod print;
+mod recent_documents;
+#[cfg(feature = "x11")]
+mod session_manager;
+mod single_instance;
 #[cfg(any(feature = "wayland", feature = "x11"))]
 mod text_system;
+mod tray;
 #[cfg(feature = "wayland")]
 mod wayland;
+mod window_tabs;
 #[cfg(feature = "x11")]
 mod x11;
 
@@ -16,13 +35,26 @@ mod x11;
 mod xdg_desktop_portal;
 
 pub use dispatcher::*;
+#[cfg(any(feature = "wayland", feature = "x11"))]
+pub(crate) use global_shortcuts::*;
 pub(crate) use headless::*;
+#[cfg(any(feature = "wayland", feature = "x11"))]
+pub(crate) use inhibit::*;
 pub(crate) use keyboard::*;
+pub(crate) use launcher_entry::*;
+#[cfg(any(feature = "wayland", feature = "x11"))]
+pub(crate) use notification::*;
 pub(crate) use platform::*;
+pub(crate) use power::*;
+#[cfg(feature = "x11")]
+pub(crate) use session_manager::*;
+pub(crate) use single_instance::*;
 #[cfg(any(feature = "wayland", feature = "x11"))]
 pub(crate) use text_system::*;
+pub(crate) use tray::*;
 #[cfg(feature = "wayland")]
 pub(crate) use wayland::*;
+pub(crate) use window_tabs::*;
 #[cfg(feature = "x11")]
 pub(crate) use x11::*;
 
@@ -0,0 +1,169 @@
+//! Starting the application at login through the XDG background portal, falling back to an
+//! entry in the user's XDG autostart directory when no portal implementation is running.
+
+use std::{env, fs, io, path::PathBuf};
+
+use anyhow::{Context as _, Result, anyhow};
+use ashpd::desktop::background::Background;
+use util::ResultExt as _;
+
+use crate::linux::{
+    application_name,
+    desktop_entry::{
+        DESKTOP_ENTRY_GROUP, config_home, desktop_file_id, key_file_value, quote_exec_argument,
+        read_desktop_entry,
+    },
+};
+
+const AUTOSTART_REASON: &str = "Start automatically when you log in";
+
+pub(crate) async fn set_autostart(enabled: bool, args: Vec<String>) -> Result<()> {
+    let mut command = vec![
+        env::current_exe()
+            .map(|path| path.to_string_lossy().into_owned())
+            .unwrap_or_else(|_| application_name()),
+    ];
+    command.extend(args);
+
+    let response = Background::request()
+        .reason(AUTOSTART_REASON)
+        .auto_start(enabled)
+        .command(command.clone())
+        .send()
+        .await
+        .and_then(|request| request.response());
+    match response {
+        Ok(background) => {
+            write_portal_autostart(background.auto_start()).log_err();
+            if background.auto_start() == enabled {
+                Ok(())
+            } else {
+                Err(anyhow!(
+                    "The desktop didn't allow changing whether the app starts at login"
+                ))
+            }
+        }
+        Err(ashpd::Error::Response(_)) => Err(anyhow!(
+            "The desktop didn't allow changing whether the app starts at login"
+        )),
+        Err(_) => {
+            remove_portal_autostart().log_err();
+            write_autostart_entry(enabled, &command)
+        }
+    }
+}
+
+/// Whether the app starts at login, as last granted by the background portal or, when it was
+/// set without the portal, whether its autostart entry exists and isn't disabled.
+pub(crate) fn autostart_enabled() -> bool {
+    if let Ok(enabled) = fs::read_to_string(portal_autostart_path()) {
+        return enabled.trim() == "true";
+    }
+    autostart_entry_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .is_some_and(|entry| is_autostart_entry_enabled(&entry))
+}
+
+/// Where the portal's answer is kept. The portal writes its autostart entry outside of the
+/// Flatpak sandbox, where it can't be read back.
+fn portal_autostart_path() -> PathBuf {
+    paths::state_dir().join(format!("{}.autostart", release_channel::app_id()))
+}
+
+fn write_portal_autostart(enabled: bool) -> Result<()> {
+    let path = portal_autostart_path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create directory {}", dir.display()))?;
+    }
+    fs::write(&path, enabled.to_string())
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn remove_portal_autostart() -> Result<()> {
+    let path = portal_autostart_path();
+    match fs::remove_file(&path) {
+        Err(error) if error.kind() != io::ErrorKind::NotFound => {
+            Err(error).with_context(|| format!("Failed to remove {}", path.display()))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn autostart_entry_path() -> Option<PathBuf> {
+    Some(
+        config_home()?
+            .join("autostart")
+            .join(format!("{}.desktop", release_channel::app_id())),
+    )
+}
+
+fn write_autostart_entry(enabled: bool, command: &[String]) -> Result<()> {
+    let path = autostart_entry_path().context("Couldn't determine the config directory")?;
+    if !enabled {
+        return match fs::remove_file(&path) {
+            Err(error) if error.kind() != io::ErrorKind::NotFound => {
+                Err(error).with_context(|| format!("Failed to remove {}", path.display()))
+            }
+            _ => Ok(()),
+        };
+    }
+
+    let name = read_desktop_entry(&desktop_file_id())
+        .and_then(|entry| {
+            key_file_value(&entry, DESKTOP_ENTRY_GROUP, "Name").map(ToString::to_string)
+        })
+        .unwrap_or_else(application_name);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create directory {}", dir.display()))?;
+    }
+    fs::write(&path, autostart_entry(&name, command))
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn autostart_entry(name: &str, command: &[String]) -> String {
+    let exec = command
+        .iter()
+        .map(|argument| quote_exec_argument(argument).replace('%', "%%"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!(
+        "[{DESKTOP_ENTRY_GROUP}]\nType=Application\nName={}\nExec={exec}\nNoDisplay=true\nX-GNOME-Autostart-enabled=true\n",
+        name.replace('\n', " ")
+    )
+}
+
+fn is_autostart_entry_enabled(entry: &str) -> bool {
+    key_file_value(entry, DESKTOP_ENTRY_GROUP, "Hidden") != Some("true")
+        && key_file_value(entry, DESKTOP_ENTRY_GROUP, "X-GNOME-Autostart-enabled") != Some("false")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_autostart_entries() {
+        let entry = autostart_entry(
+            "Sync",
+            &[
+                "/opt/My Sync/sync".to_string(),
+                "--minimized".to_string(),
+                "100%".to_string(),
+            ],
+        );
+        assert_eq!(
+            key_file_value(&entry, DESKTOP_ENTRY_GROUP, "Exec"),
+            Some("\"/opt/My Sync/sync\" --minimized 100%%")
+        );
+        assert!(is_autostart_entry_enabled(&entry));
+
+        assert!(!is_autostart_entry_enabled(
+            "[Desktop Entry]\nExec=sync\nHidden=true\n"
+        ));
+        assert!(!is_autostart_entry_enabled(
+            "[Desktop Entry]\nExec=sync\nX-GNOME-Autostart-enabled=false\n"
+        ));
+    }
+}
@@ -3,13 +3,18 @@
 //!
 //! This module provides the DBusMenu protocol implementation for global menu support.
 //! It implements the `com.canonical.dbusmenu` interface and integrates with the
-//! `com.canonical.AppMenu.Registrar` service.
+//! `com.canonical.AppMenu.Registrar` service. Tray icons are exported through
+//! `org.kde.StatusNotifierItem`, reusing the same menu implementation for their context menu.
 
 mod bridge;
 pub(crate) mod common;
 mod menu_object;
 mod registrar;
+mod status_notifier;
 mod types;
 
 pub use bridge::{Bridge, BridgeEvent};
+pub use status_notifier::{
+    StatusNotifierEvent, StatusNotifierItem, StatusNotifierPixmap, StatusNotifierProperties,
+};
 pub use types::{MenuSnapshot, RemoteMenuNode};
@@ -0,0 +1,480 @@
+// SPDX-License-Identifier: LGPL-3.0-only
+//! StatusNotifierItem implementation.
+//!
+//! Exports a tray icon through the `org.kde.StatusNotifierItem` interface and its context menu
+//! through a second [`MenuObject`], then registers the item with the
+//! `org.kde.StatusNotifierWatcher`. Like the global menu bridge, the service runs on its own
+//! thread with blocking I/O.
+
+use log::{error, warn};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use zbus::Result as ZbusResult;
+use zbus::block_on;
+use zbus::blocking::connection::Builder as ConnectionBuilder;
+use zbus::blocking::{Connection, Proxy};
+use zbus::interface;
+use zbus::names::WellKnownName;
+use zbus::object_server::SignalContext as SignalEmitter;
+use zbus::zvariant::ObjectPath;
+
+use super::bridge::Command;
+use super::menu_object::MenuObject;
+use super::types::{MenuSnapshot, MenuState, flatten_properties_updates};
+
+const ITEM_OBJECT_PATH: &str = "/StatusNotifierItem";
+const TRAY_MENU_OBJECT_PATH: &str = "/StatusNotifierItem/Menu";
+
+/// An icon as sent over D-Bus: width, height and ARGB32 pixels in network byte order.
+pub type StatusNotifierPixmap = (i32, i32, Vec<u8>);
+
+/// Events emitted by the tray icon and its menu.
+#[derive(Clone, Debug, PartialEq)]
+pub enum StatusNotifierEvent {
+    Activate { x: i32, y: i32 },
+    SecondaryActivate { x: i32, y: i32 },
+    Scroll { delta: i32, orientation: String },
+    MenuActivated(i32),
+}
+
+/// The properties of a StatusNotifierItem.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StatusNotifierProperties {
+    pub id: String,
+    pub title: String,
+    /// One of `Passive`, `Active` or `NeedsAttention`.
+    pub status: &'static str,
+    pub icon_name: String,
+    pub icon_pixmap: Vec<StatusNotifierPixmap>,
+    pub attention_icon_name: String,
+    pub attention_icon_pixmap: Vec<StatusNotifierPixmap>,
+    pub tooltip: String,
+}
+
+/// Commands sent to the tray thread.
+enum TrayCommand {
+    Update(StatusNotifierProperties),
+    UpdateMenu(MenuSnapshot),
+    Shutdown,
+}
+
+/// A tray icon exported on the session bus.
+pub struct StatusNotifierItem {
+    tx: Sender<TrayCommand>,
+}
+
+impl StatusNotifierItem {
+    pub fn start(
+        properties: StatusNotifierProperties,
+        menu: MenuSnapshot,
+        on_event: Arc<dyn Fn(StatusNotifierEvent) + Send + Sync>,
+    ) -> Option<Self> {
+        Self::start_with(ConnectionBuilder::session, properties, menu, on_event)
+    }
+
+    fn start_with(
+        connection_builder: impl FnOnce() -> ZbusResult<ConnectionBuilder<'static>> + Send + 'static,
+        properties: StatusNotifierProperties,
+        menu: MenuSnapshot,
+        on_event: Arc<dyn Fn(StatusNotifierEvent) + Send + Sync>,
+    ) -> Option<Self> {
+        let (tx, cmd_rx) = mpsc::channel();
+
+        thread::Builder::new()
+            .name("nptk-tray-icon".into())
+            .spawn(move || {
+                let result = connection_builder()
+                    .and_then(|builder| run(builder, cmd_rx, properties, menu, on_event));
+                if let Err(err) = result {
+                    error!("Tray icon thread exited: {err}");
+                }
+            })
+            .ok()?;
+
+        Some(Self { tx })
+    }
+
+    pub fn update(&self, properties: StatusNotifierProperties) {
+        self.tx.send(TrayCommand::Update(properties)).ok();
+    }
+
+    pub fn update_menu(&self, snapshot: MenuSnapshot) {
+        self.tx.send(TrayCommand::UpdateMenu(snapshot)).ok();
+    }
+}
+
+impl Drop for StatusNotifierItem {
+    fn drop(&mut self) {
+        self.tx.send(TrayCommand::Shutdown).ok();
+    }
+}
+
+/// `org.kde.StatusNotifierItem` interface implementation.
+struct StatusNotifierItemObject {
+    properties: Arc<Mutex<StatusNotifierProperties>>,
+    on_event: Arc<dyn Fn(StatusNotifierEvent) + Send + Sync>,
+}
+
+#[interface(name = "org.kde.StatusNotifierItem")]
+impl StatusNotifierItemObject {
+    #[zbus(name = "Activate")]
+    async fn activate(&self, x: i32, y: i32) {
+        (self.on_event)(StatusNotifierEvent::Activate { x, y });
+    }
+
+    #[zbus(name = "SecondaryActivate")]
+    async fn secondary_activate(&self, x: i32, y: i32) {
+        (self.on_event)(StatusNotifierEvent::SecondaryActivate { x, y });
+    }
+
+    #[zbus(name = "Scroll")]
+    async fn scroll(&self, delta: i32, orientation: &str) {
+        (self.on_event)(StatusNotifierEvent::Scroll {
+            delta,
+            orientation: orientation.to_lowercase(),
+        });
+    }
+
+    /// Hosts show the exported menu themselves; this is only called by hosts that can't.
+    #[zbus(name = "ContextMenu")]
+    async fn context_menu(&self, x: i32, y: i32) {
+        log::debug!("StatusNotifierItem.ContextMenu x={x} y={y} ignored");
+    }
+
+    #[zbus(property, name = "Category")]
+    fn category(&self) -> String {
+        "ApplicationStatus".into()
+    }
+
+    #[zbus(property, name = "Id")]
+    fn id(&self) -> String {
+        self.properties.lock().unwrap().id.clone()
+    }
+
+    #[zbus(property, name = "Title")]
+    fn title(&self) -> String {
+        self.properties.lock().unwrap().title.clone()
+    }
+
+    #[zbus(property, name = "Status")]
+    fn status(&self) -> String {
+        self.properties.lock().unwrap().status.to_string()
+    }
+
+    #[zbus(property, name = "WindowId")]
+    fn window_id(&self) -> i32 {
+        0
+    }
+
+    #[zbus(property, name = "IconName")]
+    fn icon_name(&self) -> String {
+        self.properties.lock().unwrap().icon_name.clone()
+    }
+
+    #[zbus(property, name = "IconPixmap")]
+    fn icon_pixmap(&self) -> Vec<StatusNotifierPixmap> {
+        self.properties.lock().unwrap().icon_pixmap.clone()
+    }
+
+    #[zbus(property, name = "AttentionIconName")]
+    fn attention_icon_name(&self) -> String {
+        self.properties.lock().unwrap().attention_icon_name.clone()
+    }
+
+    #[zbus(property, name = "AttentionIconPixmap")]
+    fn attention_icon_pixmap(&self) -> Vec<StatusNotifierPixmap> {
+        self.properties
+            .lock()
+            .unwrap()
+            .attention_icon_pixmap
+            .clone()
+    }
+
+    #[zbus(property, name = "ToolTip")]
+    fn tool_tip(&self) -> (String, Vec<StatusNotifierPixmap>, String, String) {
+        let properties = self.properties.lock().unwrap();
+        (
+            String::new(),
+            Vec::new(),
+            properties.tooltip.clone(),
+            String::new(),
+        )
+    }
+
+    #[zbus(property, name = "ItemIsMenu")]
+    fn item_is_menu(&self) -> bool {
+        false
+    }
+
+    #[zbus(property, name = "Menu")]
+    fn menu(&self) -> ObjectPath<'_> {
+        ObjectPath::from_static_str_unchecked(TRAY_MENU_OBJECT_PATH)
+    }
+
+    #[zbus(signal, name = "NewTitle")]
+    async fn new_title(emitter: &SignalEmitter<'_>) -> zbus::Result<()>;
+
+    #[zbus(signal, name = "NewIcon")]
+    async fn new_icon(emitter: &SignalEmitter<'_>) -> zbus::Result<()>;
+
+    #[zbus(signal, name = "NewAttentionIcon")]
+    async fn new_attention_icon(emitter: &SignalEmitter<'_>) -> zbus::Result<()>;
+
+    #[zbus(signal, name = "NewToolTip")]
+    async fn new_tool_tip(emitter: &SignalEmitter<'_>) -> zbus::Result<()>;
+
+    #[zbus(signal, name = "NewStatus")]
+    async fn new_status(emitter: &SignalEmitter<'_>, status: &str) -> zbus::Result<()>;
+}
+
+fn run(
+    connection_builder: ConnectionBuilder<'static>,
+    cmd_rx: Receiver<TrayCommand>,
+    properties: StatusNotifierProperties,
+    menu: MenuSnapshot,
+    on_event: Arc<dyn Fn(StatusNotifierEvent) + Send + Sync>,
+) -> ZbusResult<()> {
+    // The naming scheme from the StatusNotifierItem specification; the trailing number tells
+    // items of the same process apart, and we only ever export one.
+    let service_name = format!("org.kde.StatusNotifierItem-{}-1", std::process::id());
+
+    let properties = Arc::new(Mutex::new(properties));
+    let menu_state = Arc::new(Mutex::new(MenuState::default()));
+    menu_state.lock().unwrap().replace(menu);
+    let (menu_cmd_tx, menu_cmd_rx) = mpsc::channel();
+
+    let connection = connection_builder
+        .name(WellKnownName::try_from(service_name.clone())?)?
+        .serve_at(
+            ITEM_OBJECT_PATH,
+            StatusNotifierItemObject {
+                properties: properties.clone(),
+                on_event: on_event.clone(),
+            },
+        )?
+        .serve_at(
+            TRAY_MENU_OBJECT_PATH,
+            MenuObject {
+                state: menu_state.clone(),
+                cmd_tx: menu_cmd_tx,
+            },
+        )?
+        .build()?;
+    log::info!("Tray icon DBus service '{service_name}', object '{ITEM_OBJECT_PATH}'");
+
+    if let Err(err) = register_with_watcher(&connection, &service_name) {
+        warn!("Failed to register tray icon with the StatusNotifierWatcher: {err}");
+    }
+
+    let item_ref = connection
+        .object_server()
+        .interface::<_, StatusNotifierItemObject>(ITEM_OBJECT_PATH)?;
+    let menu_ref = connection
+        .object_server()
+        .interface::<_, MenuObject>(TRAY_MENU_OBJECT_PATH)?;
+
+    let emit_layout_updated = |parent: i32| {
+        let (revision, updates) = {
+            let state = menu_state.lock().unwrap();
+            (state.revision, flatten_properties_updates(&state.entries))
+        };
+        // Don't hold the state mutex while emitting, the host may call GetLayout right away.
+        if let Err(err) = block_on(MenuObject::layout_updated(
+            menu_ref.signal_context(),
+            revision,
+            parent,
+        )) {
+            warn!("Failed to emit tray menu layout update for parent {parent}: {err}");
+        }
+        let removed: Vec<(i32, Vec<String>)> = Vec::new();
+        if let Err(err) = block_on(MenuObject::items_properties_updated(
+            menu_ref.signal_context(),
+            updates,
+            removed,
+        )) {
+            warn!("Failed to emit tray menu items properties updated: {err}");
+        }
+    };
+
+    loop {
+        while let Ok(command) = menu_cmd_rx.try_recv() {
+            match command {
+                Command::Activated(menu_item_id) => {
+                    (on_event)(StatusNotifierEvent::MenuActivated(menu_item_id));
+                }
+                Command::RequestLayout(parent) => emit_layout_updated(parent),
+                Command::UpdateMenu(_) | Command::SetWindow(_) | Command::Shutdown => {}
+            }
+        }
+
+        let command = match cmd_rx.recv_timeout(Duration::from_millis(16)) {
+            Ok(command) => command,
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            Err(mpsc::RecvTimeoutError::Timeout) => continue,
+        };
+
+        match command {
+            TrayCommand::Update(next) => {
+                let previous = std::mem::replace(&mut *properties.lock().unwrap(), next.clone());
+                let context = item_ref.signal_context();
+                let mut results = Vec::new();
+                if previous.title != next.title {
+                    results.push(block_on(StatusNotifierItemObject::new_title(context)));
+                }
+                if previous.icon_name != next.icon_name || previous.icon_pixmap != next.icon_pixmap
+                {
+                    results.push(block_on(StatusNotifierItemObject::new_icon(context)));
+                }
+                if previous.attention_icon_name != next.attention_icon_name
+                    || previous.attention_icon_pixmap != next.attention_icon_pixmap
+                {
+                    results.push(block_on(StatusNotifierItemObject::new_attention_icon(
+                        context,
+                    )));
+                }
+                if previous.tooltip != next.tooltip {
+                    results.push(block_on(StatusNotifierItemObject::new_tool_tip(context)));
+                }
+                if previous.status != next.status {
+                    results.push(block_on(StatusNotifierItemObject::new_status(
+                        context,
+                        next.status,
+                    )));
+                }
+                for result in results {
+                    if let Err(err) = result {
+                        warn!("Failed to emit tray icon change: {err}");
+                    }
+                }
+            }
+            TrayCommand::UpdateMenu(snapshot) => {
+                menu_state.lock().unwrap().replace(snapshot);
+                emit_layout_updated(0);
+            }
+            TrayCommand::Shutdown => break,
+        }
+    }
+
+    Ok(())
+}
+
+fn register_with_watcher(connection: &Connection, service_name: &str) -> ZbusResult<()> {
+    let watcher = Proxy::new(
+        connection,
+        "org.kde.StatusNotifierWatcher",
+        "/StatusNotifierWatcher",
+        "org.kde.StatusNotifierWatcher",
+    )?;
+    watcher.call::<_, _, ()>("RegisterStatusNotifierItem", &(service_name,))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context as _;
+    use std::io::{BufRead as _, BufReader};
+    use std::process::{Child, Command as ProcessCommand, Stdio};
+    use std::time::Instant;
+
+    /// A private session bus, killed when dropped.
+    struct TestBus {
+        daemon: Child,
+        address: String,
+    }
+
+    impl TestBus {
+        fn start() -> anyhow::Result<Self> {
+            let mut daemon = ProcessCommand::new("dbus-daemon")
+                .args(["--session", "--nofork", "--print-address"])
+                .stdout(Stdio::piped())
+                .spawn()
+                .context("Failed to start dbus-daemon, is it installed?")?;
+            let stdout = daemon.stdout.take();
+            // Kills the daemon if its address can't be read.
+            let mut bus = Self {
+                daemon,
+                address: String::new(),
+            };
+            BufReader::new(stdout.context("dbus-daemon has no stdout")?)
+                .read_line(&mut bus.address)
+                .context("Failed to read the address of the session bus")?;
+            bus.address.truncate(bus.address.trim_end().len());
+            Ok(bus)
+        }
+    }
+
+    impl Drop for TestBus {
+        fn drop(&mut self) {
+            self.daemon.kill().ok();
+            self.daemon.wait().ok();
+        }
+    }
+
+    #[test]
+    fn test_exports_item_on_session_bus() -> anyhow::Result<()> {
+        let bus = TestBus::start()?;
+
+        let (event_tx, event_rx) = mpsc::channel();
+        let event_tx = Mutex::new(event_tx);
+        let address = bus.address.clone();
+        let _item = StatusNotifierItem::start_with(
+            move || ConnectionBuilder::address(address.as_str()),
+            StatusNotifierProperties {
+                id: "test".into(),
+                title: "Test".into(),
+                status: "NeedsAttention",
+                icon_name: "mail-unread".into(),
+                tooltip: "3 unread messages".into(),
+                ..Default::default()
+            },
+            MenuSnapshot {
+                entries: Vec::new(),
+            },
+            Arc::new(move |event| {
+                if let Ok(event_tx) = event_tx.lock() {
+                    event_tx.send(event).ok();
+                }
+            }),
+        )
+        .context("failed to start the tray icon")?;
+
+        let connection = ConnectionBuilder::address(bus.address.as_str())?.build()?;
+        let item = Proxy::new(
+            &connection,
+            format!("org.kde.StatusNotifierItem-{}-1", std::process::id()),
+            ITEM_OBJECT_PATH,
+            "org.kde.StatusNotifierItem",
+        )?;
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let status = loop {
+            match item.get_property::<String>("Status") {
+                Ok(status) => break status,
+                Err(_) if Instant::now() < deadline => thread::sleep(Duration::from_millis(20)),
+                Err(err) => return Err(err).context("tray icon was never exported"),
+            }
+        };
+        assert_eq!(status, "NeedsAttention");
+        assert_eq!(item.get_property::<String>("IconName")?, "mail-unread");
+        let (_, _, tooltip, _) =
+            item.get_property::<(String, Vec<StatusNotifierPixmap>, String, String)>("ToolTip")?;
+        assert_eq!(tooltip, "3 unread messages");
+
+        item.call::<_, _, ()>("Activate", &(10i32, 20i32))?;
+        item.call::<_, _, ()>("Scroll", &(-120i32, "Vertical"))?;
+        assert_eq!(
+            event_rx.recv_timeout(Duration::from_secs(5))?,
+            StatusNotifierEvent::Activate { x: 10, y: 20 }
+        );
+        assert_eq!(
+            event_rx.recv_timeout(Duration::from_secs(5))?,
+            StatusNotifierEvent::Scroll {
+                delta: -120,
+                orientation: "vertical".into()
+            }
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,409 @@
+//! Desktop entries and `mimeapps.list`, as described by the XDG Desktop Entry and MIME
+//! Applications Associations specifications.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context as _, Result, anyhow};
+use util::command::new_std_command;
+
+use crate::linux::{ACTION_ARGUMENT_PREFIX, application_name};
+
+pub(crate) const DESKTOP_ENTRY_GROUP: &str = "Desktop Entry";
+const DEFAULT_APPLICATIONS_GROUP: &str = "Default Applications";
+
+/// GNOME passes a client ID to the applications it autostarts through this variable, which they
+/// should register with the session manager. It's removed from the environment of the processes
+/// we spawn so that they don't register with it too.
+pub(crate) const AUTOSTART_ID_VARIABLE: &str = "DESKTOP_AUTOSTART_ID";
+
+pub(crate) fn data_home() -> Option<PathBuf> {
+    env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+}
+
+pub(crate) fn config_home() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+}
+
+/// The system data directories, in order of preference.
+pub(crate) fn data_dirs() -> Vec<PathBuf> {
+    let dirs = env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+    env::split_paths(&dirs)
+        .filter(|path| path.is_absolute())
+        .collect()
+}
+
+/// The desktop file ID of the running application: the entry it was launched from if GLib told
+/// us, otherwise one named after the executable.
+pub(crate) fn desktop_file_id() -> String {
+    env::var_os("GIO_LAUNCHED_DESKTOP_FILE")
+        .as_deref()
+        .map(Path::new)
+        .and_then(|path| path.file_name())
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| format!("{}.desktop", application_name()))
+}
+
+/// Returns the contents of the desktop entry with the given ID that the desktop would use,
+/// preferring the user's own entries over the system's.
+pub(crate) fn read_desktop_entry(desktop_file_id: &str) -> Option<String> {
+    data_home()
+        .into_iter()
+        .chain(data_dirs())
+        .find_map(|dir| fs::read_to_string(dir.join("applications").join(desktop_file_id)).ok())
+}
+
+/// Returns the contents of the desktop entry with the given ID in the user's applications
+/// directory, ignoring the system's.
+fn read_user_desktop_entry(desktop_file_id: &str) -> Option<String> {
+    fs::read_to_string(data_home()?.join("applications").join(desktop_file_id)).ok()
+}
+
+/// Writes a desktop entry to the user's applications directory.
+pub(crate) fn write_user_desktop_entry(desktop_file_id: &str, contents: &str) -> Result<()> {
+    let dir = data_home()
+        .context("Couldn't determine the data directory")?
+        .join("applications");
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create directory {}", dir.display()))?;
+    let path = dir.join(desktop_file_id);
+    fs::write(&path, contents).with_context(|| format!("Failed to write {}", path.display()))?;
+    // Refreshes the MIME type cache; desktops that don't ship the tool rescan on their own.
+    new_std_command("update-desktop-database")
+        .arg(&dir)
+        .env_remove(AUTOSTART_ID_VARIABLE)
+        .status()
+        .ok();
+    Ok(())
+}
+
+/// A minimal entry for launching the running executable, hidden from application menus.
+pub(crate) fn default_desktop_entry() -> String {
+    let exec = env::current_exe()
+        .map(|path| quote_exec_argument(&path.to_string_lossy()))
+        .unwrap_or_else(|_| application_name());
+    format!(
+        "[{DESKTOP_ENTRY_GROUP}]\nType=Application\nName={}\nExec={exec} %u\nNoDisplay=true\n",
+        application_name()
+    )
+}
+
+/// Makes the running application the default handler for `scheme://` URLs.
+pub(crate) fn register_url_scheme(scheme: &str) -> Result<()> {
+    if !is_valid_url_scheme(scheme) {
+        return Err(anyhow!("Invalid URL scheme {scheme:?}"));
+    }
+    let mime_type = format!("x-scheme-handler/{scheme}");
+    let lists_mime_type = |entry: &str| {
+        key_file_value(entry, DESKTOP_ENTRY_GROUP, "MimeType")
+            .unwrap_or_default()
+            .split(';')
+            .any(|existing| existing == mime_type)
+    };
+
+    let mut desktop_file_id = desktop_file_id();
+    if read_user_desktop_entry(&desktop_file_id).is_none()
+        && let Some(system_entry) = read_desktop_entry(&desktop_file_id)
+        && !lists_mime_type(&system_entry)
+    {
+        // A user copy of the system entry would hide any changes package updates make to it, so
+        // handle the scheme with a separate entry instead.
+        desktop_file_id = format!(
+            "{}-url-handler.desktop",
+            desktop_file_id.trim_end_matches(".desktop")
+        );
+    }
+    let entry = read_desktop_entry(&desktop_file_id).unwrap_or_else(default_desktop_entry);
+    if !lists_mime_type(&entry) {
+        let mime_types =
+            key_file_value(&entry, DESKTOP_ENTRY_GROUP, "MimeType").unwrap_or_default();
+        let mime_types = format!("{mime_types}{mime_type};");
+        let entry = set_key_file_value(&entry, DESKTOP_ENTRY_GROUP, "MimeType", &mime_types);
+        write_user_desktop_entry(&desktop_file_id, &entry)?;
+    }
+
+    let mimeapps_path = config_home()
+        .context("Couldn't determine the config directory")?
+        .join("mimeapps.list");
+    let mimeapps = fs::read_to_string(&mimeapps_path).unwrap_or_default();
+    let mimeapps = set_key_file_value(
+        &mimeapps,
+        DEFAULT_APPLICATIONS_GROUP,
+        &mime_type,
+        &format!("{desktop_file_id};"),
+    );
+    if let Some(dir) = mimeapps_path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create directory {}", dir.display()))?;
+    }
+    fs::write(&mimeapps_path, mimeapps)
+        .with_context(|| format!("Failed to write {}", mimeapps_path.display()))
+}
+
+/// Replaces the desktop actions whose IDs start with `prefix` by `actions`, given as ID and name
+/// pairs. Launching one runs the executable with `--action=<id>`.
+///
+/// Entries installed system-wide are left alone, since a user copy would hide any changes package
+/// updates make to them.
+pub(crate) fn set_desktop_actions(prefix: &str, actions: &[(String, String)]) -> Result<()> {
+    let desktop_file_id = desktop_file_id();
+    let existing = read_user_desktop_entry(&desktop_file_id);
+    if existing.is_none() {
+        if actions.is_empty() {
+            return Ok(());
+        }
+        if read_desktop_entry(&desktop_file_id).is_some() {
+            log::info!("Not adding desktop actions to the system-wide entry {desktop_file_id}");
+            return Ok(());
+        }
+    }
+    let existing = existing.unwrap_or_else(default_desktop_entry);
+    let exec = env::current_exe()
+        .map(|path| quote_exec_argument(&path.to_string_lossy()))
+        .unwrap_or_else(|_| application_name());
+
+    let mut entry = remove_key_file_groups(&existing, |group| {
+        group
+            .strip_prefix("Desktop Action ")
+            .is_some_and(|id| id.starts_with(prefix))
+    });
+    let mut action_ids = key_file_value(&entry, DESKTOP_ENTRY_GROUP, "Actions")
+        .unwrap_or_default()
+        .split(';')
+        .filter(|id| !id.is_empty() && !id.starts_with(prefix))
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    action_ids.extend(actions.iter().map(|(id, _)| id.clone()));
+    let action_list = action_ids
+        .iter()
+        .map(|id| format!("{id};"))
+        .collect::<String>();
+    entry = set_key_file_value(&entry, DESKTOP_ENTRY_GROUP, "Actions", &action_list);
+    for (id, name) in actions {
+        entry.push_str(&format!(
+            "\n[Desktop Action {id}]\nName={}\nExec={exec} {ACTION_ARGUMENT_PREFIX}{id}\n",
+            name.replace('\n', " ")
+        ));
+    }
+
+    if entry != existing {
+        write_user_desktop_entry(&desktop_file_id, &entry)?;
+    }
+    Ok(())
+}
+
+/// Schemes as defined by RFC 3986: a letter followed by letters, digits, `+`, `-` or `.`.
+fn is_valid_url_scheme(scheme: &str) -> bool {
+    let mut chars = scheme.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+/// Quotes an argument of an `Exec` key if it contains reserved characters.
+pub(crate) fn quote_exec_argument(argument: &str) -> String {
+    const RESERVED: &[char] = &[
+        ' ', '\t', '\n', '"', '\'', '\\', '>', '<', '~', '|', '&', ';', '$', '*', '?', '#', '(',
+        ')', '`',
+    ];
+    if !argument.contains(RESERVED) {
+        return argument.to_string();
+    }
+    let mut quoted = String::from("\"");
+    for c in argument.chars() {
+        if matches!(c, '"' | '`' | '$' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Returns the value of `key` in `[group]` of a key file, without locale suffixes.
+pub(crate) fn key_file_value<'a>(contents: &'a str, group: &str, key: &str) -> Option<&'a str> {
+    let mut in_group = false;
+    for line in contents.lines() {
+        let line = line.trim();
+        if let Some(name) = group_header(line) {
+            in_group = name == group;
+        } else if in_group && let Some(value) = key_value(line, key) {
+            return Some(value);
+        }
+    }
+    None
+}
+
+/// Sets `key` in `[group]` of a key file, adding the group if it's missing and leaving
+/// everything else untouched.
+pub(crate) fn set_key_file_value(contents: &str, group: &str, key: &str, value: &str) -> String {
+    let mut lines = contents.lines().map(str::to_string).collect::<Vec<_>>();
+    let entry = format!("{key}={value}");
+
+    let Some(group_start) = lines
+        .iter()
+        .position(|line| group_header(line.trim()) == Some(group))
+    else {
+        if lines.last().is_some_and(|line| !line.trim().is_empty()) {
+            lines.push(String::new());
+        }
+        lines.extend([format!("[{group}]"), entry]);
+        return lines.join("\n") + "\n";
+    };
+
+    let group_end = lines[group_start + 1..]
+        .iter()
+        .position(|line| group_header(line.trim()).is_some())
+        .map_or(lines.len(), |offset| group_start + 1 + offset);
+    if let Some(existing) = lines[group_start + 1..group_end]
+        .iter_mut()
+        .find(|line| key_value(line.trim(), key).is_some())
+    {
+        *existing = entry;
+    } else {
+        // Insert after the group's last entry rather than after the blank lines separating it
+        // from the next group.
+        let insert_at = (group_start + 1..group_end)
+            .rev()
+            .find(|&index| !lines[index].trim().is_empty())
+            .map_or(group_start + 1, |index| index + 1);
+        lines.insert(insert_at, entry);
+    }
+    lines.join("\n") + "\n"
+}
+
+/// Removes the groups for which `predicate` returns true, along with the blank lines before them.
+fn remove_key_file_groups(contents: &str, predicate: impl Fn(&str) -> bool) -> String {
+    let mut lines: Vec<&str> = Vec::new();
+    let mut removing = false;
+    for line in contents.lines() {
+        if let Some(group) = group_header(line.trim()) {
+            removing = predicate(group);
+            if removing {
+                while lines.last().is_some_and(|line| line.trim().is_empty()) {
+                    lines.pop();
+                }
+            }
+        }
+        if !removing {
+            lines.push(line);
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+fn group_header(line: &str) -> Option<&str> {
+    line.strip_prefix('[')?.strip_suffix(']')
+}
+
+fn key_value<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    let (line_key, value) = line.split_once('=')?;
+    (line_key.trim_end() == key).then(|| value.trim_start())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_file_values() {
+        let contents = "\
+[Desktop Entry]
+Name=Editor
+MimeType=text/plain;
+
+[Desktop Action new-window]
+Name=New Window
+";
+        assert_eq!(
+            key_file_value(contents, "Desktop Entry", "Name"),
+            Some("Editor")
+        );
+        assert_eq!(
+            key_file_value(contents, "Desktop Action new-window", "Name"),
+            Some("New Window")
+        );
+        assert_eq!(key_file_value(contents, "Desktop Entry", "Exec"), None);
+
+        let updated = set_key_file_value(
+            contents,
+            "Desktop Entry",
+            "MimeType",
+            "text/plain;x-scheme-handler/editor;",
+        );
+        let updated = set_key_file_value(&updated, "Desktop Entry", "Exec", "editor %u");
+        assert_eq!(
+            updated,
+            "\
+[Desktop Entry]
+Name=Editor
+MimeType=text/plain;x-scheme-handler/editor;
+Exec=editor %u
+
+[Desktop Action new-window]
+Name=New Window
+"
+        );
+
+        assert_eq!(
+            set_key_file_value(
+                "",
+                DEFAULT_APPLICATIONS_GROUP,
+                "x-scheme-handler/editor",
+                "a;"
+            ),
+            "[Default Applications]\nx-scheme-handler/editor=a;\n"
+        );
+    }
+
+    #[test]
+    fn test_remove_key_file_groups() {
+        let contents = "\
+[Desktop Entry]
+Actions=new-window;dock-action-0;
+
+[Desktop Action new-window]
+Name=New Window
+
+[Desktop Action dock-action-0]
+Name=Compose
+";
+        assert_eq!(
+            remove_key_file_groups(contents, |group| group.ends_with("dock-action-0")),
+            "\
+[Desktop Entry]
+Actions=new-window;dock-action-0;
+
+[Desktop Action new-window]
+Name=New Window
+"
+        );
+    }
+
+    #[test]
+    fn test_url_schemes_and_exec_quoting() {
+        assert!(is_valid_url_scheme("zed"));
+        assert!(is_valid_url_scheme("web+app"));
+        assert!(!is_valid_url_scheme("1password"));
+        assert!(!is_valid_url_scheme("with space"));
+        assert!(!is_valid_url_scheme(""));
+
+        assert_eq!(quote_exec_argument("/usr/bin/editor"), "/usr/bin/editor");
+        assert_eq!(
+            quote_exec_argument("/opt/My Editor/$bin"),
+            "\"/opt/My Editor/\\$bin\""
+        );
+    }
+}
@@ -0,0 +1,158 @@
+//! Global shortcuts through the XDG global shortcuts portal. X11 falls back to grabbing the keys
+//! on the root window when no portal implementation is running.
+
+use std::{cell::RefCell, rc::Rc};
+
+use anyhow::Result;
+use ashpd::desktop::{
+    Session,
+    global_shortcuts::{GlobalShortcuts as GlobalShortcutsProxy, NewShortcut},
+};
+use futures::{StreamExt as _, lock::Mutex, stream};
+use gpui::{
+    ForegroundExecutor, Keystroke, Task,
+    global_shortcut::{GlobalShortcut, GlobalShortcutId, GlobalShortcutPhase},
+};
+use util::ResultExt as _;
+use xkbcommon::xkb;
+
+use crate::linux::keysym_for_key;
+
+type GlobalShortcutCallback = Box<dyn FnMut(GlobalShortcutId, GlobalShortcutPhase)>;
+
+struct GlobalShortcutsState {
+    executor: ForegroundExecutor,
+    /// The portal session the current shortcuts are bound in, along with the task reporting
+    /// their activations. Sessions can't be rebound everywhere, so each change starts a new one.
+    session: RefCell<Option<(Session<'static, GlobalShortcutsProxy<'static>>, Task<()>)>>,
+    /// Held while binding, so that a bind started before the previous one finished doesn't leave
+    /// both sessions open.
+    binding: Mutex<()>,
+    callback: RefCell<Option<GlobalShortcutCallback>>,
+}
+
+#[derive(Clone)]
+pub(crate) struct GlobalShortcuts(Rc<GlobalShortcutsState>);
+
+impl GlobalShortcuts {
+    pub fn new(executor: ForegroundExecutor) -> Self {
+        Self(Rc::new(GlobalShortcutsState {
+            executor,
+            session: RefCell::default(),
+            binding: Mutex::new(()),
+            callback: RefCell::default(),
+        }))
+    }
+
+    pub fn on_event(&self, callback: GlobalShortcutCallback) {
+        self.0.callback.replace(Some(callback));
+    }
+
+    pub fn emit(&self, id: GlobalShortcutId, phase: GlobalShortcutPhase) {
+        let callback = self.0.callback.borrow_mut().take();
+        if let Some(mut callback) = callback {
+            callback(id, phase);
+            self.0.callback.borrow_mut().get_or_insert(callback);
+        }
+    }
+
+    /// Replaces the shortcuts bound through the portal, failing if there's no portal to bind
+    /// them with.
+    pub async fn bind(&self, shortcuts: Vec<GlobalShortcut>) -> Result<()> {
+        let _binding = self.0.binding.lock().await;
+        let previous = self.0.session.take();
+        if let Some((session, _activations)) = previous {
+            session.close().await.log_err();
+        }
+        if shortcuts.is_empty() {
+            return Ok(());
+        }
+
+        let proxy = GlobalShortcutsProxy::new().await?;
+        let session = proxy.create_session().await?;
+        let triggers = shortcuts
+            .iter()
+            .map(|shortcut| portal_trigger(&shortcut.keystroke))
+            .collect::<Vec<_>>();
+        let new_shortcuts = shortcuts
+            .iter()
+            .zip(&triggers)
+            .map(|(shortcut, trigger)| {
+                NewShortcut::new(shortcut.id.0.as_ref(), shortcut.description.as_ref())
+                    .preferred_trigger(trigger.as_deref())
+            })
+            .collect::<Vec<_>>();
+        proxy
+            .bind_shortcuts(&session, &new_shortcuts, None)
+            .await?
+            .response()?;
+
+        let this = self.clone();
+        let activations = self.0.executor.spawn(async move {
+            this.report_activations(proxy).await.log_err();
+        });
+        self.0.session.replace(Some((session, activations)));
+        Ok(())
+    }
+
+    async fn report_activations(&self, proxy: GlobalShortcutsProxy<'static>) -> Result<()> {
+        let activated = proxy.receive_activated().await?.map(|event| {
+            (
+                event.shortcut_id().to_string(),
+                GlobalShortcutPhase::Activated,
+            )
+        });
+        let deactivated = proxy.receive_deactivated().await?.map(|event| {
+            (
+                event.shortcut_id().to_string(),
+                GlobalShortcutPhase::Deactivated,
+            )
+        });
+        let mut events = stream::select(activated.boxed_local(), deactivated.boxed_local());
+        while let Some((id, phase)) = events.next().await {
+            self.emit(GlobalShortcutId(id.into()), phase);
+        }
+        Ok(())
+    }
+}
+
+/// Formats a keystroke as described by the XDG shortcuts specification, e.g. `CTRL+ALT+space`.
+fn portal_trigger(keystroke: &Keystroke) -> Option<String> {
+    let keysym = keysym_for_key(&keystroke.key)?;
+    let modifiers = keystroke.modifiers;
+    let mut trigger = String::new();
+    for (pressed, name) in [
+        (modifiers.control, "CTRL"),
+        (modifiers.alt, "ALT"),
+        (modifiers.shift, "SHIFT"),
+        (modifiers.platform, "LOGO"),
+    ] {
+        if pressed {
+            trigger.push_str(name);
+            trigger.push('+');
+        }
+    }
+    trigger.push_str(&xkb::keysym_get_name(keysym));
+    Some(trigger)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_portal_triggers() {
+        assert_eq!(
+            portal_trigger(&Keystroke::parse("ctrl-alt-space").unwrap()).as_deref(),
+            Some("CTRL+ALT+space")
+        );
+        assert_eq!(
+            portal_trigger(&Keystroke::parse("super-shift-f5").unwrap()).as_deref(),
+            Some("SHIFT+LOGO+F5")
+        );
+        assert_eq!(
+            portal_trigger(&Keystroke::parse("ctrl-pageup").unwrap()).as_deref(),
+            Some("CTRL+Prior")
+        );
+    }
+}
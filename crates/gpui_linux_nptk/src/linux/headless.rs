@@ -1,3 +1,9 @@
 mod client;
+mod display;
+#[cfg(any(feature = "wayland", feature = "x11"))]
+mod window;
 
 pub(crate) use client::*;
+pub(crate) use display::*;
+#[cfg(any(feature = "wayland", feature = "x11"))]
+pub(crate) use window::*;
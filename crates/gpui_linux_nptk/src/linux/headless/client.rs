@@ -1,19 +1,36 @@
 use std::cell::RefCell;
 use std::rc::Rc;
+#[cfg(any(feature = "wayland", feature = "x11"))]
+use std::{rc::Weak, time::Duration};
+
+#[cfg(any(feature = "wayland", feature = "x11"))]
+use anyhow::Context as _;
 
 use calloop::{EventLoop, LoopHandle};
 use util::ResultExt;
 
-use crate::linux::{LinuxClient, LinuxCommon, LinuxKeyboardLayout};
+use crate::linux::{HeadlessDisplay, LinuxClient, LinuxCommon, LinuxKeyboardLayout};
+#[cfg(any(feature = "wayland", feature = "x11"))]
+use crate::linux::{HeadlessWindow, HeadlessWindowInner};
 use gpui::{
     AnyWindowHandle, CursorStyle, DisplayId, PlatformDisplay, PlatformKeyboardLayout,
     PlatformWindow, WindowParams,
 };
 
+/// Headless windows have no compositor to pace them, so frames are requested at a fixed 60Hz.
+#[cfg(any(feature = "wayland", feature = "x11"))]
+const FRAME_INTERVAL: Duration = Duration::from_micros(16_667);
+
 pub struct HeadlessClientState {
     pub(crate) _loop_handle: LoopHandle<'static, HeadlessClient>,
     pub(crate) event_loop: Option<calloop::EventLoop<'static, HeadlessClient>>,
     pub(crate) common: LinuxCommon,
+    pub(crate) displays: Vec<Rc<HeadlessDisplay>>,
+    #[cfg(any(feature = "wayland", feature = "x11"))]
+    pub(crate) gpu_context: gpui_wgpu::GpuContext,
+    /// Open windows, most recently activated last.
+    #[cfg(any(feature = "wayland", feature = "x11"))]
+    pub(crate) windows: Vec<Weak<HeadlessWindowInner>>,
 }
 
 #[derive(Clone)]
@@ -23,7 +40,8 @@ impl HeadlessClient {
     pub(crate) fn new() -> Self {
         let event_loop = EventLoop::try_new().unwrap();
 
-        let (common, main_receiver, _bridge_receiver) = LinuxCommon::new(event_loop.get_signal());
+        let (common, main_receiver, _bridge_receiver, _instance_request_receiver) =
+            LinuxCommon::new(event_loop.get_signal());
 
         let handle = event_loop.handle();
 
@@ -39,8 +57,23 @@ impl HeadlessClient {
             event_loop: Some(event_loop),
             _loop_handle: handle,
             common,
+            displays: HeadlessDisplay::from_env()
+                .into_iter()
+                .map(Rc::new)
+                .collect(),
+            #[cfg(any(feature = "wayland", feature = "x11"))]
+            gpu_context: Rc::new(RefCell::new(None)),
+            #[cfg(any(feature = "wayland", feature = "x11"))]
+            windows: Vec::new(),
         })))
     }
+
+    #[cfg(any(feature = "wayland", feature = "x11"))]
+    fn windows(&self) -> Vec<Rc<HeadlessWindowInner>> {
+        let mut state = self.0.borrow_mut();
+        state.windows.retain(|window| window.strong_count() > 0);
+        state.windows.iter().filter_map(Weak::upgrade).collect()
+    }
 }
 
 impl LinuxClient for HeadlessClient {
@@ -53,15 +86,29 @@ impl LinuxClient for HeadlessClient {
     }
 
     fn displays(&self) -> Vec<Rc<dyn PlatformDisplay>> {
-        vec![]
+        self.0
+            .borrow()
+            .displays
+            .iter()
+            .map(|display| display.clone() as Rc<dyn PlatformDisplay>)
+            .collect()
     }
 
     fn primary_display(&self) -> Option<Rc<dyn PlatformDisplay>> {
-        None
+        self.0
+            .borrow()
+            .displays
+            .first()
+            .map(|display| display.clone() as Rc<dyn PlatformDisplay>)
     }
 
-    fn display(&self, _id: DisplayId) -> Option<Rc<dyn PlatformDisplay>> {
-        None
+    fn display(&self, id: DisplayId) -> Option<Rc<dyn PlatformDisplay>> {
+        self.0
+            .borrow()
+            .displays
+            .iter()
+            .find(|display| display.id() == id)
+            .map(|display| display.clone() as Rc<dyn PlatformDisplay>)
     }
 
     #[cfg(feature = "screen-capture")]
@@ -77,14 +124,89 @@ impl LinuxClient for HeadlessClient {
         rx
     }
 
+    #[cfg(any(feature = "wayland", feature = "x11"))]
+    fn active_window(&self) -> Option<AnyWindowHandle> {
+        self.windows()
+            .into_iter()
+            .rev()
+            .find(|window| window.state.borrow().active)
+            .map(|window| window.state.borrow().handle)
+    }
+
+    #[cfg(not(any(feature = "wayland", feature = "x11")))]
     fn active_window(&self) -> Option<AnyWindowHandle> {
         None
     }
 
+    #[cfg(any(feature = "wayland", feature = "x11"))]
+    fn window_stack(&self) -> Option<Vec<AnyWindowHandle>> {
+        Some(
+            self.windows()
+                .into_iter()
+                .rev()
+                .map(|window| window.state.borrow().handle)
+                .collect(),
+        )
+    }
+
+    #[cfg(not(any(feature = "wayland", feature = "x11")))]
     fn window_stack(&self) -> Option<Vec<AnyWindowHandle>> {
         None
     }
 
+    #[cfg(any(feature = "wayland", feature = "x11"))]
+    fn open_window(
+        &self,
+        handle: AnyWindowHandle,
+        params: WindowParams,
+    ) -> anyhow::Result<Box<dyn PlatformWindow>> {
+        let mut state = self.0.borrow_mut();
+        let display = params
+            .display_id
+            .and_then(|id| state.displays.iter().find(|display| display.id() == id))
+            .or_else(|| state.displays.first())
+            .cloned()
+            .context("no headless displays are configured")?;
+        let window = HeadlessWindow::new(
+            handle,
+            params,
+            display,
+            state.gpu_context.clone(),
+            state.common.foreground_executor.clone(),
+            state.common.appearance,
+        )?;
+        let inner = Rc::downgrade(&window.0);
+        let previous_windows = state
+            .windows
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect::<Vec<_>>();
+        state.windows.push(inner.clone());
+
+        state
+            ._loop_handle
+            .insert_source(
+                calloop::timer::Timer::immediate(),
+                move |_, _, _| match inner.upgrade() {
+                    Some(window) => {
+                        window.request_frame();
+                        calloop::timer::TimeoutAction::ToDuration(FRAME_INTERVAL)
+                    }
+                    None => calloop::timer::TimeoutAction::Drop,
+                },
+            )
+            .map_err(|error| anyhow::anyhow!("failed to schedule frames: {error}"))?;
+        drop(state);
+
+        // New windows take focus, as they would with most window managers.
+        for previous_window in previous_windows {
+            previous_window.set_active(false);
+        }
+        window.0.set_active(true);
+        Ok(Box::new(window))
+    }
+
+    #[cfg(not(any(feature = "wayland", feature = "x11")))]
     fn open_window(
         &self,
         _handle: AnyWindowHandle,
@@ -0,0 +1,122 @@
+use anyhow::{Context as _, bail};
+use uuid::Uuid;
+
+use gpui::{Bounds, DisplayId, Pixels, PlatformDisplay, point, px, size};
+
+/// Lists the virtual displays of the headless platform, as comma separated
+/// `<width>x<height>[@<scale>]` entries in device pixels, e.g. `1920x1080,2560x1440@2`.
+const GPUI_HEADLESS_DISPLAYS_ENV: &str = "GPUI_HEADLESS_DISPLAYS";
+const DEFAULT_HEADLESS_DISPLAYS: &str = "1920x1080";
+
+#[derive(Debug)]
+pub(crate) struct HeadlessDisplay {
+    id: DisplayId,
+    bounds: Bounds<Pixels>,
+    scale_factor: f32,
+    uuid: Uuid,
+}
+
+impl HeadlessDisplay {
+    /// Creates the displays configured through `GPUI_HEADLESS_DISPLAYS`, laid out left to right.
+    pub(crate) fn from_env() -> Vec<Self> {
+        let spec = std::env::var(GPUI_HEADLESS_DISPLAYS_ENV)
+            .ok()
+            .filter(|spec| !spec.is_empty());
+        match parse_displays(spec.as_deref().unwrap_or(DEFAULT_HEADLESS_DISPLAYS)) {
+            Ok(displays) => displays,
+            Err(error) => {
+                log::error!(
+                    "`{GPUI_HEADLESS_DISPLAYS_ENV}` is invalid, using {DEFAULT_HEADLESS_DISPLAYS}: {error:#}"
+                );
+                parse_displays(DEFAULT_HEADLESS_DISPLAYS).expect("the default displays are valid")
+            }
+        }
+    }
+
+    pub(crate) fn scale_factor(&self) -> f32 {
+        self.scale_factor
+    }
+}
+
+impl PlatformDisplay for HeadlessDisplay {
+    fn id(&self) -> DisplayId {
+        self.id
+    }
+
+    fn uuid(&self) -> anyhow::Result<Uuid> {
+        Ok(self.uuid)
+    }
+
+    fn bounds(&self) -> Bounds<Pixels> {
+        self.bounds
+    }
+}
+
+fn parse_displays(spec: &str) -> anyhow::Result<Vec<HeadlessDisplay>> {
+    let mut displays = Vec::new();
+    let mut x = px(0.);
+    for (index, entry) in spec.split(',').map(str::trim).enumerate() {
+        let (resolution, scale_factor) = match entry.split_once('@') {
+            Some((resolution, scale)) => (
+                resolution,
+                scale
+                    .parse::<f32>()
+                    .with_context(|| format!("invalid scale factor in {entry:?}"))?,
+            ),
+            None => (entry, 1.),
+        };
+        if !scale_factor.is_normal() || scale_factor < 0. {
+            bail!("scale factor in {entry:?} must be a positive number");
+        }
+        let (width, height) = resolution
+            .split_once('x')
+            .and_then(|(width, height)| {
+                Some((width.parse::<u32>().ok()?, height.parse::<u32>().ok()?))
+            })
+            .filter(|(width, height)| *width > 0 && *height > 0)
+            .with_context(|| format!("expected <width>x<height> in {entry:?}"))?;
+
+        let bounds = Bounds::new(
+            point(x, px(0.)),
+            size(
+                px(width as f32 / scale_factor),
+                px(height as f32 / scale_factor),
+            ),
+        );
+        x += bounds.size.width;
+        let mut uuid = [0; 16];
+        uuid[..8].copy_from_slice(&(index as u64).to_be_bytes());
+        displays.push(HeadlessDisplay {
+            id: DisplayId::new(index as u64),
+            bounds,
+            scale_factor,
+            uuid: Uuid::from_bytes(uuid),
+        });
+    }
+    Ok(displays)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_displays() {
+        let displays = parse_displays("1920x1080, 2560x1440@2").unwrap();
+        assert_eq!(displays.len(), 2);
+        assert_eq!(
+            displays[0].bounds,
+            Bounds::new(point(px(0.), px(0.)), size(px(1920.), px(1080.)))
+        );
+        assert_eq!(
+            displays[1].bounds,
+            Bounds::new(point(px(1920.), px(0.)), size(px(1280.), px(720.)))
+        );
+        assert_eq!(displays[1].scale_factor(), 2.);
+        assert_ne!(displays[0].uuid, displays[1].uuid);
+
+        assert!(parse_displays("1920").is_err());
+        assert!(parse_displays("0x1080").is_err());
+        assert!(parse_displays("1920x1080@0").is_err());
+    }
+}
@@ -0,0 +1,470 @@
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+    sync::Arc,
+};
+
+use anyhow::Context as _;
+use futures::channel::oneshot;
+use image::RgbaImage;
+use raw_window_handle as rwh;
+
+use gpui::{
+    AnyWindowHandle, Bounds, Capslock, DevicePixels, DispatchEventResult, ForegroundExecutor,
+    GpuSpecs, Modifiers, Pixels, PlatformAtlas, PlatformDisplay, PlatformInput,
+    PlatformInputHandler, PlatformWindow, Point, PromptButton, PromptLevel, RequestFrameOptions,
+    Scene, Size, WindowAppearance, WindowBackgroundAppearance, WindowBounds, WindowControlArea,
+    WindowParams, size,
+};
+use gpui_wgpu::{GpuContext, WgpuRenderer, WgpuSurfaceConfig};
+
+use crate::linux::HeadlessDisplay;
+
+#[derive(Default)]
+struct Callbacks {
+    request_frame: Option<Box<dyn FnMut(RequestFrameOptions)>>,
+    input: Option<Box<dyn FnMut(PlatformInput) -> DispatchEventResult>>,
+    active_status_change: Option<Box<dyn FnMut(bool)>>,
+    resize: Option<Box<dyn FnMut(Size<Pixels>, f32)>>,
+    moved: Option<Box<dyn FnMut()>>,
+    close: Option<Box<dyn FnOnce()>>,
+}
+
+pub(crate) struct HeadlessWindowState {
+    pub(crate) handle: AnyWindowHandle,
+    executor: ForegroundExecutor,
+    display: Rc<HeadlessDisplay>,
+    bounds: Bounds<Pixels>,
+    title: String,
+    appearance: WindowAppearance,
+    background_appearance: WindowBackgroundAppearance,
+    renderer: WgpuRenderer,
+    input_handler: Option<PlatformInputHandler>,
+    mouse_position: Point<Pixels>,
+    modifiers: Modifiers,
+    capslock: Capslock,
+    active: bool,
+    fullscreen: bool,
+    maximized: bool,
+    /// The bounds to go back to when the window is unmaximized.
+    windowed_bounds: Option<Bounds<Pixels>>,
+    /// The bounds to go back to when the window leaves fullscreen.
+    pre_fullscreen_bounds: Option<Bounds<Pixels>>,
+}
+
+/// A window without a display server, rendered into an offscreen texture. It receives frame
+/// requests from the client's frame timer and input only through [`PlatformWindow::inject_input`].
+pub(crate) struct HeadlessWindowInner {
+    pub(crate) state: RefCell<HeadlessWindowState>,
+    callbacks: RefCell<Callbacks>,
+}
+
+pub(crate) struct HeadlessWindow(pub(crate) Rc<HeadlessWindowInner>);
+
+impl HeadlessWindow {
+    pub(crate) fn new(
+        handle: AnyWindowHandle,
+        params: WindowParams,
+        display: Rc<HeadlessDisplay>,
+        gpu_context: GpuContext,
+        executor: ForegroundExecutor,
+        appearance: WindowAppearance,
+    ) -> anyhow::Result<Self> {
+        let scale_factor = display.scale_factor();
+        let renderer = WgpuRenderer::new_offscreen(
+            gpu_context,
+            WgpuSurfaceConfig {
+                size: device_size(params.bounds.size, scale_factor),
+                transparent: false,
+                preferred_present_mode: None,
+            },
+        )
+        .context("Failed to create an offscreen renderer for a headless window")?;
+
+        Ok(Self(Rc::new(HeadlessWindowInner {
+            state: RefCell::new(HeadlessWindowState {
+                handle,
+                executor,
+                display,
+                bounds: params.bounds,
+                title: String::new(),
+                appearance,
+                background_appearance: WindowBackgroundAppearance::Opaque,
+                renderer,
+                input_handler: None,
+                mouse_position: Point::default(),
+                modifiers: Modifiers::default(),
+                capslock: Capslock::default(),
+                active: false,
+                fullscreen: false,
+                maximized: false,
+                windowed_bounds: None,
+                pre_fullscreen_bounds: None,
+            }),
+            callbacks: RefCell::default(),
+        })))
+    }
+}
+
+impl HeadlessWindowInner {
+    pub(crate) fn request_frame(&self) {
+        let callback = self.callbacks.borrow_mut().request_frame.take();
+        if let Some(mut callback) = callback {
+            callback(RequestFrameOptions::default());
+            self.callbacks
+                .borrow_mut()
+                .request_frame
+                .get_or_insert(callback);
+        }
+    }
+
+    pub(crate) fn set_active(&self, active: bool) {
+        let mut state = self.state.borrow_mut();
+        if state.active == active {
+            return;
+        }
+        state.active = active;
+        drop(state);
+        let callback = self.callbacks.borrow_mut().active_status_change.take();
+        if let Some(mut callback) = callback {
+            callback(active);
+            self.callbacks
+                .borrow_mut()
+                .active_status_change
+                .get_or_insert(callback);
+        }
+    }
+
+    fn set_bounds(&self, bounds: Bounds<Pixels>) {
+        let mut state = self.state.borrow_mut();
+        let resized = state.bounds.size != bounds.size;
+        let moved = state.bounds.origin != bounds.origin;
+        state.bounds = bounds;
+        let scale_factor = state.display.scale_factor();
+        if resized {
+            state
+                .renderer
+                .update_drawable_size(device_size(bounds.size, scale_factor));
+        }
+        drop(state);
+
+        if resized {
+            let callback = self.callbacks.borrow_mut().resize.take();
+            if let Some(mut callback) = callback {
+                callback(bounds.size, scale_factor);
+                self.callbacks.borrow_mut().resize.get_or_insert(callback);
+            }
+        }
+        if moved {
+            let callback = self.callbacks.borrow_mut().moved.take();
+            if let Some(mut callback) = callback {
+                callback();
+                self.callbacks.borrow_mut().moved.get_or_insert(callback);
+            }
+        }
+    }
+
+    fn handle_input(&self, input: PlatformInput) {
+        {
+            let mut state = self.state.borrow_mut();
+            match &input {
+                PlatformInput::MouseMove(event) => {
+                    state.mouse_position = event.position;
+                    state.modifiers = event.modifiers;
+                }
+                PlatformInput::MouseDown(event) => {
+                    state.mouse_position = event.position;
+                    state.modifiers = event.modifiers;
+                }
+                PlatformInput::MouseUp(event) => {
+                    state.mouse_position = event.position;
+                    state.modifiers = event.modifiers;
+                }
+                PlatformInput::ScrollWheel(event) => {
+                    state.mouse_position = event.position;
+                    state.modifiers = event.modifiers;
+                }
+                PlatformInput::ModifiersChanged(event) => {
+                    state.modifiers = event.modifiers;
+                    state.capslock = event.capslock;
+                }
+                _ => {}
+            }
+        }
+
+        let callback = self.callbacks.borrow_mut().input.take();
+        if let Some(mut callback) = callback {
+            let result = callback(input.clone());
+            self.callbacks.borrow_mut().input.get_or_insert(callback);
+            if !result.propagate {
+                return;
+            }
+        }
+        if let PlatformInput::KeyDown(event) = input
+            && event.keystroke.modifiers.is_subset_of(&Modifiers::shift())
+            && let Some(key_char) = &event.keystroke.key_char
+        {
+            let input_handler = self.state.borrow_mut().input_handler.take();
+            if let Some(mut input_handler) = input_handler {
+                input_handler.replace_text_in_range(None, key_char);
+                self.state.borrow_mut().input_handler = Some(input_handler);
+            }
+        }
+    }
+}
+
+impl Drop for HeadlessWindow {
+    fn drop(&mut self) {
+        let mut state = self.0.state.borrow_mut();
+        state.renderer.destroy();
+        let this = self.0.clone();
+        state
+            .executor
+            .spawn(async move {
+                let callback = this.callbacks.borrow_mut().close.take();
+                if let Some(callback) = callback {
+                    callback();
+                }
+            })
+            .detach();
+    }
+}
+
+impl rwh::HasWindowHandle for HeadlessWindow {
+    fn window_handle(&self) -> Result<rwh::WindowHandle<'_>, rwh::HandleError> {
+        Err(rwh::HandleError::Unavailable)
+    }
+}
+
+impl rwh::HasDisplayHandle for HeadlessWindow {
+    fn display_handle(&self) -> Result<rwh::DisplayHandle<'_>, rwh::HandleError> {
+        Err(rwh::HandleError::Unavailable)
+    }
+}
+
+impl PlatformWindow for HeadlessWindow {
+    fn bounds(&self) -> Bounds<Pixels> {
+        self.0.state.borrow().bounds
+    }
+
+    fn is_maximized(&self) -> bool {
+        self.0.state.borrow().maximized
+    }
+
+    fn window_bounds(&self) -> WindowBounds {
+        let state = self.0.state.borrow();
+        let restore_bounds = state
+            .windowed_bounds
+            .or(state.pre_fullscreen_bounds)
+            .unwrap_or(state.bounds);
+        if state.fullscreen {
+            WindowBounds::Fullscreen(restore_bounds)
+        } else if state.maximized {
+            WindowBounds::Maximized(restore_bounds)
+        } else {
+            WindowBounds::Windowed(state.bounds)
+        }
+    }
+
+    fn content_size(&self) -> Size<Pixels> {
+        self.0.state.borrow().bounds.size
+    }
+
+    fn resize(&mut self, size: Size<Pixels>) {
+        let origin = self.0.state.borrow().bounds.origin;
+        self.0.set_bounds(Bounds { origin, size });
+    }
+
+    fn scale_factor(&self) -> f32 {
+        self.0.state.borrow().display.scale_factor()
+    }
+
+    fn appearance(&self) -> WindowAppearance {
+        self.0.state.borrow().appearance
+    }
+
+    fn display(&self) -> Option<Rc<dyn PlatformDisplay>> {
+        Some(self.0.state.borrow().display.clone())
+    }
+
+    fn mouse_position(&self) -> Point<Pixels> {
+        self.0.state.borrow().mouse_position
+    }
+
+    fn modifiers(&self) -> Modifiers {
+        self.0.state.borrow().modifiers
+    }
+
+    fn capslock(&self) -> Capslock {
+        self.0.state.borrow().capslock
+    }
+
+    fn set_input_handler(&mut self, input_handler: PlatformInputHandler) {
+        self.0.state.borrow_mut().input_handler = Some(input_handler);
+    }
+
+    fn take_input_handler(&mut self) -> Option<PlatformInputHandler> {
+        self.0.state.borrow_mut().input_handler.take()
+    }
+
+    fn prompt(
+        &self,
+        _level: PromptLevel,
+        _msg: &str,
+        _detail: Option<&str>,
+        _answers: &[PromptButton],
+    ) -> Option<oneshot::Receiver<usize>> {
+        None
+    }
+
+    fn activate(&self) {
+        self.0.set_active(true);
+    }
+
+    fn is_active(&self) -> bool {
+        self.0.state.borrow().active
+    }
+
+    fn is_hovered(&self) -> bool {
+        false
+    }
+
+    fn background_appearance(&self) -> WindowBackgroundAppearance {
+        self.0.state.borrow().background_appearance
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.0.state.borrow_mut().title = title.to_string();
+    }
+
+    fn get_title(&self) -> String {
+        self.0.state.borrow().title.clone()
+    }
+
+    fn set_background_appearance(&self, background_appearance: WindowBackgroundAppearance) {
+        let mut state = self.0.state.borrow_mut();
+        state.background_appearance = background_appearance;
+        state
+            .renderer
+            .update_transparency(background_appearance != WindowBackgroundAppearance::Opaque);
+    }
+
+    fn minimize(&self) {}
+
+    fn zoom(&self) {
+        let mut state = self.0.state.borrow_mut();
+        let bounds = if state.maximized {
+            state.maximized = false;
+            state.windowed_bounds.take().unwrap_or(state.bounds)
+        } else {
+            state.maximized = true;
+            state.windowed_bounds = Some(state.bounds);
+            state.display.bounds()
+        };
+        drop(state);
+        self.0.set_bounds(bounds);
+    }
+
+    fn toggle_fullscreen(&self) {
+        let mut state = self.0.state.borrow_mut();
+        let bounds = if state.fullscreen {
+            state.fullscreen = false;
+            state.pre_fullscreen_bounds.take().unwrap_or(state.bounds)
+        } else {
+            state.fullscreen = true;
+            state.pre_fullscreen_bounds = Some(state.bounds);
+            state.display.bounds()
+        };
+        drop(state);
+        self.0.set_bounds(bounds);
+    }
+
+    fn is_fullscreen(&self) -> bool {
+        self.0.state.borrow().fullscreen
+    }
+
+    fn on_request_frame(&self, callback: Box<dyn FnMut(RequestFrameOptions)>) {
+        self.0.callbacks.borrow_mut().request_frame = Some(callback);
+    }
+
+    fn on_input(&self, callback: Box<dyn FnMut(PlatformInput) -> DispatchEventResult>) {
+        self.0.callbacks.borrow_mut().input = Some(callback);
+    }
+
+    fn on_active_status_change(&self, callback: Box<dyn FnMut(bool)>) {
+        self.0.callbacks.borrow_mut().active_status_change = Some(callback);
+    }
+
+    fn on_hover_status_change(&self, _callback: Box<dyn FnMut(bool)>) {}
+
+    fn on_resize(&self, callback: Box<dyn FnMut(Size<Pixels>, f32)>) {
+        self.0.callbacks.borrow_mut().resize = Some(callback);
+    }
+
+    fn on_moved(&self, callback: Box<dyn FnMut()>) {
+        self.0.callbacks.borrow_mut().moved = Some(callback);
+    }
+
+    fn on_should_close(&self, _callback: Box<dyn FnMut() -> bool>) {}
+
+    fn on_close(&self, callback: Box<dyn FnOnce()>) {
+        self.0.callbacks.borrow_mut().close = Some(callback);
+    }
+
+    fn on_hit_test_window_control(&self, _callback: Box<dyn FnMut() -> Option<WindowControlArea>>) {
+    }
+
+    fn on_appearance_changed(&self, _callback: Box<dyn FnMut()>) {}
+
+    fn draw(&self, scene: &Scene) {
+        self.0.state.borrow_mut().renderer.draw(scene);
+    }
+
+    fn sprite_atlas(&self) -> Arc<dyn PlatformAtlas> {
+        self.0.state.borrow().renderer.sprite_atlas().clone()
+    }
+
+    fn is_subpixel_rendering_supported(&self) -> bool {
+        // Subpixel antialiasing depends on the physical layout of a screen there is none of.
+        false
+    }
+
+    fn gpu_specs(&self) -> Option<GpuSpecs> {
+        Some(self.0.state.borrow().renderer.gpu_specs())
+    }
+
+    fn update_ime_position(&self, _bounds: Bounds<Pixels>) {}
+
+    fn render_to_image(&self, scene: &Scene) -> anyhow::Result<RgbaImage> {
+        let mut state = self.0.state.borrow_mut();
+        state.renderer.draw(scene);
+        let size = state.renderer.viewport_size();
+        let pixels = state.renderer.read_pixels()?;
+        RgbaImage::from_raw(size.width.0 as u32, size.height.0 as u32, pixels)
+            .context("Frame read back from the GPU has an unexpected size")
+    }
+
+    fn inject_input(&self, input: PlatformInput) -> anyhow::Result<()> {
+        // Input is injected from within an update of this window, which the input callback
+        // would re-enter, so it is handled on the next turn of the event loop instead.
+        let this = Rc::downgrade(&self.0);
+        self.0
+            .state
+            .borrow()
+            .executor
+            .spawn(async move {
+                if let Some(this) = Weak::upgrade(&this) {
+                    this.handle_input(input);
+                }
+            })
+            .detach();
+        Ok(())
+    }
+}
+
+fn device_size(size: Size<Pixels>, scale_factor: f32) -> Size<DevicePixels> {
+    size(
+        DevicePixels((f32::from(size.width) * scale_factor).round() as i32),
+        DevicePixels((f32::from(size.height) * scale_factor).round() as i32),
+    )
+}
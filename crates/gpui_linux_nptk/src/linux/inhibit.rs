@@ -0,0 +1,289 @@
+//! Session inhibitors through the XDG inhibit portal, falling back to
+//! `org.gnome.SessionManager` and then logind when no portal implementation is running.
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+    time::Duration,
+};
+
+use anyhow::{Context as _, Result, anyhow};
+use ashpd::{
+    desktop::{
+        Request,
+        inhibit::{InhibitFlags as PortalInhibitFlags, InhibitProxy, SessionState},
+    },
+    enumflags2::BitFlags,
+};
+use futures::{channel::oneshot, future};
+use gpui::{
+    BackgroundExecutor, ForegroundExecutor,
+    inhibit::{InhibitFlags, InhibitorId},
+};
+use smol::stream::StreamExt;
+use util::{ResultExt as _, TryFutureExt as _};
+use zbus::zvariant::OwnedFd;
+
+use crate::linux::application_name;
+
+const GNOME_SESSION_SERVICE: &str = "org.gnome.SessionManager";
+const GNOME_SESSION_PATH: &str = "/org/gnome/SessionManager";
+
+const LOGIND_SERVICE: &str = "org.freedesktop.login1";
+const LOGIND_PATH: &str = "/org/freedesktop/login1";
+const LOGIND_MANAGER_INTERFACE: &str = "org.freedesktop.login1.Manager";
+
+/// How long the session is kept waiting for inhibitors requested while answering a
+/// session end query.
+const QUERY_END_TIMEOUT: Duration = Duration::from_secs(1);
+
+enum Inhibition {
+    /// The request is still in flight.
+    Pending,
+    Portal(Request<()>),
+    GnomeSession {
+        proxy: zbus::Proxy<'static>,
+        cookie: u32,
+    },
+    /// logind releases the inhibitor once every copy of this file descriptor is closed.
+    Logind(OwnedFd),
+}
+
+struct SessionInhibitorState {
+    executor: ForegroundExecutor,
+    background_executor: BackgroundExecutor,
+    inhibitions: RefCell<HashMap<InhibitorId, Inhibition>>,
+    monitor_started: Cell<bool>,
+    query_end_callback: RefCell<Option<Box<dyn FnMut()>>>,
+    /// Completed once no inhibitor is pending anymore.
+    settled: RefCell<Option<oneshot::Sender<()>>>,
+}
+
+#[derive(Clone)]
+pub(crate) struct SessionInhibitor(Rc<SessionInhibitorState>);
+
+impl SessionInhibitor {
+    pub fn new(executor: ForegroundExecutor, background_executor: BackgroundExecutor) -> Self {
+        Self(Rc::new(SessionInhibitorState {
+            executor,
+            background_executor,
+            inhibitions: RefCell::default(),
+            monitor_started: Cell::new(false),
+            query_end_callback: RefCell::default(),
+            settled: RefCell::default(),
+        }))
+    }
+
+    pub fn inhibit(&self, id: InhibitorId, flags: InhibitFlags, reason: &str) {
+        if flags.is_empty() {
+            return;
+        }
+        self.0
+            .inhibitions
+            .borrow_mut()
+            .insert(id, Inhibition::Pending);
+
+        let this = self.clone();
+        let reason = reason.to_string();
+        self.0
+            .executor
+            .spawn(async move {
+                let inhibition = acquire(flags, &reason).await.log_err();
+                {
+                    let mut inhibitions = this.0.inhibitions.borrow_mut();
+                    match (inhibition, inhibitions.get_mut(&id)) {
+                        (Some(inhibition), Some(entry)) => *entry = inhibition,
+                        // The inhibitor was dropped before the session manager answered.
+                        (Some(inhibition), None) => {
+                            drop(inhibitions);
+                            this.release(inhibition);
+                        }
+                        (None, Some(_)) => {
+                            inhibitions.remove(&id);
+                        }
+                        (None, None) => {}
+                    }
+                }
+                this.notify_if_settled();
+            })
+            .detach();
+    }
+
+    pub fn uninhibit(&self, id: InhibitorId) {
+        let inhibition = self.0.inhibitions.borrow_mut().remove(&id);
+        if let Some(inhibition) = inhibition {
+            self.release(inhibition);
+        }
+        self.notify_if_settled();
+    }
+
+    /// Only the portal asks applications before ending the session, so this does nothing when
+    /// it isn't available.
+    pub fn on_session_end_query(&self, callback: Box<dyn FnMut()>) {
+        self.0.query_end_callback.replace(Some(callback));
+        if self.0.monitor_started.replace(true) {
+            return;
+        }
+        let this = self.clone();
+        self.0
+            .executor
+            .spawn(async move { this.monitor().await }.log_err())
+            .detach();
+    }
+
+    fn release(&self, inhibition: Inhibition) {
+        match inhibition {
+            Inhibition::Pending => {}
+            Inhibition::Portal(request) => {
+                self.0
+                    .executor
+                    .spawn(async move { request.close().await.log_err() })
+                    .detach();
+            }
+            Inhibition::GnomeSession { proxy, cookie } => {
+                self.0
+                    .executor
+                    .spawn(async move {
+                        proxy
+                            .call::<_, _, ()>("Uninhibit", &(cookie,))
+                            .await
+                            .log_err()
+                    })
+                    .detach();
+            }
+            Inhibition::Logind(fd) => drop(fd),
+        }
+    }
+
+    async fn monitor(&self) -> Result<()> {
+        let proxy = InhibitProxy::new().await?;
+        let session = proxy.create_monitor(None).await?;
+        let mut state_changes = proxy.receive_state_changed().await?;
+        while let Some(state) = state_changes.next().await {
+            if state.session_state() != SessionState::QueryEnd {
+                continue;
+            }
+
+            let callback = self.0.query_end_callback.borrow_mut().take();
+            if let Some(mut callback) = callback {
+                callback();
+                self.0
+                    .query_end_callback
+                    .borrow_mut()
+                    .get_or_insert(callback);
+            }
+
+            // Give the inhibitors taken out by the callback a chance to reach the session
+            // manager before telling it we're done.
+            if self.has_pending_inhibitions() {
+                let (settled_tx, settled_rx) = oneshot::channel();
+                self.0.settled.replace(Some(settled_tx));
+                let timeout = self.0.background_executor.timer(QUERY_END_TIMEOUT);
+                future::select(settled_rx, timeout).await;
+                self.0.settled.take();
+            }
+            proxy.query_end_response(&session).await.log_err();
+        }
+        Ok(())
+    }
+
+    fn has_pending_inhibitions(&self) -> bool {
+        self.0
+            .inhibitions
+            .borrow()
+            .values()
+            .any(|inhibition| matches!(inhibition, Inhibition::Pending))
+    }
+
+    fn notify_if_settled(&self) {
+        if !self.has_pending_inhibitions()
+            && let Some(settled) = self.0.settled.take()
+        {
+            settled.send(()).ok();
+        }
+    }
+}
+
+async fn acquire(flags: InhibitFlags, reason: &str) -> Result<Inhibition> {
+    if let Ok(request) = portal_inhibit(flags, reason).await {
+        return Ok(Inhibition::Portal(request));
+    }
+    if let Ok((proxy, cookie)) = gnome_session_inhibit(flags, reason).await {
+        return Ok(Inhibition::GnomeSession { proxy, cookie });
+    }
+    logind_inhibit(flags, reason)
+        .await
+        .map(Inhibition::Logind)
+        .with_context(|| format!("failed to inhibit {flags:?}"))
+}
+
+async fn portal_inhibit(flags: InhibitFlags, reason: &str) -> Result<Request<()>> {
+    let mut portal_flags = BitFlags::empty();
+    for (flag, portal_flag) in [
+        (InhibitFlags::LOGOUT, PortalInhibitFlags::Logout),
+        (InhibitFlags::USER_SWITCH, PortalInhibitFlags::UserSwitch),
+        (InhibitFlags::SUSPEND, PortalInhibitFlags::Suspend),
+        (InhibitFlags::IDLE, PortalInhibitFlags::Idle),
+    ] {
+        if flags.contains(flag) {
+            portal_flags |= portal_flag;
+        }
+    }
+    Ok(InhibitProxy::new()
+        .await?
+        .inhibit(None, portal_flags, reason)
+        .await?)
+}
+
+/// `org.gnome.SessionManager` uses the same flag values as the portal.
+async fn gnome_session_inhibit(
+    flags: InhibitFlags,
+    reason: &str,
+) -> Result<(zbus::Proxy<'static>, u32)> {
+    let connection = zbus::Connection::session().await?;
+    let proxy = zbus::Proxy::new(
+        &connection,
+        GNOME_SESSION_SERVICE,
+        GNOME_SESSION_PATH,
+        GNOME_SESSION_SERVICE,
+    )
+    .await?;
+    let cookie: u32 = proxy
+        .call("Inhibit", &(application_name(), 0u32, reason, flags.bits()))
+        .await?;
+    Ok((proxy, cookie))
+}
+
+/// logind knows nothing about logging out or switching users, the closest it offers for the
+/// former is blocking shutdown.
+async fn logind_inhibit(flags: InhibitFlags, reason: &str) -> Result<OwnedFd> {
+    let mut what = Vec::new();
+    if flags.contains(InhibitFlags::LOGOUT) {
+        what.push("shutdown");
+    }
+    if flags.contains(InhibitFlags::SUSPEND) {
+        what.push("sleep");
+    }
+    if flags.contains(InhibitFlags::IDLE) {
+        what.push("idle");
+    }
+    if what.is_empty() {
+        return Err(anyhow!("logind can't inhibit {flags:?}"));
+    }
+
+    let connection = zbus::Connection::system().await?;
+    let proxy = zbus::Proxy::new(
+        &connection,
+        LOGIND_SERVICE,
+        LOGIND_PATH,
+        LOGIND_MANAGER_INTERFACE,
+    )
+    .await?;
+    Ok(proxy
+        .call(
+            "Inhibit",
+            &(what.join(":"), application_name(), reason, "block"),
+        )
+        .await?)
+}
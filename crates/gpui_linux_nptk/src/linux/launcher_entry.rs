@@ -0,0 +1,151 @@
+//! Launcher badges, progress and urgency through `com.canonical.Unity.LauncherEntry`, which the
+//! KDE task manager, Dash to Dock and Plank listen to.
+
+use std::{
+    collections::HashMap,
+    rc::Rc,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Result;
+use gpui::ForegroundExecutor;
+use smol::lock::OnceCell;
+use util::TryFutureExt as _;
+use zbus::{interface, object_server::SignalContext as SignalEmitter, zvariant::Value};
+
+use crate::linux::desktop_entry::desktop_file_id;
+
+/// What the launcher shows on top of the application's icon.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+struct LauncherProperties {
+    count: Option<u32>,
+    progress: Option<f32>,
+    urgent: bool,
+}
+
+impl LauncherProperties {
+    fn to_map(self) -> HashMap<&'static str, Value<'static>> {
+        HashMap::from([
+            ("count", Value::from(i64::from(self.count.unwrap_or(0)))),
+            ("count-visible", Value::from(self.count.is_some())),
+            (
+                "progress",
+                Value::from(f64::from(self.progress.unwrap_or(0.).clamp(0., 1.))),
+            ),
+            ("progress-visible", Value::from(self.progress.is_some())),
+            ("urgent", Value::from(self.urgent)),
+        ])
+    }
+}
+
+/// `com.canonical.Unity.LauncherEntry` interface implementation. Docks started after us call
+/// `Query` to catch up on the current state.
+struct LauncherEntryObject {
+    app_uri: String,
+    properties: Arc<Mutex<LauncherProperties>>,
+}
+
+#[interface(name = "com.canonical.Unity.LauncherEntry")]
+impl LauncherEntryObject {
+    #[zbus(name = "Query")]
+    fn query(&self) -> (String, HashMap<&'static str, Value<'static>>) {
+        (
+            self.app_uri.clone(),
+            self.properties.lock().unwrap().to_map(),
+        )
+    }
+
+    #[zbus(signal, name = "Update")]
+    async fn update(
+        emitter: &SignalEmitter<'_>,
+        app_uri: &str,
+        properties: HashMap<&'static str, Value<'static>>,
+    ) -> zbus::Result<()>;
+}
+
+struct LauncherEntryState {
+    executor: ForegroundExecutor,
+    connection: OnceCell<zbus::Connection>,
+    app_uri: String,
+    object_path: String,
+    properties: Arc<Mutex<LauncherProperties>>,
+}
+
+#[derive(Clone)]
+pub(crate) struct LauncherEntry(Rc<LauncherEntryState>);
+
+impl LauncherEntry {
+    pub fn new(executor: ForegroundExecutor) -> Self {
+        Self(Rc::new(LauncherEntryState {
+            executor,
+            connection: OnceCell::new(),
+            app_uri: format!("application://{}", desktop_file_id()),
+            object_path: format!("/com/canonical/unity/launcherentry/{}", std::process::id()),
+            properties: Arc::default(),
+        }))
+    }
+
+    pub fn set_badge(&self, count: Option<u32>) {
+        self.update(|properties| properties.count = count);
+    }
+
+    pub fn set_progress(&self, progress: Option<f32>) {
+        self.update(|properties| properties.progress = progress);
+    }
+
+    pub fn set_urgent(&self, urgent: bool) {
+        self.update(|properties| properties.urgent = urgent);
+    }
+
+    fn update(&self, change: impl FnOnce(&mut LauncherProperties)) {
+        let properties = {
+            let mut properties = self.0.properties.lock().unwrap();
+            let previous = *properties;
+            change(&mut properties);
+            if *properties == previous {
+                return;
+            }
+            *properties
+        };
+        let this = self.clone();
+        self.0
+            .executor
+            .spawn(
+                async move {
+                    let connection = this.connection().await?;
+                    let object = connection
+                        .object_server()
+                        .interface::<_, LauncherEntryObject>(this.0.object_path.as_str())
+                        .await?;
+                    LauncherEntryObject::update(
+                        object.signal_context(),
+                        &this.0.app_uri,
+                        properties.to_map(),
+                    )
+                    .await?;
+                    anyhow::Ok(())
+                }
+                .log_err(),
+            )
+            .detach();
+    }
+
+    async fn connection(&self) -> Result<&zbus::Connection> {
+        self.0
+            .connection
+            .get_or_try_init(|| async {
+                let connection = zbus::connection::Builder::session()?
+                    .serve_at(
+                        self.0.object_path.as_str(),
+                        LauncherEntryObject {
+                            app_uri: self.0.app_uri.clone(),
+                            properties: self.0.properties.clone(),
+                        },
+                    )?
+                    .build()
+                    .await?;
+                anyhow::Ok(connection)
+            })
+            .await
+    }
+}
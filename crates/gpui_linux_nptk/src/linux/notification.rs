@@ -0,0 +1,355 @@
+//! Desktop notifications through the XDG notification portal, falling back to the
+//! `org.freedesktop.Notifications` service when no portal implementation is running.
+
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+
+use anyhow::Result;
+use ashpd::desktop::{
+    Icon,
+    notification::{Button, Notification as PortalNotification, NotificationProxy, Priority},
+};
+use gpui::{
+    ForegroundExecutor, Task,
+    notification::{
+        Notification, NotificationIcon, NotificationId, NotificationResponse, NotificationUrgency,
+    },
+};
+use smol::{lock::OnceCell, stream::StreamExt};
+use util::{ResultExt as _, TryFutureExt as _};
+use zbus::zvariant::Value;
+
+use crate::linux::application_name;
+
+const DEFAULT_ACTION_NAME: &str = "default";
+const BUTTON_ACTION_PREFIX: &str = "button-";
+const PORTAL_ID_PREFIX: &str = "notification-";
+/// The `NotificationClosed` reason for notifications closed with `CloseNotification`.
+const CLOSED_BY_CALL_REASON: u32 = 3;
+
+type ResponseCallback = Box<dyn FnMut(NotificationId, NotificationResponse)>;
+
+enum Backend {
+    Portal,
+    Server(zbus::Proxy<'static>),
+}
+
+/// The parts of a [`Notification`] sent to the notification server, without its actions.
+struct NotificationContent {
+    title: String,
+    body: Option<String>,
+    icon: Option<NotificationIcon>,
+    urgency: NotificationUrgency,
+    button_labels: Vec<String>,
+    has_default_action: bool,
+}
+
+impl NotificationContent {
+    fn new(notification: &Notification) -> Self {
+        Self {
+            title: notification.title.to_string(),
+            body: notification.body.as_ref().map(ToString::to_string),
+            icon: notification.icon.clone(),
+            urgency: notification.urgency,
+            button_labels: notification
+                .actions
+                .iter()
+                .map(|action| action.label.to_string())
+                .collect(),
+            has_default_action: notification.default_action.is_some(),
+        }
+    }
+}
+
+/// The signals `org.freedesktop.Notifications` sends about a notification.
+enum ServerSignal {
+    ActionInvoked(NotificationResponse),
+    Closed { reason: u32 },
+}
+
+struct NotificationsState {
+    executor: ForegroundExecutor,
+    backend: OnceCell<Backend>,
+    /// Maps our identifiers to the ones assigned by `org.freedesktop.Notifications`.
+    server_ids: RefCell<HashMap<NotificationId, u32>>,
+    response_callback: RefCell<Option<ResponseCallback>>,
+}
+
+#[derive(Clone)]
+pub(crate) struct Notifications(Rc<NotificationsState>);
+
+impl Notifications {
+    pub fn new(executor: ForegroundExecutor) -> Self {
+        Self(Rc::new(NotificationsState {
+            executor,
+            backend: OnceCell::new(),
+            server_ids: RefCell::default(),
+            response_callback: RefCell::default(),
+        }))
+    }
+
+    pub fn on_response(&self, callback: ResponseCallback) {
+        self.0.response_callback.replace(Some(callback));
+    }
+
+    pub fn show(&self, id: NotificationId, notification: &Notification) -> Task<Result<()>> {
+        let this = self.clone();
+        let content = NotificationContent::new(notification);
+        self.0.executor.spawn(async move {
+            match this.backend().await? {
+                Backend::Portal => {
+                    let notification = portal_notification(content).await?;
+                    NotificationProxy::new()
+                        .await?
+                        .add_notification(&portal_id(id), notification)
+                        .await?;
+                }
+                Backend::Server(proxy) => {
+                    let replaces_id = this.0.server_ids.borrow().get(&id).copied().unwrap_or(0);
+                    let (app_icon, hints) = server_icon_and_hints(&content);
+                    let mut actions = Vec::new();
+                    if content.has_default_action {
+                        actions.extend([DEFAULT_ACTION_NAME.to_string(), String::new()]);
+                    }
+                    for (index, label) in content.button_labels.into_iter().enumerate() {
+                        actions.extend([button_action_name(index), label]);
+                    }
+                    let server_id: u32 = proxy
+                        .call(
+                            "Notify",
+                            &(
+                                application_name(),
+                                replaces_id,
+                                app_icon,
+                                content.title,
+                                content.body.unwrap_or_default(),
+                                actions,
+                                hints,
+                                -1i32,
+                            ),
+                        )
+                        .await?;
+                    this.0.server_ids.borrow_mut().insert(id, server_id);
+                }
+            }
+            Ok(())
+        })
+    }
+
+    pub fn withdraw(&self, id: NotificationId) {
+        let this = self.clone();
+        self.0
+            .executor
+            .spawn(
+                async move {
+                    match this.backend().await? {
+                        Backend::Portal => {
+                            NotificationProxy::new()
+                                .await?
+                                .remove_notification(&portal_id(id))
+                                .await?;
+                        }
+                        Backend::Server(proxy) => {
+                            let server_id = this.0.server_ids.borrow_mut().remove(&id);
+                            if let Some(server_id) = server_id {
+                                proxy
+                                    .call::<_, _, ()>("CloseNotification", &(server_id,))
+                                    .await?;
+                            }
+                        }
+                    }
+                    anyhow::Ok(())
+                }
+                .log_err(),
+            )
+            .detach();
+    }
+
+    async fn backend(&self) -> Result<&Backend> {
+        self.0
+            .backend
+            .get_or_try_init(|| async {
+                if NotificationProxy::new().await.is_ok() {
+                    self.listen_to_portal();
+                    return Ok(Backend::Portal);
+                }
+                let connection = zbus::Connection::session().await?;
+                let proxy = zbus::Proxy::new(
+                    &connection,
+                    "org.freedesktop.Notifications",
+                    "/org/freedesktop/Notifications",
+                    "org.freedesktop.Notifications",
+                )
+                .await?;
+                self.listen_to_server(proxy.clone());
+                anyhow::Ok(Backend::Server(proxy))
+            })
+            .await
+    }
+
+    fn listen_to_portal(&self) {
+        let this = self.clone();
+        self.0
+            .executor
+            .spawn(
+                async move {
+                    let proxy = NotificationProxy::new().await?;
+                    let mut action_invoked = proxy.receive_action_invoked().await?;
+                    while let Some(action) = action_invoked.next().await {
+                        let id = action
+                            .id()
+                            .strip_prefix(PORTAL_ID_PREFIX)
+                            .and_then(|id| id.parse().ok());
+                        if let (Some(id), Some(response)) = (id, parse_response(action.name())) {
+                            this.respond(NotificationId(id), response);
+                        }
+                    }
+                    anyhow::Ok(())
+                }
+                .log_err(),
+            )
+            .detach();
+    }
+
+    fn listen_to_server(&self, proxy: zbus::Proxy<'static>) {
+        let this = self.clone();
+        self.0
+            .executor
+            .spawn(
+                async move {
+                    let action_invoked =
+                        proxy
+                            .receive_signal("ActionInvoked")
+                            .await?
+                            .filter_map(|message| {
+                                let (server_id, action_name) =
+                                    message.body().deserialize::<(u32, String)>().log_err()?;
+                                Some((
+                                    server_id,
+                                    ServerSignal::ActionInvoked(parse_response(&action_name)?),
+                                ))
+                            });
+                    // Sent once the notification is gone, whether it was clicked, dismissed,
+                    // expired or withdrawn.
+                    let closed = proxy
+                        .receive_signal("NotificationClosed")
+                        .await?
+                        .filter_map(|message| {
+                            let (server_id, reason) =
+                                message.body().deserialize::<(u32, u32)>().log_err()?;
+                            Some((server_id, ServerSignal::Closed { reason }))
+                        });
+                    let mut signals = futures::stream::select(action_invoked, closed);
+                    // The notifications that were clicked, which aren't reported as dismissed
+                    // once the server closes them.
+                    let mut clicked = HashSet::new();
+                    while let Some((server_id, signal)) = signals.next().await {
+                        let mut server_ids = this.0.server_ids.borrow_mut();
+                        let id = server_ids
+                            .iter()
+                            .find_map(|(id, candidate)| (*candidate == server_id).then_some(*id));
+                        let Some(id) = id else {
+                            continue;
+                        };
+                        let response = match signal {
+                            ServerSignal::ActionInvoked(response) => {
+                                clicked.insert(server_id);
+                                Some(response)
+                            }
+                            ServerSignal::Closed { reason } => {
+                                server_ids.remove(&id);
+                                let clicked = clicked.remove(&server_id);
+                                (!clicked && reason != CLOSED_BY_CALL_REASON)
+                                    .then_some(NotificationResponse::Dismissed)
+                            }
+                        };
+                        drop(server_ids);
+                        if let Some(response) = response {
+                            this.respond(id, response);
+                        }
+                    }
+                    anyhow::Ok(())
+                }
+                .log_err(),
+            )
+            .detach();
+    }
+
+    fn respond(&self, id: NotificationId, response: NotificationResponse) {
+        let callback = self.0.response_callback.borrow_mut().take();
+        if let Some(mut callback) = callback {
+            callback(id, response);
+            self.0
+                .response_callback
+                .borrow_mut()
+                .get_or_insert(callback);
+        }
+    }
+}
+
+async fn portal_notification(content: NotificationContent) -> Result<PortalNotification> {
+    let icon = match content.icon {
+        Some(NotificationIcon::Named(name)) => Some(Icon::with_names([name])),
+        Some(NotificationIcon::File(path)) => Some(Icon::Bytes(smol::fs::read(path).await?)),
+        None => None,
+    };
+    let priority = match content.urgency {
+        NotificationUrgency::Low => Priority::Low,
+        NotificationUrgency::Normal => Priority::Normal,
+        NotificationUrgency::Critical => Priority::Urgent,
+    };
+    let mut notification = PortalNotification::new(&content.title)
+        .body(content.body.as_deref())
+        .icon(icon)
+        .priority(priority)
+        .default_action(content.has_default_action.then_some(DEFAULT_ACTION_NAME));
+    for (index, label) in content.button_labels.iter().enumerate() {
+        notification = notification.button(Button::new(label, &button_action_name(index)));
+    }
+    Ok(notification)
+}
+
+fn server_icon_and_hints(
+    content: &NotificationContent,
+) -> (String, HashMap<&'static str, Value<'static>>) {
+    let urgency: u8 = match content.urgency {
+        NotificationUrgency::Low => 0,
+        NotificationUrgency::Normal => 1,
+        NotificationUrgency::Critical => 2,
+    };
+    let mut hints = HashMap::from([("urgency", Value::from(urgency))]);
+    let app_icon = match &content.icon {
+        Some(NotificationIcon::Named(name)) => name.to_string(),
+        Some(NotificationIcon::File(path)) => {
+            hints.insert(
+                "image-path",
+                Value::from(path.to_string_lossy().into_owned()),
+            );
+            String::new()
+        }
+        None => String::new(),
+    };
+    (app_icon, hints)
+}
+
+fn portal_id(id: NotificationId) -> String {
+    format!("{PORTAL_ID_PREFIX}{}", id.0)
+}
+
+fn button_action_name(index: usize) -> String {
+    format!("{BUTTON_ACTION_PREFIX}{index}")
+}
+
+fn parse_response(action_name: &str) -> Option<NotificationResponse> {
+    if action_name == DEFAULT_ACTION_NAME {
+        return Some(NotificationResponse::Default);
+    }
+    let index = action_name
+        .strip_prefix(BUTTON_ACTION_PREFIX)?
+        .parse()
+        .ok()?;
+    Some(NotificationResponse::Action(index))
+}
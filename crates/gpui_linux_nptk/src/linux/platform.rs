@@ -1,4 +1,5 @@
 use std::{
+    cell::RefMut,
     collections::VecDeque,
     env,
     path::{Path, PathBuf},
@@ -11,7 +12,7 @@ use std::{
     fs::File,
     io::Read as _,
     os::fd::{AsFd, FromRawFd, IntoRawFd},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use anyhow::{Context as _, anyhow};
@@ -22,16 +23,32 @@ use util::command::{new_command, new_std_command};
 #[cfg(any(feature = "wayland", feature = "x11"))]
 use xkbcommon::xkb::{self, Keycode, Keysym, State};
 
-use crate::linux::{LinuxDispatcher, PriorityQueueCalloopReceiver};
+use crate::linux::{
+    InstanceRequest, InstanceRequestKind, LinuxDispatcher, PriorityQueueCalloopReceiver,
+    SingleInstance,
+    dbus_menu::{MenuSnapshot, RemoteMenuNode},
+    desktop_entry::AUTOSTART_ID_VARIABLE,
+};
 use gpui::{
-    Action, AnyWindowHandle, BackgroundExecutor, ClipboardItem, CursorStyle, DisplayId,
-    ForegroundExecutor, Keymap, Menu, MenuItem, OwnedMenu, PathPromptOptions, Platform,
-    PlatformDisplay, PlatformKeyboardLayout, PlatformKeyboardMapper, PlatformTextSystem,
-    PlatformWindow, Result, RunnableVariant, Task, ThermalState, WindowAppearance,
-    WindowButtonLayout, WindowParams,
+    AccessibilityPreferences, Action, AnyWindowHandle, BackgroundExecutor, ClipboardItem,
+    CursorStyle, DEFAULT_DRAG_THRESHOLD, DisplayId, ForegroundExecutor, Hsla, Keymap, Menu,
+    MenuItem, OwnedMenu, PathPromptOptions, Pixels, Platform, PlatformDisplay,
+    PlatformKeyboardLayout, PlatformKeyboardMapper, PlatformTextSystem, PlatformWindow, PowerState,
+    Result, RunnableVariant, SingleInstanceClaim, Task, ThermalState, WindowAppearance,
+    WindowButtonLayout, WindowKind, WindowParams,
+    foreign_toplevel::{
+        ForeignToplevel, ForeignToplevelEvent, ForeignToplevelId, ForeignToplevelRequest,
+    },
+    tray::{TrayEvent, TrayIcon},
 };
 #[cfg(any(feature = "wayland", feature = "x11"))]
-use gpui::{Pixels, Point, px};
+use gpui::{
+    Point, PrintOptions,
+    global_shortcut::{GlobalShortcut, GlobalShortcutId, GlobalShortcutPhase},
+    inhibit::{InhibitFlags, InhibitorId},
+    notification::{Notification, NotificationId, NotificationResponse},
+    px,
+};
 
 #[cfg(any(feature = "wayland", feature = "x11"))]
 pub(crate) const SCROLL_LINES: f32 = 3.0;
@@ -44,6 +61,9 @@ pub(crate) const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(400);
 pub(crate) const DOUBLE_CLICK_DISTANCE: Pixels = px(5.0);
 pub(crate) const KEYRING_LABEL: &str = "zed-github-account";
 
+/// Desktop action ids given to the dock menu's items, followed by the name of the item's action.
+const DOCK_ACTION_PREFIX: &str = "dock-";
+
 #[cfg(any(feature = "wayland", feature = "x11"))]
 const FILE_PICKER_PORTAL_MISSING: &str =
     "Couldn't open file picker due to missing xdg-desktop-portal implementation.";
@@ -85,6 +105,9 @@ pub(crate) trait LinuxClient {
     fn is_cursor_visible(&self) -> bool {
         true
     }
+    fn drag_threshold(&self) -> Pixels {
+        DEFAULT_DRAG_THRESHOLD
+    }
     fn open_uri(&self, uri: &str);
     fn reveal_path(&self, path: PathBuf);
     fn write_to_primary(&self, item: ClipboardItem);
@@ -94,6 +117,19 @@ pub(crate) trait LinuxClient {
     fn active_window(&self) -> Option<AnyWindowHandle>;
     fn window_stack(&self) -> Option<Vec<AnyWindowHandle>>;
     fn run(&self);
+    fn unlock_session(&self) {}
+    fn foreign_toplevels(&self) -> Vec<ForeignToplevel> {
+        Vec::new()
+    }
+    fn request_foreign_toplevel(
+        &self,
+        _id: ForeignToplevelId,
+        _request: ForeignToplevelRequest,
+    ) -> Result<()> {
+        Err(anyhow!(
+            "Compositor doesn't support managing other applications' windows"
+        ))
+    }
 
     #[cfg(any(feature = "wayland", feature = "x11"))]
     fn window_identifier(
@@ -107,6 +143,21 @@ pub(crate) trait LinuxClient {
         None
     }
 
+    /// Binds global shortcuts through the portal. Clients that have a fallback for when the
+    /// portal isn't available override this.
+    #[cfg(any(feature = "wayland", feature = "x11"))]
+    fn set_global_shortcuts(&self, shortcuts: Vec<GlobalShortcut>) {
+        let (global_shortcuts, executor) = self.with_common(|common| {
+            (
+                common.global_shortcuts.clone(),
+                common.foreground_executor.clone(),
+            )
+        });
+        executor
+            .spawn(async move { global_shortcuts.bind(shortcuts).await.log_err() })
+            .detach();
+    }
+
     fn register_global_menu_window(&self) {
         let window_id = self.global_menu_window_id().or_else(wayland_global_menu_fallback_id);
         let registration_changed =
@@ -134,6 +185,29 @@ fn wayland_global_menu_fallback_id() -> Option<u64> {
     None
 }
 
+/// Names a dock menu item's desktop action after its action, so that the id stays the same across
+/// launches even when other items are added or removed. `taken` holds the ids given out so far.
+fn dock_action_id(action: &dyn Action, taken: &[(String, String)]) -> String {
+    let id = action
+        .name()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect::<String>();
+    let id = format!("{DOCK_ACTION_PREFIX}{id}");
+    let is_taken = |candidate: &str| taken.iter().any(|(taken, _)| taken == candidate);
+    if !is_taken(&id) {
+        return id;
+    }
+    let mut n = 2;
+    loop {
+        let candidate = format!("{id}-{n}");
+        if !is_taken(&candidate) {
+            return candidate;
+        }
+        n += 1;
+    }
+}
+
 #[derive(Default)]
 pub(crate) struct PlatformHandlers {
     pub(crate) open_urls: Option<Box<dyn FnMut(Vec<String>)>>,
@@ -143,13 +217,22 @@ pub(crate) struct PlatformHandlers {
     pub(crate) will_open_app_menu: Option<Box<dyn FnMut()>>,
     pub(crate) validate_app_menu_command: Option<Box<dyn FnMut(&dyn Action) -> bool>>,
     pub(crate) keyboard_layout_change: Option<Box<dyn FnMut()>>,
+    pub(crate) foreign_toplevel_event: Option<Box<dyn FnMut(ForeignToplevelEvent)>>,
+    pub(crate) displays_changed: Option<Box<dyn FnMut()>>,
+    pub(crate) accent_color_changed: Option<Box<dyn FnMut()>>,
+    pub(crate) accessibility_preferences_changed: Option<Box<dyn FnMut()>>,
 }
 
 pub(crate) struct LinuxCommon {
     pub(crate) background_executor: BackgroundExecutor,
     pub(crate) foreground_executor: ForegroundExecutor,
     pub(crate) text_system: Arc<dyn PlatformTextSystem>,
+    /// The same text system as `text_system`, for applying the desktop's font settings.
+    #[cfg(any(feature = "wayland", feature = "x11"))]
+    pub(crate) cosmic_text_system: Arc<crate::linux::CosmicTextSystem>,
     pub(crate) appearance: WindowAppearance,
+    pub(crate) accent_color: Option<Hsla>,
+    pub(crate) accessibility_preferences: AccessibilityPreferences,
     pub(crate) auto_hide_scrollbars: bool,
     pub(crate) button_layout: WindowButtonLayout,
     pub(crate) callbacks: PlatformHandlers,
@@ -160,15 +243,41 @@ pub(crate) struct LinuxCommon {
     processing_menu_activation: bool,
     pub(crate) global_menu: Option<crate::linux::dbus_menu::Bridge>,
     last_registered_global_menu_window: std::sync::Mutex<Option<Option<u64>>>,
+    pub(crate) instance_request_sender: calloop::channel::Sender<InstanceRequest>,
+    pub(crate) single_instance: Option<SingleInstance>,
+    pub(crate) tray: crate::linux::Tray,
+    /// The dock menu's actions, along with the desktop action ids they're exported under.
+    pub(crate) dock_menu_actions: Vec<(String, Box<dyn Action>)>,
+    pub(crate) launcher_entry: crate::linux::LauncherEntry,
+    pub(crate) power_monitor: crate::linux::PowerMonitor,
+    pub(crate) window_tabs: crate::linux::WindowTabs,
+    #[cfg(any(feature = "wayland", feature = "x11"))]
+    pub(crate) notifications: crate::linux::Notifications,
+    #[cfg(any(feature = "wayland", feature = "x11"))]
+    pub(crate) session_inhibitor: crate::linux::SessionInhibitor,
+    #[cfg(feature = "x11")]
+    pub(crate) session_manager: crate::linux::SessionManager,
+    #[cfg(any(feature = "wayland", feature = "x11"))]
+    pub(crate) global_shortcuts: crate::linux::GlobalShortcuts,
 }
 
 impl LinuxCommon {
-    pub fn new(signal: LoopSignal) -> (Self, PriorityQueueCalloopReceiver<RunnableVariant>, calloop::channel::Channel<crate::linux::dbus_menu::BridgeEvent>) {
+    pub fn new(
+        signal: LoopSignal,
+    ) -> (
+        Self,
+        PriorityQueueCalloopReceiver<RunnableVariant>,
+        calloop::channel::Channel<crate::linux::dbus_menu::BridgeEvent>,
+        calloop::channel::Channel<InstanceRequest>,
+    ) {
         let (main_sender, main_receiver) = PriorityQueueCalloopReceiver::new();
         let (bridge_sender, bridge_receiver) = calloop::channel::channel();
+        let (instance_request_sender, instance_request_receiver) = calloop::channel::channel();
 
         #[cfg(any(feature = "wayland", feature = "x11"))]
-        let text_system = Arc::new(crate::linux::CosmicTextSystem::new("IBM Plex Sans"));
+        let cosmic_text_system = Arc::new(crate::linux::CosmicTextSystem::new("IBM Plex Sans"));
+        #[cfg(any(feature = "wayland", feature = "x11"))]
+        let text_system = cosmic_text_system.clone();
         #[cfg(not(any(feature = "wayland", feature = "x11")))]
         let text_system = Arc::new(gpui::NoopTextSystem::new());
 
@@ -177,12 +286,35 @@ impl LinuxCommon {
         let dispatcher = Arc::new(LinuxDispatcher::new(main_sender));
 
         let background_executor = BackgroundExecutor::new(dispatcher.clone());
+        let foreground_executor = ForegroundExecutor::new(dispatcher);
 
         let mut common = LinuxCommon {
-            background_executor,
-            foreground_executor: ForegroundExecutor::new(dispatcher),
+            background_executor: background_executor.clone(),
+            #[cfg(any(feature = "wayland", feature = "x11"))]
+            notifications: crate::linux::Notifications::new(foreground_executor.clone()),
+            #[cfg(any(feature = "wayland", feature = "x11"))]
+            session_inhibitor: crate::linux::SessionInhibitor::new(
+                foreground_executor.clone(),
+                background_executor.clone(),
+            ),
+            #[cfg(feature = "x11")]
+            session_manager: crate::linux::SessionManager::new(
+                foreground_executor.clone(),
+                signal.clone(),
+            ),
+            #[cfg(any(feature = "wayland", feature = "x11"))]
+            global_shortcuts: crate::linux::GlobalShortcuts::new(foreground_executor.clone()),
+            tray: crate::linux::Tray::new(foreground_executor.clone()),
+            launcher_entry: crate::linux::LauncherEntry::new(foreground_executor.clone()),
+            power_monitor: crate::linux::PowerMonitor::new(foreground_executor.clone()),
+            window_tabs: crate::linux::WindowTabs::new(foreground_executor.clone()),
+            foreground_executor,
             text_system,
+            #[cfg(any(feature = "wayland", feature = "x11"))]
+            cosmic_text_system,
             appearance: WindowAppearance::Light,
+            accent_color: None,
+            accessibility_preferences: AccessibilityPreferences::default(),
             auto_hide_scrollbars: false,
             button_layout: WindowButtonLayout::linux_default(),
             callbacks,
@@ -193,6 +325,9 @@ impl LinuxCommon {
             processing_menu_activation: false,
             global_menu: None,
             last_registered_global_menu_window: std::sync::Mutex::new(None),
+            instance_request_sender,
+            single_instance: None,
+            dock_menu_actions: Vec::new(),
         };
 
         let on_event = std::sync::Arc::new(move |event| {
@@ -200,7 +335,56 @@ impl LinuxCommon {
         });
         common.global_menu = crate::linux::dbus_menu::Bridge::start(on_event);
 
-        (common, main_receiver, bridge_receiver)
+        (
+            common,
+            main_receiver,
+            bridge_receiver,
+            instance_request_receiver,
+        )
+    }
+
+    /// The dock menu action behind a desktop action id exported by `set_dock_menu`.
+    pub(crate) fn dock_menu_action(&self, action_name: &str) -> Option<Box<dyn Action>> {
+        self.dock_menu_actions
+            .iter()
+            .find(|(id, _)| id == action_name)
+            .map(|(_, action)| action.boxed_clone())
+    }
+
+    /// Runs the callback for a request forwarded from a later launch of the application. `common`
+    /// borrows the client's `LinuxCommon`, which must not be borrowed while the callback runs.
+    pub(crate) fn handle_instance_request<'a>(
+        common: impl Fn() -> RefMut<'a, LinuxCommon>,
+        request: InstanceRequest,
+    ) {
+        if let InstanceRequestKind::ActivateAction(action_name) = &request.kind {
+            let action = common().dock_menu_action(action_name);
+            if let Some(action) = action {
+                let callback = common().callbacks.app_menu_action.take();
+                if let Some(mut callback) = callback {
+                    callback(action.as_ref());
+                    common().callbacks.app_menu_action = Some(callback);
+                }
+                return;
+            }
+            log::info!("Reopening for unsupported application action {action_name:?}");
+        }
+        match request.kind {
+            InstanceRequestKind::Open(uris) => {
+                let callback = common().callbacks.open_urls.take();
+                if let Some(mut callback) = callback {
+                    callback(uris);
+                    common().callbacks.open_urls = Some(callback);
+                }
+            }
+            InstanceRequestKind::Activate | InstanceRequestKind::ActivateAction(_) => {
+                let callback = common().callbacks.reopen.take();
+                if let Some(mut callback) = callback {
+                    callback();
+                    common().callbacks.reopen = Some(callback);
+                }
+            }
+        }
     }
 
     pub(crate) fn register_global_menu_for_window_id(&self, window_id: Option<u64>) -> bool {
@@ -269,11 +453,99 @@ pub(crate) fn dispatch_global_menu_bridge_event<P: LinuxClient>(
     }
 }
 
+/// Converts a gpui menu into the tree exported over DBusMenu, assigning consecutive ids starting
+/// at `next_id` and recording the action behind each id.
+pub(crate) fn remote_menu_node(
+    menu: &Menu,
+    next_id: &mut i32,
+    keymap: &Keymap,
+    actions: &mut std::collections::HashMap<i32, Box<dyn Action>>,
+) -> RemoteMenuNode {
+    let id = *next_id;
+    *next_id += 1;
+    RemoteMenuNode {
+        id,
+        label: menu.name.to_string(),
+        enabled: true,
+        is_separator: false,
+        shortcut: None,
+        children: remote_menu_items(&menu.items, next_id, keymap, actions),
+    }
+}
+
+pub(crate) fn remote_menu_items(
+    items: &[MenuItem],
+    next_id: &mut i32,
+    keymap: &Keymap,
+    actions: &mut std::collections::HashMap<i32, Box<dyn Action>>,
+) -> Vec<RemoteMenuNode> {
+    let mut children = Vec::new();
+    for item in items {
+        match item {
+            MenuItem::Separator => {
+                children.push(RemoteMenuNode {
+                    id: *next_id,
+                    label: String::new(),
+                    enabled: true,
+                    is_separator: true,
+                    shortcut: None,
+                    children: Vec::new(),
+                });
+                *next_id += 1;
+            }
+            MenuItem::Action {
+                name,
+                action,
+                checked: _,
+                disabled,
+                ..
+            } => {
+                let shortcut = keymap
+                    .bindings_for_action(action.as_ref())
+                    .next()
+                    .map(|binding| {
+                        binding
+                            .keystrokes()
+                            .iter()
+                            .map(|keystroke| keystroke.to_string())
+                            .collect::<Vec<_>>()
+                            .join("+")
+                    });
+                actions.insert(*next_id, action.boxed_clone());
+                children.push(RemoteMenuNode {
+                    id: *next_id,
+                    label: name.to_string(),
+                    enabled: !disabled,
+                    is_separator: false,
+                    shortcut,
+                    children: Vec::new(),
+                });
+                *next_id += 1;
+            }
+            MenuItem::Submenu(submenu) => {
+                children.push(remote_menu_node(submenu, next_id, keymap, actions));
+            }
+            MenuItem::SystemMenu(_) => {}
+        }
+    }
+    children
+}
+
+/// The name of the running executable, which identifies the application to desktop services.
+pub(crate) fn application_name() -> String {
+    env::current_exe()
+        .ok()
+        .as_deref()
+        .and_then(|path| path.file_name())
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
 pub(crate) struct LinuxPlatform<P> {
     pub(crate) inner: P,
 }
 
-impl<P: LinuxClient + 'static> Platform for LinuxPlatform<P> {
+impl<P: LinuxClient + Clone + 'static> Platform for LinuxPlatform<P> {
     fn background_executor(&self) -> BackgroundExecutor {
         self.inner
             .with_common(|common| common.background_executor.clone())
@@ -301,15 +573,49 @@ impl<P: LinuxClient + 'static> Platform for LinuxPlatform<P> {
             .with_common(|common| common.callbacks.keyboard_layout_change = Some(callback));
     }
 
-    fn on_thermal_state_change(&self, _callback: Box<dyn FnMut()>) {}
+    fn on_displays_changed(&self, callback: Box<dyn FnMut()>) {
+        self.inner
+            .with_common(|common| common.callbacks.displays_changed = Some(callback));
+    }
+
+    fn on_thermal_state_change(&self, callback: Box<dyn FnMut()>) {
+        self.inner
+            .with_common(|common| common.power_monitor.on_thermal_state_change(callback))
+    }
 
     fn thermal_state(&self) -> ThermalState {
-        ThermalState::Nominal
+        self.inner
+            .with_common(|common| common.power_monitor.thermal_state())
+    }
+
+    fn on_power_state_change(&self, callback: Box<dyn FnMut()>) {
+        self.inner
+            .with_common(|common| common.power_monitor.on_power_state_change(callback))
+    }
+
+    fn power_state(&self) -> PowerState {
+        self.inner
+            .with_common(|common| common.power_monitor.power_state())
     }
 
     fn run(&self, on_finish_launching: Box<dyn FnOnce()>) {
         on_finish_launching();
 
+        // Desktop actions launch the application with the action's id, which a running instance
+        // receives as a request. Without one, this launch has to perform it itself.
+        if let Some(action_name) = crate::linux::launch_action_name() {
+            let dispatch = self.inner.with_common(|common| {
+                let action = common.dock_menu_action(&action_name)?;
+                let callback = common.callbacks.app_menu_action.take()?;
+                Some((action, callback))
+            });
+            if let Some((action, mut callback)) = dispatch {
+                callback(action.as_ref());
+                self.inner
+                    .with_common(|common| common.callbacks.app_menu_action = Some(callback));
+            }
+        }
+
         LinuxClient::run(&self.inner);
 
         let quit = self
@@ -328,6 +634,120 @@ impl<P: LinuxClient + 'static> Platform for LinuxPlatform<P> {
         self.inner.compositor_name()
     }
 
+    #[cfg(any(feature = "wayland", feature = "x11"))]
+    fn show_notification(
+        &self,
+        id: NotificationId,
+        notification: &Notification,
+    ) -> Task<Result<()>> {
+        self.inner
+            .with_common(|common| common.notifications.show(id, notification))
+    }
+
+    #[cfg(any(feature = "wayland", feature = "x11"))]
+    fn withdraw_notification(&self, id: NotificationId) {
+        self.inner
+            .with_common(|common| common.notifications.withdraw(id))
+    }
+
+    #[cfg(any(feature = "wayland", feature = "x11"))]
+    fn on_notification_response(
+        &self,
+        callback: Box<dyn FnMut(NotificationId, NotificationResponse)>,
+    ) {
+        self.inner
+            .with_common(|common| common.notifications.on_response(callback))
+    }
+
+    #[cfg(any(feature = "wayland", feature = "x11"))]
+    fn set_global_shortcuts(&self, shortcuts: Vec<GlobalShortcut>) {
+        self.inner.set_global_shortcuts(shortcuts)
+    }
+
+    #[cfg(any(feature = "wayland", feature = "x11"))]
+    fn on_global_shortcut(&self, callback: Box<dyn FnMut(GlobalShortcutId, GlobalShortcutPhase)>) {
+        self.inner
+            .with_common(|common| common.global_shortcuts.on_event(callback))
+    }
+
+    #[cfg(any(feature = "wayland", feature = "x11"))]
+    fn inhibit(&self, id: InhibitorId, flags: InhibitFlags, reason: &str) {
+        self.inner
+            .with_common(|common| common.session_inhibitor.inhibit(id, flags, reason))
+    }
+
+    #[cfg(any(feature = "wayland", feature = "x11"))]
+    fn uninhibit(&self, id: InhibitorId) {
+        self.inner
+            .with_common(|common| common.session_inhibitor.uninhibit(id))
+    }
+
+    #[cfg(any(feature = "wayland", feature = "x11"))]
+    fn on_session_end_query(&self, callback: Box<dyn FnMut()>) {
+        self.inner
+            .with_common(|common| common.session_inhibitor.on_session_end_query(callback))
+    }
+
+    #[cfg(feature = "x11")]
+    fn on_save_session(&self, callback: Box<dyn FnMut()>) {
+        self.inner
+            .with_common(|common| common.session_manager.on_save_session(callback))
+    }
+
+    #[cfg(feature = "x11")]
+    fn launched_by_session_manager(&self) -> bool {
+        self.inner
+            .with_common(|common| common.session_manager.launched_by_session_manager())
+    }
+
+    fn set_tray_icon(&self, tray_icon: Option<TrayIcon>, keymap: &Keymap) -> Result<()> {
+        self.inner
+            .with_common(|common| common.tray.set(tray_icon, keymap))
+    }
+
+    fn on_tray_event(&self, callback: Box<dyn FnMut(TrayEvent)>) {
+        self.inner
+            .with_common(|common| common.tray.on_event(callback))
+    }
+
+    fn on_tray_menu_action(&self, callback: Box<dyn FnMut(&dyn Action)>) {
+        self.inner
+            .with_common(|common| common.tray.on_menu_action(callback))
+    }
+
+    fn claim_single_instance(&self, app_id: &str) -> Result<SingleInstanceClaim> {
+        let sender = self
+            .inner
+            .with_common(|common| common.instance_request_sender.clone());
+        let Some(single_instance) = crate::linux::claim_single_instance(app_id, sender)? else {
+            return Ok(SingleInstanceClaim::Forwarded);
+        };
+        self.inner
+            .with_common(|common| common.single_instance = Some(single_instance));
+        Ok(SingleInstanceClaim::Primary)
+    }
+
+    fn unlock_session(&self) {
+        self.inner.unlock_session()
+    }
+
+    fn foreign_toplevels(&self) -> Vec<ForeignToplevel> {
+        self.inner.foreign_toplevels()
+    }
+
+    fn on_foreign_toplevel_event(&self, callback: Box<dyn FnMut(ForeignToplevelEvent)>) {
+        self.inner
+            .with_common(|common| common.callbacks.foreign_toplevel_event = Some(callback));
+    }
+
+    fn request_foreign_toplevel(
+        &self,
+        id: ForeignToplevelId,
+        request: ForeignToplevelRequest,
+    ) -> Result<()> {
+        self.inner.request_foreign_toplevel(id, request)
+    }
+
     fn restart(&self, binary_path: Option<PathBuf>) {
         use std::os::unix::process::CommandExt as _;
 
@@ -368,6 +788,7 @@ impl<P: LinuxClient + 'static> Platform for LinuxPlatform<P> {
             .arg(script)
             .arg(&app_pid)
             .arg(&app_path)
+            .env_remove(AUTOSTART_ID_VARIABLE)
             .process_group(0)
             .spawn();
 
@@ -414,18 +835,40 @@ impl<P: LinuxClient + 'static> Platform for LinuxPlatform<P> {
     }
 
     fn active_window(&self) -> Option<AnyWindowHandle> {
-        self.inner.active_window()
+        let window_tabs = self.inner.with_common(|common| common.window_tabs.clone());
+        self.inner
+            .active_window()
+            .map(|handle| window_tabs.selected_tab(handle))
     }
 
     fn window_stack(&self) -> Option<Vec<AnyWindowHandle>> {
-        self.inner.window_stack()
+        let window_tabs = self.inner.with_common(|common| common.window_tabs.clone());
+        self.inner.window_stack().map(|stack| {
+            stack
+                .into_iter()
+                .map(|handle| window_tabs.selected_tab(handle))
+                .collect()
+        })
     }
 
     fn open_window(
         &self,
         handle: AnyWindowHandle,
-        options: WindowParams,
+        mut options: WindowParams,
     ) -> anyhow::Result<Box<dyn PlatformWindow>> {
+        let window_tabs = self.inner.with_common(|common| common.window_tabs.clone());
+        // Popups are attached to the native window their parent is shown in.
+        if let WindowKind::AnchoredPopUp(popup) = &mut options.kind {
+            popup.parent = window_tabs.native_window(popup.parent);
+        }
+        if options.kind == WindowKind::Normal && options.tabbing_identifier.is_some() {
+            let inner = self.inner.clone();
+            return window_tabs.open_window(
+                handle,
+                options,
+                Rc::new(move |handle, options| inner.open_window(handle, options)),
+            );
+        }
         self.inner.open_window(handle, options)
     }
 
@@ -576,6 +1019,7 @@ impl<P: LinuxClient + 'static> Platform for LinuxPlatform<P> {
             .spawn(async move {
                 let _ = new_command("xdg-open")
                     .arg(path)
+                    .env_remove(AUTOSTART_ID_VARIABLE)
                     .spawn()
                     .context("invoking xdg-open")
                     .log_err()?
@@ -631,93 +1075,16 @@ impl<P: LinuxClient + 'static> Platform for LinuxPlatform<P> {
             common.menu_actions.clear();
 
             if let Some(bridge) = &common.global_menu {
-                use crate::linux::dbus_menu::{MenuSnapshot, RemoteMenuNode};
                 let mut next_id = 1;
-                let mut root_children = Vec::new();
-
-                fn convert_menu(
-                    menu: &Menu,
-                    next_id: &mut i32,
-                    keymap: &Keymap,
-                    actions: &mut std::collections::HashMap<i32, Box<dyn Action>>,
-                ) -> RemoteMenuNode {
-                    let id = *next_id;
-                    *next_id += 1;
-                    
-                    let mut children = Vec::new();
-                    for item in &menu.items {
-                        match item {
-                            MenuItem::Separator => {
-                                children.push(RemoteMenuNode {
-                                    id: *next_id,
-                                    label: String::new(),
-                                    enabled: true,
-                                    is_separator: true,
-                                    shortcut: None,
-                                    children: Vec::new(),
-                                });
-                                *next_id += 1;
-                            }
-                            MenuItem::Action {
-                                name,
-                                action,
-                                checked: _,
-                                disabled,
-                                ..
-                            } => {
-                                let shortcut = keymap.bindings_for_action(action.as_ref())
-                                    .next()
-                                    .map(|b| {
-                                        let mut parts = Vec::new();
-                                        for k in b.keystrokes() {
-                                            parts.push(k.to_string());
-                                        }
-                                        parts.join("+")
-                                    });
-                                let enabled = !disabled;
-
-                                actions.insert(*next_id, action.boxed_clone());
-                                children.push(RemoteMenuNode {
-                                    id: *next_id,
-                                    label: name.to_string(),
-                                    enabled,
-                                    is_separator: false,
-                                    shortcut,
-                                    children: Vec::new(),
-                                });
-                                *next_id += 1;
-                            }
-                            MenuItem::Submenu(submenu) => {
-                                children.push(convert_menu(
-                                    submenu,
-                                    next_id,
-                                    keymap,
-                                    actions,
-                                ));
-                            }
-                            MenuItem::SystemMenu(_) => {}
-                        }
-                    }
-                    RemoteMenuNode {
-                        id,
-                        label: menu.name.to_string(),
-                        enabled: true,
-                        is_separator: false,
-                        shortcut: None,
-                        children,
-                    }
-                }
-
-                for menu in &menus {
-                    root_children.push(convert_menu(
-                        menu,
-                        &mut next_id,
-                        keymap,
-                        &mut common.menu_actions,
-                    ));
-                }
-
-                bridge.update_menu(MenuSnapshot { entries: root_children });
+                let root_children = menus
+                    .iter()
+                    .map(|menu| {
+                        remote_menu_node(menu, &mut next_id, keymap, &mut common.menu_actions)
+                    })
+                    .collect();
+                bridge.update_menu(MenuSnapshot {
+                    entries: root_children,
+                });
             }
 
             common.menus = menus.into_iter().map(|m| m.owned()).collect();
@@ -729,8 +1096,64 @@ impl<P: LinuxClient + 'static> Platform for LinuxPlatform<P> {
         self.inner.with_common(|common| Some(common.menus.clone()))
     }
 
-    fn set_dock_menu(&self, _menu: Vec<MenuItem>, _keymap: &Keymap) {
-        // todo(linux)
+    fn set_dock_menu(&self, menu: Vec<MenuItem>, _keymap: &Keymap) {
+        // Launchers only show the dock menu's top level as desktop actions, which have no notion
+        // of submenus, separators or disabled entries.
+        let mut desktop_actions = Vec::new();
+        let mut actions = Vec::new();
+        for item in menu {
+            if let MenuItem::Action {
+                name,
+                action,
+                disabled: false,
+                ..
+            } = item
+            {
+                let id = dock_action_id(action.as_ref(), &desktop_actions);
+                desktop_actions.push((id.clone(), name.to_string()));
+                actions.push((id, action));
+            }
+        }
+        self.inner
+            .with_common(|common| common.dock_menu_actions = actions);
+        self.background_executor()
+            .spawn(async move {
+                crate::linux::desktop_entry::set_desktop_actions(
+                    DOCK_ACTION_PREFIX,
+                    &desktop_actions,
+                )
+                .context("Failed to export the dock menu as desktop actions")
+                .log_err();
+            })
+            .detach();
+    }
+
+    fn perform_dock_menu_action(&self, action: usize) {
+        let dispatch = self.inner.with_common(|common| {
+            let action = common.dock_menu_actions.get(action)?.1.boxed_clone();
+            let callback = common.callbacks.app_menu_action.take()?;
+            Some((action, callback))
+        });
+        if let Some((action, mut callback)) = dispatch {
+            callback(action.as_ref());
+            self.inner
+                .with_common(|common| common.callbacks.app_menu_action = Some(callback));
+        }
+    }
+
+    fn set_dock_badge(&self, badge: Option<u32>) {
+        self.inner
+            .with_common(|common| common.launcher_entry.set_badge(badge))
+    }
+
+    fn set_dock_progress(&self, progress: Option<f32>) {
+        self.inner
+            .with_common(|common| common.launcher_entry.set_progress(progress))
+    }
+
+    fn set_dock_urgent(&self, urgent: bool) {
+        self.inner
+            .with_common(|common| common.launcher_entry.set_urgent(urgent))
     }
 
     fn path_for_auxiliary_executable(&self, _name: &str) -> Result<PathBuf> {
@@ -825,12 +1248,61 @@ impl<P: LinuxClient + 'static> Platform for LinuxPlatform<P> {
         self.inner.with_common(|common| common.appearance)
     }
 
+    fn accent_color(&self) -> Option<Hsla> {
+        self.inner.with_common(|common| common.accent_color)
+    }
+
+    fn on_accent_color_change(&self, callback: Box<dyn FnMut()>) {
+        self.inner
+            .with_common(|common| common.callbacks.accent_color_changed = Some(callback));
+    }
+
+    fn accessibility_preferences(&self) -> AccessibilityPreferences {
+        self.inner
+            .with_common(|common| common.accessibility_preferences)
+    }
+
+    fn on_accessibility_preferences_change(&self, callback: Box<dyn FnMut()>) {
+        self.inner.with_common(|common| {
+            common.callbacks.accessibility_preferences_changed = Some(callback)
+        });
+    }
+
     fn button_layout(&self) -> Option<WindowButtonLayout> {
         Some(self.inner.with_common(|common| common.button_layout))
     }
 
-    fn register_url_scheme(&self, _: &str) -> Task<anyhow::Result<()>> {
-        Task::ready(Err(anyhow!("register_url_scheme unimplemented")))
+    fn drag_threshold(&self) -> Pixels {
+        self.inner.drag_threshold()
+    }
+
+    fn register_url_scheme(&self, scheme: &str) -> Task<anyhow::Result<()>> {
+        let scheme = scheme.to_string();
+        self.background_executor()
+            .spawn(async move { crate::linux::desktop_entry::register_url_scheme(&scheme) })
+    }
+
+    #[cfg(any(feature = "wayland", feature = "x11"))]
+    fn set_autostart(&self, enabled: bool, args: &[String]) -> Task<anyhow::Result<()>> {
+        self.foreground_executor()
+            .spawn(crate::linux::autostart::set_autostart(
+                enabled,
+                args.to_vec(),
+            ))
+    }
+
+    #[cfg(any(feature = "wayland", feature = "x11"))]
+    fn autostart_enabled(&self) -> bool {
+        crate::linux::autostart::autostart_enabled()
+    }
+
+    #[cfg(any(feature = "wayland", feature = "x11"))]
+    fn print_pdf(&self, options: &PrintOptions, pdf: Vec<u8>) -> Task<anyhow::Result<()>> {
+        let identifier = self.inner.window_identifier();
+        let options = options.clone();
+        self.foreground_executor().spawn(async move {
+            crate::linux::print::print_pdf(identifier.await, options, pdf).await
+        })
     }
 
     fn write_to_primary(&self, item: ClipboardItem) {
@@ -849,7 +1321,22 @@ impl<P: LinuxClient + 'static> Platform for LinuxPlatform<P> {
         self.inner.read_from_clipboard()
     }
 
-    fn add_recent_document(&self, _path: &Path) {}
+    fn add_recent_document(&self, path: &Path) {
+        let path = path.to_path_buf();
+        self.background_executor()
+            .spawn(async move {
+                crate::linux::recent_documents::add_recent_document(&path)
+                    .context("Failed to add recent document")
+                    .log_err();
+            })
+            .detach();
+    }
+
+    fn recent_documents(&self) -> Vec<PathBuf> {
+        crate::linux::recent_documents::recent_documents()
+            .log_err()
+            .unwrap_or_default()
+    }
 }
 
 #[cfg(any(feature = "wayland", feature = "x11"))]
@@ -866,6 +1353,7 @@ pub(super) fn open_uri_internal(
                     if let Some(token) = activation_token.as_ref() {
                         command.env("XDG_ACTIVATION_TOKEN", token);
                     }
+                    command.env_remove(AUTOSTART_ID_VARIABLE);
                     let program = format!("{:?}", command.get_program());
                     match smol::process::Command::from(command).spawn() {
                         Ok(mut cmd) => match cmd.status().await {
@@ -922,10 +1410,18 @@ pub(super) fn reveal_path_internal(
                     Ok(_) => return,
                     Err(e) => log::error!("Failed to open with dbus: {}", e),
                 }
-                if path.is_dir() {
-                    open::that_detached(path).log_err();
+                let dir = if path.is_dir() {
+                    path.as_path()
                 } else {
-                    open::that_detached(path.parent().unwrap_or(Path::new(""))).log_err();
+                    path.parent().unwrap_or(Path::new(""))
+                };
+                for mut command in open::commands(dir) {
+                    command.env_remove(AUTOSTART_ID_VARIABLE);
+                    if let Some(mut child) = smol::process::Command::from(command).spawn().log_err()
+                    {
+                        child.status().await.log_err();
+                        return;
+                    }
                 }
             }
         })
@@ -933,9 +1429,27 @@ pub(super) fn reveal_path_internal(
 }
 
 #[cfg(any(feature = "wayland", feature = "x11"))]
-pub(super) fn is_within_click_distance(a: Point<Pixels>, b: Point<Pixels>) -> bool {
+pub(super) fn is_within_click_distance(
+    a: Point<Pixels>,
+    b: Point<Pixels>,
+    max_distance: Pixels,
+) -> bool {
     let diff = a - b;
-    diff.x.abs() <= DOUBLE_CLICK_DISTANCE && diff.y.abs() <= DOUBLE_CLICK_DISTANCE
+    diff.x.abs() <= max_distance && diff.y.abs() <= max_distance
+}
+
+/// Converts a timestamp read from `clock` into an [`Instant`] by measuring how long ago it was.
+#[cfg(any(feature = "wayland", feature = "x11"))]
+pub(super) fn instant_from_clock(clock: libc::clockid_t, timestamp: Duration) -> Option<Instant> {
+    let mut now = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    if unsafe { libc::clock_gettime(clock, &mut now) } != 0 {
+        return None;
+    }
+    let now = Duration::new(now.tv_sec as u64, now.tv_nsec as u32);
+    Instant::now().checked_sub(now.saturating_sub(timestamp))
 }
 
 #[cfg(any(feature = "wayland", feature = "x11"))]
@@ -972,7 +1486,7 @@ pub(super) unsafe fn read_fd(fd: filedescriptor::FileDescriptor) -> Result<Vec<u
 pub(super) const DEFAULT_CURSOR_ICON_NAME: &str = "left_ptr";
 
 #[cfg(any(feature = "wayland", feature = "x11"))]
-pub(super) fn cursor_style_to_icon_names(style: CursorStyle) -> &'static [&'static str] {
+pub(super) fn cursor_style_to_icon_names(style: &CursorStyle) -> &'static [&'static str] {
     // Based on cursor names from chromium:
     // https://github.com/chromium/chromium/blob/d3069cf9c973dc3627fa75f64085c6a86c8f41bf/ui/base/cursor/cursor_factory.cc#L113
     match style {
@@ -997,6 +1511,75 @@ pub(super) fn cursor_style_to_icon_names(style: CursorStyle) -> &'static [&'stat
         CursorStyle::DragLink => &["alias"],
         CursorStyle::DragCopy => &["copy"],
         CursorStyle::ContextualMenu => &["context-menu"],
+        CursorStyle::Custom { .. } | CursorStyle::Animated { .. } => &[DEFAULT_CURSOR_ICON_NAME],
+    }
+}
+
+/// One frame of an application-provided cursor, resampled for a display's scale factor.
+///
+/// `data` holds premultiplied 32-bit ARGB pixels in native byte order, which is the layout both
+/// `wl_shm`'s `argb8888` format and the X Render extension's standard ARGB32 picture format use.
+#[cfg(any(feature = "wayland", feature = "x11"))]
+pub(super) struct ScaledCursorFrame {
+    pub width: u32,
+    pub height: u32,
+    pub hotspot: (u32, u32),
+    pub data: Vec<u8>,
+    pub duration: Duration,
+}
+
+/// Resamples the images of a `Custom` or `Animated` cursor style for the given scale factor.
+/// Returns no frames for styles that come from the cursor theme.
+#[cfg(any(feature = "wayland", feature = "x11"))]
+pub(super) fn scaled_cursor_frames(style: &CursorStyle, scale: f32) -> Vec<ScaledCursorFrame> {
+    match style {
+        CursorStyle::Custom { image, hotspot } => {
+            vec![scale_cursor_frame(image, *hotspot, Duration::ZERO, scale)]
+        }
+        CursorStyle::Animated { frames } => frames
+            .iter()
+            .map(|frame| scale_cursor_frame(&frame.image, frame.hotspot, frame.duration, scale))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(any(feature = "wayland", feature = "x11"))]
+fn scale_cursor_frame(
+    image: &image::RgbaImage,
+    hotspot: Point<Pixels>,
+    duration: Duration,
+    scale: f32,
+) -> ScaledCursorFrame {
+    let width = ((image.width() as f32 * scale).round() as u32).max(1);
+    let height = ((image.height() as f32 * scale).round() as u32).max(1);
+    let resized;
+    let image = if (width, height) == image.dimensions() {
+        image
+    } else {
+        resized =
+            image::imageops::resize(image, width, height, image::imageops::FilterType::Triangle);
+        &resized
+    };
+
+    let premultiply = |channel: u8, alpha: u8| ((channel as u16 * alpha as u16 + 127) / 255) as u8;
+    let data = image
+        .pixels()
+        .flat_map(|pixel| {
+            let [r, g, b, a] = pixel.0;
+            u32::from_be_bytes([a, premultiply(r, a), premultiply(g, a), premultiply(b, a)])
+                .to_ne_bytes()
+        })
+        .collect();
+
+    let hotspot_x = (f32::from(hotspot.x) * scale).round().max(0.) as u32;
+    let hotspot_y = (f32::from(hotspot.y) * scale).round().max(0.) as u32;
+    ScaledCursorFrame {
+        width,
+        height,
+        hotspot: (hotspot_x.min(width - 1), hotspot_y.min(height - 1)),
+        data,
+        duration,
     }
 }
 
@@ -1193,6 +1776,42 @@ pub(super) fn keystroke_from_xkb(
     }
 }
 
+/// Returns the keysym for a key as named in keystrokes, the reverse of [`keystroke_from_xkb`]
+/// for keys whose name doesn't depend on the keyboard layout.
+#[cfg(any(feature = "wayland", feature = "x11"))]
+pub(crate) fn keysym_for_key(key: &str) -> Option<Keysym> {
+    let keysym = match key {
+        "enter" => Keysym::Return,
+        "pageup" => Keysym::Prior,
+        "pagedown" => Keysym::Next,
+        "space" => Keysym::space,
+        "backspace" => Keysym::BackSpace,
+        "tab" => Keysym::Tab,
+        "delete" => Keysym::Delete,
+        "escape" => Keysym::Escape,
+        "left" => Keysym::Left,
+        "right" => Keysym::Right,
+        "up" => Keysym::Up,
+        "down" => Keysym::Down,
+        "home" => Keysym::Home,
+        "end" => Keysym::End,
+        "insert" => Keysym::Insert,
+        "," => Keysym::comma,
+        "." => Keysym::period,
+        "/" => Keysym::slash,
+        ";" => Keysym::semicolon,
+        "'" => Keysym::apostrophe,
+        "[" => Keysym::bracketleft,
+        "]" => Keysym::bracketright,
+        "\\" => Keysym::backslash,
+        "`" => Keysym::grave,
+        "-" => Keysym::minus,
+        "=" => Keysym::equal,
+        _ => xkb::keysym_from_name(key, xkb::KEYSYM_CASE_INSENSITIVE),
+    };
+    (keysym != Keysym::NoSymbol).then_some(keysym)
+}
+
 /**
  * Returns which symbol the dead key represents
  * <https://developer.mozilla.org/en-US/docs/Web/API/UI_Events/Keyboard_event_key_values#dead_keycodes_for_linux>
@@ -1316,23 +1935,68 @@ pub(super) fn compositor_gpu_hint_from_dev_t(dev: u64) -> Option<gpui_wgpu::Comp
 #[cfg(test)]
 mod tests {
     use super::*;
-    use gpui::{Point, px};
+    use gpui::{NoAction, Point, px};
+
+    #[test]
+    fn test_dock_action_id() {
+        let id = dock_action_id(&NoAction, &[]);
+        assert_eq!(id, "dock-zed--NoAction");
+        assert_eq!(
+            dock_action_id(&NoAction, &[(id, "No Action".to_string())]),
+            "dock-zed--NoAction-2"
+        );
+    }
 
     #[test]
     fn test_is_within_click_distance() {
         let zero = Point::new(px(0.0), px(0.0));
-        assert!(is_within_click_distance(zero, Point::new(px(5.0), px(5.0))));
         assert!(is_within_click_distance(
             zero,
-            Point::new(px(-4.9), px(5.0))
+            Point::new(px(5.0), px(5.0)),
+            DOUBLE_CLICK_DISTANCE
+        ));
+        assert!(is_within_click_distance(
+            zero,
+            Point::new(px(-4.9), px(5.0)),
+            DOUBLE_CLICK_DISTANCE
         ));
         assert!(is_within_click_distance(
             Point::new(px(3.0), px(2.0)),
-            Point::new(px(-2.0), px(-2.0))
+            Point::new(px(-2.0), px(-2.0)),
+            DOUBLE_CLICK_DISTANCE
         ));
         assert!(!is_within_click_distance(
             zero,
-            Point::new(px(5.0), px(5.1))
+            Point::new(px(5.0), px(5.1)),
+            DOUBLE_CLICK_DISTANCE
         ),);
+        assert!(!is_within_click_distance(
+            zero,
+            Point::new(px(3.0), px(0.0)),
+            px(2.0)
+        ));
+    }
+
+    #[cfg(any(feature = "wayland", feature = "x11"))]
+    #[test]
+    fn test_scaled_cursor_frames() {
+        let image = image::RgbaImage::from_pixel(4, 4, image::Rgba([255, 128, 0, 128]));
+        let style = CursorStyle::custom(image, Point::new(px(1.0), px(3.0)));
+
+        let frames = scaled_cursor_frames(&style, 1.0);
+        assert_eq!(frames.len(), 1);
+        assert_eq!((frames[0].width, frames[0].height), (4, 4));
+        assert_eq!(frames[0].hotspot, (1, 3));
+        assert_eq!(
+            u32::from_ne_bytes(frames[0].data[..4].try_into().unwrap()),
+            0x80804000
+        );
+
+        let frames = scaled_cursor_frames(&style, 2.0);
+        assert_eq!((frames[0].width, frames[0].height), (8, 8));
+        assert_eq!(frames[0].hotspot, (2, 6));
+        assert_eq!(frames[0].data.len(), 8 * 8 * 4);
+
+        assert!(scaled_cursor_frames(&CursorStyle::Arrow, 2.0).is_empty());
     }
 }
@@ -0,0 +1,216 @@
+//! Battery, AC, power profile and thermal state from UPower and power-profiles-daemon.
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use anyhow::Result;
+use futures::{StreamExt as _, stream};
+use gpui::{ForegroundExecutor, PowerState, ThermalState};
+use util::TryFutureExt as _;
+
+const UPOWER_SERVICE: &str = "org.freedesktop.UPower";
+const UPOWER_PATH: &str = "/org/freedesktop/UPower";
+const DISPLAY_DEVICE_PATH: &str = "/org/freedesktop/UPower/devices/DisplayDevice";
+const DEVICE_INTERFACE: &str = "org.freedesktop.UPower.Device";
+
+/// power-profiles-daemon took over UPower's namespace in 0.20, older releases only own the
+/// `net.hadess` name.
+const POWER_PROFILES_SERVICES: [(&str, &str); 2] = [
+    (
+        "org.freedesktop.UPower.PowerProfiles",
+        "/org/freedesktop/UPower/PowerProfiles",
+    ),
+    ("net.hadess.PowerProfiles", "/net/hadess/PowerProfiles"),
+];
+const POWER_SAVER_PROFILE: &str = "power-saver";
+
+/// `org.freedesktop.UPower.Device` type of batteries.
+const DEVICE_TYPE_BATTERY: u32 = 2;
+
+struct PowerMonitorState {
+    executor: ForegroundExecutor,
+    started: Cell<bool>,
+    power_state: Cell<PowerState>,
+    power_state_callback: RefCell<Option<Box<dyn FnMut()>>>,
+    thermal_state: Cell<ThermalState>,
+    thermal_state_callback: RefCell<Option<Box<dyn FnMut()>>>,
+}
+
+/// Follows the system's power and thermal state, connecting to the system bus once a callback
+/// reporting their changes is registered.
+#[derive(Clone)]
+pub(crate) struct PowerMonitor(Rc<PowerMonitorState>);
+
+impl PowerMonitor {
+    pub fn new(executor: ForegroundExecutor) -> Self {
+        Self(Rc::new(PowerMonitorState {
+            executor,
+            started: Cell::new(false),
+            power_state: Cell::default(),
+            power_state_callback: RefCell::default(),
+            thermal_state: Cell::new(ThermalState::Nominal),
+            thermal_state_callback: RefCell::default(),
+        }))
+    }
+
+    pub fn power_state(&self) -> PowerState {
+        self.0.power_state.get()
+    }
+
+    pub fn on_power_state_change(&self, callback: Box<dyn FnMut()>) {
+        self.0.power_state_callback.replace(Some(callback));
+        self.start();
+    }
+
+    /// Only power-profiles-daemon reports anything besides [`ThermalState::Nominal`], once it
+    /// holds back the performance profile because the machine is too hot.
+    pub fn thermal_state(&self) -> ThermalState {
+        self.0.thermal_state.get()
+    }
+
+    pub fn on_thermal_state_change(&self, callback: Box<dyn FnMut()>) {
+        self.0.thermal_state_callback.replace(Some(callback));
+        self.start();
+    }
+
+    fn start(&self) {
+        if self.0.started.replace(true) {
+            return;
+        }
+        let this = self.clone();
+        self.0
+            .executor
+            .spawn(async move { this.watch().await }.log_err())
+            .detach();
+    }
+
+    async fn watch(&self) -> Result<()> {
+        let connection = zbus::Connection::system().await?;
+        let upower =
+            zbus::Proxy::new(&connection, UPOWER_SERVICE, UPOWER_PATH, UPOWER_SERVICE).await?;
+        let display_device = zbus::Proxy::new(
+            &connection,
+            UPOWER_SERVICE,
+            DISPLAY_DEVICE_PATH,
+            DEVICE_INTERFACE,
+        )
+        .await?;
+        let mut power_profiles = None;
+        for (service, path) in POWER_PROFILES_SERVICES {
+            if let Ok(proxy) = zbus::Proxy::new(&connection, service, path, service).await
+                && proxy.get_property::<String>("ActiveProfile").await.is_ok()
+            {
+                power_profiles = Some(proxy);
+                break;
+            }
+        }
+
+        let mut changes = stream::select_all([
+            upower
+                .receive_property_changed::<bool>("OnBattery")
+                .await
+                .map(drop)
+                .boxed_local(),
+            display_device
+                .receive_property_changed::<bool>("IsPresent")
+                .await
+                .map(drop)
+                .boxed_local(),
+            display_device
+                .receive_property_changed::<f64>("Percentage")
+                .await
+                .map(drop)
+                .boxed_local(),
+        ]);
+        if let Some(power_profiles) = &power_profiles {
+            changes.push(
+                power_profiles
+                    .receive_property_changed::<String>("ActiveProfile")
+                    .await
+                    .map(drop)
+                    .boxed_local(),
+            );
+            changes.push(
+                power_profiles
+                    .receive_property_changed::<String>("PerformanceDegraded")
+                    .await
+                    .map(drop)
+                    .boxed_local(),
+            );
+        }
+
+        loop {
+            let is_battery = display_device
+                .get_property::<bool>("IsPresent")
+                .await
+                .is_ok_and(|is_present| is_present)
+                && display_device
+                    .get_property::<u32>("Type")
+                    .await
+                    .is_ok_and(|kind| kind == DEVICE_TYPE_BATTERY);
+            let battery_level = if is_battery {
+                display_device
+                    .get_property::<f64>("Percentage")
+                    .await
+                    .ok()
+                    .map(|percentage| (percentage / 100.).clamp(0., 1.) as f32)
+            } else {
+                None
+            };
+            let low_power_mode = match &power_profiles {
+                Some(power_profiles) => power_profiles
+                    .get_property::<String>("ActiveProfile")
+                    .await
+                    .is_ok_and(|profile| profile == POWER_SAVER_PROFILE),
+                None => false,
+            };
+            // Empty unless the performance profile is being held back, which happens for
+            // thermal reasons: `high-operating-temperature`, or `lap-detected` to keep the
+            // machine from getting too hot on the user's lap.
+            let performance_degraded = match &power_profiles {
+                Some(power_profiles) => power_profiles
+                    .get_property::<String>("PerformanceDegraded")
+                    .await
+                    .is_ok_and(|reason| !reason.is_empty()),
+                None => false,
+            };
+            let power_state = PowerState {
+                on_battery: upower.get_property("OnBattery").await.unwrap_or(false),
+                low_power_mode,
+                battery_level,
+            };
+            self.update(power_state);
+            self.update_thermal_state(if performance_degraded {
+                ThermalState::Serious
+            } else {
+                ThermalState::Nominal
+            });
+
+            if changes.next().await.is_none() {
+                return Ok(());
+            }
+        }
+    }
+
+    fn update(&self, power_state: PowerState) {
+        if self.0.power_state.replace(power_state) != power_state {
+            notify(&self.0.power_state_callback);
+        }
+    }
+
+    fn update_thermal_state(&self, thermal_state: ThermalState) {
+        if self.0.thermal_state.replace(thermal_state) != thermal_state {
+            notify(&self.0.thermal_state_callback);
+        }
+    }
+}
+
+fn notify(callback: &RefCell<Option<Box<dyn FnMut()>>>) {
+    let taken = callback.borrow_mut().take();
+    if let Some(mut taken) = taken {
+        taken();
+        callback.borrow_mut().get_or_insert(taken);
+    }
+}
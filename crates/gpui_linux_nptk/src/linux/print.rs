@@ -0,0 +1,85 @@
+//! Printing through the XDG print portal, which shows the desktop's print dialog and hands the
+//! document to the printing system.
+
+use std::{
+    fs::File,
+    io::{Seek as _, Write as _},
+};
+
+use anyhow::{Context as _, Result, anyhow};
+use ashpd::{
+    WindowIdentifier,
+    desktop::print::{Orientation, PageSetup, PrintProxy, Settings},
+};
+use gpui::PrintOptions;
+
+const PRINT_PORTAL_MISSING: &str =
+    "Printing requires xdg-desktop-portal and one of its implementations to be running";
+
+/// Shows the print dialog, defaulting to the paper size and margins the document was laid out
+/// for, then prints `pdf` on the printer the user picked. Cancelling the dialog isn't an error.
+pub(crate) async fn print_pdf(
+    identifier: Option<WindowIdentifier>,
+    options: PrintOptions,
+    pdf: Vec<u8>,
+) -> Result<()> {
+    let proxy = PrintProxy::new().await.map_err(|error| match error {
+        ashpd::Error::PortalNotFound(_) => anyhow!(PRINT_PORTAL_MISSING),
+        error => error.into(),
+    })?;
+
+    let title = if options.title.is_empty() {
+        "Document"
+    } else {
+        options.title.as_ref()
+    };
+    let paper = options.paper_size;
+    let orientation = if paper.is_landscape() {
+        Orientation::Landscape
+    } else {
+        Orientation::Portrait
+    };
+    let settings = Settings::default()
+        .orientation(orientation)
+        .paper_format(paper.pwg_name());
+    // Page setups describe the paper upright and turn it with the orientation.
+    let page_setup = PageSetup::default()
+        .name(paper.pwg_name())
+        .width(paper.width.min(paper.height) as f64)
+        .height(paper.width.max(paper.height) as f64)
+        .margin_top(options.margins.top as f64)
+        .margin_bottom(options.margins.bottom as f64)
+        .margin_left(options.margins.left as f64)
+        .margin_right(options.margins.right as f64)
+        .orientation(orientation);
+
+    let prepared = proxy
+        .prepare_print(identifier.as_ref(), title, settings, page_setup, None, true)
+        .await?
+        .response();
+    let token = match prepared {
+        Ok(prepared) => prepared.token,
+        Err(ashpd::Error::Response(_)) => return Ok(()),
+        Err(error) => return Err(error.into()),
+    };
+
+    let file = smol::unblock(move || document_file(&pdf)).await?;
+    match proxy
+        .print(identifier.as_ref(), title, &file, Some(token), true)
+        .await?
+        .response()
+    {
+        Ok(()) | Err(ashpd::Error::Response(_)) => Ok(()),
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// The portal reads the document from a file descriptor rather than a path. The file is
+/// anonymous and only readable by the user, so it's gone once the portal has read it.
+fn document_file(pdf: &[u8]) -> Result<File> {
+    let mut file = tempfile::tempfile().context("Failed to create a file for the document")?;
+    file.write_all(pdf)
+        .and_then(|()| file.rewind())
+        .context("Failed to write the document")?;
+    Ok(file)
+}
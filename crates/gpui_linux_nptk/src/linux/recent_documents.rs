@@ -0,0 +1,474 @@
+//! The recently used files shared by desktop applications, kept in `recently-used.xbel` as
+//! described by the Desktop Bookmark specification.
+
+use std::{
+    borrow::Cow,
+    fs::{self, File},
+    io,
+    os::fd::AsRawFd as _,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context as _, Result, anyhow, bail};
+use chrono::{DateTime, SecondsFormat, Utc};
+use quick_xml::{
+    Reader, Writer,
+    escape::{escape, unescape},
+    events::{BytesStart, Event},
+};
+use url::Url;
+
+use crate::linux::{
+    application_name,
+    desktop_entry::{data_dirs, data_home, quote_exec_argument},
+};
+
+const EMPTY_XBEL: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+</xbel>
+"#;
+
+/// The application recorded in each bookmark we add.
+struct RecentApplication {
+    name: String,
+    exec: String,
+}
+
+impl RecentApplication {
+    fn current() -> Self {
+        let exec = std::env::current_exe()
+            .map(|path| quote_exec_argument(&path.to_string_lossy()))
+            .unwrap_or_else(|_| application_name());
+        Self {
+            name: application_name(),
+            exec: format!("'{exec} %u'"),
+        }
+    }
+}
+
+fn recently_used_path() -> Result<PathBuf> {
+    Ok(data_home()
+        .context("Couldn't determine the data directory")?
+        .join("recently-used.xbel"))
+}
+
+/// Records that the running application opened `path`, moving it to the front of the list.
+pub(crate) fn add_recent_document(path: &Path) -> Result<()> {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let href = Url::from_file_path(&path)
+        .map_err(|()| anyhow!("{} isn't an absolute path", path.display()))?
+        .to_string();
+    let mime_type = if path.is_dir() {
+        "inode/directory".to_string()
+    } else {
+        mime_type_for_path(&path)
+    };
+
+    let xbel_path = recently_used_path()?;
+    if let Some(dir) = xbel_path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create directory {}", dir.display()))?;
+    }
+    // Keeps applications built on gpui that record a file at the same time from dropping each
+    // other's bookmark. Others, e.g. GLib's `GBookmarkFile`, don't take the lock and can still
+    // race with us.
+    let _lock = lock_recently_used(&xbel_path)?;
+
+    let contents = match fs::read_to_string(&xbel_path) {
+        Ok(contents) => contents,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(error) => return Err(error).context("Failed to read recently used files"),
+    };
+    let timestamp = Utc::now().to_rfc3339_opts(SecondsFormat::Micros, true);
+    let contents = match add_bookmark(
+        &contents,
+        &href,
+        &mime_type,
+        &RecentApplication::current(),
+        &timestamp,
+    ) {
+        Ok(contents) => contents,
+        Err(error) => {
+            // Leave a file we don't understand alone rather than losing every other
+            // application's history.
+            log::error!(
+                "Not recording {}, {} couldn't be parsed: {error:#}",
+                path.display(),
+                xbel_path.display()
+            );
+            return Ok(());
+        }
+    };
+
+    // Replace the file atomically, other applications may be reading it.
+    let temporary_path = xbel_path.with_extension(format!("xbel.{}", std::process::id()));
+    let result = fs::write(&temporary_path, contents)
+        .with_context(|| format!("Failed to write {}", temporary_path.display()))
+        .and_then(|()| {
+            fs::rename(&temporary_path, &xbel_path)
+                .with_context(|| format!("Failed to write {}", xbel_path.display()))
+        });
+    if result.is_err() {
+        fs::remove_file(&temporary_path).ok();
+    }
+    result
+}
+
+/// Takes an exclusive lock on a file next to `xbel_path`, released when the returned file is
+/// dropped. The bookmarks file itself can't be locked since it's replaced rather than rewritten.
+fn lock_recently_used(xbel_path: &Path) -> Result<File> {
+    let lock_path = xbel_path.with_extension("xbel.lock");
+    let file = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .with_context(|| format!("Failed to open {}", lock_path.display()))?;
+    // SAFETY: the descriptor stays open for as long as `file` is alive.
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } != 0 {
+        return Err(io::Error::last_os_error())
+            .with_context(|| format!("Failed to lock {}", lock_path.display()));
+    }
+    Ok(file)
+}
+
+/// Returns the files the running application opened that still exist, most recent first.
+pub(crate) fn recent_documents() -> Result<Vec<PathBuf>> {
+    let contents = match fs::read_to_string(recently_used_path()?) {
+        Ok(contents) => contents,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(error).context("Failed to read recently used files"),
+    };
+    Ok(parse_recent_documents(&contents, &application_name())?
+        .into_iter()
+        .filter_map(|href| Url::parse(&href).ok()?.to_file_path().ok())
+        .filter(|path| path.exists())
+        .collect())
+}
+
+fn add_bookmark(
+    contents: &str,
+    href: &str,
+    mime_type: &str,
+    application: &RecentApplication,
+    timestamp: &str,
+) -> Result<String> {
+    let contents = if contents.trim().is_empty() {
+        EMPTY_XBEL
+    } else {
+        contents
+    };
+    let mut reader = Reader::from_str(contents);
+    let mut writer = Writer::new(Vec::new());
+    let mut in_bookmark = false;
+    let mut found_bookmark = false;
+    let mut found_application = false;
+    let mut found_xbel = false;
+
+    loop {
+        match reader.read_event()? {
+            Event::Eof => break,
+            Event::Start(element) if element.name().as_ref() == b"bookmark" => {
+                if attribute(&element, b"href").as_deref() == Some(href) {
+                    in_bookmark = true;
+                    found_bookmark = true;
+                    writer.write_event(Event::Start(with_attributes(
+                        &element,
+                        &[("modified", timestamp), ("visited", timestamp)],
+                    )))?;
+                } else {
+                    writer.write_event(Event::Start(element))?;
+                }
+            }
+            Event::Empty(element)
+                if in_bookmark
+                    && element.name().as_ref() == b"bookmark:application"
+                    && attribute(&element, b"name").as_deref()
+                        == Some(application.name.as_str()) =>
+            {
+                found_application = true;
+                let count = attribute(&element, b"count")
+                    .and_then(|count| count.parse::<u32>().ok())
+                    .unwrap_or(0)
+                    + 1;
+                writer.write_event(Event::Empty(with_attributes(
+                    &element,
+                    &[("modified", timestamp), ("count", &count.to_string())],
+                )))?;
+            }
+            Event::End(element)
+                if in_bookmark && element.name().as_ref() == b"bookmark:applications" =>
+            {
+                if !found_application {
+                    writer.get_mut().extend_from_slice(
+                        format!(
+                            "  {}\n        ",
+                            application_element(application, timestamp)
+                        )
+                        .as_bytes(),
+                    );
+                }
+                writer.write_event(Event::End(element))?;
+            }
+            Event::End(element) if element.name().as_ref() == b"bookmark" => {
+                in_bookmark = false;
+                writer.write_event(Event::End(element))?;
+            }
+            Event::End(element) if element.name().as_ref() == b"xbel" => {
+                found_xbel = true;
+                if !found_bookmark {
+                    writer.get_mut().extend_from_slice(
+                        bookmark_element(href, mime_type, application, timestamp).as_bytes(),
+                    );
+                }
+                writer.write_event(Event::End(element))?;
+            }
+            event => writer.write_event(event)?,
+        }
+    }
+
+    if !found_xbel {
+        bail!("missing the xbel element");
+    }
+    Ok(String::from_utf8(writer.into_inner())?)
+}
+
+fn bookmark_element(
+    href: &str,
+    mime_type: &str,
+    application: &RecentApplication,
+    timestamp: &str,
+) -> String {
+    format!(
+        r#"  <bookmark href="{href}" added="{timestamp}" modified="{timestamp}" visited="{timestamp}">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="{mime_type}"/>
+        <bookmark:applications>
+          {application}
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+"#,
+        href = escape(href),
+        mime_type = escape(mime_type),
+        application = application_element(application, timestamp),
+    )
+}
+
+fn application_element(application: &RecentApplication, timestamp: &str) -> String {
+    format!(
+        r#"<bookmark:application name="{}" exec="{}" modified="{timestamp}" count="1"/>"#,
+        escape(&application.name),
+        escape(&application.exec),
+    )
+}
+
+/// Returns the URIs of the bookmarks registered by `application_name`, most recently used first.
+fn parse_recent_documents(contents: &str, application_name: &str) -> Result<Vec<String>> {
+    let mut reader = Reader::from_str(contents);
+    let mut href = None;
+    let mut documents = Vec::new();
+
+    loop {
+        match reader.read_event()? {
+            Event::Eof => break,
+            Event::Start(element) if element.name().as_ref() == b"bookmark" => {
+                href = attribute(&element, b"href");
+            }
+            Event::End(element) if element.name().as_ref() == b"bookmark" => href = None,
+            Event::Start(element) | Event::Empty(element)
+                if element.name().as_ref() == b"bookmark:application"
+                    && attribute(&element, b"name").as_deref() == Some(application_name) =>
+            {
+                if let Some(href) = href.clone() {
+                    let modified = attribute(&element, b"modified")
+                        .and_then(|modified| DateTime::parse_from_rfc3339(&modified).ok());
+                    documents.push((href, modified));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    documents.sort_by(|(_, a), (_, b)| b.cmp(a));
+    Ok(documents.into_iter().map(|(href, _)| href).collect())
+}
+
+fn attribute(element: &BytesStart, name: &[u8]) -> Option<String> {
+    let attribute = element
+        .attributes()
+        .flatten()
+        .find(|attribute| attribute.key.as_ref() == name)?;
+    let value = std::str::from_utf8(&attribute.value).ok()?;
+    unescape(value).ok().map(Cow::into_owned)
+}
+
+/// Copies an element, replacing or adding the given attributes.
+fn with_attributes(element: &BytesStart, updates: &[(&str, &str)]) -> BytesStart<'static> {
+    let mut updated =
+        BytesStart::new(String::from_utf8_lossy(element.name().as_ref()).into_owned());
+    for attribute in element.attributes().flatten() {
+        if !updates
+            .iter()
+            .any(|(key, _)| attribute.key.as_ref() == key.as_bytes())
+        {
+            updated.push_attribute(attribute);
+        }
+    }
+    for update in updates {
+        updated.push_attribute(*update);
+    }
+    updated
+}
+
+/// Guesses the MIME type of a file from its name, using the shared MIME-info database's globs.
+fn mime_type_for_path(path: &Path) -> String {
+    let Some(file_name) = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+    else {
+        return "application/octet-stream".to_string();
+    };
+
+    let mut best_match: Option<(u32, usize, String)> = None;
+    for dir in data_home().into_iter().chain(data_dirs()) {
+        let Ok(globs) = fs::read_to_string(dir.join("mime/globs2")) else {
+            continue;
+        };
+        for (weight, mime_type, glob) in globs.lines().filter_map(parse_glob_line) {
+            if !glob_matches(&glob.to_lowercase(), &file_name) {
+                continue;
+            }
+            let candidate = (weight, glob.len(), mime_type.to_string());
+            if best_match
+                .as_ref()
+                .is_none_or(|best| (candidate.0, candidate.1) > (best.0, best.1))
+            {
+                best_match = Some(candidate);
+            }
+        }
+    }
+    best_match
+        .map(|(_, _, mime_type)| mime_type)
+        .unwrap_or_else(|| "application/octet-stream".to_string())
+}
+
+/// Parses a `weight:mime-type:glob[:flags]` line of a `globs2` file.
+fn parse_glob_line(line: &str) -> Option<(u32, &str, &str)> {
+    if line.starts_with('#') {
+        return None;
+    }
+    let mut fields = line.split(':');
+    let weight = fields.next()?.parse().ok()?;
+    let mime_type = fields.next()?;
+    let glob = fields.next()?;
+    Some((weight, mime_type, glob))
+}
+
+/// Matches the literal names and `*.ext` patterns that make up nearly all of the database.
+fn glob_matches(glob: &str, file_name: &str) -> bool {
+    const SPECIAL: &[char] = &['*', '?', '['];
+    match glob.strip_prefix('*') {
+        Some(suffix) if !suffix.contains(SPECIAL) => file_name.ends_with(suffix),
+        Some(_) => false,
+        None => !glob.contains(SPECIAL) && glob == file_name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn application(name: &str) -> RecentApplication {
+        RecentApplication {
+            name: name.to_string(),
+            exec: format!("'{name} %u'"),
+        }
+    }
+
+    #[test]
+    fn test_add_and_read_bookmarks() {
+        let editor = application("editor");
+        let viewer = application("viewer");
+        let a = "file:///home/user/a.txt";
+        let b = "file:///home/user/b%20%26%20c.txt";
+
+        let contents =
+            add_bookmark("", a, "text/plain", &editor, "2024-01-01T00:00:00.000000Z").unwrap();
+        let contents = add_bookmark(
+            &contents,
+            b,
+            "text/plain",
+            &editor,
+            "2024-01-02T00:00:00.000000Z",
+        )
+        .unwrap();
+        assert_eq!(
+            parse_recent_documents(&contents, "editor").unwrap(),
+            vec![b.to_string(), a.to_string()]
+        );
+
+        // Opening a file again moves it to the front, and other applications keep their own
+        // records of the same file.
+        let contents = add_bookmark(
+            &contents,
+            a,
+            "text/plain",
+            &editor,
+            "2024-01-03T00:00:00.000000Z",
+        )
+        .unwrap();
+        let contents = add_bookmark(
+            &contents,
+            a,
+            "text/plain",
+            &viewer,
+            "2024-01-04T00:00:00.000000Z",
+        )
+        .unwrap();
+        assert_eq!(
+            parse_recent_documents(&contents, "editor").unwrap(),
+            vec![a.to_string(), b.to_string()]
+        );
+        assert_eq!(
+            parse_recent_documents(&contents, "viewer").unwrap(),
+            vec![a.to_string()]
+        );
+        assert_eq!(contents.matches("<bookmark ").count(), 2);
+        assert!(contents.contains(r#"count="2""#));
+    }
+
+    #[test]
+    fn test_add_bookmark_to_unparsable_file() {
+        let editor = application("editor");
+        let add = |contents| {
+            add_bookmark(
+                contents,
+                "file:///home/user/a.txt",
+                "text/plain",
+                &editor,
+                "2024-01-01T00:00:00.000000Z",
+            )
+        };
+        assert!(add("not bookmarks").is_err());
+        assert!(add("<xbel><bookmark></xbel>").is_err());
+    }
+
+    #[test]
+    fn test_glob_matching() {
+        assert_eq!(
+            parse_glob_line("50:text/x-rust:*.rs"),
+            Some((50, "text/x-rust", "*.rs"))
+        );
+        assert_eq!(parse_glob_line("# comment"), None);
+        assert!(glob_matches("*.rs", "main.rs"));
+        assert!(glob_matches("makefile", "makefile"));
+        assert!(!glob_matches("*.rs", "main.rst"));
+        assert!(!glob_matches("*.[ch]", "main.c"));
+    }
+}
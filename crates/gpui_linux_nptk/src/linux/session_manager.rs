@@ -0,0 +1,506 @@
+//! Registers with the session manager over XSMP, so that the application is asked to save its
+//! session before the user logs out and is relaunched when they log back in.
+
+use std::{
+    cell::{Cell, RefCell},
+    env,
+    ffi::{CStr, CString, OsString, c_char, c_int, c_ulong, c_void},
+    os::{
+        fd::{AsFd, BorrowedFd, RawFd},
+        unix::ffi::OsStrExt as _,
+    },
+    ptr,
+    rc::Rc,
+    sync::OnceLock,
+};
+
+use anyhow::{Context as _, Result, anyhow};
+use calloop::LoopSignal;
+use gpui::ForegroundExecutor;
+use libloading::Library;
+use smol::Async;
+use util::TryFutureExt as _;
+
+use crate::linux::desktop_entry::AUTOSTART_ID_VARIABLE;
+
+type SmcConn = *mut c_void;
+type IceConn = *mut c_void;
+type SmPointer = *mut c_void;
+type Bool = c_int;
+
+type SaveYourselfProc = unsafe extern "C" fn(SmcConn, SmPointer, c_int, Bool, c_int, Bool);
+type ClientProc = unsafe extern "C" fn(SmcConn, SmPointer);
+
+#[repr(C)]
+struct SmcCallback<F> {
+    callback: Option<F>,
+    client_data: SmPointer,
+}
+
+#[repr(C)]
+struct SmcCallbacks {
+    save_yourself: SmcCallback<SaveYourselfProc>,
+    die: SmcCallback<ClientProc>,
+    save_complete: SmcCallback<ClientProc>,
+    shutdown_cancelled: SmcCallback<ClientProc>,
+}
+
+#[repr(C)]
+struct SmPropValue {
+    length: c_int,
+    value: SmPointer,
+}
+
+#[repr(C)]
+struct SmProp {
+    name: *mut c_char,
+    type_: *mut c_char,
+    num_vals: c_int,
+    vals: *mut SmPropValue,
+}
+
+type OpenConnectionFn = unsafe extern "C" fn(
+    network_ids_list: *mut c_char,
+    context: SmPointer,
+    xsmp_major_rev: c_int,
+    xsmp_minor_rev: c_int,
+    mask: c_ulong,
+    callbacks: *mut SmcCallbacks,
+    previous_id: *const c_char,
+    client_id_ret: *mut *mut c_char,
+    error_length: c_int,
+    error_string_ret: *mut c_char,
+) -> SmcConn;
+type CloseConnectionFn =
+    unsafe extern "C" fn(conn: SmcConn, count: c_int, reason_msgs: *mut *mut c_char) -> c_int;
+type GetIceConnectionFn = unsafe extern "C" fn(conn: SmcConn) -> IceConn;
+type SetPropertiesFn =
+    unsafe extern "C" fn(conn: SmcConn, num_props: c_int, props: *mut *mut SmProp);
+type SaveYourselfDoneFn = unsafe extern "C" fn(conn: SmcConn, success: Bool);
+type IceConnectionNumberFn = unsafe extern "C" fn(conn: IceConn) -> c_int;
+type IceProcessMessagesFn = unsafe extern "C" fn(
+    conn: IceConn,
+    reply_wait: *mut c_void,
+    reply_ready_ret: *mut Bool,
+) -> c_int;
+
+/// The parts of libSM and libICE used here. They're loaded when first connecting, so that they
+/// aren't needed on systems without an XSMP session manager.
+struct SmLibrary {
+    open_connection: OpenConnectionFn,
+    close_connection: CloseConnectionFn,
+    get_ice_connection: GetIceConnectionFn,
+    set_properties: SetPropertiesFn,
+    save_yourself_done: SaveYourselfDoneFn,
+    ice_connection_number: IceConnectionNumberFn,
+    ice_process_messages: IceProcessMessagesFn,
+    _sm: Library,
+    _ice: Library,
+}
+
+impl SmLibrary {
+    fn get() -> Result<&'static Self> {
+        static LIBRARY: OnceLock<Result<SmLibrary>> = OnceLock::new();
+        LIBRARY
+            .get_or_init(Self::load)
+            .as_ref()
+            .map_err(|error| anyhow!("{error:#}"))
+    }
+
+    fn load() -> Result<Self> {
+        // SAFETY: neither library runs any code when it's loaded.
+        let (ice, sm) = unsafe { (Library::new("libICE.so.6")?, Library::new("libSM.so.6")?) };
+        Ok(Self {
+            open_connection: symbol(&sm, b"SmcOpenConnection\0")?,
+            close_connection: symbol(&sm, b"SmcCloseConnection\0")?,
+            get_ice_connection: symbol(&sm, b"SmcGetIceConnection\0")?,
+            set_properties: symbol(&sm, b"SmcSetProperties\0")?,
+            save_yourself_done: symbol(&sm, b"SmcSaveYourselfDone\0")?,
+            ice_connection_number: symbol(&ice, b"IceConnectionNumber\0")?,
+            ice_process_messages: symbol(&ice, b"IceProcessMessages\0")?,
+            _sm: sm,
+            _ice: ice,
+        })
+    }
+}
+
+/// Looks up a function, which stays valid for as long as its library is loaded.
+fn symbol<T: Copy>(library: &Library, name: &[u8]) -> Result<T> {
+    // SAFETY: the callers pass the function type the symbol is declared with.
+    let symbol = unsafe { library.get::<T>(name) }?;
+    Ok(*symbol)
+}
+
+const SMC_SAVE_YOURSELF_PROC_MASK: c_ulong = 1 << 0;
+const SMC_DIE_PROC_MASK: c_ulong = 1 << 1;
+const SMC_SAVE_COMPLETE_PROC_MASK: c_ulong = 1 << 2;
+const SMC_SHUTDOWN_CANCELLED_PROC_MASK: c_ulong = 1 << 3;
+
+const ICE_PROCESS_MESSAGES_SUCCESS: c_int = 0;
+
+/// Only restart the application if it was running when the session was saved.
+const SM_RESTART_IF_RUNNING: u8 = 0;
+
+/// Passed to the application when the session manager relaunches it, with the client ID it had
+/// in the saved session.
+const CLIENT_ID_ARGUMENT: &str = "--sm-client-id";
+
+struct SessionManagerState {
+    executor: ForegroundExecutor,
+    signal: LoopSignal,
+    /// Set once connected.
+    library: Cell<Option<&'static SmLibrary>>,
+    /// Null while not connected.
+    connection: Cell<SmcConn>,
+    client_id: RefCell<Option<String>>,
+    /// The client ID this process had in the session it was relaunched from.
+    previous_client_id: Option<String>,
+    autostart_id: Option<String>,
+    save_callback: RefCell<Option<Box<dyn FnMut()>>>,
+}
+
+#[derive(Clone)]
+pub(crate) struct SessionManager(Rc<SessionManagerState>);
+
+impl SessionManager {
+    pub fn new(executor: ForegroundExecutor, signal: LoopSignal) -> Self {
+        // The ID belongs to this process, the commands the platform spawns leave it out of their
+        // environment.
+        let autostart_id = env::var(AUTOSTART_ID_VARIABLE)
+            .ok()
+            .filter(|id| !id.is_empty());
+
+        Self(Rc::new(SessionManagerState {
+            executor,
+            signal,
+            library: Cell::new(None),
+            connection: Cell::new(ptr::null_mut()),
+            client_id: RefCell::default(),
+            previous_client_id: client_id_argument(env::args_os().skip(1)),
+            autostart_id,
+            save_callback: RefCell::default(),
+        }))
+    }
+
+    /// Whether the session manager started this process to restore the previous session.
+    pub fn launched_by_session_manager(&self) -> bool {
+        self.0.previous_client_id.is_some()
+    }
+
+    /// Connects to the session manager the first time it's called. Nothing happens outside of
+    /// sessions managed over XSMP.
+    pub fn on_save_session(&self, callback: Box<dyn FnMut()>) {
+        self.0.save_callback.replace(Some(callback));
+        if !self.0.connection.get().is_null() {
+            return;
+        }
+        if let Err(error) = self.connect() {
+            log::debug!("Not registering with a session manager: {error:#}");
+        }
+    }
+
+    fn connect(&self) -> Result<()> {
+        let library = SmLibrary::get()?;
+        // The callbacks are only invoked from `IceProcessMessages`, while `process_messages`
+        // holds a reference to the state.
+        let client_data = Rc::as_ptr(&self.0) as SmPointer;
+        let mut callbacks = SmcCallbacks {
+            save_yourself: SmcCallback {
+                callback: Some(save_yourself),
+                client_data,
+            },
+            die: SmcCallback {
+                callback: Some(die),
+                client_data,
+            },
+            save_complete: SmcCallback {
+                callback: Some(ignore),
+                client_data,
+            },
+            shutdown_cancelled: SmcCallback {
+                callback: Some(ignore),
+                client_data,
+            },
+        };
+        let previous_id = self
+            .0
+            .previous_client_id
+            .as_deref()
+            .or(self.0.autostart_id.as_deref())
+            .map(CString::new)
+            .transpose()?;
+        let mut client_id: *mut c_char = ptr::null_mut();
+        let mut error: [c_char; 256] = [0; 256];
+
+        // SAFETY: libSM copies the callbacks, and the error buffer's length is passed along.
+        let connection = unsafe {
+            (library.open_connection)(
+                ptr::null_mut(),
+                ptr::null_mut(),
+                1,
+                0,
+                SMC_SAVE_YOURSELF_PROC_MASK
+                    | SMC_DIE_PROC_MASK
+                    | SMC_SAVE_COMPLETE_PROC_MASK
+                    | SMC_SHUTDOWN_CANCELLED_PROC_MASK,
+                &mut callbacks,
+                previous_id.as_ref().map_or(ptr::null(), |id| id.as_ptr()),
+                &mut client_id,
+                error.len() as c_int,
+                error.as_mut_ptr(),
+            )
+        };
+        if connection.is_null() {
+            // SAFETY: libSM writes a null-terminated message into the buffer.
+            let error = unsafe { CStr::from_ptr(error.as_ptr()) };
+            return Err(anyhow!("{}", error.to_string_lossy()));
+        }
+        if !client_id.is_null() {
+            // SAFETY: the client ID is a C string allocated with `malloc` that we now own.
+            unsafe {
+                self.0.client_id.replace(Some(
+                    CStr::from_ptr(client_id).to_string_lossy().into_owned(),
+                ));
+                libc::free(client_id as *mut c_void);
+            }
+        }
+        self.0.library.set(Some(library));
+        self.0.connection.set(connection);
+
+        let this = self.clone();
+        self.0
+            .executor
+            .spawn(async move { this.process_messages().await }.log_err())
+            .detach();
+        Ok(())
+    }
+
+    async fn process_messages(&self) -> Result<()> {
+        let library = self
+            .0
+            .library
+            .get()
+            .context("Not connected to a session manager")?;
+        // SAFETY: the connection stays open until `close` is called.
+        let ice_connection = unsafe { (library.get_ice_connection)(self.0.connection.get()) };
+        // SAFETY: as above.
+        let fd = unsafe { (library.ice_connection_number)(ice_connection) };
+        // libICE reads from the socket itself and doesn't expect it to be non-blocking, so it's
+        // only watched for readiness here.
+        let socket = Async::new_nonblocking(IceSocket(fd))?;
+        loop {
+            socket.readable().await?;
+            // SAFETY: the connection is open, and closing it from a callback is deferred by
+            // libICE until the messages have been processed.
+            let status = unsafe {
+                (library.ice_process_messages)(ice_connection, ptr::null_mut(), ptr::null_mut())
+            };
+            if self.0.connection.get().is_null() {
+                return Ok(());
+            }
+            if status != ICE_PROCESS_MESSAGES_SUCCESS {
+                drop(socket);
+                self.0.close();
+                return Err(anyhow!("Lost the connection to the session manager"));
+            }
+        }
+    }
+}
+
+impl SessionManagerState {
+    fn close(&self) {
+        let connection = self.connection.replace(ptr::null_mut());
+        if let Some(library) = self.library.get()
+            && !connection.is_null()
+        {
+            // SAFETY: the connection is open and isn't used after this.
+            unsafe { (library.close_connection)(connection, 0, ptr::null_mut()) };
+        }
+    }
+
+    /// Tells the session manager how to relaunch the application. The restart command hands the
+    /// client ID back, so that the relaunched process takes this one's place in the session.
+    fn set_properties(&self, library: &SmLibrary, connection: SmcConn) {
+        let Some(client_id) = self.client_id.borrow().clone() else {
+            return;
+        };
+        let mut args = env::args_os();
+        let arg0 = args.next();
+        let program = env::current_exe()
+            .map(OsString::from)
+            .ok()
+            .or(arg0)
+            .unwrap_or_default();
+        let args = without_client_id_argument(args);
+
+        let mut clone_command = vec![program.as_bytes().to_vec()];
+        clone_command.extend(args.iter().map(|arg| arg.as_bytes().to_vec()));
+        let mut restart_command = clone_command.clone();
+        restart_command.insert(1, format!("{CLIENT_ID_ARGUMENT}={client_id}").into_bytes());
+        let user = env::var_os("USER").unwrap_or_default();
+
+        let properties: [(&CStr, &CStr, Vec<Vec<u8>>); 5] = [
+            (c"Program", c"ARRAY8", vec![program.as_bytes().to_vec()]),
+            (c"UserID", c"ARRAY8", vec![user.as_bytes().to_vec()]),
+            (c"CloneCommand", c"LISTofARRAY8", clone_command),
+            (c"RestartCommand", c"LISTofARRAY8", restart_command),
+            (
+                c"RestartStyleHint",
+                c"CARD8",
+                vec![vec![SM_RESTART_IF_RUNNING]],
+            ),
+        ];
+        let mut values = properties
+            .iter()
+            .map(|(_, _, values)| {
+                values
+                    .iter()
+                    .map(|value| SmPropValue {
+                        length: value.len() as c_int,
+                        value: value.as_ptr() as SmPointer,
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let mut props = properties
+            .iter()
+            .zip(&mut values)
+            .map(|((name, type_, _), values)| SmProp {
+                name: name.as_ptr() as *mut c_char,
+                type_: type_.as_ptr() as *mut c_char,
+                num_vals: values.len() as c_int,
+                vals: values.as_mut_ptr(),
+            })
+            .collect::<Vec<_>>();
+        let mut prop_pointers = props
+            .iter_mut()
+            .map(|prop| prop as *mut SmProp)
+            .collect::<Vec<_>>();
+
+        // SAFETY: libSM only reads the properties, which outlive the call.
+        unsafe {
+            (library.set_properties)(
+                connection,
+                prop_pointers.len() as c_int,
+                prop_pointers.as_mut_ptr(),
+            )
+        };
+    }
+}
+
+/// Returns the client ID passed with `CLIENT_ID_ARGUMENT`, either as `--sm-client-id=ID` or as
+/// `--sm-client-id ID`.
+fn client_id_argument(args: impl IntoIterator<Item = OsString>) -> Option<String> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let Some(arg) = arg.to_str() else {
+            continue;
+        };
+        if arg == CLIENT_ID_ARGUMENT {
+            return args.next()?.into_string().ok();
+        }
+        if let Some(id) = arg
+            .strip_prefix(CLIENT_ID_ARGUMENT)
+            .and_then(|rest| rest.strip_prefix('='))
+        {
+            return Some(id.to_string());
+        }
+    }
+    None
+}
+
+/// Removes `CLIENT_ID_ARGUMENT` and its value, so that the restart command only passes the new
+/// client ID.
+fn without_client_id_argument(args: impl IntoIterator<Item = OsString>) -> Vec<OsString> {
+    let mut result = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == CLIENT_ID_ARGUMENT {
+            args.next();
+        } else if !arg
+            .to_str()
+            .is_some_and(|arg| arg.starts_with(&format!("{CLIENT_ID_ARGUMENT}=")))
+        {
+            result.push(arg);
+        }
+    }
+    result
+}
+
+/// The file descriptor of an ICE connection, which is owned by libICE.
+struct IceSocket(RawFd);
+
+impl AsFd for IceSocket {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        // SAFETY: libICE keeps the socket open until the connection is closed, after which
+        // `process_messages` stops watching it.
+        unsafe { BorrowedFd::borrow_raw(self.0) }
+    }
+}
+
+/// Called when the session is saved, e.g. before the user logs out, and once after connecting.
+unsafe extern "C" fn save_yourself(
+    connection: SmcConn,
+    client_data: SmPointer,
+    _save_type: c_int,
+    _shutdown: Bool,
+    _interact_style: c_int,
+    _fast: Bool,
+) {
+    // SAFETY: see `SessionManager::connect`.
+    let state = unsafe { &*(client_data as *const SessionManagerState) };
+    let callback = state.save_callback.borrow_mut().take();
+    if let Some(mut callback) = callback {
+        callback();
+        state.save_callback.borrow_mut().get_or_insert(callback);
+    }
+    let Some(library) = state.library.get() else {
+        return;
+    };
+    state.set_properties(library, connection);
+    // SAFETY: the connection is open while its callbacks run.
+    unsafe { (library.save_yourself_done)(connection, 1) };
+}
+
+/// Called when the session ends. The session was saved just before.
+unsafe extern "C" fn die(_connection: SmcConn, client_data: SmPointer) {
+    // SAFETY: see `SessionManager::connect`.
+    let state = unsafe { &*(client_data as *const SessionManagerState) };
+    state.close();
+    state.signal.stop();
+}
+
+unsafe extern "C" fn ignore(_connection: SmcConn, _client_data: SmPointer) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<OsString> {
+        args.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn test_client_id_argument() {
+        assert_eq!(
+            client_id_argument(args(&["--sm-client-id", "10abc"])).as_deref(),
+            Some("10abc")
+        );
+        assert_eq!(
+            client_id_argument(args(&["file.txt", "--sm-client-id=10abc"])).as_deref(),
+            Some("10abc")
+        );
+        assert_eq!(client_id_argument(args(&["--sm-client-id"])), None);
+        assert_eq!(client_id_argument(args(&["file.txt"])), None);
+
+        assert_eq!(
+            without_client_id_argument(args(&[
+                "--sm-client-id",
+                "10abc",
+                "file.txt",
+                "--sm-client-id=10def"
+            ])),
+            args(&["file.txt"])
+        );
+    }
+}
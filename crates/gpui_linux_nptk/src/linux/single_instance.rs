@@ -0,0 +1,453 @@
+//! Single-instance support.
+//!
+//! The first instance owns the application ID as a well-known name on the session bus and serves
+//! `org.freedesktop.Application` on it, the same interface GLib applications and desktop
+//! launchers use. When no session bus is reachable, a Unix socket in the state directory takes
+//! its place. Later launches hand their arguments and activation token to the first instance and
+//! then exit.
+
+use std::{
+    collections::HashMap,
+    env,
+    ffi::OsStr,
+    fs::File,
+    io::{self, Read as _, Write as _},
+    os::{
+        fd::AsRawFd as _,
+        unix::net::{UnixListener, UnixStream},
+    },
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context as _, Result};
+use url::Url;
+use util::ResultExt as _;
+use zbus::{
+    blocking::{Connection, Proxy},
+    fdo::{RequestNameFlags, RequestNameReply},
+    interface,
+    zvariant::{OwnedValue, Value},
+};
+
+const APPLICATION_INTERFACE: &str = "org.freedesktop.Application";
+const ACTIVATION_TOKEN_KEY: &str = "activation-token";
+const DESKTOP_STARTUP_ID_KEY: &str = "desktop-startup-id";
+const SOCKET_TIMEOUT: Duration = Duration::from_secs(1);
+/// How often a later launch retries connecting while the running instance is still binding its
+/// socket.
+const SOCKET_RETRY_INTERVAL: Duration = Duration::from_millis(10);
+/// Launches with `--action=<name>` activate the named desktop action instead of opening files.
+pub(crate) const ACTION_ARGUMENT_PREFIX: &str = "--action=";
+
+/// What a later launch asked the running instance to do.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum InstanceRequestKind {
+    Activate,
+    Open(Vec<String>),
+    ActivateAction(String),
+}
+
+/// A request forwarded from a later launch of the application.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct InstanceRequest {
+    pub kind: InstanceRequestKind,
+    /// The xdg-activation or startup-notification token the launcher handed to the later launch.
+    pub activation_token: Option<String>,
+}
+
+/// Keeps this process registered as the running instance for as long as it's alive.
+pub(crate) struct SingleInstance {
+    /// Owns the well-known bus name.
+    _connection: Option<Connection>,
+    socket_path: Option<PathBuf>,
+    /// Held for as long as this process serves the socket, so that only one launch claims it.
+    /// Released after the socket is removed, since fields are dropped after `drop` runs.
+    _socket_lock: Option<File>,
+}
+
+impl Drop for SingleInstance {
+    fn drop(&mut self) {
+        if let Some(socket_path) = &self.socket_path {
+            std::fs::remove_file(socket_path).log_err();
+        }
+    }
+}
+
+/// Claims the application ID for this process, returning `None` if another instance already
+/// holds it, in which case this launch's request has been forwarded to that instance.
+pub(crate) fn claim_single_instance(
+    app_id: &str,
+    sender: calloop::channel::Sender<InstanceRequest>,
+) -> Result<Option<SingleInstance>> {
+    match Connection::session() {
+        Ok(connection) => claim_on_session_bus(connection, app_id, sender),
+        Err(error) => {
+            log::info!("No session bus for single instance, falling back to a socket: {error}");
+            claim_socket(app_id, sender)
+        }
+    }
+}
+
+fn claim_on_session_bus(
+    connection: Connection,
+    app_id: &str,
+    sender: calloop::channel::Sender<InstanceRequest>,
+) -> Result<Option<SingleInstance>> {
+    let object_path = application_object_path(app_id);
+    connection
+        .object_server()
+        .at(object_path.as_str(), ApplicationObject { sender })
+        .context("Failed to export org.freedesktop.Application")?;
+
+    let reply =
+        match connection.request_name_with_flags(app_id, RequestNameFlags::DoNotQueue.into()) {
+            Ok(reply) => reply,
+            Err(zbus::Error::NameTaken) => RequestNameReply::Exists,
+            Err(error) => {
+                return Err(error).with_context(|| format!("Failed to request bus name {app_id}"));
+            }
+        };
+    match reply {
+        RequestNameReply::PrimaryOwner | RequestNameReply::AlreadyOwner => {
+            Ok(Some(SingleInstance {
+                _connection: Some(connection),
+                socket_path: None,
+                _socket_lock: None,
+            }))
+        }
+        RequestNameReply::Exists | RequestNameReply::InQueue => {
+            forward_over_session_bus(&connection, app_id, &object_path, current_launch_request())?;
+            Ok(None)
+        }
+    }
+}
+
+fn forward_over_session_bus(
+    connection: &Connection,
+    app_id: &str,
+    object_path: &str,
+    request: InstanceRequest,
+) -> Result<()> {
+    let proxy = Proxy::new(connection, app_id, object_path, APPLICATION_INTERFACE)?;
+    let mut platform_data = HashMap::<&str, Value>::new();
+    if let Some(token) = request.activation_token.as_deref() {
+        platform_data.insert(ACTIVATION_TOKEN_KEY, Value::from(token));
+        platform_data.insert(DESKTOP_STARTUP_ID_KEY, Value::from(token));
+    }
+    match request.kind {
+        InstanceRequestKind::Activate => proxy.call_method("Activate", &(platform_data,)),
+        InstanceRequestKind::Open(uris) => proxy.call_method("Open", &(uris, platform_data)),
+        InstanceRequestKind::ActivateAction(action_name) => proxy.call_method(
+            "ActivateAction",
+            &(action_name, Vec::<Value>::new(), platform_data),
+        ),
+    }
+    .with_context(|| format!("Failed to forward launch to the running instance of {app_id}"))?;
+    Ok(())
+}
+
+struct ApplicationObject {
+    sender: calloop::channel::Sender<InstanceRequest>,
+}
+
+impl ApplicationObject {
+    fn send(&self, kind: InstanceRequestKind, platform_data: &HashMap<String, OwnedValue>) {
+        let activation_token = [ACTIVATION_TOKEN_KEY, DESKTOP_STARTUP_ID_KEY]
+            .iter()
+            .filter_map(|key| platform_data.get(*key))
+            .find_map(|value| <&str>::try_from(value).ok())
+            .map(str::to_string);
+        self.sender
+            .send(InstanceRequest {
+                kind,
+                activation_token,
+            })
+            .log_err();
+    }
+}
+
+#[interface(name = "org.freedesktop.Application")]
+impl ApplicationObject {
+    #[zbus(name = "Activate")]
+    async fn activate(&self, platform_data: HashMap<String, OwnedValue>) {
+        self.send(InstanceRequestKind::Activate, &platform_data);
+    }
+
+    #[zbus(name = "Open")]
+    async fn open(&self, uris: Vec<String>, platform_data: HashMap<String, OwnedValue>) {
+        self.send(InstanceRequestKind::Open(uris), &platform_data);
+    }
+
+    #[zbus(name = "ActivateAction")]
+    async fn activate_action(
+        &self,
+        action_name: String,
+        _parameter: Vec<OwnedValue>,
+        platform_data: HashMap<String, OwnedValue>,
+    ) {
+        self.send(
+            InstanceRequestKind::ActivateAction(action_name),
+            &platform_data,
+        );
+    }
+}
+
+fn claim_socket(
+    app_id: &str,
+    sender: calloop::channel::Sender<InstanceRequest>,
+) -> Result<Option<SingleInstance>> {
+    let directory = paths::state_dir();
+    std::fs::create_dir_all(directory)
+        .with_context(|| format!("Failed to create {}", directory.display()))?;
+    let socket_path = directory.join(format!("{app_id}.sock"));
+
+    // Launches racing each other would otherwise both find no socket to connect to, and the last
+    // one to bind would unlink the other's.
+    let lock_path = directory.join(format!("{app_id}.lock"));
+    let lock = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .with_context(|| format!("Failed to open {}", lock_path.display()))?;
+    if !try_lock_exclusive(&lock)
+        .with_context(|| format!("Failed to lock {}", lock_path.display()))?
+    {
+        forward_over_socket(&socket_path, &current_launch_request())?;
+        return Ok(None);
+    }
+
+    // Whoever bound the socket has exited since we hold the lock.
+    if socket_path.exists() {
+        std::fs::remove_file(&socket_path)
+            .with_context(|| format!("Failed to remove stale socket {}", socket_path.display()))?;
+    }
+    let listener = UnixListener::bind(&socket_path)
+        .with_context(|| format!("Failed to bind {}", socket_path.display()))?;
+    std::thread::Builder::new()
+        .name("SingleInstanceSocket".to_string())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                let Some(mut stream) = stream.log_err() else {
+                    continue;
+                };
+                // A launch that stops writing halfway mustn't keep later ones from being served.
+                stream.set_read_timeout(Some(SOCKET_TIMEOUT)).log_err();
+                let mut contents = String::new();
+                if stream.read_to_string(&mut contents).log_err().is_none() {
+                    continue;
+                }
+                let Some(request) = decode_socket_request(&contents) else {
+                    log::warn!("Ignoring malformed single instance request");
+                    continue;
+                };
+                if sender.send(request).is_err() {
+                    break;
+                }
+            }
+        })
+        .context("Failed to spawn single instance socket thread")?;
+
+    Ok(Some(SingleInstance {
+        _connection: None,
+        socket_path: Some(socket_path),
+        _socket_lock: Some(lock),
+    }))
+}
+
+/// Takes an exclusive lock on `file` without blocking, returning whether it was acquired.
+fn try_lock_exclusive(file: &File) -> io::Result<bool> {
+    // SAFETY: the descriptor stays open for as long as `file` is alive.
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } == 0 {
+        return Ok(true);
+    }
+    let error = io::Error::last_os_error();
+    if error.kind() == io::ErrorKind::WouldBlock {
+        Ok(false)
+    } else {
+        Err(error)
+    }
+}
+
+/// Hands a request to the instance holding the lock, which may not have bound its socket yet.
+fn forward_over_socket(socket_path: &Path, request: &InstanceRequest) -> Result<()> {
+    let deadline = Instant::now() + SOCKET_TIMEOUT;
+    let mut stream = loop {
+        match UnixStream::connect(socket_path) {
+            Ok(stream) => break stream,
+            Err(_) if Instant::now() < deadline => std::thread::sleep(SOCKET_RETRY_INTERVAL),
+            Err(error) => {
+                return Err(error)
+                    .with_context(|| format!("Failed to connect to {}", socket_path.display()));
+            }
+        }
+    };
+    stream.set_write_timeout(Some(SOCKET_TIMEOUT))?;
+    stream
+        .write_all(encode_socket_request(request).as_bytes())
+        .context("Failed to forward launch to the running instance")
+}
+
+/// Encodes a request as lines: the kind, the activation token (empty if there is none), then the
+/// URIs to open or the action name.
+fn encode_socket_request(request: &InstanceRequest) -> String {
+    let token = request.activation_token.as_deref().unwrap_or_default();
+    let mut lines = match &request.kind {
+        InstanceRequestKind::Activate => vec!["activate", token],
+        InstanceRequestKind::Open(uris) => {
+            let mut lines = vec!["open", token];
+            lines.extend(uris.iter().map(String::as_str));
+            lines
+        }
+        InstanceRequestKind::ActivateAction(action_name) => vec!["action", token, action_name],
+    };
+    lines.push("");
+    lines.join("\n")
+}
+
+fn decode_socket_request(contents: &str) -> Option<InstanceRequest> {
+    let mut lines = contents.lines();
+    let kind = lines.next()?;
+    let activation_token = Some(lines.next()?)
+        .filter(|token| !token.is_empty())
+        .map(str::to_string);
+    let kind = match kind {
+        "activate" => InstanceRequestKind::Activate,
+        "open" => InstanceRequestKind::Open(lines.map(str::to_string).collect()),
+        "action" => InstanceRequestKind::ActivateAction(lines.next()?.to_string()),
+        _ => return None,
+    };
+    Some(InstanceRequest {
+        kind,
+        activation_token,
+    })
+}
+
+/// Follows the GApplication convention of turning `org.example.App-Name` into
+/// `/org/example/App_Name`.
+fn application_object_path(app_id: &str) -> String {
+    format!("/{}", app_id.replace('.', "/").replace('-', "_"))
+}
+
+fn current_launch_request() -> InstanceRequest {
+    let working_directory = env::current_dir().log_err();
+    let uris = env::args_os()
+        .skip(1)
+        .filter_map(|argument| launch_argument_uri(&argument, working_directory.as_deref()))
+        .collect::<Vec<_>>();
+    let action_name = launch_action_name();
+    let activation_token = env::var("XDG_ACTIVATION_TOKEN")
+        .or_else(|_| env::var("DESKTOP_STARTUP_ID"))
+        .ok()
+        .filter(|token| !token.is_empty());
+    InstanceRequest {
+        kind: if let Some(action_name) = action_name {
+            InstanceRequestKind::ActivateAction(action_name)
+        } else if uris.is_empty() {
+            InstanceRequestKind::Activate
+        } else {
+            InstanceRequestKind::Open(uris)
+        },
+        activation_token,
+    }
+}
+
+/// The desktop action this process was launched to perform, passed as `--action=<name>`.
+/// Arguments that aren't valid UTF-8 are skipped.
+pub(crate) fn launch_action_name() -> Option<String> {
+    env::args_os().skip(1).find_map(|argument| {
+        argument
+            .to_str()?
+            .strip_prefix(ACTION_ARGUMENT_PREFIX)
+            .map(ToString::to_string)
+    })
+}
+
+/// Converts a command-line argument to a URI, skipping flags. Arguments that name an existing
+/// file or don't parse as a URI are treated as paths relative to `working_directory`.
+fn launch_argument_uri(argument: &OsStr, working_directory: Option<&Path>) -> Option<String> {
+    if argument.as_encoded_bytes().starts_with(b"-") {
+        return None;
+    }
+    let path = match working_directory {
+        Some(working_directory) => working_directory.join(argument),
+        None => PathBuf::from(argument),
+    };
+    if !path.exists()
+        && let Some(url) = argument
+            .to_str()
+            .and_then(|argument| Url::parse(argument).ok())
+    {
+        return Some(url.into());
+    }
+    let path = std::fs::canonicalize(&path).unwrap_or(path);
+    Url::from_file_path(path).ok().map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_socket_request_round_trip() {
+        let requests = [
+            InstanceRequest {
+                kind: InstanceRequestKind::Activate,
+                activation_token: None,
+            },
+            InstanceRequest {
+                kind: InstanceRequestKind::Open(vec![
+                    "file:///tmp/a.txt".to_string(),
+                    "https://example.com/".to_string(),
+                ]),
+                activation_token: Some("token".to_string()),
+            },
+            InstanceRequest {
+                kind: InstanceRequestKind::ActivateAction("new-window".to_string()),
+                activation_token: None,
+            },
+        ];
+        for request in requests {
+            assert_eq!(
+                decode_socket_request(&encode_socket_request(&request)),
+                Some(request)
+            );
+        }
+        assert_eq!(decode_socket_request("unknown\n\n"), None);
+    }
+
+    #[test]
+    fn test_socket_lock_is_exclusive() {
+        let lock_file = tempfile::NamedTempFile::new().unwrap();
+        let first = File::open(lock_file.path()).unwrap();
+        let second = File::open(lock_file.path()).unwrap();
+
+        assert!(try_lock_exclusive(&first).unwrap());
+        assert!(!try_lock_exclusive(&second).unwrap());
+        drop(first);
+        assert!(try_lock_exclusive(&second).unwrap());
+    }
+
+    #[test]
+    fn test_launch_argument_uri() {
+        let working_directory = Path::new("/nonexistent-directory");
+        assert_eq!(
+            launch_argument_uri(OsStr::new("--verbose"), Some(working_directory)),
+            None
+        );
+        assert_eq!(
+            launch_argument_uri(OsStr::new("notes.txt"), Some(working_directory)).as_deref(),
+            Some("file:///nonexistent-directory/notes.txt")
+        );
+        assert_eq!(
+            launch_argument_uri(OsStr::new("https://example.com/"), Some(working_directory))
+                .as_deref(),
+            Some("https://example.com/")
+        );
+        assert_eq!(
+            application_object_path("org.example.App-Name"),
+            "/org/example/App_Name"
+        );
+    }
+}
@@ -0,0 +1,205 @@
+//! The system tray icon, exported as a StatusNotifierItem.
+
+use std::{cell::RefCell, collections::HashMap, rc::Rc, sync::Arc};
+
+use anyhow::{Context as _, Result};
+use futures::{StreamExt as _, channel::mpsc};
+use gpui::{
+    Action, Axis, ForegroundExecutor, Keymap, point, px,
+    tray::{TrayEvent, TrayIcon, TrayIconImage, TrayStatus},
+};
+
+use crate::linux::{
+    application_name,
+    dbus_menu::{
+        MenuSnapshot, StatusNotifierEvent, StatusNotifierItem, StatusNotifierPixmap,
+        StatusNotifierProperties,
+    },
+    remote_menu_items,
+};
+
+struct TrayState {
+    executor: ForegroundExecutor,
+    item: RefCell<Option<StatusNotifierItem>>,
+    /// The actions of the tray menu, kept apart from the global menu's since both number their
+    /// items from 1.
+    menu_actions: RefCell<HashMap<i32, Box<dyn Action>>>,
+    event_callback: RefCell<Option<Box<dyn FnMut(TrayEvent)>>>,
+    menu_action_callback: RefCell<Option<Box<dyn FnMut(&dyn Action)>>>,
+}
+
+#[derive(Clone)]
+pub(crate) struct Tray(Rc<TrayState>);
+
+impl Tray {
+    pub fn new(executor: ForegroundExecutor) -> Self {
+        Self(Rc::new(TrayState {
+            executor,
+            item: RefCell::default(),
+            menu_actions: RefCell::default(),
+            event_callback: RefCell::default(),
+            menu_action_callback: RefCell::default(),
+        }))
+    }
+
+    pub fn on_event(&self, callback: Box<dyn FnMut(TrayEvent)>) {
+        self.0.event_callback.replace(Some(callback));
+    }
+
+    pub fn on_menu_action(&self, callback: Box<dyn FnMut(&dyn Action)>) {
+        self.0.menu_action_callback.replace(Some(callback));
+    }
+
+    pub fn set(&self, tray_icon: Option<TrayIcon>, keymap: &Keymap) -> Result<()> {
+        let Some(tray_icon) = tray_icon else {
+            self.0.item.take();
+            self.0.menu_actions.borrow_mut().clear();
+            return Ok(());
+        };
+
+        let mut next_id = 1;
+        let mut menu_actions = HashMap::new();
+        let menu = MenuSnapshot {
+            entries: remote_menu_items(&tray_icon.menu, &mut next_id, keymap, &mut menu_actions),
+        };
+        self.0.menu_actions.replace(menu_actions);
+        let properties = status_notifier_properties(&tray_icon);
+
+        if let Some(item) = self.0.item.borrow().as_ref() {
+            item.update(properties);
+            item.update_menu(menu);
+            return Ok(());
+        }
+
+        let (sender, mut receiver) = mpsc::unbounded();
+        let item = StatusNotifierItem::start(
+            properties,
+            menu,
+            Arc::new(move |event| {
+                sender.unbounded_send(event).ok();
+            }),
+        )
+        .context("Failed to start the tray icon thread")?;
+        self.0.item.replace(Some(item));
+
+        // Ends once the item is dropped and its thread lets go of the sender.
+        let this = self.clone();
+        self.0
+            .executor
+            .spawn(async move {
+                while let Some(event) = receiver.next().await {
+                    this.handle_event(event);
+                }
+            })
+            .detach();
+        Ok(())
+    }
+
+    fn handle_event(&self, event: StatusNotifierEvent) {
+        let event = match event {
+            StatusNotifierEvent::MenuActivated(menu_item_id) => {
+                let Some(action) = self
+                    .0
+                    .menu_actions
+                    .borrow()
+                    .get(&menu_item_id)
+                    .map(|action| action.boxed_clone())
+                else {
+                    return;
+                };
+                let callback = self.0.menu_action_callback.borrow_mut().take();
+                if let Some(mut callback) = callback {
+                    callback(action.as_ref());
+                    self.0
+                        .menu_action_callback
+                        .borrow_mut()
+                        .get_or_insert(callback);
+                }
+                return;
+            }
+            StatusNotifierEvent::Activate { x, y } => TrayEvent::Activate {
+                position: point(px(x as f32), px(y as f32)),
+            },
+            StatusNotifierEvent::SecondaryActivate { x, y } => TrayEvent::SecondaryActivate {
+                position: point(px(x as f32), px(y as f32)),
+            },
+            StatusNotifierEvent::Scroll { delta, orientation } => TrayEvent::Scroll {
+                delta,
+                axis: if orientation == "horizontal" {
+                    Axis::Horizontal
+                } else {
+                    Axis::Vertical
+                },
+            },
+        };
+
+        let callback = self.0.event_callback.borrow_mut().take();
+        if let Some(mut callback) = callback {
+            callback(event);
+            self.0.event_callback.borrow_mut().get_or_insert(callback);
+        }
+    }
+}
+
+fn status_notifier_properties(tray_icon: &TrayIcon) -> StatusNotifierProperties {
+    let (icon_name, icon_pixmap) = status_notifier_icon(tray_icon.icon.as_ref());
+    let (attention_icon_name, attention_icon_pixmap) = status_notifier_icon(
+        tray_icon
+            .attention_icon
+            .as_ref()
+            .or(tray_icon.icon.as_ref()),
+    );
+    StatusNotifierProperties {
+        id: application_name(),
+        title: tray_icon.title.to_string(),
+        status: match tray_icon.status {
+            TrayStatus::Passive => "Passive",
+            TrayStatus::Active => "Active",
+            TrayStatus::NeedsAttention => "NeedsAttention",
+        },
+        icon_name,
+        icon_pixmap,
+        attention_icon_name,
+        attention_icon_pixmap,
+        tooltip: tray_icon
+            .tooltip
+            .as_ref()
+            .map(ToString::to_string)
+            .unwrap_or_default(),
+    }
+}
+
+fn status_notifier_icon(image: Option<&TrayIconImage>) -> (String, Vec<StatusNotifierPixmap>) {
+    match image {
+        Some(TrayIconImage::Named(name)) => (name.to_string(), Vec::new()),
+        Some(TrayIconImage::Rgba {
+            width,
+            height,
+            bytes,
+        }) => (
+            String::new(),
+            vec![(*width as i32, *height as i32, rgba_to_argb(bytes))],
+        ),
+        None => (String::new(), Vec::new()),
+    }
+}
+
+/// StatusNotifierItem pixmaps are ARGB32 in network byte order.
+fn rgba_to_argb(rgba: &[u8]) -> Vec<u8> {
+    rgba.chunks_exact(4)
+        .flat_map(|pixel| [pixel[3], pixel[0], pixel[1], pixel[2]])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rgba_to_argb() {
+        assert_eq!(
+            rgba_to_argb(&[1, 2, 3, 4, 5, 6, 7, 8]),
+            vec![4, 1, 2, 3, 8, 5, 6, 7]
+        );
+    }
+}
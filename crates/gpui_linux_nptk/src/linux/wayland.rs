@@ -2,6 +2,8 @@ mod client;
 mod clipboard;
 mod cursor;
 mod display;
+mod foreign_toplevel;
+mod popup;
 mod serial;
 mod window;
 
@@ -14,7 +16,7 @@ use wayland_protocols::wp::cursor_shape::v1::client::wp_cursor_shape_device_v1::
 
 use gpui::CursorStyle;
 
-pub(super) fn to_shape(style: CursorStyle) -> Shape {
+pub(super) fn to_shape(style: &CursorStyle) -> Shape {
     match style {
         CursorStyle::Arrow => Shape::Default,
         CursorStyle::IBeam => Shape::Text,
@@ -37,5 +39,7 @@ pub(super) fn to_shape(style: CursorStyle) -> Shape {
         CursorStyle::DragLink => Shape::Alias,
         CursorStyle::DragCopy => Shape::Copy,
         CursorStyle::ContextualMenu => Shape::ContextMenu,
+        // Image cursors are drawn on our own surface instead, see `Cursor::set_image`.
+        CursorStyle::Custom { .. } | CursorStyle::Animated { .. } => Shape::Default,
     }
 }
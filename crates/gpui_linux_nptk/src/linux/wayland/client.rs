@@ -9,7 +9,7 @@ use std::{
 
 use ashpd::WindowIdentifier;
 use calloop::{
-    EventLoop, LoopHandle,
+    EventLoop, LoopHandle, RegistrationToken,
     timer::{TimeoutAction, Timer},
 };
 use calloop_wayland_source::WaylandSource;
@@ -36,9 +36,22 @@ use wayland_client::{
         wl_shm_pool, wl_surface,
     },
 };
+use wayland_protocols::ext::foreign_toplevel_list::v1::client::{
+    ext_foreign_toplevel_handle_v1, ext_foreign_toplevel_list_v1,
+};
+use wayland_protocols::ext::session_lock::v1::client::{
+    ext_session_lock_manager_v1, ext_session_lock_surface_v1, ext_session_lock_v1,
+};
+use wayland_protocols::wp::idle_inhibit::zv1::client::{
+    zwp_idle_inhibit_manager_v1, zwp_idle_inhibitor_v1,
+};
+use wayland_protocols::wp::pointer_constraints::zv1::client::{
+    zwp_confined_pointer_v1, zwp_locked_pointer_v1, zwp_pointer_constraints_v1,
+};
 use wayland_protocols::wp::pointer_gestures::zv1::client::{
     zwp_pointer_gesture_pinch_v1, zwp_pointer_gestures_v1,
 };
+use wayland_protocols::wp::presentation_time::client::{wp_presentation, wp_presentation_feedback};
 use wayland_protocols::wp::primary_selection::zv1::client::zwp_primary_selection_offer_v1::{
     self, ZwpPrimarySelectionOfferV1,
 };
@@ -46,6 +59,9 @@ use wayland_protocols::wp::primary_selection::zv1::client::{
     zwp_primary_selection_device_manager_v1, zwp_primary_selection_device_v1,
     zwp_primary_selection_source_v1,
 };
+use wayland_protocols::wp::relative_pointer::zv1::client::{
+    zwp_relative_pointer_manager_v1, zwp_relative_pointer_v1,
+};
 use wayland_protocols::wp::text_input::zv3::client::zwp_text_input_v3::{
     ContentHint, ContentPurpose,
 };
@@ -57,8 +73,11 @@ use wayland_protocols::xdg::activation::v1::client::{xdg_activation_token_v1, xd
 use wayland_protocols::xdg::decoration::zv1::client::{
     zxdg_decoration_manager_v1, zxdg_toplevel_decoration_v1,
 };
-use wayland_protocols::xdg::shell::client::{xdg_surface, xdg_toplevel, xdg_wm_base};
+use wayland_protocols::xdg::shell::client::{
+    xdg_popup, xdg_positioner, xdg_surface, xdg_toplevel, xdg_wm_base,
+};
 use wayland_protocols::xdg::system_bell::v1::client::xdg_system_bell_v1;
+use wayland_protocols::xdg::xdg_output::zv1::client::{zxdg_output_manager_v1, zxdg_output_v1};
 use wayland_protocols::{
     wp::cursor_shape::v1::client::{wp_cursor_shape_device_v1, wp_cursor_shape_manager_v1},
     xdg::dialog::v1::client::xdg_wm_dialog_v1::{self, XdgWmDialogV1},
@@ -69,6 +88,9 @@ use wayland_protocols::{
 };
 use wayland_protocols_plasma::appmenu::client::{org_kde_kwin_appmenu, org_kde_kwin_appmenu_manager};
 use wayland_protocols_plasma::blur::client::{org_kde_kwin_blur, org_kde_kwin_blur_manager};
+use wayland_protocols_wlr::foreign_toplevel::v1::client::{
+    zwlr_foreign_toplevel_handle_v1, zwlr_foreign_toplevel_manager_v1,
+};
 use wayland_protocols_wlr::layer_shell::v1::client::{zwlr_layer_shell_v1, zwlr_layer_surface_v1};
 use xkbcommon::xkb::ffi::XKB_KEYMAP_FORMAT_TEXT_V1;
 use xkbcommon::xkb::{self, KEYMAP_COMPILE_NO_FLAGS, Keycode};
@@ -79,16 +101,18 @@ use super::{
 };
 
 use crate::linux::{
-    DOUBLE_CLICK_INTERVAL, LinuxClient, LinuxCommon, LinuxKeyboardLayout, SCROLL_LINES,
-    capslock_from_xkb, cursor_style_to_icon_names, get_xkb_compose_state, is_within_click_distance,
-    keystroke_from_xkb, keystroke_underlying_dead_key, modifiers_from_xkb, open_uri_internal,
-    read_fd, reveal_path_internal,
+    DOUBLE_CLICK_DISTANCE, DOUBLE_CLICK_INTERVAL, InstanceRequest, LinuxClient, LinuxCommon,
+    LinuxKeyboardLayout, SCROLL_LINES, capslock_from_xkb, cursor_style_to_icon_names,
+    get_xkb_compose_state, instant_from_clock, is_within_click_distance, keystroke_from_xkb,
+    keystroke_underlying_dead_key, modifiers_from_xkb, open_uri_internal, read_fd,
+    reveal_path_internal,
     wayland::{
         clipboard::{
             Clipboard, DataOffer, FILE_LIST_MIME_TYPE, GNOME_COPIED_FILES_MIME_TYPE,
             TEXT_MIME_TYPES,
         },
         cursor::Cursor,
+        foreign_toplevel::{ForeignToplevelHandle, ForeignToplevels, parse_wlr_state},
         serial::{SerialKind, SerialTracker},
         to_shape,
         window::WaylandWindow,
@@ -100,8 +124,12 @@ use gpui::{
     ForegroundExecutor, KeyDownEvent, KeyUpEvent, Keystroke, Modifiers, ModifiersChangedEvent,
     MouseButton, MouseDownEvent, MouseExitEvent, MouseMoveEvent, MouseUpEvent, NavigationDirection,
     Pixels, PlatformDisplay, PlatformInput, PlatformKeyboardLayout, PlatformWindow, Point,
-    ScrollDelta, ScrollWheelEvent, SharedString, Size, TouchPhase, WindowButtonLayout,
-    WindowParams, point, profiler, px, size,
+    PresentationFeedback, RawMouseMotionEvent, ScrollDelta, ScrollWheelEvent, SharedString, Size,
+    TouchPhase, WindowButtonLayout, WindowKind, WindowParams,
+    foreign_toplevel::{
+        ForeignToplevel, ForeignToplevelEvent, ForeignToplevelId, ForeignToplevelRequest,
+    },
+    point, profiler, px, size,
 };
 use gpui_wgpu::{CompositorGpuHint, GpuContext};
 use wayland_protocols::wp::linux_dmabuf::zv1::client::{
@@ -128,14 +156,24 @@ pub struct Globals {
     pub viewporter: Option<wp_viewporter::WpViewporter>,
     pub fractional_scale_manager:
         Option<wp_fractional_scale_manager_v1::WpFractionalScaleManagerV1>,
+    pub presentation: Option<wp_presentation::WpPresentation>,
     pub decoration_manager: Option<zxdg_decoration_manager_v1::ZxdgDecorationManagerV1>,
     pub layer_shell: Option<zwlr_layer_shell_v1::ZwlrLayerShellV1>,
     pub appmenu_manager: Option<org_kde_kwin_appmenu_manager::OrgKdeKwinAppmenuManager>,
     pub blur_manager: Option<org_kde_kwin_blur_manager::OrgKdeKwinBlurManager>,
     pub text_input_manager: Option<zwp_text_input_manager_v3::ZwpTextInputManagerV3>,
     pub gesture_manager: Option<zwp_pointer_gestures_v1::ZwpPointerGesturesV1>,
+    pub idle_inhibit_manager: Option<zwp_idle_inhibit_manager_v1::ZwpIdleInhibitManagerV1>,
+    pub pointer_constraints: Option<zwp_pointer_constraints_v1::ZwpPointerConstraintsV1>,
+    pub relative_pointer_manager:
+        Option<zwp_relative_pointer_manager_v1::ZwpRelativePointerManagerV1>,
+    pub session_lock_manager: Option<ext_session_lock_manager_v1::ExtSessionLockManagerV1>,
+    pub foreign_toplevel_manager:
+        Option<zwlr_foreign_toplevel_manager_v1::ZwlrForeignToplevelManagerV1>,
+    pub foreign_toplevel_list: Option<ext_foreign_toplevel_list_v1::ExtForeignToplevelListV1>,
     pub dialog: Option<xdg_wm_dialog_v1::XdgWmDialogV1>,
     pub system_bell: Option<xdg_system_bell_v1::XdgSystemBellV1>,
+    pub xdg_output_manager: Option<zxdg_output_manager_v1::ZxdgOutputManagerV1>,
     pub executor: ForegroundExecutor,
 }
 
@@ -147,6 +185,13 @@ impl Globals {
         seat: wl_seat::WlSeat,
     ) -> Self {
         let dialog_v = XdgWmDialogV1::interface().version;
+        let foreign_toplevel_manager = globals.bind(&qh, 1..=3, ()).ok();
+        // Both protocols report the same toplevels, so only fall back to the list-only protocol
+        let foreign_toplevel_list = if foreign_toplevel_manager.is_none() {
+            globals.bind(&qh, 1..=1, ()).ok()
+        } else {
+            None
+        };
         Globals {
             activation: globals.bind(&qh, 1..=1, ()).ok(),
             compositor: globals
@@ -171,27 +216,41 @@ impl Globals {
             wm_base: globals.bind(&qh, 1..=5, ()).unwrap(),
             viewporter: globals.bind(&qh, 1..=1, ()).ok(),
             fractional_scale_manager: globals.bind(&qh, 1..=1, ()).ok(),
+            presentation: globals.bind(&qh, 1..=1, ()).ok(),
             decoration_manager: globals.bind(&qh, 1..=1, ()).ok(),
             layer_shell: globals.bind(&qh, 1..=5, ()).ok(),
             appmenu_manager: globals.bind(&qh, 1..=2, ()).ok(),
             blur_manager: globals.bind(&qh, 1..=1, ()).ok(),
             text_input_manager: globals.bind(&qh, 1..=1, ()).ok(),
             gesture_manager: globals.bind(&qh, 1..=3, ()).ok(),
+            idle_inhibit_manager: globals.bind(&qh, 1..=1, ()).ok(),
+            pointer_constraints: globals.bind(&qh, 1..=1, ()).ok(),
+            relative_pointer_manager: globals.bind(&qh, 1..=1, ()).ok(),
+            session_lock_manager: globals.bind(&qh, 1..=1, ()).ok(),
+            foreign_toplevel_manager,
+            foreign_toplevel_list,
             dialog: globals.bind(&qh, dialog_v..=dialog_v, ()).ok(),
             system_bell: globals.bind(&qh, 1..=1, ()).ok(),
+            xdg_output_manager: globals.bind(&qh, 1..=3, ()).ok(),
             executor,
             qh,
         }
     }
 }
 
+/// The state of an output as reported so far. Output events only carry what changed, so this is
+/// kept for the lifetime of the output and applied on every `done` event.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct InProgressOutput {
     name: Option<String>,
     scale: Option<i32>,
     position: Option<Point<DevicePixels>>,
     size: Option<Size<DevicePixels>>,
+    refresh: Option<i32>,
+    logical_position: Option<Point<DevicePixels>>,
+    logical_size: Option<Size<DevicePixels>>,
     subpixel: Option<wl_output::Subpixel>,
+    transform: Option<wl_output::Transform>,
 }
 
 impl InProgressOutput {
@@ -202,7 +261,13 @@ impl InProgressOutput {
                 name: self.name.clone(),
                 scale,
                 bounds: Bounds::new(position, size),
+                logical_bounds: self
+                    .logical_position
+                    .zip(self.logical_size)
+                    .map(|(position, size)| Bounds::new(position, size)),
+                refresh: self.refresh,
                 subpixel: self.subpixel,
+                transform: self.transform,
             })
         } else {
             None
@@ -215,7 +280,50 @@ pub struct Output {
     pub name: Option<String>,
     pub scale: i32,
     pub bounds: Bounds<DevicePixels>,
+    /// The area of the output in the compositor's logical coordinate space, if the compositor
+    /// supports xdg-output.
+    pub logical_bounds: Option<Bounds<DevicePixels>>,
+    /// The refresh rate of the current mode in mHz.
+    pub refresh: Option<i32>,
     pub subpixel: Option<wl_output::Subpixel>,
+    /// The rotation of the output, which the mode size doesn't include.
+    pub transform: Option<wl_output::Transform>,
+}
+
+impl Output {
+    /// The size of the current mode as it's shown, with width and height swapped for outputs
+    /// rotated by 90 or 270 degrees.
+    fn transformed_size(&self) -> Size<DevicePixels> {
+        match self.transform {
+            Some(
+                wl_output::Transform::_90
+                | wl_output::Transform::_270
+                | wl_output::Transform::Flipped90
+                | wl_output::Transform::Flipped270,
+            ) => size(self.bounds.size.height, self.bounds.size.width),
+            _ => self.bounds.size,
+        }
+    }
+
+    /// The area of the output in logical pixels.
+    pub fn bounds_in_pixels(&self) -> Bounds<Pixels> {
+        match self.logical_bounds {
+            Some(logical_bounds) => logical_bounds.to_pixels(1.0),
+            None => Bounds::new(self.bounds.origin, self.transformed_size())
+                .to_pixels(self.scale as f32),
+        }
+    }
+
+    /// The scale factor of the output, which is fractional if the compositor scales the logical
+    /// area to a different mode size.
+    pub fn scale_factor(&self) -> f32 {
+        match self.logical_bounds {
+            Some(logical_bounds) if logical_bounds.size.width.0 > 0 => {
+                self.transformed_size().width.0 as f32 / logical_bounds.size.width.0 as f32
+            }
+            _ => self.scale as f32,
+        }
+    }
 }
 
 pub(crate) struct WaylandClientState {
@@ -224,10 +332,13 @@ pub(crate) struct WaylandClientState {
     globals: Globals,
     pub gpu_context: GpuContext,
     pub compositor_gpu: Option<CompositorGpuHint>,
+    /// The clock `wp_presentation` timestamps are taken from.
+    presentation_clock: libc::clockid_t,
     wl_seat: wl_seat::WlSeat, // TODO: Multi seat support
     wl_pointer: Option<wl_pointer::WlPointer>,
     pinch_gesture: Option<zwp_pointer_gesture_pinch_v1::ZwpPointerGesturePinchV1>,
     pinch_scale: f32,
+    relative_pointer: Option<zwp_relative_pointer_v1::ZwpRelativePointerV1>,
     wl_keyboard: Option<wl_keyboard::WlKeyboard>,
     cursor_shape_device: Option<wp_cursor_shape_device_v1::WpCursorShapeDeviceV1>,
     data_device: Option<wl_data_device::WlDataDevice>,
@@ -242,6 +353,8 @@ pub(crate) struct WaylandClientState {
     outputs: HashMap<ObjectId, Output>,
     in_progress_outputs: HashMap<ObjectId, InProgressOutput>,
     wl_outputs: HashMap<ObjectId, wl_output::WlOutput>,
+    xdg_outputs: HashMap<ObjectId, zxdg_output_v1::ZxdgOutputV1>,
+    output_globals: HashMap<u32, ObjectId>,
     keyboard_layout: LinuxKeyboardLayout,
     keymap_state: Option<xkb::State>,
     compose_state: Option<xkb::compose::State>,
@@ -264,6 +377,7 @@ pub(crate) struct WaylandClientState {
     loop_handle: LoopHandle<'static, WaylandClientStatePtr>,
     cursor_style: Option<CursorStyle>,
     cursor_hidden_window: Option<WaylandWindowStatePtr>,
+    cursor_animation: Option<RegistrationToken>,
     clipboard: Clipboard,
     data_offers: Vec<DataOffer<WlDataOffer>>,
     primary_data_offer: Option<DataOffer<ZwpPrimarySelectionOfferV1>>,
@@ -271,6 +385,11 @@ pub(crate) struct WaylandClientState {
     appmenu_objects: HashMap<u32, org_kde_kwin_appmenu::OrgKdeKwinAppmenu>,
     appmenu_binding_by_surface: HashMap<u32, (String, String)>,
     pending_activation: Option<PendingActivation>,
+    /// Handed over by a later launch of the application, to activate the next window.
+    launch_activation_token: Option<String>,
+    session_lock: Option<ext_session_lock_v1::ExtSessionLockV1>,
+    session_locked: bool,
+    foreign_toplevels: ForeignToplevels,
     event_loop: Option<EventLoop<'static, WaylandClientStatePtr>>,
     pub common: LinuxCommon,
 }
@@ -320,6 +439,10 @@ impl WaylandClientStatePtr {
         self.0.upgrade().unwrap().borrow().serial_tracker.get(kind)
     }
 
+    pub fn get_pointer(&self) -> Option<wl_pointer::WlPointer> {
+        self.0.upgrade().unwrap().borrow().wl_pointer.clone()
+    }
+
     pub fn set_pending_activation(&self, window: ObjectId) {
         self.0.upgrade().unwrap().borrow_mut().pending_activation =
             Some(PendingActivation::Window(window));
@@ -406,6 +529,103 @@ impl WaylandClientStatePtr {
         }
     }
 
+    pub fn get_output(&self, display_id: DisplayId) -> Option<wl_output::WlOutput> {
+        let target_protocol_id: u64 = display_id.into();
+        self.get_client()
+            .borrow()
+            .wl_outputs
+            .iter()
+            .find(|(id, _)| id.protocol_id() as u64 == target_protocol_id)
+            .map(|(_, output)| output.clone())
+    }
+
+    /// Applies the changes reported for an output, notifying about display changes if its
+    /// properties differ from before.
+    fn handle_output_done(&self, output_id: &ObjectId) {
+        let client = self.get_client();
+        let mut state = client.borrow_mut();
+        let Some(output) = state
+            .in_progress_outputs
+            .get(output_id)
+            .and_then(InProgressOutput::complete)
+        else {
+            return;
+        };
+        let previous = state.outputs.insert(output_id.clone(), output.clone());
+        drop(state);
+
+        if previous.as_ref() != Some(&output) {
+            self.handle_displays_changed();
+        }
+    }
+
+    fn handle_displays_changed(&self) {
+        let client = self.get_client();
+        let mut state = client.borrow_mut();
+        if let Some(mut callback) = state.common.callbacks.displays_changed.take() {
+            drop(state);
+            callback();
+            state = client.borrow_mut();
+            state.common.callbacks.displays_changed = Some(callback);
+        }
+    }
+
+    pub fn take_launch_activation_token(&self) -> Option<String> {
+        self.get_client()
+            .borrow_mut()
+            .launch_activation_token
+            .take()
+    }
+
+    fn handle_instance_request(&self, request: InstanceRequest) {
+        let client = self.get_client();
+        if request.activation_token.is_some() {
+            client.borrow_mut().launch_activation_token = request.activation_token.clone();
+        }
+        LinuxCommon::handle_instance_request(
+            || RefMut::map(client.borrow_mut(), |state| &mut state.common),
+            request,
+        );
+    }
+
+    fn emit_foreign_toplevel_event(&self, event: ForeignToplevelEvent) {
+        let client = self.get_client();
+        let mut state = client.borrow_mut();
+        if let Some(mut callback) = state.common.callbacks.foreign_toplevel_event.take() {
+            drop(state);
+            callback(event);
+            state = client.borrow_mut();
+            state.common.callbacks.foreign_toplevel_event = Some(callback);
+        }
+    }
+
+    pub fn end_session_lock(&self) {
+        let client = self.get_client();
+        let mut state = client.borrow_mut();
+        let Some(session_lock) = state.session_lock.take() else {
+            return;
+        };
+        if std::mem::take(&mut state.session_locked) {
+            session_lock.unlock_and_destroy();
+        } else {
+            session_lock.destroy();
+        }
+        if let Err(error) = state.connection.flush() {
+            log::error!("Error flushing Wayland connection after unlocking session: {error:?}");
+        }
+        let lock_windows = state
+            .windows
+            .values()
+            .filter(|window| window.is_session_lock())
+            .cloned()
+            .collect::<Vec<_>>();
+        drop(state);
+
+        for window in lock_windows {
+            window.close();
+        }
+    }
+
     pub fn drop_window(&self, surface_id: &ObjectId) {
         let client = self.get_client();
         let mut state = client.borrow_mut();
@@ -445,6 +665,7 @@ impl WaylandClientState {
             return;
         };
         let serial = self.serial_tracker.get(SerialKind::MouseEnter);
+        self.stop_cursor_animation();
         wl_pointer.set_cursor(serial, None, 0, 0);
         self.cursor_hidden_window = Some(focused_window);
     }
@@ -453,12 +674,14 @@ impl WaylandClientState {
         if self.cursor_hidden_window.take().is_none() {
             return;
         }
-        let Some(style) = self.cursor_style else {
+        let Some(style) = self.cursor_style.clone() else {
             return;
         };
         let serial = self.serial_tracker.get(SerialKind::MouseEnter);
-        if let Some(cursor_shape_device) = &self.cursor_shape_device {
-            cursor_shape_device.set_shape(serial, to_shape(style));
+        if let Some(cursor_shape_device) = &self.cursor_shape_device
+            && !style.is_image()
+        {
+            cursor_shape_device.set_shape(serial, to_shape(&style));
             return;
         }
         let Some(focused_window) = self.mouse_focused_window.clone() else {
@@ -476,12 +699,74 @@ impl WaylandClientState {
             return;
         };
         let scale = focused_window.primary_output_scale();
-        self.cursor.set_icon(
-            &wl_pointer,
-            serial,
-            cursor_style_to_icon_names(style),
-            scale,
-        );
+        self.apply_cursor_style(&wl_pointer, serial, &style, scale);
+    }
+
+    /// Shows `style` on the pointer, preferring cursor-shape-v1 for theme cursors and drawing
+    /// image cursors on our own surface.
+    fn apply_cursor_style(
+        &mut self,
+        wl_pointer: &wl_pointer::WlPointer,
+        serial: u32,
+        style: &CursorStyle,
+        scale: i32,
+    ) {
+        self.stop_cursor_animation();
+        if style.is_image() {
+            match self.cursor.set_image(wl_pointer, serial, style, 0, scale) {
+                Ok(Some(frame_duration)) => self.start_cursor_animation(frame_duration),
+                Ok(None) => {}
+                Err(error) => log::warn!("{error:#}"),
+            }
+        } else if let Some(cursor_shape_device) = &self.cursor_shape_device {
+            cursor_shape_device.set_shape(serial, to_shape(style));
+        } else {
+            // cursor-shape-v1 isn't supported, set the cursor using a surface.
+            self.cursor
+                .set_icon(wl_pointer, serial, cursor_style_to_icon_names(style), scale);
+        }
+    }
+
+    fn start_cursor_animation(&mut self, first_frame_duration: Duration) {
+        let mut frame_index = 0;
+        self.cursor_animation = self
+            .loop_handle
+            .insert_source(
+                Timer::from_duration(first_frame_duration),
+                move |_, _, this| {
+                    let client = this.get_client();
+                    let mut state = client.borrow_mut();
+                    let state = &mut *state;
+                    let (Some(window), Some(wl_pointer), Some(style)) = (
+                        state.mouse_focused_window.as_ref(),
+                        state.wl_pointer.as_ref(),
+                        state.cursor_style.as_ref(),
+                    ) else {
+                        state.cursor_animation = None;
+                        return TimeoutAction::Drop;
+                    };
+                    frame_index += 1;
+                    let serial = state.serial_tracker.get(SerialKind::MouseEnter);
+                    let scale = window.primary_output_scale();
+                    match state
+                        .cursor
+                        .set_image(wl_pointer, serial, style, frame_index, scale)
+                    {
+                        Ok(Some(frame_duration)) => TimeoutAction::ToDuration(frame_duration),
+                        _ => {
+                            state.cursor_animation = None;
+                            TimeoutAction::Drop
+                        }
+                    }
+                },
+            )
+            .log_err();
+    }
+
+    fn stop_cursor_animation(&mut self) {
+        if let Some(token) = self.cursor_animation.take() {
+            self.loop_handle.remove(token);
+        }
     }
 
     fn flush_after_appmenu_set_address(&self, surface_id: u32) {
@@ -649,6 +934,7 @@ impl WaylandClient {
         let mut in_progress_outputs = HashMap::default();
         #[allow(clippy::mutable_key_type)]
         let mut wl_outputs: HashMap<ObjectId, wl_output::WlOutput> = HashMap::default();
+        let mut output_globals = HashMap::default();
         globals.contents().with_list(|list| {
             for global in list {
                 match &global.interface[..] {
@@ -668,6 +954,7 @@ impl WaylandClient {
                             (),
                         );
                         in_progress_outputs.insert(output.id(), InProgressOutput::default());
+                        output_globals.insert(global.name, output.id());
                         wl_outputs.insert(output.id(), output);
                     }
                     _ => {}
@@ -677,7 +964,8 @@ impl WaylandClient {
 
         let event_loop = EventLoop::<WaylandClientStatePtr>::try_new().unwrap();
 
-        let (common, main_receiver, bridge_receiver) = LinuxCommon::new(event_loop.get_signal());
+        let (common, main_receiver, bridge_receiver, instance_request_receiver) =
+            LinuxCommon::new(event_loop.get_signal());
 
         let handle = event_loop.handle();
         handle
@@ -713,6 +1001,17 @@ impl WaylandClient {
             })
             .unwrap();
 
+        handle
+            .insert_source(
+                instance_request_receiver,
+                |event, _, client: &mut WaylandClientStatePtr| {
+                    if let calloop::channel::Event::Msg(request) = event {
+                        client.handle_instance_request(request);
+                    }
+                },
+            )
+            .unwrap();
+
         let compositor_gpu = detect_compositor_gpu();
         let gpu_context = Rc::new(RefCell::new(None));
 
@@ -734,6 +1033,21 @@ impl WaylandClient {
             .as_ref()
             .map(|primary_selection_manager| primary_selection_manager.get_device(&seat, &qh, ()));
 
+        #[allow(clippy::mutable_key_type)]
+        let xdg_outputs = globals
+            .xdg_output_manager
+            .as_ref()
+            .map(|xdg_output_manager| {
+                wl_outputs
+                    .iter()
+                    .map(|(id, output)| {
+                        let xdg_output = xdg_output_manager.get_xdg_output(output, &qh, id.clone());
+                        (id.clone(), xdg_output)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
         let cursor = Cursor::new(&conn, &globals, 24);
 
         handle
@@ -750,6 +1064,45 @@ impl WaylandClient {
                             }
                         }
                     }
+                    XDPEvent::AccentColor(accent_color) => {
+                        if let Some(client) = client.0.upgrade() {
+                            let mut state = client.borrow_mut();
+                            if state.common.accent_color == accent_color {
+                                return;
+                            }
+                            state.common.accent_color = accent_color;
+                            if let Some(mut callback) =
+                                state.common.callbacks.accent_color_changed.take()
+                            {
+                                drop(state);
+                                callback();
+                                state = client.borrow_mut();
+                                state.common.callbacks.accent_color_changed = Some(callback);
+                            }
+                        }
+                    }
+                    XDPEvent::Accessibility(setting) => {
+                        if let Some(client) = client.0.upgrade() {
+                            let mut state = client.borrow_mut();
+                            let previous = state.common.accessibility_preferences;
+                            setting.apply(&mut state.common.accessibility_preferences);
+                            if state.common.accessibility_preferences == previous {
+                                return;
+                            }
+                            if let Some(mut callback) = state
+                                .common
+                                .callbacks
+                                .accessibility_preferences_changed
+                                .take()
+                            {
+                                drop(state);
+                                callback();
+                                state = client.borrow_mut();
+                                state.common.callbacks.accessibility_preferences_changed =
+                                    Some(callback);
+                            }
+                        }
+                    }
                     XDPEvent::ButtonLayout(layout_str) => {
                         if let Some(client) = client.0.upgrade() {
                             let layout = WindowButtonLayout::parse(&layout_str)
@@ -785,11 +1138,13 @@ impl WaylandClient {
             globals,
             gpu_context,
             compositor_gpu,
+            presentation_clock: libc::CLOCK_MONOTONIC,
             wl_seat: seat,
             wl_pointer: None,
             wl_keyboard: None,
             pinch_gesture: None,
             pinch_scale: 1.0,
+            relative_pointer: None,
             cursor_shape_device: None,
             data_device,
             primary_selection,
@@ -800,6 +1155,8 @@ impl WaylandClient {
             outputs: HashMap::default(),
             in_progress_outputs,
             wl_outputs,
+            xdg_outputs,
+            output_globals,
             windows: HashMap::default(),
             common,
             keyboard_layout: LinuxKeyboardLayout::new(UNKNOWN_KEYBOARD_LAYOUT_NAME),
@@ -844,6 +1201,7 @@ impl WaylandClient {
             enter_token: None,
             cursor_style: None,
             cursor_hidden_window: None,
+            cursor_animation: None,
             clipboard: Clipboard::new(conn.clone(), handle.clone()),
             data_offers: Vec::new(),
             primary_data_offer: None,
@@ -851,6 +1209,10 @@ impl WaylandClient {
             appmenu_objects: HashMap::default(),
             appmenu_binding_by_surface: HashMap::default(),
             pending_activation: None,
+            launch_activation_token: None,
+            session_lock: None,
+            session_locked: false,
+            foreign_toplevels: ForeignToplevels::default(),
             event_loop: Some(event_loop),
         }));
 
@@ -873,11 +1235,7 @@ impl LinuxClient for WaylandClient {
             .outputs
             .iter()
             .map(|(id, output)| {
-                Rc::new(WaylandDisplay {
-                    id: id.clone(),
-                    name: output.name.clone(),
-                    bounds: output.bounds.to_pixels(output.scale as f32),
-                }) as Rc<dyn PlatformDisplay>
+                Rc::new(WaylandDisplay::new(id.clone(), output)) as Rc<dyn PlatformDisplay>
             })
             .collect()
     }
@@ -889,11 +1247,8 @@ impl LinuxClient for WaylandClient {
             .iter()
             .find_map(|(object_id, output)| {
                 (object_id.protocol_id() as u64 == u64::from(id)).then(|| {
-                    Rc::new(WaylandDisplay {
-                        id: object_id.clone(),
-                        name: output.name.clone(),
-                        bounds: output.bounds.to_pixels(output.scale as f32),
-                    }) as Rc<dyn PlatformDisplay>
+                    Rc::new(WaylandDisplay::new(object_id.clone(), output))
+                        as Rc<dyn PlatformDisplay>
                 })
             })
     }
@@ -927,7 +1282,19 @@ impl LinuxClient for WaylandClient {
     ) -> anyhow::Result<Box<dyn PlatformWindow>> {
         let mut state = self.0.borrow_mut();
 
-        let parent = state.keyboard_focused_window.clone();
+        let parent = if let WindowKind::AnchoredPopUp(options) = &params.kind {
+            state
+                .windows
+                .values()
+                .find(|window| window.handle() == options.parent)
+                .cloned()
+        } else {
+            state.keyboard_focused_window.clone()
+        };
+        let grab_serial = state
+            .serial_tracker
+            .get(SerialKind::MousePress)
+            .max(state.serial_tracker.get(SerialKind::KeyPress));
 
         let target_output = params.display_id.and_then(|display_id| {
             let target_protocol_id: u64 = display_id.into();
@@ -938,9 +1305,25 @@ impl LinuxClient for WaylandClient {
                 .map(|(_, output)| output.clone())
         });
 
+        // Check everything the lock surface needs before locking, as the session can't be
+        // unlocked by the user once it's locked without one.
+        let mut locked_session = false;
+        if params.kind == WindowKind::SessionLock {
+            if target_output.is_none() {
+                anyhow::bail!("Session lock windows must be opened on a display");
+            }
+            if state.session_lock.is_none()
+                && let Some(session_lock_manager) = state.globals.session_lock_manager.as_ref()
+            {
+                let session_lock = session_lock_manager.lock(&state.globals.qh, ());
+                state.session_lock = Some(session_lock);
+                locked_session = true;
+            }
+        }
+
         let appearance = state.common.appearance;
         let compositor_gpu = state.compositor_gpu.take();
-        let (window, surface_id) = WaylandWindow::new(
+        let (window, surface_id) = match WaylandWindow::new(
             handle,
             state.globals.clone(),
             state.gpu_context.clone(),
@@ -950,8 +1333,24 @@ impl LinuxClient for WaylandClient {
             appearance,
             parent,
             target_output,
-        )?;
+            grab_serial,
+            state.session_lock.as_ref(),
+        ) {
+            Ok(window) => window,
+            Err(error) => {
+                if locked_session {
+                    drop(state);
+                    WaylandClientStatePtr(Rc::downgrade(&self.0)).end_session_lock();
+                }
+                return Err(error);
+            }
+        };
         state.windows.insert(surface_id, window.0.clone());
+        if let Some(activation) = state.globals.activation.as_ref()
+            && let Some(token) = state.launch_activation_token.take()
+        {
+            activation.activate(token, &window.0.surface());
+        }
         if crate::linux::dbus_menu::common::MenuInfoStorage::get().is_some() {
             state.sync_global_menu_surface_binding_for_all_windows();
         }
@@ -962,7 +1361,7 @@ impl LinuxClient for WaylandClient {
     fn set_cursor_style(&self, style: CursorStyle) {
         let mut state = self.0.borrow_mut();
 
-        let need_update = state.cursor_style != Some(style)
+        let need_update = state.cursor_style.as_ref() != Some(&style)
             && (state.mouse_focused_window.is_none()
                 || state
                     .mouse_focused_window
@@ -973,7 +1372,7 @@ impl LinuxClient for WaylandClient {
             return;
         }
 
-        state.cursor_style = Some(style);
+        state.cursor_style = Some(style.clone());
 
         // Don't clobber the invisible cursor; restore reads back from `cursor_style`.
         if state.cursor_hidden_window.is_some() {
@@ -981,21 +1380,17 @@ impl LinuxClient for WaylandClient {
         }
 
         let serial = state.serial_tracker.get(SerialKind::MouseEnter);
-        if let Some(cursor_shape_device) = &state.cursor_shape_device {
-            cursor_shape_device.set_shape(serial, to_shape(style));
-        } else if let Some(focused_window) = &state.mouse_focused_window {
-            // cursor-shape-v1 isn't supported, set the cursor using a surface.
+        if let Some(cursor_shape_device) = &state.cursor_shape_device
+            && !style.is_image()
+        {
+            cursor_shape_device.set_shape(serial, to_shape(&style));
+        } else if let Some(focused_window) = state.mouse_focused_window.clone() {
             let wl_pointer = state
                 .wl_pointer
                 .clone()
                 .expect("window is focused by pointer");
             let scale = focused_window.primary_output_scale();
-            state.cursor.set_icon(
-                &wl_pointer,
-                serial,
-                cursor_style_to_icon_names(style),
-                scale,
-            );
+            state.apply_cursor_style(&wl_pointer, serial, &style, scale);
         }
     }
 
@@ -1142,6 +1537,31 @@ impl LinuxClient for WaylandClient {
         "Wayland"
     }
 
+    fn unlock_session(&self) {
+        WaylandClientStatePtr(Rc::downgrade(&self.0)).end_session_lock();
+    }
+
+    fn foreign_toplevels(&self) -> Vec<ForeignToplevel> {
+        self.0.borrow().foreign_toplevels.toplevels()
+    }
+
+    fn request_foreign_toplevel(
+        &self,
+        id: ForeignToplevelId,
+        request: ForeignToplevelRequest,
+    ) -> anyhow::Result<()> {
+        let state = self.0.borrow();
+        state
+            .foreign_toplevels
+            .request(id, request, &state.wl_seat)?;
+        if let Err(error) = state.connection.flush() {
+            log::error!(
+                "Error flushing Wayland connection after foreign toplevel request: {error:?}"
+            );
+        }
+        Ok(())
+    }
+
     fn window_identifier(&self) -> impl Future<Output = Option<WindowIdentifier>> + Send + 'static {
         async fn inner(surface: Option<wl_surface::WlSurface>) -> Option<WindowIdentifier> {
             if let Some(surface) = surface {
@@ -1290,12 +1710,35 @@ impl Dispatch<wl_registry::WlRegistry, GlobalListContents> for WaylandClientStat
                     state
                         .in_progress_outputs
                         .insert(output.id(), InProgressOutput::default());
+                    if let Some(xdg_output_manager) = state.globals.xdg_output_manager.as_ref() {
+                        let xdg_output =
+                            xdg_output_manager.get_xdg_output(&output, qh, output.id());
+                        state.xdg_outputs.insert(output.id(), xdg_output);
+                    }
+                    state.output_globals.insert(name, output.id());
                     state.wl_outputs.insert(output.id(), output);
                 }
                 _ => {}
             },
-            wl_registry::Event::GlobalRemove { name: _ } => {
-                // TODO: handle global removal
+            wl_registry::Event::GlobalRemove { name } => {
+                let Some(output_id) = state.output_globals.remove(&name) else {
+                    return;
+                };
+                state.in_progress_outputs.remove(&output_id);
+                let removed = state.outputs.remove(&output_id).is_some();
+                if let Some(xdg_output) = state.xdg_outputs.remove(&output_id) {
+                    xdg_output.destroy();
+                }
+                if let Some(output) = state.wl_outputs.remove(&output_id)
+                    && output.version() >= wl_output::REQ_RELEASE_SINCE
+                {
+                    output.release();
+                }
+                drop(state);
+
+                if removed {
+                    this.handle_displays_changed();
+                }
             }
             _ => {}
         }
@@ -1304,6 +1747,7 @@ impl Dispatch<wl_registry::WlRegistry, GlobalListContents> for WaylandClientStat
 
 delegate_noop!(WaylandClientStatePtr: ignore xdg_activation_v1::XdgActivationV1);
 delegate_noop!(WaylandClientStatePtr: ignore xdg_system_bell_v1::XdgSystemBellV1);
+delegate_noop!(WaylandClientStatePtr: ignore zxdg_output_manager_v1::ZxdgOutputManagerV1);
 delegate_noop!(WaylandClientStatePtr: ignore wl_compositor::WlCompositor);
 delegate_noop!(WaylandClientStatePtr: ignore wp_cursor_shape_device_v1::WpCursorShapeDeviceV1);
 delegate_noop!(WaylandClientStatePtr: ignore wp_cursor_shape_manager_v1::WpCursorShapeManagerV1);
@@ -1323,6 +1767,14 @@ delegate_noop!(WaylandClientStatePtr: ignore org_kde_kwin_appmenu::OrgKdeKwinApp
 delegate_noop!(WaylandClientStatePtr: ignore org_kde_kwin_blur::OrgKdeKwinBlur);
 delegate_noop!(WaylandClientStatePtr: ignore wp_viewporter::WpViewporter);
 delegate_noop!(WaylandClientStatePtr: ignore wp_viewport::WpViewport);
+delegate_noop!(WaylandClientStatePtr: ignore xdg_positioner::XdgPositioner);
+delegate_noop!(WaylandClientStatePtr: ignore zwp_idle_inhibit_manager_v1::ZwpIdleInhibitManagerV1);
+delegate_noop!(WaylandClientStatePtr: ignore zwp_idle_inhibitor_v1::ZwpIdleInhibitorV1);
+delegate_noop!(WaylandClientStatePtr: ignore zwp_pointer_constraints_v1::ZwpPointerConstraintsV1);
+delegate_noop!(WaylandClientStatePtr: ignore zwp_locked_pointer_v1::ZwpLockedPointerV1);
+delegate_noop!(WaylandClientStatePtr: ignore zwp_confined_pointer_v1::ZwpConfinedPointerV1);
+delegate_noop!(WaylandClientStatePtr: ignore zwp_relative_pointer_manager_v1::ZwpRelativePointerManagerV1);
+delegate_noop!(WaylandClientStatePtr: ignore ext_session_lock_manager_v1::ExtSessionLockManagerV1);
 
 impl Dispatch<WlCallback, ObjectId> for WaylandClientStatePtr {
     fn event(
@@ -1399,20 +1851,71 @@ impl Dispatch<wl_output::WlOutput, ()> for WaylandClientStatePtr {
             wl_output::Event::Scale { factor } => {
                 in_progress_output.scale = Some(factor);
             }
-            wl_output::Event::Geometry { x, y, subpixel, .. } => {
+            wl_output::Event::Geometry {
+                x,
+                y,
+                subpixel,
+                transform,
+                ..
+            } => {
                 in_progress_output.position = Some(point(DevicePixels(x), DevicePixels(y)));
                 if let WEnum::Value(subpixel) = subpixel {
                     in_progress_output.subpixel = Some(subpixel);
                 }
+                if let WEnum::Value(transform) = transform {
+                    in_progress_output.transform = Some(transform);
+                }
             }
-            wl_output::Event::Mode { width, height, .. } => {
-                in_progress_output.size = Some(size(DevicePixels(width), DevicePixels(height)))
+            wl_output::Event::Mode {
+                width,
+                height,
+                refresh,
+                ..
+            } => {
+                in_progress_output.size = Some(size(DevicePixels(width), DevicePixels(height)));
+                in_progress_output.refresh = (refresh > 0).then_some(refresh);
             }
             wl_output::Event::Done => {
-                if let Some(complete) = in_progress_output.complete() {
-                    state.outputs.insert(output.id(), complete);
-                }
-                state.in_progress_outputs.remove(&output.id());
+                drop(state);
+                this.handle_output_done(&output.id());
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<zxdg_output_v1::ZxdgOutputV1, ObjectId> for WaylandClientStatePtr {
+    fn event(
+        this: &mut Self,
+        xdg_output: &zxdg_output_v1::ZxdgOutputV1,
+        event: zxdg_output_v1::Event,
+        output_id: &ObjectId,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let client = this.get_client();
+        let mut state = client.borrow_mut();
+
+        let Some(in_progress_output) = state.in_progress_outputs.get_mut(output_id) else {
+            return;
+        };
+
+        match event {
+            zxdg_output_v1::Event::LogicalPosition { x, y } => {
+                in_progress_output.logical_position = Some(point(DevicePixels(x), DevicePixels(y)));
+            }
+            zxdg_output_v1::Event::LogicalSize { width, height } => {
+                in_progress_output.logical_size =
+                    Some(size(DevicePixels(width), DevicePixels(height)));
+            }
+            // wl_output has carried the name since version 4.
+            zxdg_output_v1::Event::Name { name } if in_progress_output.name.is_none() => {
+                in_progress_output.name = Some(name);
+            }
+            // Since version 3 the changes are applied on the wl_output's done event instead.
+            zxdg_output_v1::Event::Done if xdg_output.version() < 3 => {
+                drop(state);
+                this.handle_output_done(output_id);
             }
             _ => {}
         }
@@ -1463,6 +1966,31 @@ impl Dispatch<xdg_toplevel::XdgToplevel, ObjectId> for WaylandClientStatePtr {
     }
 }
 
+impl Dispatch<xdg_popup::XdgPopup, ObjectId> for WaylandClientStatePtr {
+    fn event(
+        this: &mut Self,
+        _: &xdg_popup::XdgPopup,
+        event: <xdg_popup::XdgPopup as Proxy>::Event,
+        surface_id: &ObjectId,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let client = this.get_client();
+        let mut state = client.borrow_mut();
+        let Some(window) = get_window(&mut state, surface_id) else {
+            return;
+        };
+
+        drop(state);
+        let should_close = window.handle_popup_event(event);
+
+        if should_close {
+            // The close logic will be handled in drop_window()
+            window.close();
+        }
+    }
+}
+
 impl Dispatch<zwlr_layer_surface_v1::ZwlrLayerSurfaceV1, ObjectId> for WaylandClientStatePtr {
     fn event(
         this: &mut Self,
@@ -1488,6 +2016,215 @@ impl Dispatch<zwlr_layer_surface_v1::ZwlrLayerSurfaceV1, ObjectId> for WaylandCl
     }
 }
 
+impl Dispatch<ext_session_lock_v1::ExtSessionLockV1, ()> for WaylandClientStatePtr {
+    fn event(
+        this: &mut Self,
+        _: &ext_session_lock_v1::ExtSessionLockV1,
+        event: <ext_session_lock_v1::ExtSessionLockV1 as Proxy>::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            ext_session_lock_v1::Event::Locked => {
+                this.get_client().borrow_mut().session_locked = true;
+            }
+            ext_session_lock_v1::Event::Finished => {
+                log::warn!("Compositor refused or ended the session lock");
+                this.end_session_lock();
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<ext_session_lock_surface_v1::ExtSessionLockSurfaceV1, ObjectId>
+    for WaylandClientStatePtr
+{
+    fn event(
+        this: &mut Self,
+        _: &ext_session_lock_surface_v1::ExtSessionLockSurfaceV1,
+        event: <ext_session_lock_surface_v1::ExtSessionLockSurfaceV1 as Proxy>::Event,
+        surface_id: &ObjectId,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let client = this.get_client();
+        let mut state = client.borrow_mut();
+        let Some(window) = get_window(&mut state, surface_id) else {
+            return;
+        };
+
+        drop(state);
+        window.handle_session_lock_surface_event(event);
+    }
+}
+
+impl Dispatch<zwlr_foreign_toplevel_manager_v1::ZwlrForeignToplevelManagerV1, ()>
+    for WaylandClientStatePtr
+{
+    fn event(
+        this: &mut Self,
+        _: &zwlr_foreign_toplevel_manager_v1::ZwlrForeignToplevelManagerV1,
+        event: <zwlr_foreign_toplevel_manager_v1::ZwlrForeignToplevelManagerV1 as Proxy>::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            zwlr_foreign_toplevel_manager_v1::Event::Toplevel { toplevel } => {
+                let client = this.get_client();
+                let mut state = client.borrow_mut();
+                state
+                    .foreign_toplevels
+                    .insert(ForeignToplevelHandle::Wlr(toplevel));
+            }
+            zwlr_foreign_toplevel_manager_v1::Event::Finished => {
+                log::info!("Compositor stopped sending foreign toplevel events");
+            }
+            _ => {}
+        }
+    }
+
+    event_created_child!(WaylandClientStatePtr, zwlr_foreign_toplevel_manager_v1::ZwlrForeignToplevelManagerV1, [
+        zwlr_foreign_toplevel_manager_v1::EVT_TOPLEVEL_OPCODE => (zwlr_foreign_toplevel_handle_v1::ZwlrForeignToplevelHandleV1, ()),
+    ]);
+}
+
+impl Dispatch<zwlr_foreign_toplevel_handle_v1::ZwlrForeignToplevelHandleV1, ()>
+    for WaylandClientStatePtr
+{
+    fn event(
+        this: &mut Self,
+        handle: &zwlr_foreign_toplevel_handle_v1::ZwlrForeignToplevelHandleV1,
+        event: <zwlr_foreign_toplevel_handle_v1::ZwlrForeignToplevelHandleV1 as Proxy>::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let client = this.get_client();
+        let mut state = client.borrow_mut();
+        let object_id = handle.id();
+        let event = match event {
+            zwlr_foreign_toplevel_handle_v1::Event::Done => {
+                state.foreign_toplevels.commit(&object_id)
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::Closed => {
+                state.foreign_toplevels.remove(&object_id)
+            }
+            event => {
+                let Some(toplevel) = state.foreign_toplevels.pending_mut(&object_id) else {
+                    return;
+                };
+                match event {
+                    zwlr_foreign_toplevel_handle_v1::Event::Title { title } => {
+                        toplevel.title = title;
+                    }
+                    zwlr_foreign_toplevel_handle_v1::Event::AppId { app_id } => {
+                        toplevel.app_id = app_id;
+                    }
+                    zwlr_foreign_toplevel_handle_v1::Event::State { state } => {
+                        toplevel.state = parse_wlr_state(&state);
+                    }
+                    zwlr_foreign_toplevel_handle_v1::Event::OutputEnter { output } => {
+                        let display_id = DisplayId::new(output.id().protocol_id() as u64);
+                        if !toplevel.displays.contains(&display_id) {
+                            toplevel.displays.push(display_id);
+                        }
+                    }
+                    zwlr_foreign_toplevel_handle_v1::Event::OutputLeave { output } => {
+                        let display_id = DisplayId::new(output.id().protocol_id() as u64);
+                        toplevel.displays.retain(|id| *id != display_id);
+                    }
+                    _ => {}
+                }
+                None
+            }
+        };
+        drop(state);
+
+        if let Some(event) = event {
+            this.emit_foreign_toplevel_event(event);
+        }
+    }
+}
+
+impl Dispatch<ext_foreign_toplevel_list_v1::ExtForeignToplevelListV1, ()>
+    for WaylandClientStatePtr
+{
+    fn event(
+        this: &mut Self,
+        _: &ext_foreign_toplevel_list_v1::ExtForeignToplevelListV1,
+        event: <ext_foreign_toplevel_list_v1::ExtForeignToplevelListV1 as Proxy>::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            ext_foreign_toplevel_list_v1::Event::Toplevel { toplevel } => {
+                let client = this.get_client();
+                let mut state = client.borrow_mut();
+                state
+                    .foreign_toplevels
+                    .insert(ForeignToplevelHandle::Ext(toplevel));
+            }
+            ext_foreign_toplevel_list_v1::Event::Finished => {
+                log::info!("Compositor stopped sending foreign toplevel events");
+            }
+            _ => {}
+        }
+    }
+
+    event_created_child!(WaylandClientStatePtr, ext_foreign_toplevel_list_v1::ExtForeignToplevelListV1, [
+        ext_foreign_toplevel_list_v1::EVT_TOPLEVEL_OPCODE => (ext_foreign_toplevel_handle_v1::ExtForeignToplevelHandleV1, ()),
+    ]);
+}
+
+impl Dispatch<ext_foreign_toplevel_handle_v1::ExtForeignToplevelHandleV1, ()>
+    for WaylandClientStatePtr
+{
+    fn event(
+        this: &mut Self,
+        handle: &ext_foreign_toplevel_handle_v1::ExtForeignToplevelHandleV1,
+        event: <ext_foreign_toplevel_handle_v1::ExtForeignToplevelHandleV1 as Proxy>::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let client = this.get_client();
+        let mut state = client.borrow_mut();
+        let object_id = handle.id();
+        let event = match event {
+            ext_foreign_toplevel_handle_v1::Event::Done => {
+                state.foreign_toplevels.commit(&object_id)
+            }
+            ext_foreign_toplevel_handle_v1::Event::Closed => {
+                state.foreign_toplevels.remove(&object_id)
+            }
+            event => {
+                let Some(toplevel) = state.foreign_toplevels.pending_mut(&object_id) else {
+                    return;
+                };
+                match event {
+                    ext_foreign_toplevel_handle_v1::Event::Title { title } => {
+                        toplevel.title = title;
+                    }
+                    ext_foreign_toplevel_handle_v1::Event::AppId { app_id } => {
+                        toplevel.app_id = app_id;
+                    }
+                    _ => {}
+                }
+                None
+            }
+        };
+        drop(state);
+
+        if let Some(event) = event {
+            this.emit_foreign_toplevel_event(event);
+        }
+    }
+}
+
 impl Dispatch<xdg_wm_base::XdgWmBase, ()> for WaylandClientStatePtr {
     fn event(
         _: &mut Self,
@@ -1592,6 +2329,16 @@ impl Dispatch<wl_seat::WlSeat, ()> for WaylandClientStatePtr {
                     },
                 );
 
+                if let Some(relative_pointer) = state.relative_pointer.take() {
+                    relative_pointer.destroy();
+                }
+
+                state.relative_pointer = state
+                    .globals
+                    .relative_pointer_manager
+                    .as_ref()
+                    .map(|manager| manager.get_relative_pointer(&pointer, qh, ()));
+
                 if let Some(wl_pointer) = &state.wl_pointer {
                     wl_pointer.release();
                 }
@@ -1965,18 +2712,9 @@ impl Dispatch<wl_pointer::WlPointer, ()> for WaylandClientStatePtr {
                         state.enter_token = None;
                     }
                     state.restore_cursor_after_hide();
-                    if let Some(style) = state.cursor_style {
-                        if let Some(cursor_shape_device) = &state.cursor_shape_device {
-                            cursor_shape_device.set_shape(serial, to_shape(style));
-                        } else {
-                            let scale = window.primary_output_scale();
-                            state.cursor.set_icon(
-                                wl_pointer,
-                                serial,
-                                cursor_style_to_icon_names(style),
-                                scale,
-                            );
-                        }
+                    if let Some(style) = state.cursor_style.clone() {
+                        let scale = window.primary_output_scale();
+                        state.apply_cursor_style(wl_pointer, serial, &style, scale);
                     }
                     drop(state);
                     window.set_hovered(true);
@@ -2013,26 +2751,15 @@ impl Dispatch<wl_pointer::WlPointer, ()> for WaylandClientStatePtr {
                 if let Some(window) = state.mouse_focused_window.clone() {
                     if window.is_blocked() {
                         let default_style = CursorStyle::Arrow;
-                        if state.cursor_style != Some(default_style) {
+                        if state.cursor_style.as_ref() != Some(&default_style) {
                             let serial = state.serial_tracker.get(SerialKind::MouseEnter);
+                            let wl_pointer = state
+                                .wl_pointer
+                                .clone()
+                                .expect("window is focused by pointer");
+                            let scale = window.primary_output_scale();
+                            state.apply_cursor_style(&wl_pointer, serial, &default_style, scale);
                             state.cursor_style = Some(default_style);
-
-                            if let Some(cursor_shape_device) = &state.cursor_shape_device {
-                                cursor_shape_device.set_shape(serial, to_shape(default_style));
-                            } else {
-                                // cursor-shape-v1 isn't supported, set the cursor using a surface.
-                                let wl_pointer = state
-                                    .wl_pointer
-                                    .clone()
-                                    .expect("window is focused by pointer");
-                                let scale = window.primary_output_scale();
-                                state.cursor.set_icon(
-                                    &wl_pointer,
-                                    serial,
-                                    cursor_style_to_icon_names(default_style),
-                                    scale,
-                                );
-                            }
                         }
                     }
                     if state
@@ -2092,6 +2819,7 @@ impl Dispatch<wl_pointer::WlPointer, ()> for WaylandClientStatePtr {
                             && is_within_click_distance(
                                 state.click.last_location,
                                 state.mouse_location.unwrap(),
+                                DOUBLE_CLICK_DISTANCE,
                             )
                         {
                             state.click.current_count += 1;
@@ -2349,6 +3077,45 @@ impl Dispatch<zwp_pointer_gesture_pinch_v1::ZwpPointerGesturePinchV1, ()>
     }
 }
 
+impl Dispatch<zwp_relative_pointer_v1::ZwpRelativePointerV1, ()> for WaylandClientStatePtr {
+    fn event(
+        this: &mut Self,
+        _: &zwp_relative_pointer_v1::ZwpRelativePointerV1,
+        event: zwp_relative_pointer_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let client = this.get_client();
+        let state = client.borrow();
+
+        let Some(window) = state.mouse_focused_window.clone() else {
+            return;
+        };
+
+        if let zwp_relative_pointer_v1::Event::RelativeMotion {
+            dx,
+            dy,
+            dx_unaccel,
+            dy_unaccel,
+            ..
+        } = event
+        {
+            if !window.has_pointer_constraint() {
+                return;
+            }
+
+            let input = PlatformInput::RawMouseMotion(RawMouseMotionEvent {
+                delta: point(px(dx as f32), px(dy as f32)),
+                unaccelerated_delta: point(px(dx_unaccel as f32), px(dy_unaccel as f32)),
+                modifiers: state.modifiers,
+            });
+            drop(state);
+            window.handle_input(input);
+        }
+    }
+}
+
 impl Dispatch<wp_fractional_scale_v1::WpFractionalScaleV1, ObjectId> for WaylandClientStatePtr {
     fn event(
         this: &mut Self,
@@ -2370,6 +3137,68 @@ impl Dispatch<wp_fractional_scale_v1::WpFractionalScaleV1, ObjectId> for Wayland
     }
 }
 
+impl Dispatch<wp_presentation::WpPresentation, ()> for WaylandClientStatePtr {
+    fn event(
+        this: &mut Self,
+        _: &wp_presentation::WpPresentation,
+        event: wp_presentation::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let wp_presentation::Event::ClockId { clk_id } = event {
+            this.get_client().borrow_mut().presentation_clock = clk_id as libc::clockid_t;
+        }
+    }
+}
+
+impl Dispatch<wp_presentation_feedback::WpPresentationFeedback, (ObjectId, u64)>
+    for WaylandClientStatePtr
+{
+    fn event(
+        this: &mut Self,
+        _: &wp_presentation_feedback::WpPresentationFeedback,
+        event: wp_presentation_feedback::Event,
+        (surface_id, frame): &(ObjectId, u64),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let client = this.get_client();
+        let mut state = client.borrow_mut();
+        let Some(window) = get_window(&mut state, surface_id) else {
+            return;
+        };
+        let clock = state.presentation_clock;
+        drop(state);
+
+        let feedback = match event {
+            wp_presentation_feedback::Event::Presented {
+                tv_sec_hi,
+                tv_sec_lo,
+                tv_nsec,
+                refresh,
+                ..
+            } => {
+                let timestamp =
+                    Duration::new((u64::from(tv_sec_hi) << 32) | u64::from(tv_sec_lo), tv_nsec);
+                PresentationFeedback {
+                    frame: *frame,
+                    presented_at: instant_from_clock(clock, timestamp),
+                    refresh_interval: (refresh > 0)
+                        .then(|| Duration::from_nanos(u64::from(refresh))),
+                }
+            }
+            wp_presentation_feedback::Event::Discarded => PresentationFeedback {
+                frame: *frame,
+                presented_at: None,
+                refresh_interval: None,
+            },
+            _ => return,
+        };
+        window.handle_presentation_feedback(feedback);
+    }
+}
+
 impl Dispatch<zxdg_toplevel_decoration_v1::ZxdgToplevelDecorationV1, ObjectId>
     for WaylandClientStatePtr
 {
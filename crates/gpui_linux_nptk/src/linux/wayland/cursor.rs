@@ -1,20 +1,59 @@
-use crate::linux::Globals;
-use crate::linux::{DEFAULT_CURSOR_ICON_NAME, log_cursor_icon_warning};
+use std::fs::File;
+use std::io::Write as _;
+use std::os::fd::{AsFd, FromRawFd};
+use std::time::Duration;
+
+use crate::linux::{
+    DEFAULT_CURSOR_ICON_NAME, ScaledCursorFrame, log_cursor_icon_warning, scaled_cursor_frames,
+};
+use crate::linux::{Globals, WaylandClientStatePtr};
 use anyhow::{Context as _, anyhow};
+use gpui::CursorStyle;
 use util::ResultExt;
 
-use wayland_client::Connection;
+use wayland_client::protocol::wl_buffer::WlBuffer;
 use wayland_client::protocol::wl_surface::WlSurface;
-use wayland_client::protocol::{wl_pointer::WlPointer, wl_shm::WlShm};
+use wayland_client::protocol::{wl_pointer::WlPointer, wl_shm, wl_shm::WlShm};
+use wayland_client::{Connection, QueueHandle};
 use wayland_cursor::{CursorImageBuffer, CursorTheme};
 
+/// How many image cursors keep their buffers around. Tools that resize a brush cursor produce a
+/// new image for every size, so older ones are released.
+const MAX_CACHED_IMAGE_CURSORS: usize = 8;
+
+/// Lower bound on animation frame durations so a zero-length frame doesn't spin the event loop.
+const MIN_CURSOR_FRAME_DURATION: Duration = Duration::from_millis(10);
+
 pub(crate) struct Cursor {
     loaded_theme: Option<LoadedTheme>,
     size: u32,
     scaled_size: u32,
     surface: WlSurface,
     shm: WlShm,
+    qh: QueueHandle<WaylandClientStatePtr>,
     connection: Connection,
+    /// Buffers for image cursors, most recently used first.
+    image_cache: Vec<CachedImageCursor>,
+}
+
+struct CachedImageCursor {
+    style: CursorStyle,
+    scale: i32,
+    frames: Vec<ImageCursorFrame>,
+}
+
+struct ImageCursorFrame {
+    buffer: WlBuffer,
+    width: i32,
+    height: i32,
+    hotspot: (i32, i32),
+    duration: Duration,
+}
+
+impl Drop for ImageCursorFrame {
+    fn drop(&mut self) {
+        self.buffer.destroy();
+    }
 }
 
 pub(crate) struct LoadedTheme {
@@ -26,6 +65,7 @@ pub(crate) struct LoadedTheme {
 impl Drop for Cursor {
     fn drop(&mut self) {
         self.loaded_theme.take();
+        self.image_cache.clear();
         self.surface.destroy();
     }
 }
@@ -38,7 +78,9 @@ impl Cursor {
             scaled_size: size,
             surface: globals.compositor.create_surface(&globals.qh, ()),
             shm: globals.shm.clone(),
+            qh: globals.qh.clone(),
             connection: connection.clone(),
+            image_cache: Vec::new(),
         };
         this.set_theme_internal(None);
         this
@@ -149,4 +191,92 @@ impl Cursor {
         self.surface.damage(0, 0, width as i32, height as i32);
         self.surface.commit();
     }
+    /// Shows frame `frame_index` of a `Custom` or `Animated` cursor style, creating buffers for
+    /// the style at this scale if needed. Returns how long the frame should stay up when the
+    /// cursor is animated.
+    pub fn set_image(
+        &mut self,
+        wl_pointer: &WlPointer,
+        serial_id: u32,
+        style: &CursorStyle,
+        frame_index: usize,
+        scale: i32,
+    ) -> anyhow::Result<Option<Duration>> {
+        let cached = match self
+            .image_cache
+            .iter()
+            .position(|cached| cached.scale == scale && cached.style == *style)
+        {
+            Some(ix) => self.image_cache.remove(ix),
+            None => CachedImageCursor {
+                style: style.clone(),
+                scale,
+                frames: scaled_cursor_frames(style, scale as f32)
+                    .into_iter()
+                    .map(|frame| self.create_frame(frame))
+                    .collect::<anyhow::Result<_>>()?,
+            },
+        };
+        self.image_cache.insert(0, cached);
+        self.image_cache.truncate(MAX_CACHED_IMAGE_CURSORS);
+
+        let frames = &self.image_cache[0].frames;
+        if frames.is_empty() {
+            return Ok(None);
+        }
+        let frame = &frames[frame_index % frames.len()];
+
+        self.surface.set_buffer_scale(scale);
+
+        wl_pointer.set_cursor(
+            serial_id,
+            Some(&self.surface),
+            frame.hotspot.0 / scale,
+            frame.hotspot.1 / scale,
+        );
+
+        self.surface.attach(Some(&frame.buffer), 0, 0);
+        self.surface.damage(0, 0, frame.width, frame.height);
+        self.surface.commit();
+
+        Ok((frames.len() > 1).then(|| frame.duration.max(MIN_CURSOR_FRAME_DURATION)))
+    }
+
+    fn create_frame(&self, frame: ScaledCursorFrame) -> anyhow::Result<ImageCursorFrame> {
+        // SAFETY: the name is a valid C string and the returned descriptor is checked below.
+        let fd = unsafe { libc::memfd_create(c"gpui-cursor".as_ptr(), libc::MFD_CLOEXEC) };
+        if fd < 0 {
+            return Err(std::io::Error::last_os_error())
+                .context("Wayland: Failed to create cursor image buffer");
+        }
+        // SAFETY: `fd` was just created and nothing else owns it.
+        let mut file = unsafe { File::from_raw_fd(fd) };
+        file.write_all(&frame.data)
+            .context("Wayland: Failed to write cursor image buffer")?;
+
+        let width = frame.width as i32;
+        let height = frame.height as i32;
+        let stride = width * 4;
+        let pool = self
+            .shm
+            .create_pool(file.as_fd(), stride * height, &self.qh, ());
+        let buffer = pool.create_buffer(
+            0,
+            width,
+            height,
+            stride,
+            wl_shm::Format::Argb8888,
+            &self.qh,
+            (),
+        );
+        pool.destroy();
+
+        Ok(ImageCursorFrame {
+            buffer,
+            width,
+            height,
+            hotspot: (frame.hotspot.0 as i32, frame.hotspot.1 as i32),
+            duration: frame.duration,
+        })
+    }
 }
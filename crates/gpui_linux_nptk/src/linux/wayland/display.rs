@@ -7,7 +7,9 @@ use anyhow::Context as _;
 use uuid::Uuid;
 use wayland_backend::client::ObjectId;
 
-use gpui::{Bounds, DisplayId, Pixels, PlatformDisplay};
+use gpui::{Bounds, DisplayId, Pixels, PlatformDisplay, SharedString};
+
+use crate::linux::wayland::client::Output;
 
 #[derive(Debug, Clone)]
 pub(crate) struct WaylandDisplay {
@@ -15,6 +17,20 @@ pub(crate) struct WaylandDisplay {
     pub id: ObjectId,
     pub name: Option<String>,
     pub bounds: Bounds<Pixels>,
+    pub scale_factor: f32,
+    pub refresh_rate: Option<f32>,
+}
+
+impl WaylandDisplay {
+    pub fn new(id: ObjectId, output: &Output) -> Self {
+        Self {
+            id,
+            name: output.name.clone(),
+            bounds: output.bounds_in_pixels(),
+            scale_factor: output.scale_factor(),
+            refresh_rate: output.refresh.map(|refresh| refresh as f32 / 1000.0),
+        }
+    }
 }
 
 impl Hash for WaylandDisplay {
@@ -39,4 +55,16 @@ impl PlatformDisplay for WaylandDisplay {
     fn bounds(&self) -> Bounds<Pixels> {
         self.bounds
     }
+
+    fn name(&self) -> Option<SharedString> {
+        self.name.clone().map(SharedString::from)
+    }
+
+    fn scale_factor(&self) -> f32 {
+        self.scale_factor
+    }
+
+    fn refresh_rate(&self) -> Option<f32> {
+        self.refresh_rate
+    }
 }
@@ -0,0 +1,151 @@
+use anyhow::{Context as _, Result, anyhow};
+use collections::HashMap;
+use gpui::foreign_toplevel::{
+    ForeignToplevel, ForeignToplevelEvent, ForeignToplevelId, ForeignToplevelRequest,
+    ForeignToplevelState,
+};
+use wayland_backend::client::ObjectId;
+use wayland_client::{Proxy, protocol::wl_seat};
+use wayland_protocols::ext::foreign_toplevel_list::v1::client::ext_foreign_toplevel_handle_v1;
+use wayland_protocols_wlr::foreign_toplevel::v1::client::zwlr_foreign_toplevel_handle_v1;
+
+pub(crate) enum ForeignToplevelHandle {
+    /// Supports requests as well as state reporting.
+    Wlr(zwlr_foreign_toplevel_handle_v1::ZwlrForeignToplevelHandleV1),
+    /// Only reports titles and application IDs.
+    Ext(ext_foreign_toplevel_handle_v1::ExtForeignToplevelHandleV1),
+}
+
+impl ForeignToplevelHandle {
+    fn id(&self) -> ObjectId {
+        match self {
+            ForeignToplevelHandle::Wlr(handle) => handle.id(),
+            ForeignToplevelHandle::Ext(handle) => handle.id(),
+        }
+    }
+
+    fn destroy(&self) {
+        match self {
+            ForeignToplevelHandle::Wlr(handle) => handle.destroy(),
+            ForeignToplevelHandle::Ext(handle) => handle.destroy(),
+        }
+    }
+}
+
+struct ForeignToplevelEntry {
+    handle: ForeignToplevelHandle,
+    /// Accumulates changes until the compositor sends `done`.
+    pending: ForeignToplevel,
+    /// The last state reported to the app, if it has been announced yet.
+    current: Option<ForeignToplevel>,
+}
+
+/// Tracks other clients' toplevels, double-buffering their properties the way the protocols do.
+#[derive(Default)]
+pub(crate) struct ForeignToplevels {
+    entries: HashMap<ObjectId, ForeignToplevelEntry>,
+}
+
+impl ForeignToplevels {
+    pub fn insert(&mut self, handle: ForeignToplevelHandle) {
+        let object_id = handle.id();
+        let pending = ForeignToplevel {
+            id: ForeignToplevelId(object_id.protocol_id() as u64),
+            title: String::new(),
+            app_id: String::new(),
+            state: ForeignToplevelState::default(),
+            displays: Vec::new(),
+        };
+        self.entries.insert(
+            object_id,
+            ForeignToplevelEntry {
+                handle,
+                pending,
+                current: None,
+            },
+        );
+    }
+
+    pub fn pending_mut(&mut self, object_id: &ObjectId) -> Option<&mut ForeignToplevel> {
+        self.entries
+            .get_mut(object_id)
+            .map(|entry| &mut entry.pending)
+    }
+
+    pub fn commit(&mut self, object_id: &ObjectId) -> Option<ForeignToplevelEvent> {
+        let entry = self.entries.get_mut(object_id)?;
+        match &entry.current {
+            Some(current) if *current == entry.pending => None,
+            Some(_) => {
+                entry.current = Some(entry.pending.clone());
+                Some(ForeignToplevelEvent::Changed(entry.pending.clone()))
+            }
+            None => {
+                entry.current = Some(entry.pending.clone());
+                Some(ForeignToplevelEvent::Added(entry.pending.clone()))
+            }
+        }
+    }
+
+    pub fn remove(&mut self, object_id: &ObjectId) -> Option<ForeignToplevelEvent> {
+        let entry = self.entries.remove(object_id)?;
+        entry.handle.destroy();
+        entry
+            .current
+            .map(|current| ForeignToplevelEvent::Removed(current.id))
+    }
+
+    pub fn toplevels(&self) -> Vec<ForeignToplevel> {
+        let mut toplevels = self
+            .entries
+            .values()
+            .filter_map(|entry| entry.current.clone())
+            .collect::<Vec<_>>();
+        toplevels.sort_by_key(|toplevel| toplevel.id);
+        toplevels
+    }
+
+    pub fn request(
+        &self,
+        id: ForeignToplevelId,
+        request: ForeignToplevelRequest,
+        seat: &wl_seat::WlSeat,
+    ) -> Result<()> {
+        let entry = self
+            .entries
+            .values()
+            .find(|entry| entry.pending.id == id)
+            .with_context(|| format!("No foreign toplevel with id {}", id.0))?;
+        let ForeignToplevelHandle::Wlr(handle) = &entry.handle else {
+            return Err(anyhow!(
+                "Compositor only supports listing toplevels, not managing them"
+            ));
+        };
+        match request {
+            ForeignToplevelRequest::Activate => handle.activate(seat),
+            ForeignToplevelRequest::Minimize => handle.set_minimized(),
+            ForeignToplevelRequest::Unminimize => handle.unset_minimized(),
+            ForeignToplevelRequest::Maximize => handle.set_maximized(),
+            ForeignToplevelRequest::Unmaximize => handle.unset_maximized(),
+            ForeignToplevelRequest::Close => handle.close(),
+        }
+        Ok(())
+    }
+}
+
+/// Decodes the `state` array of a `zwlr_foreign_toplevel_handle_v1`, which holds native-endian
+/// `u32` state values.
+pub(crate) fn parse_wlr_state(state: &[u8]) -> ForeignToplevelState {
+    let mut result = ForeignToplevelState::default();
+    for chunk in state.chunks_exact(4) {
+        let value = u32::from_ne_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        match zwlr_foreign_toplevel_handle_v1::State::try_from(value) {
+            Ok(zwlr_foreign_toplevel_handle_v1::State::Maximized) => result.maximized = true,
+            Ok(zwlr_foreign_toplevel_handle_v1::State::Minimized) => result.minimized = true,
+            Ok(zwlr_foreign_toplevel_handle_v1::State::Activated) => result.activated = true,
+            Ok(zwlr_foreign_toplevel_handle_v1::State::Fullscreen) => result.fullscreen = true,
+            _ => {}
+        }
+    }
+    result
+}
@@ -0,0 +1,55 @@
+use gpui::{Anchor, popup::PopupConstraintAdjustment};
+use wayland_protocols::xdg::shell::client::xdg_positioner;
+
+pub(crate) fn wayland_anchor(attach: Anchor) -> xdg_positioner::Anchor {
+    match attach {
+        Anchor::TopLeft => xdg_positioner::Anchor::TopLeft,
+        Anchor::TopRight => xdg_positioner::Anchor::TopRight,
+        Anchor::BottomLeft => xdg_positioner::Anchor::BottomLeft,
+        Anchor::BottomRight => xdg_positioner::Anchor::BottomRight,
+        Anchor::TopCenter => xdg_positioner::Anchor::Top,
+        Anchor::BottomCenter => xdg_positioner::Anchor::Bottom,
+        Anchor::LeftCenter => xdg_positioner::Anchor::Left,
+        Anchor::RightCenter => xdg_positioner::Anchor::Right,
+    }
+}
+
+/// xdg_positioner describes the direction the popup extends away from the anchor point, which is
+/// the opposite of the popup corner that sits on it.
+pub(crate) fn wayland_gravity(anchor: Anchor) -> xdg_positioner::Gravity {
+    match anchor.opposite() {
+        Anchor::TopLeft => xdg_positioner::Gravity::TopLeft,
+        Anchor::TopRight => xdg_positioner::Gravity::TopRight,
+        Anchor::BottomLeft => xdg_positioner::Gravity::BottomLeft,
+        Anchor::BottomRight => xdg_positioner::Gravity::BottomRight,
+        Anchor::TopCenter => xdg_positioner::Gravity::Top,
+        Anchor::BottomCenter => xdg_positioner::Gravity::Bottom,
+        Anchor::LeftCenter => xdg_positioner::Gravity::Left,
+        Anchor::RightCenter => xdg_positioner::Gravity::Right,
+    }
+}
+
+pub(crate) fn wayland_constraint_adjustment(
+    adjustment: PopupConstraintAdjustment,
+) -> xdg_positioner::ConstraintAdjustment {
+    let mut result = xdg_positioner::ConstraintAdjustment::empty();
+    if adjustment.flip_x {
+        result |= xdg_positioner::ConstraintAdjustment::FlipX;
+    }
+    if adjustment.flip_y {
+        result |= xdg_positioner::ConstraintAdjustment::FlipY;
+    }
+    if adjustment.slide_x {
+        result |= xdg_positioner::ConstraintAdjustment::SlideX;
+    }
+    if adjustment.slide_y {
+        result |= xdg_positioner::ConstraintAdjustment::SlideY;
+    }
+    if adjustment.resize_x {
+        result |= xdg_positioner::ConstraintAdjustment::ResizeX;
+    }
+    if adjustment.resize_y {
+        result |= xdg_positioner::ConstraintAdjustment::ResizeY;
+    }
+    result
+}
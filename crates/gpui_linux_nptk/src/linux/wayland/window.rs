@@ -16,25 +16,37 @@ use wayland_client::{
     Proxy,
     protocol::{wl_output, wl_surface},
 };
+use wayland_protocols::ext::session_lock::v1::client::{
+    ext_session_lock_surface_v1, ext_session_lock_v1,
+};
+use wayland_protocols::wp::idle_inhibit::zv1::client::zwp_idle_inhibitor_v1;
+use wayland_protocols::wp::pointer_constraints::zv1::client::{
+    zwp_confined_pointer_v1, zwp_locked_pointer_v1, zwp_pointer_constraints_v1,
+};
 use wayland_protocols::wp::viewporter::client::wp_viewport;
 use wayland_protocols::xdg::decoration::zv1::client::zxdg_toplevel_decoration_v1;
 use wayland_protocols::xdg::shell::client::xdg_surface;
 use wayland_protocols::xdg::shell::client::xdg_toplevel::{self};
+use wayland_protocols::xdg::shell::client::{xdg_popup, xdg_positioner};
 use wayland_protocols::{
     wp::fractional_scale::v1::client::wp_fractional_scale_v1,
     xdg::dialog::v1::client::xdg_dialog_v1::XdgDialogV1,
 };
 use wayland_protocols_plasma::blur::client::org_kde_kwin_blur;
-use wayland_protocols_wlr::layer_shell::v1::client::zwlr_layer_surface_v1;
+use wayland_protocols_wlr::layer_shell::v1::client::{zwlr_layer_shell_v1, zwlr_layer_surface_v1};
 
 use crate::linux::wayland::{display::WaylandDisplay, serial::SerialKind};
 use crate::linux::{Globals, Output, WaylandClientStatePtr, get_window};
 use gpui::{
-    AnyWindowHandle, Bounds, Capslock, Decorations, DevicePixels, GpuSpecs, Modifiers, Pixels,
-    PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow, Point,
-    PromptButton, PromptLevel, RequestFrameOptions, ResizeEdge, Scene, Size, Tiling,
-    WindowAppearance, WindowBackgroundAppearance, WindowBounds, WindowControlArea, WindowControls,
-    WindowDecorations, WindowKind, WindowParams, layer_shell::LayerShellNotSupportedError, px,
+    AnyWindowHandle, Bounds, Capslock, Decorations, DevicePixels, DisplayId, GpuSpecs, Modifiers,
+    Pixels, PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow,
+    Point, PointerConstraint, PromptButton, PromptLevel, RequestFrameOptions, ResizeEdge, Scene,
+    Size, Tiling, WindowAppearance, WindowBackgroundAppearance, WindowBounds, WindowControlArea,
+    WindowControls, WindowDecorations, WindowKind, WindowParams,
+    layer_shell::{LayerShellNotSupportedError, LayerShellOptions},
+    popup::PopupOptions,
+    px,
+    session_lock::SessionLockNotSupportedError,
     size,
 };
 use gpui_wgpu::{CompositorGpuHint, WgpuRenderer, WgpuSurfaceConfig, wgpu};
@@ -51,6 +63,7 @@ pub(crate) struct Callbacks {
     close: Option<Box<dyn FnOnce()>>,
     appearance_changed: Option<Box<dyn FnMut()>>,
     button_layout_changed: Option<Box<dyn FnMut()>>,
+    presentation_feedback: Option<Box<dyn FnMut(gpui::PresentationFeedback)>>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -94,11 +107,14 @@ pub struct WaylandWindowState {
     acknowledged_first_configure: bool,
     parent: Option<WaylandWindowStatePtr>,
     children: FxHashSet<ObjectId>,
+    popups: FxHashSet<ObjectId>,
     pub surface: wl_surface::WlSurface,
     app_id: Option<String>,
     appearance: WindowAppearance,
     blur: Option<org_kde_kwin_blur::OrgKdeKwinBlur>,
     viewport: Option<wp_viewport::WpViewport>,
+    idle_inhibitor: Option<zwp_idle_inhibitor_v1::ZwpIdleInhibitorV1>,
+    pointer_constraint: Option<WaylandPointerConstraint>,
     outputs: HashMap<ObjectId, Output>,
     display: Option<(ObjectId, Output)>,
     globals: Globals,
@@ -118,6 +134,8 @@ pub struct WaylandWindowState {
     hovered: bool,
     pub(crate) force_render_after_recovery: bool,
     renderer_presented: bool,
+    /// Number of frames passed to `draw`, which presentation feedback refers back to.
+    drawn_frames: u64,
     in_progress_configure: Option<InProgressConfigure>,
     resize_throttle: bool,
     in_progress_window_controls: Option<WindowControls>,
@@ -125,9 +143,25 @@ pub struct WaylandWindowState {
     client_inset: Option<Pixels>,
 }
 
+enum WaylandPointerConstraint {
+    Locked(zwp_locked_pointer_v1::ZwpLockedPointerV1),
+    Confined(zwp_confined_pointer_v1::ZwpConfinedPointerV1),
+}
+
+impl WaylandPointerConstraint {
+    fn destroy(&self) {
+        match self {
+            WaylandPointerConstraint::Locked(locked_pointer) => locked_pointer.destroy(),
+            WaylandPointerConstraint::Confined(confined_pointer) => confined_pointer.destroy(),
+        }
+    }
+}
+
 pub enum WaylandSurfaceState {
     Xdg(WaylandXdgSurfaceState),
     LayerShell(WaylandLayerSurfaceState),
+    Popup(WaylandPopupSurfaceState),
+    SessionLock(WaylandSessionLockSurfaceState),
 }
 
 impl WaylandSurfaceState {
@@ -137,52 +171,73 @@ impl WaylandSurfaceState {
         params: &WindowParams,
         parent: Option<WaylandWindowStatePtr>,
         target_output: Option<wl_output::WlOutput>,
+        grab_serial: u32,
+        session_lock: Option<&ext_session_lock_v1::ExtSessionLockV1>,
     ) -> anyhow::Result<Self> {
-        // For layer_shell windows, create a layer surface instead of an xdg surface
-        if let WindowKind::LayerShell(options) = &params.kind {
-            let Some(layer_shell) = globals.layer_shell.as_ref() else {
-                return Err(LayerShellNotSupportedError.into());
+        if let WindowKind::AnchoredPopUp(options) = &params.kind {
+            let Some(parent) = parent.as_ref() else {
+                anyhow::bail!("Popup windows require an open parent window");
             };
 
-            let layer_surface = layer_shell.get_layer_surface(
-                &surface,
-                target_output.as_ref(),
-                super::layer_shell::wayland_layer(options.layer),
-                options.namespace.clone(),
-                &globals.qh,
-                surface.id(),
-            );
+            let xdg_surface = globals
+                .wm_base
+                .get_xdg_surface(&surface, &globals.qh, surface.id());
+            let positioner = globals.wm_base.create_positioner(&globals.qh, ());
+            parent.configure_positioner(&positioner, options, params.bounds.size);
+
+            let popup = parent.create_popup(&xdg_surface, &positioner, &globals.qh, surface.id());
+            positioner.destroy();
+            let popup = match popup {
+                Ok(popup) => popup,
+                Err(error) => {
+                    xdg_surface.destroy();
+                    return Err(error);
+                }
+            };
+            parent.add_popup(surface.id());
 
-            let width = f32::from(params.bounds.size.width);
-            let height = f32::from(params.bounds.size.height);
-            layer_surface.set_size(width as u32, height as u32);
+            if options.grab {
+                popup.grab(&globals.seat, grab_serial);
+            }
 
-            layer_surface.set_anchor(super::layer_shell::wayland_anchor(options.anchor));
-            layer_surface.set_keyboard_interactivity(
-                super::layer_shell::wayland_keyboard_interactivity(options.keyboard_interactivity),
-            );
+            return Ok(WaylandSurfaceState::Popup(WaylandPopupSurfaceState {
+                xdg_surface,
+                popup,
+            }));
+        }
 
-            if let Some(margin) = options.margin {
-                layer_surface.set_margin(
-                    f32::from(margin.0) as i32,
-                    f32::from(margin.1) as i32,
-                    f32::from(margin.2) as i32,
-                    f32::from(margin.3) as i32,
-                )
-            }
+        if params.kind == WindowKind::SessionLock {
+            let Some(session_lock) = session_lock else {
+                return Err(SessionLockNotSupportedError.into());
+            };
+            let Some(output) = target_output.as_ref() else {
+                anyhow::bail!("Session lock windows must be opened on a display");
+            };
 
-            if let Some(exclusive_zone) = options.exclusive_zone {
-                layer_surface.set_exclusive_zone(f32::from(exclusive_zone) as i32);
-            }
+            let lock_surface =
+                session_lock.get_lock_surface(&surface, output, &globals.qh, surface.id());
 
-            if let Some(exclusive_edge) = options.exclusive_edge {
-                layer_surface
-                    .set_exclusive_edge(super::layer_shell::wayland_anchor(exclusive_edge));
-            }
+            return Ok(WaylandSurfaceState::SessionLock(
+                WaylandSessionLockSurfaceState { lock_surface },
+            ));
+        }
 
-            return Ok(WaylandSurfaceState::LayerShell(WaylandLayerSurfaceState {
-                layer_surface,
-            }));
+        // For layer_shell windows, create a layer surface instead of an xdg surface
+        if let WindowKind::LayerShell(options) = &params.kind {
+            let Some(layer_shell) = globals.layer_shell.as_ref() else {
+                return Err(LayerShellNotSupportedError.into());
+            };
+
+            return Ok(WaylandSurfaceState::LayerShell(
+                WaylandLayerSurfaceState::new(
+                    surface,
+                    layer_shell,
+                    &globals.qh,
+                    options.clone(),
+                    target_output.as_ref(),
+                    params.bounds.size,
+                ),
+            ));
         }
 
         // All other WindowKinds result in a regular xdg surface
@@ -243,6 +298,94 @@ pub struct WaylandXdgSurfaceState {
 
 pub struct WaylandLayerSurfaceState {
     layer_surface: zwlr_layer_surface_v1::ZwlrLayerSurfaceV1,
+    options: LayerShellOptions,
+}
+
+impl WaylandLayerSurfaceState {
+    fn new(
+        surface: &wl_surface::WlSurface,
+        layer_shell: &zwlr_layer_shell_v1::ZwlrLayerShellV1,
+        qh: &wayland_client::QueueHandle<WaylandClientStatePtr>,
+        options: LayerShellOptions,
+        output: Option<&wl_output::WlOutput>,
+        size: Size<Pixels>,
+    ) -> Self {
+        let layer_surface = layer_shell.get_layer_surface(
+            surface,
+            output,
+            super::layer_shell::wayland_layer(options.layer),
+            options.namespace.clone(),
+            qh,
+            surface.id(),
+        );
+
+        let width = f32::from(size.width);
+        let height = f32::from(size.height);
+        layer_surface.set_size(width as u32, height as u32);
+
+        let this = Self {
+            layer_surface,
+            options,
+        };
+        this.apply_options();
+        this
+    }
+
+    /// Sends every option except the namespace and layer, which are set separately.
+    fn apply_options(&self) {
+        let layer_surface = &self.layer_surface;
+        let options = &self.options;
+        layer_surface.set_anchor(super::layer_shell::wayland_anchor(options.anchor));
+        layer_surface.set_keyboard_interactivity(
+            super::layer_shell::wayland_keyboard_interactivity(options.keyboard_interactivity),
+        );
+
+        let margin = options.margin.unwrap_or_default();
+        layer_surface.set_margin(
+            f32::from(margin.0) as i32,
+            f32::from(margin.1) as i32,
+            f32::from(margin.2) as i32,
+            f32::from(margin.3) as i32,
+        );
+
+        let exclusive_zone = options.exclusive_zone.unwrap_or_default();
+        layer_surface.set_exclusive_zone(f32::from(exclusive_zone) as i32);
+
+        if layer_surface.version() >= zwlr_layer_surface_v1::REQ_SET_EXCLUSIVE_EDGE_SINCE {
+            let exclusive_edge = options.exclusive_edge.unwrap_or_default();
+            layer_surface.set_exclusive_edge(super::layer_shell::wayland_anchor(exclusive_edge));
+        }
+    }
+
+    fn set_options(&mut self, options: LayerShellOptions) {
+        if options.namespace != self.options.namespace {
+            log::warn!("The namespace of a layer shell surface can't be changed");
+        }
+        if options.layer != self.options.layer {
+            if self.layer_surface.version() >= zwlr_layer_surface_v1::REQ_SET_LAYER_SINCE {
+                self.layer_surface
+                    .set_layer(super::layer_shell::wayland_layer(options.layer));
+            } else {
+                log::warn!(
+                    "Compositor doesn't support changing the layer of a layer shell surface"
+                );
+            }
+        }
+        self.options = LayerShellOptions {
+            namespace: std::mem::take(&mut self.options.namespace),
+            ..options
+        };
+        self.apply_options();
+    }
+}
+
+pub struct WaylandPopupSurfaceState {
+    xdg_surface: xdg_surface::XdgSurface,
+    popup: xdg_popup::XdgPopup,
+}
+
+pub struct WaylandSessionLockSurfaceState {
+    lock_surface: ext_session_lock_surface_v1::ExtSessionLockSurfaceV1,
 }
 
 impl WaylandSurfaceState {
@@ -254,6 +397,12 @@ impl WaylandSurfaceState {
             WaylandSurfaceState::LayerShell(WaylandLayerSurfaceState { layer_surface, .. }) => {
                 layer_surface.ack_configure(serial);
             }
+            WaylandSurfaceState::Popup(WaylandPopupSurfaceState { xdg_surface, .. }) => {
+                xdg_surface.ack_configure(serial);
+            }
+            WaylandSurfaceState::SessionLock(WaylandSessionLockSurfaceState { lock_surface }) => {
+                lock_surface.ack_configure(serial);
+            }
         }
     }
 
@@ -282,6 +431,12 @@ impl WaylandSurfaceState {
                 // cannot set window position of a layer surface
                 layer_surface.set_size(width as u32, height as u32);
             }
+            WaylandSurfaceState::Popup(WaylandPopupSurfaceState { xdg_surface, .. }) => {
+                xdg_surface.set_window_geometry(x, y, width, height);
+            }
+            WaylandSurfaceState::SessionLock(_) => {
+                // the compositor decides the size of lock surfaces
+            }
         }
     }
 
@@ -303,9 +458,16 @@ impl WaylandSurfaceState {
                 toplevel.destroy();
                 xdg_surface.destroy();
             }
-            WaylandSurfaceState::LayerShell(WaylandLayerSurfaceState { layer_surface }) => {
+            WaylandSurfaceState::LayerShell(WaylandLayerSurfaceState { layer_surface, .. }) => {
                 layer_surface.destroy();
             }
+            WaylandSurfaceState::Popup(WaylandPopupSurfaceState { xdg_surface, popup }) => {
+                popup.destroy();
+                xdg_surface.destroy();
+            }
+            WaylandSurfaceState::SessionLock(WaylandSessionLockSurfaceState { lock_surface }) => {
+                lock_surface.destroy();
+            }
         }
     }
 }
@@ -370,10 +532,13 @@ impl WaylandWindowState {
             acknowledged_first_configure: false,
             parent,
             children: FxHashSet::default(),
+            popups: FxHashSet::default(),
             surface,
             app_id: Some("com.nptk.app".to_string()),
             blur: None,
             viewport,
+            idle_inhibitor: None,
+            pointer_constraint: None,
             globals,
             outputs: HashMap::default(),
             display: None,
@@ -396,6 +561,7 @@ impl WaylandWindowState {
             hovered: false,
             force_render_after_recovery: false,
             renderer_presented: false,
+            drawn_frames: 0,
             in_progress_window_controls: None,
             window_controls: WindowControls::default(),
             client_inset: None,
@@ -455,7 +621,9 @@ impl Drop for WaylandWindow {
         let mut state = self.0.state.borrow_mut();
         let surface_id = state.surface.id();
         if let Some(parent) = state.parent.as_ref() {
-            parent.state.borrow_mut().children.remove(&surface_id);
+            let mut parent_state = parent.state.borrow_mut();
+            parent_state.children.remove(&surface_id);
+            parent_state.popups.remove(&surface_id);
         }
 
         let client = state.client.clone();
@@ -467,6 +635,14 @@ impl Drop for WaylandWindow {
             blur.release();
         }
 
+        if let Some(idle_inhibitor) = &state.idle_inhibitor {
+            idle_inhibitor.destroy();
+        }
+
+        if let Some(pointer_constraint) = &state.pointer_constraint {
+            pointer_constraint.destroy();
+        }
+
         // Decorations must be destroyed before the xdg state.
         // See https://wayland.app/protocols/xdg-decoration-unstable-v1#zxdg_toplevel_decoration_v1
         if let Some(decoration) = &state.surface_state.decoration() {
@@ -518,10 +694,19 @@ impl WaylandWindow {
         appearance: WindowAppearance,
         parent: Option<WaylandWindowStatePtr>,
         target_output: Option<wl_output::WlOutput>,
+        grab_serial: u32,
+        session_lock: Option<&ext_session_lock_v1::ExtSessionLockV1>,
     ) -> anyhow::Result<(Self, ObjectId)> {
         let surface = globals.compositor.create_surface(&globals.qh, ());
-        let surface_state =
-            WaylandSurfaceState::new(&surface, &globals, &params, parent.clone(), target_output)?;
+        let surface_state = WaylandSurfaceState::new(
+            &surface,
+            &globals,
+            &params,
+            parent.clone(),
+            target_output,
+            grab_serial,
+            session_lock,
+        )?;
 
         if let Some(fractional_scale_manager) = globals.fractional_scale_manager.as_ref() {
             fractional_scale_manager.get_fractional_scale(&surface, &globals.qh, surface.id());
@@ -578,6 +763,82 @@ impl WaylandWindowStatePtr {
         state.children.insert(child);
     }
 
+    pub fn add_popup(&self, popup: ObjectId) {
+        let mut state = self.state.borrow_mut();
+        state.popups.insert(popup);
+    }
+
+    fn configure_positioner(
+        &self,
+        positioner: &xdg_positioner::XdgPositioner,
+        options: &PopupOptions,
+        size: Size<Pixels>,
+    ) {
+        let state = self.state.borrow();
+        // The anchor rectangle is relative to the parent's window geometry, which excludes the
+        // client-side decoration inset.
+        let geometry_origin = inset_by_tiling(
+            state.bounds.map_origin(|_| px(0.0)),
+            state.inset(),
+            state.tiling,
+        )
+        .origin;
+        let anchor_rect = Bounds {
+            origin: options.anchor_rect.origin - geometry_origin,
+            size: options.anchor_rect.size,
+        };
+
+        positioner.set_size(
+            (f32::from(size.width) as i32).max(1),
+            (f32::from(size.height) as i32).max(1),
+        );
+        positioner.set_anchor_rect(
+            f32::from(anchor_rect.origin.x) as i32,
+            f32::from(anchor_rect.origin.y) as i32,
+            (f32::from(anchor_rect.size.width) as i32).max(1),
+            (f32::from(anchor_rect.size.height) as i32).max(1),
+        );
+        positioner.set_anchor(super::popup::wayland_anchor(options.attach));
+        positioner.set_gravity(super::popup::wayland_gravity(options.anchor));
+        positioner.set_constraint_adjustment(super::popup::wayland_constraint_adjustment(
+            options.constraint_adjustment,
+        ));
+        positioner.set_offset(
+            f32::from(options.offset.x) as i32,
+            f32::from(options.offset.y) as i32,
+        );
+    }
+
+    fn create_popup(
+        &self,
+        xdg_surface: &xdg_surface::XdgSurface,
+        positioner: &xdg_positioner::XdgPositioner,
+        qh: &wayland_client::QueueHandle<WaylandClientStatePtr>,
+        surface_id: ObjectId,
+    ) -> anyhow::Result<xdg_popup::XdgPopup> {
+        let state = self.state.borrow();
+        Ok(match &state.surface_state {
+            WaylandSurfaceState::Xdg(WaylandXdgSurfaceState {
+                xdg_surface: parent,
+                ..
+            })
+            | WaylandSurfaceState::Popup(WaylandPopupSurfaceState {
+                xdg_surface: parent,
+                ..
+            }) => xdg_surface.get_popup(Some(parent), positioner, qh, surface_id),
+            WaylandSurfaceState::LayerShell(WaylandLayerSurfaceState { layer_surface, .. }) => {
+                // Popups of layer surfaces are created without a parent and assigned one through
+                // the layer surface.
+                let popup = xdg_surface.get_popup(None, positioner, qh, surface_id);
+                layer_surface.get_popup(&popup);
+                popup
+            }
+            WaylandSurfaceState::SessionLock(_) => {
+                anyhow::bail!("Popups can't be opened from session lock windows")
+            }
+        })
+    }
+
     pub(crate) fn ensure_app_id(&self, app_id: &str) {
         let mut state = self.state.borrow_mut();
         if state.app_id.is_some() {
@@ -594,6 +855,17 @@ impl WaylandWindowStatePtr {
         !state.children.is_empty()
     }
 
+    pub fn is_session_lock(&self) -> bool {
+        matches!(
+            self.state.borrow().surface_state,
+            WaylandSurfaceState::SessionLock(_)
+        )
+    }
+
+    pub fn has_pointer_constraint(&self) -> bool {
+        self.state.borrow().pointer_constraint.is_some()
+    }
+
     pub fn frame(&self) {
         let mut state = self.state.borrow_mut();
         state.surface.frame(&state.globals.qh, state.surface.id());
@@ -716,6 +988,17 @@ impl WaylandWindowStatePtr {
         }
     }
 
+    pub fn handle_presentation_feedback(&self, feedback: gpui::PresentationFeedback) {
+        let callback = self.callbacks.borrow_mut().presentation_feedback.take();
+        if let Some(mut callback) = callback {
+            callback(feedback);
+            self.callbacks
+                .borrow_mut()
+                .presentation_feedback
+                .get_or_insert(callback);
+        }
+    }
+
     pub fn handle_fractional_scale_event(&self, event: wp_fractional_scale_v1::Event) {
         if let wp_fractional_scale_v1::Event::PreferredScale { scale } = event {
             self.rescale(scale as f32 / 120.0);
@@ -834,6 +1117,32 @@ impl WaylandWindowStatePtr {
         }
     }
 
+    pub fn handle_popup_event(&self, event: xdg_popup::Event) -> bool {
+        match event {
+            xdg_popup::Event::Configure { width, height, .. } => {
+                let size = if width <= 0 || height <= 0 {
+                    None
+                } else {
+                    Some(size(px(width as f32), px(height as f32)))
+                };
+
+                let mut state = self.state.borrow_mut();
+                state.in_progress_configure = Some(InProgressConfigure {
+                    size,
+                    fullscreen: false,
+                    maximized: false,
+                    resizing: false,
+                    tiling: Tiling::default(),
+                });
+
+                false
+            }
+            // The compositor dismissed the popup, e.g. because the user clicked outside of it.
+            xdg_popup::Event::PopupDone => true,
+            _ => false,
+        }
+    }
+
     pub fn handle_layersurface_event(&self, event: zwlr_layer_surface_v1::Event) -> bool {
         match event {
             zwlr_layer_surface_v1::Event::Configure {
@@ -870,6 +1179,27 @@ impl WaylandWindowStatePtr {
         }
     }
 
+    pub fn handle_session_lock_surface_event(&self, event: ext_session_lock_surface_v1::Event) {
+        if let ext_session_lock_surface_v1::Event::Configure {
+            serial,
+            width,
+            height,
+        } = event
+        {
+            let mut state = self.state.borrow_mut();
+            state.in_progress_configure = Some(InProgressConfigure {
+                size: Some(size(px(width as f32), px(height as f32))),
+                fullscreen: true,
+                maximized: false,
+                resizing: false,
+                tiling: Tiling::default(),
+            });
+            drop(state);
+
+            self.handle_xdg_surface_event(xdg_surface::Event::Configure { serial });
+        }
+    }
+
     #[allow(clippy::mutable_key_type)]
     pub fn handle_surface_event(
         &self,
@@ -1010,9 +1340,11 @@ impl WaylandWindowStatePtr {
         let client = state.client.get_client();
         #[allow(clippy::mutable_key_type)]
         let children = state.children.clone();
+        #[allow(clippy::mutable_key_type)]
+        let popups = state.popups.clone();
         drop(state);
 
-        for child in children {
+        for child in children.into_iter().chain(popups) {
             let mut client_state = client.borrow_mut();
             let window = get_window(&mut client_state, &child);
             drop(client_state);
@@ -1211,11 +1543,7 @@ impl PlatformWindow for WaylandWindow {
     fn display(&self) -> Option<Rc<dyn PlatformDisplay>> {
         let state = self.borrow();
         state.display.as_ref().map(|(id, display)| {
-            Rc::new(WaylandDisplay {
-                id: id.clone(),
-                name: display.name.clone(),
-                bounds: display.bounds.to_pixels(state.scale),
-            }) as Rc<dyn PlatformDisplay>
+            Rc::new(WaylandDisplay::new(id.clone(), display)) as Rc<dyn PlatformDisplay>
         })
     }
 
@@ -1255,11 +1583,16 @@ impl PlatformWindow for WaylandWindow {
     }
 
     fn activate(&self) {
-        // Try to request an activation token. Even though the activation is likely going to be rejected,
-        // KWin and Mutter can use the app_id to visually indicate we're requesting attention.
         let state = self.borrow();
-        if let (Some(activation), Some(app_id)) = (&state.globals.activation, state.app_id.clone())
+        if let Some(activation) = &state.globals.activation
+            && let Some(token) = state.client.take_launch_activation_token()
+        {
+            activation.activate(token, &state.surface);
+        } else if let (Some(activation), Some(app_id)) =
+            (&state.globals.activation, state.app_id.clone())
         {
+            // Try to request an activation token. Even though the activation is likely going to be rejected,
+            // KWin and Mutter can use the app_id to visually indicate we're requesting attention.
             state.client.set_pending_activation(state.surface.id());
             let token = activation.get_activation_token(&state.globals.qh, ());
             // The serial isn't exactly important here, since the activation is probably going to be rejected anyway.
@@ -1388,8 +1721,17 @@ impl PlatformWindow for WaylandWindow {
         self.0.callbacks.borrow_mut().button_layout_changed = Some(callback);
     }
 
+    fn on_presentation_feedback(
+        &self,
+        callback: Box<dyn FnMut(gpui::PresentationFeedback)>,
+    ) -> bool {
+        self.0.callbacks.borrow_mut().presentation_feedback = Some(callback);
+        self.borrow().globals.presentation.is_some()
+    }
+
     fn draw(&self, scene: &Scene) {
         let mut state = self.borrow_mut();
+        state.drawn_frames += 1;
 
         if state.renderer.device_lost() {
             let raw_window = RawWindow {
@@ -1413,6 +1755,15 @@ impl PlatformWindow for WaylandWindow {
             return;
         }
 
+        // Feedback applies to the next commit, which is made while presenting the frame or by
+        // `completed_frame` if there was nothing to present.
+        if let Some(presentation) = state.globals.presentation.as_ref() {
+            presentation.feedback(
+                &state.surface,
+                &state.globals.qh,
+                (state.surface.id(), state.drawn_frames),
+            );
+        }
         state.renderer_presented = state.renderer.draw(scene);
 
         if state.renderer.needs_redraw() {
@@ -1511,6 +1862,134 @@ impl PlatformWindow for WaylandWindow {
         }
     }
 
+    fn set_idle_inhibited(&self, inhibited: bool) {
+        let mut state = self.borrow_mut();
+        if !inhibited {
+            if let Some(idle_inhibitor) = state.idle_inhibitor.take() {
+                idle_inhibitor.destroy();
+            }
+            return;
+        }
+
+        if state.idle_inhibitor.is_some() {
+            return;
+        }
+        let Some(idle_inhibit_manager) = state.globals.idle_inhibit_manager.as_ref() else {
+            log::info!("Idle inhibition is not supported by the compositor");
+            return;
+        };
+        let idle_inhibitor =
+            idle_inhibit_manager.create_inhibitor(&state.surface, &state.globals.qh, ());
+        state.idle_inhibitor = Some(idle_inhibitor);
+    }
+
+    fn layer_shell_options(&self) -> Option<LayerShellOptions> {
+        match &self.borrow().surface_state {
+            WaylandSurfaceState::LayerShell(layer_state) => Some(layer_state.options.clone()),
+            _ => None,
+        }
+    }
+
+    fn set_layer_shell_options(&self, options: LayerShellOptions) {
+        let mut state = self.borrow_mut();
+        let state = &mut *state;
+        let WaylandSurfaceState::LayerShell(layer_state) = &mut state.surface_state else {
+            return;
+        };
+        layer_state.set_options(options);
+        state.surface.commit();
+    }
+
+    fn move_to_display(&self, display_id: DisplayId) -> anyhow::Result<()> {
+        let mut state = self.borrow_mut();
+        let state = &mut *state;
+        // Fullscreen windows can be asked to go fullscreen on a specific output, the compositor
+        // places any other toplevel itself.
+        if let Some(toplevel) = state.surface_state.toplevel()
+            && state.fullscreen
+        {
+            let Some(output) = state.client.get_output(display_id) else {
+                anyhow::bail!("No display with id {display_id:?}");
+            };
+            toplevel.set_fullscreen(Some(&output));
+            return Ok(());
+        }
+        let WaylandSurfaceState::LayerShell(layer_state) = &mut state.surface_state else {
+            anyhow::bail!(
+                "Only layer shell and fullscreen windows can be moved to another display"
+            );
+        };
+        let Some(output) = state.client.get_output(display_id) else {
+            anyhow::bail!("No display with id {display_id:?}");
+        };
+        let Some(layer_shell) = state.globals.layer_shell.as_ref() else {
+            return Err(LayerShellNotSupportedError.into());
+        };
+
+        // The output of a layer surface is fixed, so it has to be recreated. Layer surfaces can
+        // only be created for surfaces without a buffer, so unmap the surface first.
+        layer_state.layer_surface.destroy();
+        state.surface.attach(None, 0, 0);
+        state.surface.commit();
+
+        *layer_state = WaylandLayerSurfaceState::new(
+            &state.surface,
+            layer_shell,
+            &state.globals.qh,
+            std::mem::take(&mut layer_state.options),
+            Some(&output),
+            state.bounds.size,
+        );
+        // Rendering resumes once the new layer surface has been configured.
+        state.acknowledged_first_configure = false;
+        state.surface.commit();
+        Ok(())
+    }
+
+    fn set_pointer_constraint(&self, constraint: Option<PointerConstraint>) {
+        let mut state = self.borrow_mut();
+        if let Some(pointer_constraint) = state.pointer_constraint.take() {
+            pointer_constraint.destroy();
+        }
+
+        let Some(constraint) = constraint else {
+            return;
+        };
+        let Some(pointer_constraints) = state.globals.pointer_constraints.as_ref() else {
+            log::info!("Pointer constraints are not supported by the compositor");
+            return;
+        };
+        let Some(pointer) = state.client.get_pointer() else {
+            return;
+        };
+
+        // Persistent constraints are reactivated whenever the pointer enters the window again.
+        let lifetime = zwp_pointer_constraints_v1::Lifetime::Persistent;
+        let pointer_constraint = match constraint {
+            PointerConstraint::Locked => {
+                WaylandPointerConstraint::Locked(pointer_constraints.lock_pointer(
+                    &state.surface,
+                    &pointer,
+                    None,
+                    lifetime,
+                    &state.globals.qh,
+                    (),
+                ))
+            }
+            PointerConstraint::Confined => {
+                WaylandPointerConstraint::Confined(pointer_constraints.confine_pointer(
+                    &state.surface,
+                    &pointer,
+                    None,
+                    lifetime,
+                    &state.globals.qh,
+                    (),
+                ))
+            }
+        };
+        state.pointer_constraint = Some(pointer_constraint);
+    }
+
     fn update_ime_position(&self, bounds: Bounds<Pixels>) {
         let state = self.borrow();
         state.client.update_ime_position(bounds);
@@ -0,0 +1,1276 @@
+//! Window tabs for windows opened with a tabbing identifier. Linux window managers don't group
+//! windows into tabs, so the toolkit does it: every tab in a group is shown in the same native
+//! window, which draws the selected tab and routes events to it. Apps draw the tab bar
+//! themselves from [`gpui::SystemWindowTabController`].
+
+use std::{
+    borrow::Cow,
+    cell::{Cell, RefCell},
+    ptr,
+    rc::{Rc, Weak},
+    sync::Arc,
+};
+
+use anyhow::{Context as _, Result, anyhow};
+use futures::channel::oneshot;
+use image::RgbaImage;
+use raw_window_handle as rwh;
+use util::ResultExt as _;
+
+use gpui::{
+    AnyWindowHandle, AtlasKey, AtlasTile, Bounds, Capslock, Decorations, DevicePixels,
+    DispatchEventResult, DisplayId, ForegroundExecutor, GpuSpecs, KeyDownEvent, Modifiers, Pixels,
+    PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow, Point,
+    PointerConstraint, PromptButton, PromptLevel, RequestFrameOptions, ResizeEdge, Scene, Size,
+    SystemWindowTab, WindowAppearance, WindowBackgroundAppearance, WindowBounds, WindowControlArea,
+    WindowControls, WindowDecorations, WindowParams, point, px,
+};
+
+/// Opens the native window a tab group is shown in.
+pub(crate) type OpenNativeWindow =
+    dyn Fn(AnyWindowHandle, WindowParams) -> Result<Box<dyn PlatformWindow>>;
+
+/// The tab groups of every window opened with a tabbing identifier.
+#[derive(Clone)]
+pub(crate) struct WindowTabs(Rc<WindowTabsState>);
+
+struct WindowTabsState {
+    executor: ForegroundExecutor,
+    groups: RefCell<Vec<Weak<TabGroup>>>,
+}
+
+impl WindowTabs {
+    pub fn new(executor: ForegroundExecutor) -> Self {
+        Self(Rc::new(WindowTabsState {
+            executor,
+            groups: RefCell::default(),
+        }))
+    }
+
+    /// Opens a window as a tab. It joins the active group with the same tabbing identifier, or
+    /// the most recently opened one, and gets a native window of its own if there's none.
+    pub fn open_window(
+        &self,
+        handle: AnyWindowHandle,
+        params: WindowParams,
+        open_native: Rc<OpenNativeWindow>,
+    ) -> Result<Box<dyn PlatformWindow>> {
+        let identifier = params.tabbing_identifier.clone();
+        let groups = self.groups();
+        let host = groups
+            .iter()
+            .filter(|group| identifier.is_some() && *group.identifier.borrow() == identifier)
+            .max_by_key(|group| group.native.borrow().is_active());
+
+        let tab = if let Some(group) = host {
+            let tab = Tab::new(handle, params, group);
+            tab.joining.set(true);
+            tab
+        } else {
+            let native = open_native(handle, params.clone())?;
+            let group = TabGroup::new(self, identifier, handle, native, open_native);
+            Tab::new(handle, params, &group)
+        };
+        tab.group().insert(&tab);
+        Ok(Box::new(TabWindow(tab)))
+    }
+
+    /// Returns the tab that's selected in the native window opened for `handle`. The display
+    /// server only knows windows by the handle of the tab they were opened for, which may have
+    /// opened another one since being moved out of its group.
+    pub fn selected_tab(&self, handle: AnyWindowHandle) -> AnyWindowHandle {
+        self.groups()
+            .into_iter()
+            .filter(|group| group.native_handle == handle)
+            .max_by_key(|group| group.native.borrow().is_active())
+            .and_then(|group| group.selected_tab())
+            .map_or(handle, |tab| tab.handle)
+    }
+
+    /// Returns the handle the native window showing `handle`'s tab was opened for.
+    pub fn native_window(&self, handle: AnyWindowHandle) -> AnyWindowHandle {
+        self.groups()
+            .into_iter()
+            .find(|group| group.tabs().iter().any(|tab| tab.handle == handle))
+            .map_or(handle, |group| group.native_handle)
+    }
+
+    fn groups(&self) -> Vec<Rc<TabGroup>> {
+        let mut groups = self.0.groups.borrow_mut();
+        groups.retain(|group| group.strong_count() > 0);
+        groups.iter().filter_map(Weak::upgrade).collect()
+    }
+
+    fn remove_group(&self, group: &TabGroup) {
+        self.0
+            .groups
+            .borrow_mut()
+            .retain(|other| !ptr::eq(other.as_ptr(), group));
+    }
+
+    /// Runs `f` once the current event has been handled, as callbacks can't be invoked while
+    /// the app is being updated.
+    fn defer(&self, f: impl FnOnce() + 'static) {
+        self.0.executor.spawn(async move { f() }).detach();
+    }
+}
+
+/// Tabs sharing a native window. The group is kept alive by its tabs, and drops the native
+/// window along with the last of them.
+struct TabGroup {
+    window_tabs: WindowTabs,
+    identifier: RefCell<Option<String>>,
+    /// The window the native window was opened for.
+    native_handle: AnyWindowHandle,
+    native: RefCell<Box<dyn PlatformWindow>>,
+    open_native: Rc<OpenNativeWindow>,
+    /// How the native window was configured, so that a window opened for a detached tab looks
+    /// the same.
+    app_id: RefCell<Option<String>>,
+    decorations: Cell<Option<WindowDecorations>>,
+    background_appearance: Cell<WindowBackgroundAppearance>,
+    tabs: RefCell<Vec<Weak<Tab>>>,
+    selected: Cell<usize>,
+}
+
+impl TabGroup {
+    fn new(
+        window_tabs: &WindowTabs,
+        identifier: Option<String>,
+        native_handle: AnyWindowHandle,
+        native: Box<dyn PlatformWindow>,
+        open_native: Rc<OpenNativeWindow>,
+    ) -> Rc<Self> {
+        let background_appearance = native.background_appearance();
+        let group = Rc::new(Self {
+            window_tabs: window_tabs.clone(),
+            identifier: RefCell::new(identifier),
+            native_handle,
+            native: RefCell::new(native),
+            open_native,
+            app_id: RefCell::default(),
+            decorations: Cell::default(),
+            background_appearance: Cell::new(background_appearance),
+            tabs: RefCell::default(),
+            selected: Cell::new(0),
+        });
+        group.route_native_callbacks();
+        window_tabs
+            .0
+            .groups
+            .borrow_mut()
+            .push(Rc::downgrade(&group));
+        group
+    }
+
+    /// Forwards the native window's events to the selected tab. Events that concern every tab,
+    /// like closing the native window, go to all of them.
+    fn route_native_callbacks(self: &Rc<Self>) {
+        let native = self.native.borrow();
+        let this = Rc::downgrade(self);
+        let selected = move || this.upgrade().and_then(|group| group.selected_tab());
+
+        native.on_request_frame(Box::new({
+            let selected = selected.clone();
+            move |mut options| {
+                let Some(tab) = selected() else {
+                    return;
+                };
+                // The tab may have been resized, or painted with another window's atlas,
+                // while it wasn't shown.
+                if tab.needs_redraw.take() {
+                    options.force_render = true;
+                }
+                tab.with_callback(
+                    |callbacks| &mut callbacks.request_frame,
+                    |callback| callback(options),
+                );
+            }
+        }));
+        native.on_input(Box::new({
+            let selected = selected.clone();
+            move |input| {
+                let Some(tab) = selected() else {
+                    return DispatchEventResult::default();
+                };
+                let switch = TabSwitch::for_input(&input);
+                let result = tab
+                    .with_callback(|callbacks| &mut callbacks.input, |callback| callback(input))
+                    .unwrap_or_default();
+                let has_other_tabs = tab.group().tabs.borrow().len() > 1;
+                if let Some(switch) = switch
+                    && result.propagate
+                    && has_other_tabs
+                {
+                    match switch {
+                        TabSwitch::Next => tab.with_callback(
+                            |callbacks| &mut callbacks.select_next_tab,
+                            |callback| callback(),
+                        ),
+                        TabSwitch::Previous => tab.with_callback(
+                            |callbacks| &mut callbacks.select_previous_tab,
+                            |callback| callback(),
+                        ),
+                    };
+                    return DispatchEventResult {
+                        propagate: false,
+                        default_prevented: true,
+                    };
+                }
+                result
+            }
+        }));
+        native.on_active_status_change(Box::new({
+            let selected = selected.clone();
+            move |active| {
+                if let Some(tab) = selected() {
+                    tab.with_callback(
+                        |callbacks| &mut callbacks.active_status_change,
+                        |callback| callback(active),
+                    );
+                }
+            }
+        }));
+        native.on_hover_status_change(Box::new({
+            let selected = selected.clone();
+            move |hovered| {
+                if let Some(tab) = selected() {
+                    tab.with_callback(
+                        |callbacks| &mut callbacks.hover_status_change,
+                        |callback| callback(hovered),
+                    );
+                }
+            }
+        }));
+        native.on_resize(Box::new({
+            let selected = selected.clone();
+            move |size, scale_factor| {
+                if let Some(tab) = selected() {
+                    tab.with_callback(
+                        |callbacks| &mut callbacks.resize,
+                        |callback| callback(size, scale_factor),
+                    );
+                }
+            }
+        }));
+        native.on_moved(Box::new({
+            let selected = selected.clone();
+            move || {
+                if let Some(tab) = selected() {
+                    tab.with_callback(|callbacks| &mut callbacks.moved, |callback| callback());
+                }
+            }
+        }));
+        native.on_hit_test_window_control(Box::new(move || {
+            selected()?.with_callback(
+                |callbacks| &mut callbacks.hit_test_window_control,
+                |callback| callback(),
+            )?
+        }));
+        native.on_should_close(Box::new({
+            let this = Rc::downgrade(self);
+            move || {
+                let Some(group) = this.upgrade() else {
+                    return true;
+                };
+                let Some(tab) = group.selected_tab() else {
+                    return true;
+                };
+                let should_close = tab
+                    .with_callback(
+                        |callbacks| &mut callbacks.should_close,
+                        |callback| callback(),
+                    )
+                    .unwrap_or(true);
+                if !should_close || group.tabs.borrow().len() == 1 {
+                    return should_close;
+                }
+
+                // Closing the native window would close every tab, so only the selected one is
+                // closed and the native window stays open for the others.
+                let close = tab.callbacks.borrow_mut().close.take();
+                if let Some(close) = close {
+                    close();
+                }
+                false
+            }
+        }));
+        native.on_close(Box::new({
+            let this = Rc::downgrade(self);
+            move || {
+                let Some(group) = this.upgrade() else {
+                    return;
+                };
+                for tab in group.tabs() {
+                    let close = tab.callbacks.borrow_mut().close.take();
+                    if let Some(close) = close {
+                        close();
+                    }
+                }
+            }
+        }));
+        native.on_appearance_changed(Box::new({
+            let this = Rc::downgrade(self);
+            move || {
+                let Some(group) = this.upgrade() else {
+                    return;
+                };
+                for tab in group.tabs() {
+                    tab.with_callback(
+                        |callbacks| &mut callbacks.appearance_changed,
+                        |callback| callback(),
+                    );
+                }
+            }
+        }));
+        native.on_button_layout_changed(Box::new({
+            let this = Rc::downgrade(self);
+            move || {
+                let Some(group) = this.upgrade() else {
+                    return;
+                };
+                for tab in group.tabs() {
+                    tab.with_callback(
+                        |callbacks| &mut callbacks.button_layout_changed,
+                        |callback| callback(),
+                    );
+                }
+            }
+        }));
+    }
+
+    fn tabs(&self) -> Vec<Rc<Tab>> {
+        self.tabs
+            .borrow()
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect()
+    }
+
+    fn selected_tab(&self) -> Option<Rc<Tab>> {
+        self.tabs
+            .borrow()
+            .get(self.selected.get())
+            .and_then(Weak::upgrade)
+    }
+
+    fn position(&self, tab: &Tab) -> Option<usize> {
+        self.tabs
+            .borrow()
+            .iter()
+            .position(|other| ptr::eq(other.as_ptr(), tab))
+    }
+
+    fn is_selected(&self, tab: &Tab) -> bool {
+        self.position(tab) == Some(self.selected.get())
+    }
+
+    /// Adds a tab after the others, without selecting it.
+    fn insert(&self, tab: &Rc<Tab>) {
+        let mut tabs = self.tabs.borrow_mut();
+        tabs.push(Rc::downgrade(tab));
+        if tabs.len() == 2 {
+            drop(tabs);
+            self.tab_bar_toggled();
+        }
+    }
+
+    /// Removes a tab, selecting its neighbor if it was selected. The tab keeps its input
+    /// handler, in case it's shown in another window.
+    fn remove(&self, tab: &Tab) {
+        let Some(index) = self.position(tab) else {
+            return;
+        };
+        let selected = self.selected.get();
+        if index == selected {
+            *tab.input_handler.borrow_mut() = self.native.borrow_mut().take_input_handler();
+        }
+
+        let remaining = {
+            let mut tabs = self.tabs.borrow_mut();
+            tabs.remove(index);
+            tabs.len()
+        };
+        if remaining == 0 {
+            self.window_tabs.remove_group(self);
+            return;
+        }
+        if index < selected || selected == remaining {
+            self.selected.set(selected - 1);
+        }
+        if index == selected {
+            self.show_selected(None);
+        }
+        if remaining == 1 {
+            self.tab_bar_toggled();
+        }
+    }
+
+    fn select(&self, tab: &Tab) {
+        let Some(index) = self.position(tab) else {
+            return;
+        };
+        if index == self.selected.get() {
+            return;
+        }
+
+        let previous = self.selected_tab();
+        if let Some(previous) = &previous {
+            *previous.input_handler.borrow_mut() = self.native.borrow_mut().take_input_handler();
+        }
+        self.selected.set(index);
+        self.show_selected(previous);
+    }
+
+    /// Shows the selected tab in the native window, after `previous` was shown in it.
+    fn show_selected(&self, previous: Option<Rc<Tab>>) {
+        let Some(tab) = self.selected_tab() else {
+            return;
+        };
+        {
+            let mut native = self.native.borrow_mut();
+            if let Some(input_handler) = tab.input_handler.borrow_mut().take() {
+                native.set_input_handler(input_handler);
+            }
+            native.set_title(&tab.title.borrow());
+        }
+        tab.needs_redraw.set(true);
+
+        let previous = previous.as_ref().map(Rc::downgrade);
+        let tab = Rc::downgrade(&tab);
+        self.window_tabs.defer(move || {
+            // Another tab may have been selected in the meantime.
+            if let Some(previous) = previous.and_then(|previous| previous.upgrade())
+                && !previous.group().is_selected(&previous)
+            {
+                previous.deactivate();
+            }
+            if let Some(tab) = tab.upgrade() {
+                let group = tab.group();
+                let selected = group.is_selected(&tab);
+                let (size, scale_factor, active, hovered) = {
+                    let native = group.native.borrow();
+                    (
+                        native.content_size(),
+                        native.scale_factor(),
+                        selected && native.is_active(),
+                        selected && native.is_hovered(),
+                    )
+                };
+                tab.with_callback(
+                    |callbacks| &mut callbacks.resize,
+                    |callback| callback(size, scale_factor),
+                );
+                tab.with_callback(
+                    |callbacks| &mut callbacks.active_status_change,
+                    |callback| callback(active),
+                );
+                tab.with_callback(
+                    |callbacks| &mut callbacks.hover_status_change,
+                    |callback| callback(hovered),
+                );
+            }
+        });
+    }
+
+    /// Lets the app know the tab bar was shown or hidden, which happens when a second tab is
+    /// added to the group or when it's down to one.
+    fn tab_bar_toggled(&self) {
+        let Some(tab) = self.selected_tab() else {
+            return;
+        };
+        let tab = Rc::downgrade(&tab);
+        self.window_tabs.defer(move || {
+            if let Some(tab) = tab.upgrade() {
+                tab.with_callback(
+                    |callbacks| &mut callbacks.toggle_tab_bar,
+                    |callback| callback(),
+                );
+            }
+        });
+    }
+}
+
+/// Keyboard shortcuts for switching tabs, used when the app doesn't handle them itself.
+enum TabSwitch {
+    Next,
+    Previous,
+}
+
+impl TabSwitch {
+    fn for_input(input: &PlatformInput) -> Option<Self> {
+        let PlatformInput::KeyDown(KeyDownEvent { keystroke, .. }) = input else {
+            return None;
+        };
+        let control = keystroke.modifiers == Modifiers::control();
+        let control_shift = keystroke.modifiers == Modifiers::control_shift();
+        match keystroke.key.as_str() {
+            "tab" if control => Some(Self::Next),
+            "tab" if control_shift => Some(Self::Previous),
+            "pagedown" if control => Some(Self::Next),
+            "pageup" if control => Some(Self::Previous),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct TabCallbacks {
+    request_frame: Option<Box<dyn FnMut(RequestFrameOptions)>>,
+    input: Option<Box<dyn FnMut(PlatformInput) -> DispatchEventResult>>,
+    active_status_change: Option<Box<dyn FnMut(bool)>>,
+    hover_status_change: Option<Box<dyn FnMut(bool)>>,
+    resize: Option<Box<dyn FnMut(Size<Pixels>, f32)>>,
+    moved: Option<Box<dyn FnMut()>>,
+    should_close: Option<Box<dyn FnMut() -> bool>>,
+    hit_test_window_control: Option<Box<dyn FnMut() -> Option<WindowControlArea>>>,
+    close: Option<Box<dyn FnOnce()>>,
+    appearance_changed: Option<Box<dyn FnMut()>>,
+    button_layout_changed: Option<Box<dyn FnMut()>>,
+    move_tab_to_new_window: Option<Box<dyn FnMut()>>,
+    merge_all_windows: Option<Box<dyn FnMut()>>,
+    select_previous_tab: Option<Box<dyn FnMut()>>,
+    select_next_tab: Option<Box<dyn FnMut()>>,
+    toggle_tab_bar: Option<Box<dyn FnMut()>>,
+}
+
+struct Tab {
+    handle: AnyWindowHandle,
+    /// The parameters the window was opened with, for opening a native window if it's moved
+    /// out of its group.
+    params: WindowParams,
+    group: RefCell<Rc<TabGroup>>,
+    title: RefCell<String>,
+    atlas: Arc<TabAtlas>,
+    callbacks: RefCell<TabCallbacks>,
+    /// The tab's input handler while it isn't shown. The selected tab's is kept by the native
+    /// window, which uses it for input methods.
+    input_handler: RefCell<Option<PlatformInputHandler>>,
+    needs_redraw: Cell<bool>,
+    /// Whether the tab was opened in a group that's already shown, and is still being set up.
+    /// The native window is left as it is until the tab is selected.
+    joining: Cell<bool>,
+}
+
+impl Tab {
+    fn new(handle: AnyWindowHandle, params: WindowParams, group: &Rc<TabGroup>) -> Rc<Self> {
+        let title = params
+            .titlebar
+            .as_ref()
+            .and_then(|titlebar| titlebar.title.as_ref())
+            .map(ToString::to_string)
+            .unwrap_or_default();
+        Rc::new(Self {
+            handle,
+            params,
+            group: RefCell::new(group.clone()),
+            title: RefCell::new(title),
+            atlas: TabAtlas::new(group.native.borrow().sprite_atlas()),
+            callbacks: RefCell::default(),
+            input_handler: RefCell::default(),
+            needs_redraw: Cell::new(false),
+            joining: Cell::new(false),
+        })
+    }
+
+    fn group(&self) -> Rc<TabGroup> {
+        self.group.borrow().clone()
+    }
+
+    /// Calls one of the tab's callbacks, if it's set. The callback is taken out while it runs,
+    /// so that it can call back into the window.
+    fn with_callback<T: ?Sized, R>(
+        &self,
+        field: impl Fn(&mut TabCallbacks) -> &mut Option<Box<T>>,
+        f: impl FnOnce(&mut Box<T>) -> R,
+    ) -> Option<R> {
+        let mut callback = field(&mut *self.callbacks.borrow_mut()).take()?;
+        let result = f(&mut callback);
+        field(&mut *self.callbacks.borrow_mut()).get_or_insert(callback);
+        Some(result)
+    }
+
+    fn deactivate(&self) {
+        self.with_callback(
+            |callbacks| &mut callbacks.active_status_change,
+            |callback| callback(false),
+        );
+        self.with_callback(
+            |callbacks| &mut callbacks.hover_status_change,
+            |callback| callback(false),
+        );
+    }
+
+    /// Moves the tab into another group, which paints it with the sprite atlas of that group's
+    /// native window from then on.
+    fn move_to(self: &Rc<Self>, group: &Rc<TabGroup>) {
+        self.group().remove(self);
+        self.atlas.0.replace(group.native.borrow().sprite_atlas());
+        self.group.replace(group.clone());
+        self.needs_redraw.set(true);
+        group.insert(self);
+    }
+}
+
+/// Sprite atlas of a tab, forwarding to the atlas of the native window it's shown in.
+struct TabAtlas(RefCell<Arc<dyn PlatformAtlas>>);
+
+impl TabAtlas {
+    // Like the atlases of native windows, it's only used on the main thread.
+    #[allow(clippy::arc_with_non_send_sync)]
+    fn new(atlas: Arc<dyn PlatformAtlas>) -> Arc<Self> {
+        Arc::new(Self(RefCell::new(atlas)))
+    }
+}
+
+impl PlatformAtlas for TabAtlas {
+    fn get_or_insert_with<'a>(
+        &self,
+        key: &AtlasKey,
+        build: &mut dyn FnMut() -> Result<Option<(Size<DevicePixels>, Cow<'a, [u8]>)>>,
+    ) -> Result<Option<AtlasTile>> {
+        let atlas = self.0.borrow().clone();
+        atlas.get_or_insert_with(key, build)
+    }
+
+    fn remove(&self, key: &AtlasKey) {
+        let atlas = self.0.borrow().clone();
+        atlas.remove(key);
+    }
+}
+
+/// A window that's shown as a tab of a native window.
+pub(crate) struct TabWindow(Rc<Tab>);
+
+impl TabWindow {
+    fn is_selected(&self) -> bool {
+        self.0.group().is_selected(&self.0)
+    }
+
+    /// Calls `f` with the native window, if this tab is the one shown in it.
+    fn with_native_if_selected(&self, f: impl FnOnce(&dyn PlatformWindow)) {
+        let group = self.0.group();
+        if group.is_selected(&self.0) {
+            f(&**group.native.borrow());
+        }
+    }
+
+    /// Moves this tab out of its group into a native window of its own, configured like the one
+    /// it was shown in.
+    fn move_to_new_group(&self, bounds: Bounds<Pixels>, identifier: Option<String>) -> Result<()> {
+        let group = self.0.group();
+        let mut params = self.0.params.clone();
+        params.bounds = bounds;
+        params.titlebar.get_or_insert_default().title = Some(self.0.title.borrow().clone().into());
+        let native = (group.open_native)(self.0.handle, params)?;
+
+        let new_group = TabGroup::new(
+            &group.window_tabs,
+            identifier,
+            self.0.handle,
+            native,
+            group.open_native.clone(),
+        );
+        {
+            let mut native = new_group.native.borrow_mut();
+            if let Some(app_id) = group.app_id.borrow().as_deref() {
+                native.set_app_id(app_id);
+            }
+            if let Some(decorations) = group.decorations.get() {
+                native.request_decorations(decorations);
+            }
+            native.set_background_appearance(group.background_appearance.get());
+        }
+        *new_group.app_id.borrow_mut() = group.app_id.borrow().clone();
+        new_group.decorations.set(group.decorations.get());
+        new_group
+            .background_appearance
+            .set(group.background_appearance.get());
+
+        self.0.move_to(&new_group);
+        new_group.show_selected(None);
+        new_group.native.borrow_mut().map_window().log_err();
+        Ok(())
+    }
+}
+
+impl Drop for TabWindow {
+    fn drop(&mut self) {
+        self.0.group().remove(&self.0);
+    }
+}
+
+impl rwh::HasWindowHandle for TabWindow {
+    fn window_handle(&self) -> Result<rwh::WindowHandle<'_>, rwh::HandleError> {
+        let raw = rwh::HasWindowHandle::window_handle(&**self.0.group().native.borrow())?.as_raw();
+        // The native window stays open at least as long as the tab is shown in it.
+        Ok(unsafe { rwh::WindowHandle::borrow_raw(raw) })
+    }
+}
+
+impl rwh::HasDisplayHandle for TabWindow {
+    fn display_handle(&self) -> Result<rwh::DisplayHandle<'_>, rwh::HandleError> {
+        let raw =
+            rwh::HasDisplayHandle::display_handle(&**self.0.group().native.borrow())?.as_raw();
+        Ok(unsafe { rwh::DisplayHandle::borrow_raw(raw) })
+    }
+}
+
+impl PlatformWindow for TabWindow {
+    fn bounds(&self) -> Bounds<Pixels> {
+        self.0.group().native.borrow().bounds()
+    }
+
+    fn is_maximized(&self) -> bool {
+        self.0.group().native.borrow().is_maximized()
+    }
+
+    fn window_bounds(&self) -> WindowBounds {
+        self.0.group().native.borrow().window_bounds()
+    }
+
+    fn inner_window_bounds(&self) -> WindowBounds {
+        self.0.group().native.borrow().inner_window_bounds()
+    }
+
+    fn content_size(&self) -> Size<Pixels> {
+        self.0.group().native.borrow().content_size()
+    }
+
+    fn resize(&mut self, size: Size<Pixels>) {
+        self.0.group().native.borrow_mut().resize(size);
+    }
+
+    fn scale_factor(&self) -> f32 {
+        self.0.group().native.borrow().scale_factor()
+    }
+
+    fn appearance(&self) -> WindowAppearance {
+        self.0.group().native.borrow().appearance()
+    }
+
+    fn display(&self) -> Option<Rc<dyn PlatformDisplay>> {
+        self.0.group().native.borrow().display()
+    }
+
+    fn mouse_position(&self) -> Point<Pixels> {
+        self.0.group().native.borrow().mouse_position()
+    }
+
+    fn modifiers(&self) -> Modifiers {
+        self.0.group().native.borrow().modifiers()
+    }
+
+    fn capslock(&self) -> Capslock {
+        self.0.group().native.borrow().capslock()
+    }
+
+    fn set_input_handler(&mut self, input_handler: PlatformInputHandler) {
+        let group = self.0.group();
+        if group.is_selected(&self.0) {
+            group.native.borrow_mut().set_input_handler(input_handler);
+        } else {
+            *self.0.input_handler.borrow_mut() = Some(input_handler);
+        }
+    }
+
+    fn take_input_handler(&mut self) -> Option<PlatformInputHandler> {
+        let group = self.0.group();
+        if group.is_selected(&self.0) {
+            group.native.borrow_mut().take_input_handler()
+        } else {
+            self.0.input_handler.borrow_mut().take()
+        }
+    }
+
+    fn prompt(
+        &self,
+        level: PromptLevel,
+        msg: &str,
+        detail: Option<&str>,
+        answers: &[PromptButton],
+    ) -> Option<oneshot::Receiver<usize>> {
+        self.0
+            .group()
+            .native
+            .borrow()
+            .prompt(level, msg, detail, answers)
+    }
+
+    fn activate(&self) {
+        let group = self.0.group();
+        group.select(&self.0);
+        group.native.borrow().activate();
+    }
+
+    fn is_active(&self) -> bool {
+        self.is_selected() && self.0.group().native.borrow().is_active()
+    }
+
+    fn is_hovered(&self) -> bool {
+        self.is_selected() && self.0.group().native.borrow().is_hovered()
+    }
+
+    fn background_appearance(&self) -> WindowBackgroundAppearance {
+        self.0.group().native.borrow().background_appearance()
+    }
+
+    fn set_title(&mut self, title: &str) {
+        *self.0.title.borrow_mut() = title.to_string();
+        let group = self.0.group();
+        if group.is_selected(&self.0) {
+            group.native.borrow_mut().set_title(title);
+        }
+    }
+
+    fn set_background_appearance(&self, background_appearance: WindowBackgroundAppearance) {
+        if self.0.joining.get() {
+            return;
+        }
+        let group = self.0.group();
+        group.background_appearance.set(background_appearance);
+        group
+            .native
+            .borrow()
+            .set_background_appearance(background_appearance);
+    }
+
+    fn minimize(&self) {
+        self.0.group().native.borrow().minimize();
+    }
+
+    fn zoom(&self) {
+        if !self.0.joining.get() {
+            self.0.group().native.borrow().zoom();
+        }
+    }
+
+    fn toggle_fullscreen(&self) {
+        if !self.0.joining.get() {
+            self.0.group().native.borrow().toggle_fullscreen();
+        }
+    }
+
+    fn is_fullscreen(&self) -> bool {
+        self.0.group().native.borrow().is_fullscreen()
+    }
+
+    fn on_request_frame(&self, callback: Box<dyn FnMut(RequestFrameOptions)>) {
+        self.0.callbacks.borrow_mut().request_frame = Some(callback);
+    }
+
+    fn on_input(&self, callback: Box<dyn FnMut(PlatformInput) -> DispatchEventResult>) {
+        self.0.callbacks.borrow_mut().input = Some(callback);
+    }
+
+    fn on_active_status_change(&self, callback: Box<dyn FnMut(bool)>) {
+        self.0.callbacks.borrow_mut().active_status_change = Some(callback);
+    }
+
+    fn on_hover_status_change(&self, callback: Box<dyn FnMut(bool)>) {
+        self.0.callbacks.borrow_mut().hover_status_change = Some(callback);
+    }
+
+    fn on_resize(&self, callback: Box<dyn FnMut(Size<Pixels>, f32)>) {
+        self.0.callbacks.borrow_mut().resize = Some(callback);
+    }
+
+    fn on_moved(&self, callback: Box<dyn FnMut()>) {
+        self.0.callbacks.borrow_mut().moved = Some(callback);
+    }
+
+    fn on_should_close(&self, callback: Box<dyn FnMut() -> bool>) {
+        self.0.callbacks.borrow_mut().should_close = Some(callback);
+    }
+
+    fn on_hit_test_window_control(&self, callback: Box<dyn FnMut() -> Option<WindowControlArea>>) {
+        self.0.callbacks.borrow_mut().hit_test_window_control = Some(callback);
+    }
+
+    fn on_close(&self, callback: Box<dyn FnOnce()>) {
+        self.0.callbacks.borrow_mut().close = Some(callback);
+    }
+
+    fn on_appearance_changed(&self, callback: Box<dyn FnMut()>) {
+        self.0.callbacks.borrow_mut().appearance_changed = Some(callback);
+    }
+
+    fn on_button_layout_changed(&self, callback: Box<dyn FnMut()>) {
+        self.0.callbacks.borrow_mut().button_layout_changed = Some(callback);
+    }
+
+    fn draw(&self, scene: &Scene) {
+        self.with_native_if_selected(|native| native.draw(scene));
+    }
+
+    fn completed_frame(&self) {
+        self.with_native_if_selected(|native| native.completed_frame());
+    }
+
+    fn sprite_atlas(&self) -> Arc<dyn PlatformAtlas> {
+        self.0.atlas.clone()
+    }
+
+    fn is_subpixel_rendering_supported(&self) -> bool {
+        self.0
+            .group()
+            .native
+            .borrow()
+            .is_subpixel_rendering_supported()
+    }
+
+    fn get_title(&self) -> String {
+        self.0.title.borrow().clone()
+    }
+
+    fn tabbed_windows(&self) -> Option<Vec<SystemWindowTab>> {
+        Some(
+            self.0
+                .group()
+                .tabs()
+                .iter()
+                .map(|tab| SystemWindowTab::new(tab.title.borrow().clone().into(), tab.handle))
+                .collect(),
+        )
+    }
+
+    fn tab_bar_visible(&self) -> bool {
+        self.0.group().tabs.borrow().len() > 1
+    }
+
+    fn on_move_tab_to_new_window(&self, callback: Box<dyn FnMut()>) {
+        self.0.callbacks.borrow_mut().move_tab_to_new_window = Some(callback);
+    }
+
+    fn on_merge_all_windows(&self, callback: Box<dyn FnMut()>) {
+        self.0.callbacks.borrow_mut().merge_all_windows = Some(callback);
+    }
+
+    fn on_select_previous_tab(&self, callback: Box<dyn FnMut()>) {
+        self.0.callbacks.borrow_mut().select_previous_tab = Some(callback);
+    }
+
+    fn on_select_next_tab(&self, callback: Box<dyn FnMut()>) {
+        self.0.callbacks.borrow_mut().select_next_tab = Some(callback);
+    }
+
+    fn on_toggle_tab_bar(&self, callback: Box<dyn FnMut()>) {
+        self.0.callbacks.borrow_mut().toggle_tab_bar = Some(callback);
+    }
+
+    /// Moves the tabs of every other group with the same tabbing identifier into this one.
+    fn merge_all_windows(&self) {
+        let group = self.0.group();
+        let identifier = group.identifier.borrow().clone();
+        for other in group.window_tabs.groups() {
+            if Rc::ptr_eq(&other, &group) || *other.identifier.borrow() != identifier {
+                continue;
+            }
+            for tab in other.tabs() {
+                tab.move_to(&group);
+                let tab = Rc::downgrade(&tab);
+                group.window_tabs.defer(move || {
+                    if let Some(tab) = tab.upgrade() {
+                        tab.deactivate();
+                    }
+                });
+            }
+        }
+
+        let tab = Rc::downgrade(&self.0);
+        group.window_tabs.defer(move || {
+            if let Some(tab) = tab.upgrade() {
+                tab.with_callback(
+                    |callbacks| &mut callbacks.merge_all_windows,
+                    |callback| callback(),
+                );
+            }
+        });
+    }
+
+    /// Moves this tab into a native window of its own, centered horizontally under the
+    /// pointer, so that a tab dragged out of the tab bar ends up where it was dropped.
+    fn move_tab_to_new_window(&self) {
+        let group = self.0.group();
+        if group.tabs.borrow().len() < 2 {
+            return;
+        }
+
+        let bounds = {
+            let native = group.native.borrow();
+            let bounds = native.bounds();
+            let pointer = bounds.origin + native.mouse_position();
+            Bounds {
+                origin: pointer - point(bounds.size.width / 2., px(0.)),
+                size: bounds.size,
+            }
+        };
+        let identifier = group.identifier.borrow().clone();
+        if let Err(error) = self.move_to_new_group(bounds, identifier) {
+            log::error!("Failed to open a window for the tab: {error:#}");
+            return;
+        }
+
+        let tab = Rc::downgrade(&self.0);
+        group.window_tabs.defer(move || {
+            if let Some(tab) = tab.upgrade() {
+                tab.with_callback(
+                    |callbacks| &mut callbacks.move_tab_to_new_window,
+                    |callback| callback(),
+                );
+            }
+        });
+    }
+
+    /// Sets which windows this tab is grouped with from now on. A tab sharing its window with
+    /// others moves into a window of its own, as the others keep their identifier.
+    fn set_tabbing_identifier(&self, identifier: Option<String>) {
+        let group = self.0.group();
+        if *group.identifier.borrow() == identifier {
+            return;
+        }
+        if group.tabs.borrow().len() < 2 {
+            *group.identifier.borrow_mut() = identifier;
+            return;
+        }
+        let bounds = group.native.borrow().bounds();
+        self.move_to_new_group(bounds, identifier)
+            .context("Failed to open a window for the tab")
+            .log_err();
+    }
+
+    fn show_window_menu(&self, position: Point<Pixels>) {
+        self.0.group().native.borrow().show_window_menu(position);
+    }
+
+    fn start_window_move(&self) {
+        self.0.group().native.borrow().start_window_move();
+    }
+
+    fn start_window_resize(&self, edge: ResizeEdge) {
+        self.0.group().native.borrow().start_window_resize(edge);
+    }
+
+    fn window_decorations(&self) -> Decorations {
+        self.0.group().native.borrow().window_decorations()
+    }
+
+    fn request_decorations(&self, decorations: WindowDecorations) {
+        if self.0.joining.get() {
+            return;
+        }
+        let group = self.0.group();
+        group.decorations.set(Some(decorations));
+        group.native.borrow().request_decorations(decorations);
+    }
+
+    fn set_app_id(&mut self, app_id: &str) {
+        if self.0.joining.get() {
+            return;
+        }
+        let group = self.0.group();
+        *group.app_id.borrow_mut() = Some(app_id.to_string());
+        group.native.borrow_mut().set_app_id(app_id);
+    }
+
+    /// Maps the native window, or selects the tab if it joined a window that's already shown.
+    fn map_window(&mut self) -> Result<()> {
+        let group = self.0.group();
+        if self.0.joining.replace(false) {
+            group.select(&self.0);
+            return Ok(());
+        }
+        group.native.borrow_mut().map_window()
+    }
+
+    fn window_controls(&self) -> WindowControls {
+        self.0.group().native.borrow().window_controls()
+    }
+
+    fn set_client_inset(&self, inset: Pixels) {
+        self.0.group().native.borrow().set_client_inset(inset);
+    }
+
+    fn set_idle_inhibited(&self, inhibited: bool) {
+        self.with_native_if_selected(|native| native.set_idle_inhibited(inhibited));
+    }
+
+    fn set_pointer_constraint(&self, constraint: Option<PointerConstraint>) {
+        self.with_native_if_selected(|native| native.set_pointer_constraint(constraint));
+    }
+
+    fn move_to_display(&self, display_id: DisplayId) -> Result<()> {
+        self.0.group().native.borrow().move_to_display(display_id)
+    }
+
+    fn gpu_specs(&self) -> Option<GpuSpecs> {
+        self.0.group().native.borrow().gpu_specs()
+    }
+
+    fn update_ime_position(&self, bounds: Bounds<Pixels>) {
+        self.with_native_if_selected(|native| native.update_ime_position(bounds));
+    }
+
+    fn play_system_bell(&self) {
+        self.0.group().native.borrow().play_system_bell();
+    }
+
+    fn render_to_image(&self, scene: &Scene) -> Result<RgbaImage> {
+        self.0.group().native.borrow().render_to_image(scene)
+    }
+
+    fn inject_input(&self, input: PlatformInput) -> Result<()> {
+        let group = self.0.group();
+        if !group.is_selected(&self.0) {
+            return Err(anyhow!("Input can only be injected into the selected tab"));
+        }
+        group.native.borrow().inject_input(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linux::{HeadlessClient, LinuxClient as _};
+    use gpui::{Empty, Keystroke, WindowHandle, WindowId, WindowKind, size};
+
+    fn key_down(keystroke: &str) -> PlatformInput {
+        PlatformInput::KeyDown(KeyDownEvent {
+            keystroke: Keystroke::parse(keystroke).unwrap(),
+            is_held: false,
+            prefer_character_input: false,
+        })
+    }
+
+    #[test]
+    fn test_tab_switch_shortcuts() {
+        assert!(matches!(
+            TabSwitch::for_input(&key_down("ctrl-tab")),
+            Some(TabSwitch::Next)
+        ));
+        assert!(matches!(
+            TabSwitch::for_input(&key_down("ctrl-shift-tab")),
+            Some(TabSwitch::Previous)
+        ));
+        assert!(matches!(
+            TabSwitch::for_input(&key_down("ctrl-pagedown")),
+            Some(TabSwitch::Next)
+        ));
+        assert!(matches!(
+            TabSwitch::for_input(&key_down("ctrl-pageup")),
+            Some(TabSwitch::Previous)
+        ));
+        assert!(TabSwitch::for_input(&key_down("tab")).is_none());
+        assert!(TabSwitch::for_input(&key_down("ctrl-alt-tab")).is_none());
+    }
+
+    struct TestTabs {
+        client: HeadlessClient,
+        tabs: WindowTabs,
+    }
+
+    impl TestTabs {
+        fn new() -> Self {
+            let client = HeadlessClient::new();
+            let executor = client.with_common(|common| common.foreground_executor.clone());
+            Self {
+                client,
+                tabs: WindowTabs::new(executor),
+            }
+        }
+
+        /// Opens a window as a tab.
+        fn open(&self, id: u64, identifier: &str) -> Box<dyn PlatformWindow> {
+            let params = WindowParams {
+                bounds: Bounds::new(point(px(0.), px(0.)), size(px(400.), px(300.))),
+                titlebar: None,
+                kind: WindowKind::Normal,
+                is_movable: true,
+                is_resizable: true,
+                is_minimizable: true,
+                focus: true,
+                show: true,
+                icon: None,
+                display_id: None,
+                window_min_size: None,
+                tabbing_identifier: Some(identifier.to_string()),
+            };
+            let client = self.client.clone();
+            let mut window = self
+                .tabs
+                .open_window(
+                    handle(id),
+                    params,
+                    Rc::new(move |handle, params| client.open_window(handle, params)),
+                )
+                .unwrap();
+            window.map_window().unwrap();
+            window
+        }
+    }
+
+    fn handle(id: u64) -> AnyWindowHandle {
+        WindowHandle::<Empty>::new(WindowId::from(id)).into()
+    }
+
+    fn tab_handles(window: &dyn PlatformWindow) -> Vec<AnyWindowHandle> {
+        window
+            .tabbed_windows()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|tab| tab.handle)
+            .collect()
+    }
+
+    #[test]
+    #[ignore = "opens headless windows, which needs a GPU adapter"]
+    fn test_tabs_grouped_by_identifier() {
+        let cx = TestTabs::new();
+        let first = cx.open(1, "documents");
+        let second = cx.open(2, "documents");
+        let other = cx.open(3, "settings");
+
+        assert_eq!(tab_handles(&*first), [handle(1), handle(2)]);
+        assert_eq!(tab_handles(&*second), [handle(1), handle(2)]);
+        assert_eq!(tab_handles(&*other), [handle(3)]);
+        assert!(first.tab_bar_visible());
+        assert!(!other.tab_bar_visible());
+        assert_eq!(cx.tabs.native_window(handle(2)), handle(1));
+        assert_eq!(cx.tabs.native_window(handle(3)), handle(3));
+    }
+
+    #[test]
+    #[ignore = "opens headless windows, which needs a GPU adapter"]
+    fn test_detach_and_merge_tabs() {
+        let cx = TestTabs::new();
+        let first = cx.open(1, "documents");
+        let second = cx.open(2, "documents");
+
+        second.move_tab_to_new_window();
+        assert_eq!(tab_handles(&*first), [handle(1)]);
+        assert_eq!(tab_handles(&*second), [handle(2)]);
+        assert_eq!(cx.tabs.native_window(handle(2)), handle(2));
+
+        first.merge_all_windows();
+        assert_eq!(tab_handles(&*first), [handle(1), handle(2)]);
+        assert_eq!(tab_handles(&*second), [handle(1), handle(2)]);
+        assert_eq!(cx.tabs.native_window(handle(2)), handle(1));
+    }
+
+    #[test]
+    #[ignore = "opens headless windows, which needs a GPU adapter"]
+    fn test_set_tabbing_identifier_moves_only_the_tab() {
+        let cx = TestTabs::new();
+        let first = cx.open(1, "documents");
+        let second = cx.open(2, "documents");
+
+        second.set_tabbing_identifier(Some("settings".to_string()));
+        assert_eq!(tab_handles(&*first), [handle(1)]);
+        assert_eq!(tab_handles(&*second), [handle(2)]);
+
+        let third = cx.open(3, "documents");
+        let fourth = cx.open(4, "settings");
+        assert_eq!(tab_handles(&*third), [handle(1), handle(3)]);
+        assert_eq!(tab_handles(&*fourth), [handle(2), handle(4)]);
+    }
+}
@@ -4,9 +4,11 @@ mod display;
 mod event;
 mod window;
 mod xim_handler;
+mod xsettings;
 
 pub(crate) use client::*;
 pub(crate) use display::*;
 pub(crate) use event::*;
 pub(crate) use window::*;
 pub(crate) use xim_handler::*;
+pub(crate) use xsettings::*;
@@ -11,7 +11,7 @@ use url::Url;
 use log::Level;
 use smallvec::SmallVec;
 use std::{
-    cell::RefCell,
+    cell::{RefCell, RefMut},
     collections::{BTreeMap, HashSet},
     ops::Deref,
     path::PathBuf,
@@ -25,13 +25,14 @@ use x11rb::{
     cursor,
     errors::ConnectionError,
     protocol::randr::ConnectionExt as _,
+    protocol::render::ConnectionExt as _,
     protocol::xinput::ConnectionExt,
     protocol::xkb::ConnectionExt as _,
     protocol::xproto::{
         AtomEnum, ChangeWindowAttributesAux, ClientMessageData, ClientMessageEvent,
         ConnectionExt as _, EventMask, Visibility,
     },
-    protocol::{Event, dri3, randr, render, xinput, xkb, xproto},
+    protocol::{Event, dri3, present, randr, render, xinput, xkb, xproto},
     resource_manager::Database,
     wrapper::ConnectionExt as _,
     xcb_ffi::XCBConnection,
@@ -41,28 +42,35 @@ use xkbc::x11::ffi::{XKB_X11_MIN_MAJOR_XKB_VERSION, XKB_X11_MIN_MINOR_XKB_VERSIO
 use xkbcommon::xkb::{self as xkbc, STATE_LAYOUT_EFFECTIVE};
 
 use super::{
-    ButtonOrScroll, ScrollDirection, X11Display, X11WindowStatePtr, XcbAtoms, XimCallbackEvent,
-    XimHandler, button_or_scroll_from_event_detail, check_reply,
+    ButtonOrScroll, ScrollDirection, X11Display, X11Displays, X11WindowStatePtr, XSettings,
+    XcbAtoms, XimCallbackEvent, XimHandler, button_or_scroll_from_event_detail, check_reply,
     clipboard::{self, Clipboard},
     get_reply, get_valuator_axis_index, handle_connection_error, modifiers_from_state,
-    pressed_button_from_mask, xcb_flush,
+    pressed_button_from_mask, read_xsettings, watch_for_display_changes,
+    watch_for_xsettings_manager, xcb_flush,
 };
 
 use crate::linux::{
-    DEFAULT_CURSOR_ICON_NAME, LinuxClient, capslock_from_xkb, cursor_style_to_icon_names,
-    get_xkb_compose_state, is_within_click_distance, keystroke_from_xkb,
-    keystroke_underlying_dead_key, log_cursor_icon_warning, modifiers_from_xkb, open_uri_internal,
-    platform::{DOUBLE_CLICK_INTERVAL, SCROLL_LINES},
-    reveal_path_internal,
+    DEFAULT_CURSOR_ICON_NAME, FontRenderingOptions, LinuxClient, capslock_from_xkb,
+    cursor_style_to_icon_names, get_xkb_compose_state, is_within_click_distance,
+    keystroke_from_xkb, keystroke_underlying_dead_key, keysym_for_key, log_cursor_icon_warning,
+    modifiers_from_xkb, open_uri_internal,
+    platform::{DOUBLE_CLICK_DISTANCE, DOUBLE_CLICK_INTERVAL, SCROLL_LINES},
+    reveal_path_internal, scaled_cursor_frames,
     xdg_desktop_portal::{Event as XDPEvent, XDPEventSource},
 };
-use crate::linux::{LinuxCommon, LinuxKeyboardLayout, X11Window, modifiers_from_xinput_info};
+use crate::linux::{
+    InstanceRequest, LinuxCommon, LinuxKeyboardLayout, X11Window, modifiers_from_xinput_info,
+};
 
 use gpui::{
-    AnyWindowHandle, Bounds, ClipboardItem, CursorStyle, DisplayId, FileDropEvent, Keystroke,
-    Modifiers, ModifiersChangedEvent, MouseButton, Pixels, PlatformDisplay, PlatformInput,
-    PlatformKeyboardLayout, PlatformWindow, Point, RequestFrameOptions, ScrollDelta, Size,
-    TouchPhase, WindowButtonLayout, WindowParams, point, px,
+    AnyWindowHandle, Bounds, ClipboardItem, CursorStyle, DEFAULT_DRAG_THRESHOLD, DisplayId,
+    FileDropEvent, Keystroke, Modifiers, ModifiersChangedEvent, MouseButton, Pixels,
+    PlatformDisplay, PlatformInput, PlatformKeyboardLayout, PlatformWindow, Point,
+    RequestFrameOptions, ScrollDelta, Size, TouchPhase, WindowButtonLayout, WindowKind,
+    WindowParams,
+    global_shortcut::{GlobalShortcut, GlobalShortcutId, GlobalShortcutPhase},
+    point, px,
 };
 use gpui_wgpu::{CompositorGpuHint, GpuContext};
 
@@ -78,6 +86,30 @@ pub(crate) const XINPUT_ALL_DEVICE_GROUPS: xinput::DeviceId = 1;
 
 const GPUI_X11_SCALE_FACTOR_ENV: &str = "GPUI_X11_SCALE_FACTOR";
 
+/// How many cursors created from application images are kept before the oldest is freed.
+const MAX_CACHED_IMAGE_CURSORS: usize = 8;
+
+/// The modifiers that distinguish global shortcuts grabbed on the root window.
+const GLOBAL_SHORTCUT_MODIFIERS: u16 = xproto::ModMask::SHIFT.bits()
+    | xproto::ModMask::CONTROL.bits()
+    | xproto::ModMask::M1.bits()
+    | xproto::ModMask::M4.bits();
+/// Caps Lock and Num Lock, which shouldn't stop global shortcuts from firing. Grabs need an exact
+/// match, so each shortcut is grabbed with every combination of them.
+const IGNORED_GLOBAL_SHORTCUT_MODIFIERS: [u16; 4] = [
+    0,
+    xproto::ModMask::LOCK.bits(),
+    xproto::ModMask::M2.bits(),
+    xproto::ModMask::LOCK.bits() | xproto::ModMask::M2.bits(),
+];
+
+/// A key grabbed on the root window for a global shortcut.
+struct GlobalShortcutGrab {
+    keycode: xproto::Keycode,
+    modifiers: u16,
+    id: GlobalShortcutId,
+}
+
 pub(crate) struct WindowRef {
     window: X11WindowStatePtr,
     refresh_state: Option<RefreshState>,
@@ -90,6 +122,14 @@ impl WindowRef {
     pub fn handle(&self) -> AnyWindowHandle {
         self.window.state.borrow().handle
     }
+
+    fn refresh_rate(&self) -> Option<Duration> {
+        match self.refresh_state {
+            Some(RefreshState::Hidden { refresh_rate })
+            | Some(RefreshState::PeriodicRefresh { refresh_rate, .. }) => Some(refresh_rate),
+            None => None,
+        }
+    }
 }
 
 impl Deref for WindowRef {
@@ -182,6 +222,7 @@ pub struct X11ClientState {
     pub(crate) compositor_gpu: Option<CompositorGpuHint>,
 
     pub(crate) scale_factor: f32,
+    pub(crate) displays: X11Displays,
 
     xkb_context: xkbc::Context,
     pub(crate) xcb_connection: Rc<XCBConnection>,
@@ -189,6 +230,10 @@ pub struct X11ClientState {
     client_side_decorations_supported: bool,
     pub(crate) x_root_index: usize,
     pub(crate) resource_database: Database,
+    /// The `_XSETTINGS_S<screen>` selection, owned by the running settings manager.
+    xsettings_selection: xproto::Atom,
+    xsettings_owner: Option<xproto::Window>,
+    pub(crate) xsettings: XSettings,
     pub(crate) atoms: XcbAtoms,
     pub(crate) windows: HashMap<xproto::Window, WindowRef>,
     pub(crate) mouse_focused_window: Option<xproto::Window>,
@@ -211,8 +256,14 @@ pub struct X11ClientState {
     pub(crate) cursor_handle: cursor::Handle,
     pub(crate) cursor_styles: HashMap<xproto::Window, CursorStyle>,
     pub(crate) cursor_cache: HashMap<CursorStyle, Option<xproto::Cursor>>,
+    /// Cursors created from application images with the scale factor they were rendered at, most
+    /// recently used first.
+    pub(crate) image_cursor_cache: Vec<(CursorStyle, f32, xproto::Cursor)>,
     pub(crate) invisible_cursor_cache: Option<xproto::Cursor>,
     pub(crate) cursor_hidden_window: Option<xproto::Window>,
+    /// Whether re-querying the displays is already scheduled, so that a burst of RandR events
+    /// only queries them once.
+    randr_change_pending: bool,
 
     pointer_device_states: BTreeMap<xinput::DeviceId, PointerDeviceState>,
 
@@ -222,6 +273,11 @@ pub struct X11ClientState {
     pub(crate) clipboard: Clipboard,
     pub(crate) clipboard_item: Option<ClipboardItem>,
     pub(crate) xdnd_state: Xdnd,
+
+    /// Global shortcuts grabbed on the root window when there's no portal to bind them with.
+    global_shortcut_grabs: Vec<GlobalShortcutGrab>,
+    /// The global shortcuts whose keys are held down, by keycode.
+    pressed_global_shortcuts: HashMap<xproto::Keycode, GlobalShortcutId>,
 }
 
 #[derive(Clone)]
@@ -309,7 +365,8 @@ impl X11Client {
     pub(crate) fn new() -> anyhow::Result<Self> {
         let event_loop = EventLoop::try_new()?;
 
-        let (common, main_receiver, bridge_receiver) = LinuxCommon::new(event_loop.get_signal());
+        let (common, main_receiver, bridge_receiver, instance_request_receiver) =
+            LinuxCommon::new(event_loop.get_signal());
 
         let handle = event_loop.handle();
 
@@ -345,11 +402,23 @@ impl X11Client {
             })
             .unwrap();
 
+        handle
+            .insert_source(
+                instance_request_receiver,
+                |event, _, client: &mut X11Client| {
+                    if let calloop::channel::Event::Msg(request) = event {
+                        client.handle_instance_request(request);
+                    }
+                },
+            )
+            .unwrap();
+
         let (xcb_connection, x_root_index) = XCBConnection::connect(None)?;
         xcb_connection.prefetch_extension_information(xkb::X11_EXTENSION_NAME)?;
         xcb_connection.prefetch_extension_information(randr::X11_EXTENSION_NAME)?;
         xcb_connection.prefetch_extension_information(render::X11_EXTENSION_NAME)?;
         xcb_connection.prefetch_extension_information(xinput::X11_EXTENSION_NAME)?;
+        xcb_connection.prefetch_extension_information(present::X11_EXTENSION_NAME)?;
 
         // Announce to X server that XInput up to 2.4 is supported.
         // Version 2.4 is needed for gesture events (GesturePinchBegin/Update/End).
@@ -442,11 +511,53 @@ impl X11Client {
 
         let resource_database = x11rb::resource_manager::new_from_default(&xcb_connection)
             .context("Failed to create resource database")?;
-        let scale_factor = get_scale_factor(&xcb_connection, &resource_database, x_root_index);
-        let cursor_handle = cursor::Handle::new(&xcb_connection, x_root_index, &resource_database)
-            .context("Failed to initialize cursor theme handler")?
-            .reply()
-            .context("Failed to initialize cursor theme handler")?;
+
+        let xsettings_selection = get_reply(
+            || "Failed to intern XSETTINGS selection atom",
+            xcb_connection.intern_atom(false, format!("_XSETTINGS_S{x_root_index}").as_bytes()),
+        )?
+        .atom;
+        watch_for_xsettings_manager(
+            &xcb_connection,
+            xcb_connection.setup().roots[x_root_index].root,
+        )
+        .context("Failed to watch for an XSETTINGS manager")
+        .log_err();
+        let (xsettings_owner, xsettings) = read_xsettings(
+            &xcb_connection,
+            xsettings_selection,
+            atoms._XSETTINGS_SETTINGS,
+        )
+        .context("Failed to read XSETTINGS")
+        .log_err()
+        .flatten()
+        .unzip();
+        let xsettings = xsettings.unwrap_or_default();
+        common
+            .cosmic_text_system
+            .set_rendering_options(font_rendering_options(&resource_database, &xsettings));
+
+        let scale_factor = get_scale_factor(
+            &xcb_connection,
+            &resource_database,
+            x_root_index,
+            xsettings.dpi,
+        );
+        watch_for_display_changes(&xcb_connection, x_root_index)
+            .context("Failed to watch for display changes")
+            .log_err();
+        let displays = Rc::new(RefCell::new(X11Display::query_all(
+            &xcb_connection,
+            scale_factor,
+        )));
+        let cursor_handle = cursor::Handle::new(
+            &xcb_connection,
+            x_root_index,
+            &cursor_resource_database(&resource_database, &xsettings),
+        )
+        .context("Failed to initialize cursor theme handler")?
+        .reply()
+        .context("Failed to initialize cursor theme handler")?;
 
         let clipboard = Clipboard::new().context("Failed to initialize clipboard")?;
 
@@ -491,6 +602,37 @@ impl X11Client {
                             window.window.set_appearance(appearance);
                         }
                     }
+                    XDPEvent::AccentColor(accent_color) => {
+                        let callback = client.with_common(|common| {
+                            if common.accent_color == accent_color {
+                                return None;
+                            }
+                            common.accent_color = accent_color;
+                            common.callbacks.accent_color_changed.take()
+                        });
+                        if let Some(mut callback) = callback {
+                            callback();
+                            client.with_common(|common| {
+                                common.callbacks.accent_color_changed = Some(callback)
+                            });
+                        }
+                    }
+                    XDPEvent::Accessibility(setting) => {
+                        let callback = client.with_common(|common| {
+                            let previous = common.accessibility_preferences;
+                            setting.apply(&mut common.accessibility_preferences);
+                            if common.accessibility_preferences == previous {
+                                return None;
+                            }
+                            common.callbacks.accessibility_preferences_changed.take()
+                        });
+                        if let Some(mut callback) = callback {
+                            callback();
+                            client.with_common(|common| {
+                                common.callbacks.accessibility_preferences_changed = Some(callback)
+                            });
+                        }
+                    }
                     XDPEvent::ButtonLayout(layout_str) => {
                         let layout = WindowButtonLayout::parse(&layout_str)
                             .log_err()
@@ -525,6 +667,7 @@ impl X11Client {
             gpu_context: Rc::new(RefCell::new(None)),
             compositor_gpu,
             scale_factor,
+            displays,
 
             xkb_context,
             xcb_connection,
@@ -532,6 +675,9 @@ impl X11Client {
             client_side_decorations_supported,
             x_root_index,
             resource_database,
+            xsettings_selection,
+            xsettings_owner,
+            xsettings,
             atoms,
             windows: HashMap::default(),
             mouse_focused_window: None,
@@ -549,8 +695,10 @@ impl X11Client {
             cursor_handle,
             cursor_styles: HashMap::default(),
             cursor_cache: HashMap::default(),
+            image_cursor_cache: Vec::new(),
             cursor_hidden_window: None,
             invisible_cursor_cache: None,
+            randr_change_pending: false,
 
             pointer_device_states,
 
@@ -559,6 +707,9 @@ impl X11Client {
             clipboard,
             clipboard_item: None,
             xdnd_state: Xdnd::default(),
+
+            global_shortcut_grabs: Vec::new(),
+            pressed_global_shortcuts: HashMap::default(),
         }))))
     }
 
@@ -783,6 +934,240 @@ impl X11Client {
             .map(|window_reference| window_reference.window.clone())
     }
 
+    fn is_xsettings_manager_announcement(&self, event: &xproto::ClientMessageEvent) -> bool {
+        let state = self.0.borrow();
+        event.type_ == state.atoms.MANAGER && event.data.as_data32()[1] == state.xsettings_selection
+    }
+
+    fn is_xsettings_change(&self, event: &xproto::PropertyNotifyEvent) -> bool {
+        let state = self.0.borrow();
+        state.xsettings_owner == Some(event.window) && event.atom == state.atoms._XSETTINGS_SETTINGS
+    }
+
+    /// Replaces the keys grabbed on the root window for global shortcuts.
+    fn grab_global_shortcuts(&self, shortcuts: &[GlobalShortcut]) {
+        let mut state = self.0.borrow_mut();
+        let xcb_connection = state.xcb_connection.clone();
+        let root = xcb_connection.setup().roots[state.x_root_index].root;
+        for grab in state.global_shortcut_grabs.drain(..) {
+            for ignored in IGNORED_GLOBAL_SHORTCUT_MODIFIERS {
+                xcb_connection
+                    .ungrab_key(grab.keycode, root, (grab.modifiers | ignored).into())
+                    .log_err();
+            }
+        }
+        state.pressed_global_shortcuts.clear();
+
+        let keymap = state.xkb.get_keymap();
+        let layout = state.xkb.serialize_layout(STATE_LAYOUT_EFFECTIVE);
+        for shortcut in shortcuts {
+            let keycode = keysym_for_key(&shortcut.keystroke.key).and_then(|keysym| {
+                (keymap.min_keycode().raw()..=keymap.max_keycode().raw())
+                    .find(|&code| {
+                        keymap
+                            .key_get_syms_by_level(xkbc::Keycode::new(code), layout, 0)
+                            .contains(&keysym)
+                    })
+                    .and_then(|code| xproto::Keycode::try_from(code).ok())
+            });
+            let Some(keycode) = keycode else {
+                log::warn!(
+                    "Can't grab global shortcut {}, its key isn't on the keyboard",
+                    shortcut.keystroke
+                );
+                continue;
+            };
+
+            let modifiers = shortcut.keystroke.modifiers;
+            let modifiers = [
+                (modifiers.shift, xproto::ModMask::SHIFT),
+                (modifiers.control, xproto::ModMask::CONTROL),
+                (modifiers.alt, xproto::ModMask::M1),
+                (modifiers.platform, xproto::ModMask::M4),
+            ]
+            .into_iter()
+            .filter(|(pressed, _)| *pressed)
+            .fold(0, |mask, (_, modifier)| mask | modifier.bits());
+            for ignored in IGNORED_GLOBAL_SHORTCUT_MODIFIERS {
+                // Fails if another client grabbed the same keys first.
+                check_reply(
+                    || format!("Failed to grab global shortcut {}", shortcut.keystroke),
+                    xcb_connection.grab_key(
+                        false,
+                        root,
+                        (modifiers | ignored).into(),
+                        keycode,
+                        xproto::GrabMode::ASYNC,
+                        xproto::GrabMode::ASYNC,
+                    ),
+                )
+                .log_err();
+            }
+            state.global_shortcut_grabs.push(GlobalShortcutGrab {
+                keycode,
+                modifiers,
+                id: shortcut.id.clone(),
+            });
+        }
+        xcb_flush(&xcb_connection);
+    }
+
+    /// Reports presses and releases of keys grabbed for global shortcuts, returning whether the
+    /// event was one of them.
+    fn handle_global_shortcut_key(&self, event: &xproto::KeyPressEvent, pressed: bool) -> bool {
+        let mut state = self.0.borrow_mut();
+        // Key events are only selected on our own windows, so the root window only receives
+        // the grabbed ones.
+        if event.event != state.xcb_connection.setup().roots[state.x_root_index].root {
+            return false;
+        }
+        let report = if pressed {
+            let modifiers = u16::from(event.state) & GLOBAL_SHORTCUT_MODIFIERS;
+            let id = state
+                .global_shortcut_grabs
+                .iter()
+                .find(|grab| grab.keycode == event.detail && grab.modifiers == modifiers)
+                .map(|grab| grab.id.clone());
+            // Holding the keys repeats the press without releasing them in between.
+            id.filter(|id| {
+                state
+                    .pressed_global_shortcuts
+                    .insert(event.detail, id.clone())
+                    .is_none()
+            })
+            .map(|id| (id, GlobalShortcutPhase::Activated))
+        } else {
+            state
+                .pressed_global_shortcuts
+                .remove(&event.detail)
+                .map(|id| (id, GlobalShortcutPhase::Deactivated))
+        };
+        let global_shortcuts = state.common.global_shortcuts.clone();
+        drop(state);
+        if let Some((id, phase)) = report {
+            global_shortcuts.emit(id, phase);
+        }
+        true
+    }
+
+    fn reload_xsettings(&self) {
+        let mut state = self.0.borrow_mut();
+        let Some((owner, xsettings)) = read_xsettings(
+            &state.xcb_connection,
+            state.xsettings_selection,
+            state.atoms._XSETTINGS_SETTINGS,
+        )
+        .context("X11: Failed to read XSETTINGS")
+        .log_err()
+        .flatten() else {
+            return;
+        };
+        state.xsettings_owner = Some(owner);
+        if xsettings == state.xsettings {
+            return;
+        }
+        let previous = std::mem::replace(&mut state.xsettings, xsettings);
+        let xsettings = &state.xsettings;
+
+        let font_rendering_changed = previous.antialias != xsettings.antialias
+            || previous.hinting != xsettings.hinting
+            || previous.rgba != xsettings.rgba;
+        let cursor_theme_changed = previous.cursor_theme != xsettings.cursor_theme
+            || previous.cursor_size != xsettings.cursor_size
+            || previous.dpi != xsettings.dpi;
+        let is_bgr = subpixel_order_is_bgr(&state.resource_database, xsettings);
+        if font_rendering_changed {
+            state
+                .common
+                .cosmic_text_system
+                .set_rendering_options(font_rendering_options(&state.resource_database, xsettings));
+        }
+
+        let scale_factor = get_scale_factor(
+            &state.xcb_connection,
+            &state.resource_database,
+            state.x_root_index,
+            xsettings.dpi,
+        );
+        let scale_factor_changed = scale_factor != state.scale_factor;
+        state.scale_factor = scale_factor;
+        if scale_factor_changed {
+            let displays = X11Display::query_all(&state.xcb_connection, scale_factor);
+            *state.displays.borrow_mut() = displays;
+        }
+
+        if cursor_theme_changed {
+            state.reload_cursor_theme();
+        }
+        if cursor_theme_changed || scale_factor_changed {
+            state.reapply_cursor_styles();
+        }
+
+        let windows = state
+            .windows
+            .values()
+            .map(|window_ref| window_ref.window.clone())
+            .collect::<Vec<_>>();
+        drop(state);
+
+        for window in windows {
+            if font_rendering_changed {
+                window.set_font_rendering(is_bgr);
+            }
+            if scale_factor_changed {
+                window.set_scale_factor(scale_factor);
+            }
+        }
+        if scale_factor_changed {
+            self.handle_displays_changed();
+        }
+    }
+
+    /// Re-queries the displays after RandR reported a change to the monitor configuration, e.g.
+    /// a monitor being connected or rescaled.
+    fn handle_randr_change(&self) {
+        let mut state = self.0.borrow_mut();
+        state.randr_change_pending = false;
+        let scale_factor = get_scale_factor(
+            &state.xcb_connection,
+            &state.resource_database,
+            state.x_root_index,
+            state.xsettings.dpi,
+        );
+        let scale_factor_changed = scale_factor != state.scale_factor;
+        state.scale_factor = scale_factor;
+        if scale_factor_changed {
+            state.reapply_cursor_styles();
+        }
+        let displays = X11Display::query_all(&state.xcb_connection, scale_factor);
+        *state.displays.borrow_mut() = displays;
+
+        let windows = state
+            .windows
+            .values()
+            .map(|window_ref| window_ref.window.clone())
+            .collect::<Vec<_>>();
+        drop(state);
+
+        for window in &windows {
+            if scale_factor_changed {
+                window.set_scale_factor(scale_factor);
+            }
+            window.update_display();
+        }
+        self.handle_displays_changed();
+    }
+
+    fn handle_displays_changed(&self) {
+        let mut state = self.0.borrow_mut();
+        if let Some(mut callback) = state.common.callbacks.displays_changed.take() {
+            drop(state);
+            callback();
+            state = self.0.borrow_mut();
+            state.common.callbacks.displays_changed = Some(callback);
+        }
+    }
+
     fn handle_event(&self, event: Event) -> Option<()> {
         match event {
             Event::UnmapNotify(event) => {
@@ -806,6 +1191,28 @@ impl X11Client {
                 }
                 state.update_refresh_loop(event.window);
             }
+            Event::PresentCompleteNotify(event) => {
+                let window = self.get_window(event.window)?;
+                let refresh_interval = self
+                    .0
+                    .borrow()
+                    .windows
+                    .get(&event.window)
+                    .and_then(WindowRef::refresh_rate);
+                window.handle_present_complete(&event, refresh_interval);
+            }
+            Event::ClientMessage(event) if self.is_xsettings_manager_announcement(&event) => {
+                self.reload_xsettings();
+            }
+            Event::RandrScreenChangeNotify(_) | Event::RandrNotify(_) => {
+                let mut state = self.0.borrow_mut();
+                if !state.randr_change_pending {
+                    state.randr_change_pending = true;
+                    state
+                        .loop_handle
+                        .insert_idle(|client| client.handle_randr_change());
+                }
+            }
             Event::ClientMessage(event) => {
                 let window = self.get_window(event.window)?;
                 let [atom, arg1, arg2, arg3, arg4] = event.data.as_data32();
@@ -950,6 +1357,15 @@ impl X11Client {
                     .context("X11: Failed to set window bounds")
                     .log_err();
             }
+            Event::PropertyNotify(event) if self.is_xsettings_change(&event) => {
+                self.reload_xsettings();
+            }
+            Event::DestroyNotify(event)
+                if self.0.borrow().xsettings_owner == Some(event.window) =>
+            {
+                // Keep the last settings until another manager takes over.
+                self.0.borrow_mut().xsettings_owner = None;
+            }
             Event::PropertyNotify(event) => {
                 let window = self.get_window(event.window)?;
                 window
@@ -993,6 +1409,7 @@ impl X11Client {
                 drop(state);
                 self.reset_ime();
                 window.handle_ime_delete();
+                window.close_popups();
             }
             Event::XkbNewKeyboardNotify(_) | Event::XkbMapNotify(_) => {
                 let mut state = self.0.borrow_mut();
@@ -1053,6 +1470,9 @@ impl X11Client {
                 }
             }
             Event::KeyPress(event) => {
+                if self.handle_global_shortcut_key(&event, true) {
+                    return Some(());
+                }
                 let window = self.get_window(event.event)?;
                 let mut state = self.0.borrow_mut();
 
@@ -1117,6 +1537,9 @@ impl X11Client {
                 }));
             }
             Event::KeyRelease(event) => {
+                if self.handle_global_shortcut_key(&event, false) {
+                    return Some(());
+                }
                 let window = self.get_window(event.event)?;
                 let mut state = self.0.borrow_mut();
 
@@ -1166,11 +1589,15 @@ impl X11Client {
                 match button_or_scroll_from_event_detail(event.detail) {
                     Some(ButtonOrScroll::Button(button)) => {
                         let click_elapsed = state.last_click.elapsed();
-                        if click_elapsed < DOUBLE_CLICK_INTERVAL
+                        if click_elapsed < state.double_click_interval()
                             && state
                                 .last_mouse_button
                                 .is_some_and(|prev_button| prev_button == button)
-                            && is_within_click_distance(state.last_location, position)
+                            && is_within_click_distance(
+                                state.last_location,
+                                position,
+                                state.double_click_distance(),
+                            )
                         {
                             state.current_count += 1;
                         } else {
@@ -1254,7 +1681,7 @@ impl X11Client {
                         .get(&window.x_window)
                         .unwrap_or(&CursorStyle::Arrow);
                     if *current_style != style
-                        && let Some(cursor) = state.get_cursor_icon(style)
+                        && let Some(cursor) = state.get_cursor_icon(&style)
                     {
                         state.cursor_styles.insert(window.x_window, style);
                         check_reply(
@@ -1280,7 +1707,9 @@ impl X11Client {
                 state.modifiers = modifiers;
                 drop(state);
 
-                if event.valuator_mask[0] & 3 != 0 {
+                if event.valuator_mask[0] & 3 != 0
+                    && !window.handle_constrained_motion(position, modifiers)
+                {
                     window.handle_input(PlatformInput::MouseMove(gpui::MouseMoveEvent {
                         position,
                         pressed_button,
@@ -1528,6 +1957,13 @@ impl X11Client {
         Some(())
     }
 
+    fn handle_instance_request(&self, request: InstanceRequest) {
+        LinuxCommon::handle_instance_request(
+            || RefMut::map(self.0.borrow_mut(), |state| &mut state.common),
+            request,
+        );
+    }
+
     fn handle_keyboard_layout_change(&self) {
         let mut state = self.0.borrow_mut();
         let layout_idx = state.xkb.serialize_layout(STATE_LAYOUT_EFFECTIVE);
@@ -1550,6 +1986,15 @@ impl LinuxClient for X11Client {
         "X11"
     }
 
+    fn drag_threshold(&self) -> Pixels {
+        self.0
+            .borrow()
+            .xsettings
+            .drag_threshold
+            .map(|threshold| px(threshold as f32))
+            .unwrap_or(DEFAULT_DRAG_THRESHOLD)
+    }
+
     fn with_common<R>(&self, f: impl FnOnce(&mut LinuxCommon) -> R) -> R {
         f(&mut self.0.borrow_mut().common)
     }
@@ -1561,41 +2006,28 @@ impl LinuxClient for X11Client {
 
     fn displays(&self) -> Vec<Rc<dyn PlatformDisplay>> {
         let state = self.0.borrow();
-        let setup = state.xcb_connection.setup();
-        setup
-            .roots
+        state
+            .displays
+            .borrow()
             .iter()
-            .enumerate()
-            .filter_map(|(root_id, _)| {
-                Some(Rc::new(
-                    X11Display::new(&state.xcb_connection, state.scale_factor, root_id).ok()?,
-                ) as Rc<dyn PlatformDisplay>)
-            })
+            .map(|display| display.clone() as Rc<dyn PlatformDisplay>)
             .collect()
     }
 
     fn primary_display(&self) -> Option<Rc<dyn PlatformDisplay>> {
         let state = self.0.borrow();
-        X11Display::new(
-            &state.xcb_connection,
-            state.scale_factor,
-            state.x_root_index,
-        )
-        .log_err()
-        .map(|display| Rc::new(display) as Rc<dyn PlatformDisplay>)
+        X11Display::primary(&state.displays.borrow(), state.x_root_index)
+            .map(|display| display as Rc<dyn PlatformDisplay>)
     }
 
     fn display(&self, id: DisplayId) -> Option<Rc<dyn PlatformDisplay>> {
         let state = self.0.borrow();
-
-        Some(Rc::new(
-            X11Display::new(
-                &state.xcb_connection,
-                state.scale_factor,
-                u64::from(id) as usize,
-            )
-            .ok()?,
-        ))
+        state
+            .displays
+            .borrow()
+            .iter()
+            .find(|display| display.id() == id)
+            .map(|display| display.clone() as Rc<dyn PlatformDisplay>)
     }
 
     #[cfg(feature = "screen-capture")]
@@ -1614,13 +2046,26 @@ impl LinuxClient for X11Client {
     fn open_window(
         &self,
         handle: AnyWindowHandle,
-        params: WindowParams,
+        mut params: WindowParams,
     ) -> anyhow::Result<Box<dyn PlatformWindow>> {
         let mut state = self.0.borrow_mut();
-        let parent_window = state
-            .keyboard_focused_window
-            .and_then(|focused_window| state.windows.get(&focused_window))
-            .map(|w| w.window.clone());
+        let parent_window = if let WindowKind::AnchoredPopUp(options) = &params.kind {
+            state
+                .windows
+                .values()
+                .find(|window_ref| window_ref.window.state.borrow().handle == options.parent)
+                .map(|window_ref| window_ref.window.clone())
+        } else {
+            state
+                .keyboard_focused_window
+                .and_then(|focused_window| state.windows.get(&focused_window))
+                .map(|w| w.window.clone())
+        };
+        if let WindowKind::AnchoredPopUp(options) = &params.kind
+            && let Some(parent_window) = parent_window.as_ref()
+        {
+            params.bounds = parent_window.popup_bounds(options, params.bounds.size)?;
+        }
         let x_window = state
             .xcb_connection
             .generate_id()
@@ -1631,13 +2076,11 @@ impl LinuxClient for X11Client {
         let x_root_index = state.x_root_index;
         let atoms = state.atoms;
         let scale_factor = state.scale_factor;
+        let displays = state.displays.clone();
         let appearance = state.common.appearance;
         let compositor_gpu = state.compositor_gpu.take();
         let supports_xinput_gestures = state.supports_xinput_gestures;
-        let is_bgr = state
-            .resource_database
-            .get_string("Xft.rgba", "Xft.Rgba")
-            .is_some_and(|v| v.eq_ignore_ascii_case("bgr"));
+        let is_bgr = subpixel_order_is_bgr(&state.resource_database, &state.xsettings);
         let window = X11Window::new(
             handle,
             X11ClientStatePtr(Rc::downgrade(&self.0)),
@@ -1651,6 +2094,7 @@ impl LinuxClient for X11Client {
             x_window,
             &atoms,
             scale_factor,
+            displays,
             appearance,
             parent_window,
             supports_xinput_gestures,
@@ -1702,14 +2146,14 @@ impl LinuxClient for X11Client {
             return;
         }
 
-        state.cursor_styles.insert(focused_window, style);
+        state.cursor_styles.insert(focused_window, style.clone());
 
         // Don't clobber the invisible cursor; restore reads back from `cursor_styles`.
         if state.cursor_hidden_window == Some(focused_window) {
             return;
         }
 
-        let Some(cursor) = state.get_cursor_icon(style) else {
+        let Some(cursor) = state.get_cursor_icon(&style) else {
             return;
         };
 
@@ -1885,6 +2329,28 @@ impl LinuxClient for X11Client {
         Some(handles)
     }
 
+    fn set_global_shortcuts(&self, shortcuts: Vec<GlobalShortcut>) {
+        let (global_shortcuts, executor) = self.with_common(|common| {
+            (
+                common.global_shortcuts.clone(),
+                common.foreground_executor.clone(),
+            )
+        });
+        let client = X11ClientStatePtr(Rc::downgrade(&self.0));
+        executor
+            .spawn(async move {
+                // Keys grabbed before are released once the portal has taken over.
+                let grabbed = match global_shortcuts.bind(shortcuts.clone()).await {
+                    Ok(()) => Vec::new(),
+                    Err(_) => shortcuts,
+                };
+                if let Some(client) = client.get_client() {
+                    client.grab_global_shortcuts(&grabbed);
+                }
+            })
+            .detach();
+    }
+
     fn window_identifier(&self) -> impl Future<Output = Option<WindowIdentifier>> + Send + 'static {
         let state = self.0.borrow();
         state
@@ -1905,6 +2371,19 @@ impl LinuxClient for X11Client {
 }
 
 impl X11ClientState {
+    fn double_click_interval(&self) -> Duration {
+        self.xsettings
+            .double_click_time
+            .unwrap_or(DOUBLE_CLICK_INTERVAL)
+    }
+
+    fn double_click_distance(&self) -> Pixels {
+        self.xsettings
+            .double_click_distance
+            .map(|distance| px(distance as f32))
+            .unwrap_or(DOUBLE_CLICK_DISTANCE)
+    }
+
     fn has_xim(&self) -> bool {
         self.ximc.is_some() && self.xim_handler.is_some()
     }
@@ -2050,8 +2529,11 @@ impl X11ClientState {
             .expect("Failed to initialize window refresh timer")
     }
 
-    fn get_cursor_icon(&mut self, style: CursorStyle) -> Option<xproto::Cursor> {
-        if let Some(cursor) = self.cursor_cache.get(&style) {
+    fn get_cursor_icon(&mut self, style: &CursorStyle) -> Option<xproto::Cursor> {
+        if style.is_image() {
+            return self.get_image_cursor(style);
+        }
+        if let Some(cursor) = self.cursor_cache.get(style) {
             return *cursor;
         }
 
@@ -2110,10 +2592,85 @@ impl X11ClientState {
             }
         };
 
-        self.cursor_cache.insert(style, cursor);
+        self.cursor_cache.insert(style.clone(), cursor);
         cursor
     }
 
+    fn reload_cursor_theme(&mut self) {
+        let Some(cursor_handle) = cursor::Handle::new(
+            self.xcb_connection.as_ref(),
+            self.x_root_index,
+            &cursor_resource_database(&self.resource_database, &self.xsettings),
+        )
+        .context("X11: Failed to reload cursor theme")
+        .log_err()
+        .and_then(|cookie| {
+            cookie
+                .reply()
+                .context("X11: Failed to reload cursor theme")
+                .log_err()
+        }) else {
+            return;
+        };
+        self.cursor_handle = cursor_handle;
+        for (_, cursor) in self.cursor_cache.drain() {
+            if let Some(cursor) = cursor {
+                self.xcb_connection.free_cursor(cursor).log_err();
+            }
+        }
+    }
+
+    /// Sets each window's cursor again, after the theme or scale it was created for changed.
+    fn reapply_cursor_styles(&mut self) {
+        let cursor_styles = self
+            .cursor_styles
+            .iter()
+            .filter(|(x_window, _)| self.cursor_hidden_window != Some(**x_window))
+            .map(|(x_window, style)| (*x_window, style.clone()))
+            .collect::<Vec<_>>();
+        for (x_window, style) in cursor_styles {
+            let Some(cursor) = self.get_cursor_icon(&style) else {
+                continue;
+            };
+            check_reply(
+                || "Failed to set cursor style",
+                self.xcb_connection.change_window_attributes(
+                    x_window,
+                    &ChangeWindowAttributesAux {
+                        cursor: Some(cursor),
+                        ..Default::default()
+                    },
+                ),
+            )
+            .log_err();
+        }
+        self.xcb_connection.flush().log_err();
+    }
+
+    fn get_image_cursor(&mut self, style: &CursorStyle) -> Option<xproto::Cursor> {
+        let scale_factor = self.scale_factor;
+        let cached_ix = self
+            .image_cursor_cache
+            .iter()
+            .position(|(cached, cached_scale, _)| *cached_scale == scale_factor && cached == style);
+        let cursor = match cached_ix {
+            Some(ix) => self.image_cursor_cache.remove(ix).2,
+            None => create_image_cursor(&self.xcb_connection, style, scale_factor)
+                .context("X11: error while creating image cursor")
+                .log_err()?,
+        };
+        self.image_cursor_cache
+            .insert(0, (style.clone(), scale_factor, cursor));
+
+        // Windows still showing an evicted cursor keep it until they switch away from it.
+        if self.image_cursor_cache.len() > MAX_CACHED_IMAGE_CURSORS {
+            for (_, _, evicted) in self.image_cursor_cache.drain(MAX_CACHED_IMAGE_CURSORS..) {
+                self.xcb_connection.free_cursor(evicted).log_err();
+            }
+        }
+        Some(cursor)
+    }
+
     fn get_or_create_invisible_cursor(&mut self) -> Option<xproto::Cursor> {
         if let Some(cursor) = self.invisible_cursor_cache {
             return Some(cursor);
@@ -2158,9 +2715,9 @@ impl X11ClientState {
         let style = self
             .cursor_styles
             .get(&hidden_window)
-            .copied()
+            .cloned()
             .unwrap_or(CursorStyle::Arrow);
-        let Some(cursor) = self.get_cursor_icon(style) else {
+        let Some(cursor) = self.get_cursor_icon(&style) else {
             log::warn!(
                 "X11: no cursor icon available to restore {:?} after hide; cursor may stay invisible",
                 style
@@ -2185,16 +2742,22 @@ impl X11ClientState {
 // Adapted from:
 // https://docs.rs/winit/0.29.11/src/winit/platform_impl/linux/x11/monitor.rs.html#103-111
 pub fn mode_refresh_rate(mode: &randr::ModeInfo) -> Duration {
-    if mode.dot_clock == 0 || mode.htotal == 0 || mode.vtotal == 0 {
+    let Some(millihertz) = mode_millihertz(mode) else {
         return Duration::from_millis(16);
-    }
+    };
 
-    let millihertz = mode.dot_clock as u64 * 1_000 / (mode.htotal as u64 * mode.vtotal as u64);
     let micros = 1_000_000_000 / millihertz;
     log::info!("Refreshing every {}ms", micros / 1_000);
     Duration::from_micros(micros)
 }
 
+pub(crate) fn mode_millihertz(mode: &randr::ModeInfo) -> Option<u64> {
+    if mode.dot_clock == 0 || mode.htotal == 0 || mode.vtotal == 0 {
+        return None;
+    }
+    Some(mode.dot_clock as u64 * 1_000 / (mode.htotal as u64 * mode.vtotal as u64))
+}
+
 fn fp3232_to_f32(value: xinput::Fp3232) -> f32 {
     value.integral as f32 + value.frac as f32 / u32::MAX as f32
 }
@@ -2557,6 +3120,168 @@ fn create_invisible_cursor(
     Ok(cursor)
 }
 
+fn create_image_cursor(
+    connection: &XCBConnection,
+    style: &CursorStyle,
+    scale_factor: f32,
+) -> anyhow::Result<xproto::Cursor> {
+    let formats = connection.render_query_pict_formats()?.reply()?;
+    let argb32_format = formats
+        .formats
+        .iter()
+        .find(|format| {
+            format.type_ == render::PictType::DIRECT
+                && format.depth == 32
+                && format.direct.red_shift == 16
+                && format.direct.red_mask == 0xff
+                && format.direct.green_shift == 8
+                && format.direct.green_mask == 0xff
+                && format.direct.blue_shift == 0
+                && format.direct.blue_mask == 0xff
+                && format.direct.alpha_shift == 24
+                && format.direct.alpha_mask == 0xff
+        })
+        .context("server has no ARGB32 picture format")?
+        .id;
+    let root = connection.setup().roots[0].root;
+
+    let mut frames = Vec::new();
+    for frame in scaled_cursor_frames(style, scale_factor) {
+        let (Ok(width), Ok(height)) = (u16::try_from(frame.width), u16::try_from(frame.height))
+        else {
+            anyhow::bail!(
+                "cursor image of {}x{} pixels is too large",
+                frame.width,
+                frame.height
+            );
+        };
+
+        let pixmap = connection.generate_id()?;
+        connection.create_pixmap(32, pixmap, root, width, height)?;
+        let gc = connection.generate_id()?;
+        connection.create_gc(gc, pixmap, &Default::default())?;
+        connection.put_image(
+            xproto::ImageFormat::Z_PIXMAP,
+            pixmap,
+            gc,
+            width,
+            height,
+            0,
+            0,
+            0,
+            32,
+            &frame.data,
+        )?;
+        connection.free_gc(gc)?;
+
+        let picture = connection.generate_id()?;
+        connection.render_create_picture(picture, pixmap, argb32_format, &Default::default())?;
+        let cursor = connection.generate_id()?;
+        connection.render_create_cursor(
+            cursor,
+            picture,
+            frame.hotspot.0 as u16,
+            frame.hotspot.1 as u16,
+        )?;
+        connection.render_free_picture(picture)?;
+        connection.free_pixmap(pixmap)?;
+
+        frames.push(render::Animcursorelt {
+            cursor,
+            delay: frame.duration.as_millis().min(u32::MAX as u128) as u32,
+        });
+    }
+
+    let cursor = match frames.as_slice() {
+        [] => anyhow::bail!("cursor style has no images"),
+        [frame] => frame.cursor,
+        frames => {
+            let cursor = connection.generate_id()?;
+            connection.render_create_anim_cursor(cursor, frames)?;
+            for frame in frames {
+                connection.free_cursor(frame.cursor)?;
+            }
+            cursor
+        }
+    };
+
+    xcb_flush(connection);
+    Ok(cursor)
+}
+
+/// `Xft/RGBA` from XSETTINGS, falling back to the `Xft.rgba` resource.
+fn subpixel_order<'a>(
+    resource_database: &'a Database,
+    xsettings: &'a XSettings,
+) -> Option<&'a str> {
+    xsettings
+        .rgba
+        .as_deref()
+        .or_else(|| resource_database.get_string("Xft.rgba", "Xft.Rgba"))
+}
+
+fn subpixel_order_is_bgr(resource_database: &Database, xsettings: &XSettings) -> bool {
+    subpixel_order(resource_database, xsettings).is_some_and(|v| v.eq_ignore_ascii_case("bgr"))
+}
+
+fn font_rendering_options(
+    resource_database: &Database,
+    xsettings: &XSettings,
+) -> FontRenderingOptions {
+    let defaults = FontRenderingOptions::default();
+    let hinting = xsettings.hinting.or_else(|| {
+        match (
+            resource_database.get_bool("Xft.hinting", "Xft.Hinting"),
+            resource_database.get_string("Xft.hintstyle", "Xft.Hintstyle"),
+        ) {
+            (_, Some("hintnone")) => Some(false),
+            (hinting, _) => hinting,
+        }
+    });
+    FontRenderingOptions {
+        antialias: xsettings
+            .antialias
+            .or_else(|| resource_database.get_bool("Xft.antialias", "Xft.Antialias"))
+            .unwrap_or(defaults.antialias),
+        hinting: hinting.unwrap_or(defaults.hinting),
+        subpixel: subpixel_order(resource_database, xsettings).is_none_or(|order| {
+            order.eq_ignore_ascii_case("rgb") || order.eq_ignore_ascii_case("bgr")
+        }),
+    }
+}
+
+/// `cursor::Handle` reads the cursor theme, size and DPI from a resource database, so the
+/// XSETTINGS values are layered over the X resources in a database of their own.
+fn cursor_resource_database(resource_database: &Database, xsettings: &XSettings) -> Database {
+    let mut data = String::new();
+    let theme = xsettings
+        .cursor_theme
+        .as_deref()
+        .or_else(|| resource_database.get_string("Xcursor.theme", ""));
+    if let Some(theme) = theme {
+        data.push_str(&format!("Xcursor.theme: {theme}\n"));
+    }
+    let size = xsettings.cursor_size.or_else(|| {
+        resource_database
+            .get_value::<u32>("Xcursor.size", "")
+            .ok()
+            .flatten()
+    });
+    if let Some(size) = size {
+        data.push_str(&format!("Xcursor.size: {size}\n"));
+    }
+    let dpi = xsettings.dpi.map(|dpi| dpi.round() as u32).or_else(|| {
+        resource_database
+            .get_value::<u32>("Xft.dpi", "")
+            .ok()
+            .flatten()
+    });
+    if let Some(dpi) = dpi {
+        data.push_str(&format!("Xft.dpi: {dpi}\n"));
+    }
+    Database::new_from_data(data.as_bytes())
+}
+
 enum DpiMode {
     Randr,
     Scale(f32),
@@ -2567,6 +3292,7 @@ fn get_scale_factor(
     connection: &XCBConnection,
     resource_database: &Database,
     screen_index: usize,
+    xsettings_dpi: Option<f32>,
 ) -> f32 {
     let env_dpi = std::env::var(GPUI_X11_SCALE_FACTOR_ENV)
         .ok()
@@ -2617,7 +3343,11 @@ fn get_scale_factor(
         DpiMode::NotSet => {}
     }
 
-    // TODO: Use scale factor from XSettings here
+    if let Some(dpi) = xsettings_dpi {
+        let scale = dpi / 96.0; // base dpi
+        log::info!("Using scale factor from XSETTINGS Xft/DPI: {}", scale);
+        return scale;
+    }
 
     if let Some(dpi) = resource_database
         .get_value::<f32>("Xft.dpi", "Xft.dpi")
@@ -1,17 +1,38 @@
+use std::{cell::RefCell, rc::Rc};
+
 use anyhow::Context as _;
 use uuid::Uuid;
-use x11rb::{connection::Connection as _, xcb_ffi::XCBConnection};
+use x11rb::{
+    connection::Connection as _,
+    protocol::{
+        randr::{self, ConnectionExt as _},
+        xproto::ConnectionExt as _,
+    },
+    xcb_ffi::XCBConnection,
+};
+
+use gpui::{Bounds, DisplayId, Pixels, PlatformDisplay, SharedString, Size, point, px, size};
 
-use gpui::{Bounds, DisplayId, Pixels, PlatformDisplay, Size, px};
+use super::mode_millihertz;
+
+/// The connected displays, shared between the client and its windows. The client re-queries them
+/// when RandR reports a change, e.g. a monitor being connected.
+pub(crate) type X11Displays = Rc<RefCell<Vec<Rc<X11Display>>>>;
 
 #[derive(Debug)]
 pub(crate) struct X11Display {
+    id: DisplayId,
     x_screen_index: usize,
+    name: Option<SharedString>,
     bounds: Bounds<Pixels>,
+    scale_factor: f32,
+    refresh_rate: Option<f32>,
+    primary: bool,
     uuid: Uuid,
 }
 
 impl X11Display {
+    /// A display covering a whole X screen, for servers without RandR monitors.
     pub(crate) fn new(
         xcb: &XCBConnection,
         scale_factor: f32,
@@ -23,7 +44,9 @@ impl X11Display {
             .get(x_screen_index)
             .with_context(|| format!("No screen found with index {x_screen_index}"))?;
         Ok(Self {
+            id: display_id(x_screen_index, 0),
             x_screen_index,
+            name: None,
             bounds: Bounds {
                 origin: Default::default(),
                 size: Size {
@@ -31,14 +54,171 @@ impl X11Display {
                     height: px(screen.height_in_pixels as f32 / scale_factor),
                 },
             },
+            scale_factor,
+            refresh_rate: None,
+            primary: true,
             uuid: Uuid::from_bytes([0; 16]),
         })
     }
+
+    /// Queries the monitors of all X screens.
+    pub(crate) fn query_all(xcb: &XCBConnection, scale_factor: f32) -> Vec<Rc<Self>> {
+        (0..xcb.setup().roots.len())
+            .flat_map(|x_screen_index| {
+                query_monitors(xcb, scale_factor, x_screen_index).unwrap_or_else(|| {
+                    X11Display::new(xcb, scale_factor, x_screen_index)
+                        .into_iter()
+                        .collect()
+                })
+            })
+            .map(Rc::new)
+            .collect()
+    }
+
+    /// Returns the primary monitor of the given X screen, or its first one.
+    pub(crate) fn primary(displays: &[Rc<Self>], x_screen_index: usize) -> Option<Rc<Self>> {
+        let mut displays = displays
+            .iter()
+            .filter(|display| display.x_screen_index == x_screen_index);
+        displays
+            .clone()
+            .find(|display| display.primary)
+            .or_else(|| displays.next())
+            .cloned()
+    }
+
+    /// Returns the monitor showing most of the given bounds on the given X screen, if any.
+    pub(crate) fn containing(
+        displays: &[Rc<Self>],
+        x_screen_index: usize,
+        bounds: Bounds<Pixels>,
+    ) -> Option<Rc<Self>> {
+        displays
+            .iter()
+            .filter(|display| display.x_screen_index == x_screen_index)
+            .map(|display| {
+                let overlap = display.bounds.intersect(&bounds).size;
+                (
+                    display,
+                    f32::from(overlap.width) * f32::from(overlap.height),
+                )
+            })
+            .filter(|(_, overlap)| *overlap > 0.)
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(display, _)| display.clone())
+    }
+
+    /// Returns the X screen the display with the given ID is on.
+    pub(crate) fn x_screen_index(id: DisplayId) -> usize {
+        (u64::from(id) >> 32) as usize
+    }
+}
+
+/// Asks RandR to report changes to the monitor configuration of the given X screen.
+pub(crate) fn watch_for_display_changes(
+    xcb: &XCBConnection,
+    x_screen_index: usize,
+) -> anyhow::Result<()> {
+    let root = xcb
+        .setup()
+        .roots
+        .get(x_screen_index)
+        .with_context(|| format!("No screen found with index {x_screen_index}"))?
+        .root;
+    xcb.randr_select_input(
+        root,
+        randr::NotifyMask::SCREEN_CHANGE
+            | randr::NotifyMask::CRTC_CHANGE
+            | randr::NotifyMask::OUTPUT_CHANGE,
+    )?
+    .check()?;
+    Ok(())
+}
+
+/// Display IDs combine the X screen with the name of the RandR monitor, which stays the same
+/// while the monitor is connected.
+fn display_id(x_screen_index: usize, monitor_name: u32) -> DisplayId {
+    DisplayId::new(((x_screen_index as u64) << 32) | monitor_name as u64)
+}
+
+fn query_monitors(
+    xcb: &XCBConnection,
+    scale_factor: f32,
+    x_screen_index: usize,
+) -> Option<Vec<X11Display>> {
+    let root = xcb.setup().roots.get(x_screen_index)?.root;
+    // Monitors were added in RandR 1.5.
+    let version = xcb.randr_query_version(1, 5).ok()?.reply().ok()?;
+    if (version.major_version, version.minor_version) < (1, 5) {
+        return None;
+    }
+    let monitors = xcb.randr_get_monitors(root, true).ok()?.reply().ok()?;
+    if monitors.monitors.is_empty() {
+        return None;
+    }
+    let resources = xcb
+        .randr_get_screen_resources_current(root)
+        .ok()?
+        .reply()
+        .ok()?;
+
+    let displays = monitors
+        .monitors
+        .iter()
+        .map(|monitor| {
+            let name = xcb
+                .get_atom_name(monitor.name)
+                .ok()
+                .and_then(|cookie| cookie.reply().ok())
+                .map(|reply| SharedString::from(String::from_utf8_lossy(&reply.name).into_owned()));
+            X11Display {
+                id: display_id(x_screen_index, monitor.name),
+                x_screen_index,
+                uuid: name.as_ref().map_or(Uuid::from_bytes([0; 16]), |name| {
+                    Uuid::new_v5(&Uuid::NAMESPACE_DNS, name.as_bytes())
+                }),
+                name,
+                bounds: Bounds::new(
+                    point(
+                        px(monitor.x as f32 / scale_factor),
+                        px(monitor.y as f32 / scale_factor),
+                    ),
+                    size(
+                        px(monitor.width as f32 / scale_factor),
+                        px(monitor.height as f32 / scale_factor),
+                    ),
+                ),
+                scale_factor,
+                refresh_rate: monitor_refresh_rate(xcb, &resources, monitor),
+                primary: monitor.primary,
+            }
+        })
+        .collect();
+    Some(displays)
+}
+
+fn monitor_refresh_rate(
+    xcb: &XCBConnection,
+    resources: &randr::GetScreenResourcesCurrentReply,
+    monitor: &randr::MonitorInfo,
+) -> Option<f32> {
+    let output = xcb
+        .randr_get_output_info(*monitor.outputs.first()?, resources.config_timestamp)
+        .ok()?
+        .reply()
+        .ok()?;
+    let crtc = xcb
+        .randr_get_crtc_info(output.crtc, resources.config_timestamp)
+        .ok()?
+        .reply()
+        .ok()?;
+    let mode = resources.modes.iter().find(|mode| mode.id == crtc.mode)?;
+    mode_millihertz(mode).map(|millihertz| millihertz as f32 / 1000.0)
 }
 
 impl PlatformDisplay for X11Display {
     fn id(&self) -> DisplayId {
-        DisplayId::new(self.x_screen_index as u64)
+        self.id
     }
 
     fn uuid(&self) -> anyhow::Result<Uuid> {
@@ -48,4 +228,16 @@ impl PlatformDisplay for X11Display {
     fn bounds(&self) -> Bounds<Pixels> {
         self.bounds
     }
+
+    fn name(&self) -> Option<SharedString> {
+        self.name.clone()
+    }
+
+    fn scale_factor(&self) -> f32 {
+        self.scale_factor
+    }
+
+    fn refresh_rate(&self) -> Option<f32> {
+        self.refresh_rate
+    }
 }
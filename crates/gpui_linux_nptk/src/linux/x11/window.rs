@@ -1,13 +1,14 @@
 use anyhow::{Context as _, anyhow};
 use x11rb::connection::RequestConnection;
 
-use crate::linux::X11ClientStatePtr;
+use crate::linux::{X11ClientStatePtr, instant_from_clock};
 use gpui::{
-    AnyWindowHandle, Bounds, Decorations, DevicePixels, ForegroundExecutor, GpuSpecs, Modifiers,
-    Pixels, PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow,
-    Point, PromptButton, PromptLevel, RequestFrameOptions, ResizeEdge, ScaledPixels, Scene, Size,
-    Tiling, WindowAppearance, WindowBackgroundAppearance, WindowBounds, WindowControlArea,
-    WindowDecorations, WindowKind, WindowParams, px,
+    AnyWindowHandle, Bounds, Decorations, DevicePixels, DisplayId, ForegroundExecutor, GpuSpecs,
+    Modifiers, Pixels, PlatformAtlas, PlatformDisplay, PlatformInput, PlatformInputHandler,
+    PlatformWindow, Point, PointerConstraint, PromptButton, PromptLevel, RawMouseMotionEvent,
+    RequestFrameOptions, ResizeEdge, ScaledPixels, Scene, Size, Tiling, WindowAppearance,
+    WindowBackgroundAppearance, WindowBounds, WindowControlArea, WindowDecorations, WindowKind,
+    WindowParams, point, popup::PopupOptions, px,
 };
 use gpui_wgpu::{CompositorGpuHint, WgpuRenderer, WgpuSurfaceConfig};
 
@@ -20,6 +21,8 @@ use x11rb::{
     errors::ConnectionError,
     properties::WmSizeHints,
     protocol::{
+        present::{self, ConnectionExt as _},
+        screensaver::ConnectionExt as _,
         sync,
         xinput::{self, ConnectionExt as _},
         xproto::{self, ClientMessageEvent, ConnectionExt, TranslateCoordinatesReply},
@@ -29,10 +32,11 @@ use x11rb::{
 };
 
 use std::{
-    cell::RefCell, ffi::c_void, fmt::Display, num::NonZeroU32, ptr::NonNull, rc::Rc, sync::Arc,
+    cell::RefCell, collections::VecDeque, ffi::c_void, fmt::Display, mem, num::NonZeroU32,
+    ptr::NonNull, rc::Rc, sync::Arc, time::Duration,
 };
 
-use super::{X11Display, XINPUT_ALL_DEVICE_GROUPS, XINPUT_ALL_DEVICES};
+use super::{X11Display, X11Displays, XINPUT_ALL_DEVICE_GROUPS, XINPUT_ALL_DEVICES};
 
 x11rb::atom_manager! {
     pub XcbAtoms: AtomsCookie {
@@ -75,6 +79,7 @@ x11rb::atom_manager! {
         _NET_WM_WINDOW_TYPE,
         _NET_WM_WINDOW_TYPE_NOTIFICATION,
         _NET_WM_WINDOW_TYPE_DIALOG,
+        _NET_WM_WINDOW_TYPE_POPUP_MENU,
         _NET_WM_STATE_MODAL,
         _NET_WM_SYNC,
         _NET_SUPPORTED,
@@ -83,6 +88,8 @@ x11rb::atom_manager! {
         _GTK_FRAME_EXTENTS,
         _GTK_EDGE_CONSTRAINTS,
         _NET_CLIENT_LIST_STACKING,
+        _XSETTINGS_SETTINGS,
+        MANAGER,
     }
 }
 
@@ -252,12 +259,16 @@ pub struct Callbacks {
     close: Option<Box<dyn FnOnce()>>,
     appearance_changed: Option<Box<dyn FnMut()>>,
     button_layout_changed: Option<Box<dyn FnMut()>>,
+    presentation_feedback: Option<Box<dyn FnMut(gpui::PresentationFeedback)>>,
 }
 
 pub struct X11WindowState {
     pub destroyed: bool,
     parent: Option<X11WindowStatePtr>,
     children: FxHashSet<xproto::Window>,
+    /// Anchored popups opened from this window. Override-redirect windows never take the focus,
+    /// so they're closed when this window loses it instead.
+    popups: FxHashSet<xproto::Window>,
     client: X11ClientStatePtr,
     executor: ForegroundExecutor,
     atoms: XcbAtoms,
@@ -269,7 +280,10 @@ pub struct X11WindowState {
     bounds: Bounds<Pixels>,
     scale_factor: f32,
     renderer: WgpuRenderer,
-    display: Rc<dyn PlatformDisplay>,
+    displays: X11Displays,
+    /// The monitor showing most of the window. Updated when the window moves or the monitors
+    /// change, as finding it takes a round trip to the X server.
+    display: Option<Rc<X11Display>>,
     input_handler: Option<PlatformInputHandler>,
     appearance: WindowAppearance,
     background_appearance: WindowBackgroundAppearance,
@@ -279,12 +293,20 @@ pub struct X11WindowState {
     active: bool,
     hovered: bool,
     pub(crate) force_render_after_recovery: bool,
+    /// Whether the Present extension reports frames completing on this window.
+    presentation_feedback_supported: bool,
+    drawn_frames: u64,
+    /// Frames handed to the X server that haven't completed yet, oldest first.
+    pending_presentations: VecDeque<u64>,
     fullscreen: bool,
     client_side_decorations_supported: bool,
     decorations: WindowDecorations,
     edge_constraints: Option<EdgeConstraints>,
     pub handle: AnyWindowHandle,
     last_insets: [u32; 4],
+    idle_inhibited: bool,
+    pointer_constraint: Option<PointerConstraint>,
+    last_pointer_position: Option<Point<Pixels>>,
 }
 
 impl X11WindowState {
@@ -341,10 +363,7 @@ impl rwh::HasDisplayHandle for X11Window {
         let Some(non_zero) = NonNull::new(connection) else {
             return Err(rwh::HandleError::Unavailable);
         };
-        let screen_id = {
-            let state = self.0.state.borrow();
-            u64::from(state.display.id()) as i32
-        };
+        let screen_id = self.0.state.borrow().x_screen_index as i32;
         let handle = rwh::XcbDisplayHandle::new(Some(non_zero), screen_id);
         Ok(unsafe { rwh::DisplayHandle::borrow_raw(handle.into()) })
     }
@@ -422,6 +441,7 @@ impl X11WindowState {
         x_window: xproto::Window,
         atoms: &XcbAtoms,
         scale_factor: f32,
+        displays: X11Displays,
         appearance: WindowAppearance,
         parent_window: Option<X11WindowStatePtr>,
         supports_xinput_gestures: bool,
@@ -429,7 +449,7 @@ impl X11WindowState {
     ) -> anyhow::Result<Self> {
         let x_screen_index = params
             .display_id
-            .map_or(x_main_screen_index, |did| u64::from(did) as usize);
+            .map_or(x_main_screen_index, X11Display::x_screen_index);
 
         let visual_set = find_visuals(xcb, x_screen_index);
 
@@ -454,11 +474,12 @@ impl X11WindowState {
             id
         };
 
+        let is_anchored_popup = matches!(params.kind, WindowKind::AnchoredPopUp(_));
         let win_aux = xproto::CreateWindowAux::new()
             // https://stackoverflow.com/questions/43218127/x11-xlib-xcb-creating-a-window-requires-border-pixel-if-specifying-colormap-wh
             .border_pixel(visual_set.black_pixel)
             .colormap(colormap)
-            .override_redirect((params.kind == WindowKind::PopUp) as u32)
+            .override_redirect((params.kind == WindowKind::PopUp || is_anchored_popup) as u32)
             .event_mask(
                 xproto::EventMask::EXPOSURE
                     | xproto::EventMask::STRUCTURE_NOTIFY
@@ -480,6 +501,9 @@ impl X11WindowState {
             bounds.size.height = 600.into();
         }
 
+        // Override-redirect popups are placed exactly where they're requested, so they skip the
+        // offset applied to managed windows.
+        let x_offset = if is_anchored_popup { 0 } else { 2 };
         check_reply(
             || {
                 format!(
@@ -487,7 +511,7 @@ impl X11WindowState {
                     visual.depth,
                     x_window,
                     visual_set.root,
-                    bounds.origin.x.0 + 2,
+                    bounds.origin.x.0 + x_offset,
                     bounds.origin.y.0,
                     bounds.size.width.0,
                     bounds.size.height.0
@@ -497,7 +521,7 @@ impl X11WindowState {
                 visual.depth,
                 x_window,
                 visual_set.root,
-                (bounds.origin.x.0 + 2) as i16,
+                (bounds.origin.x.0 + x_offset) as i16,
                 bounds.origin.y.0 as i16,
                 bounds.size.width.0 as u16,
                 bounds.size.height.0 as u16,
@@ -523,7 +547,7 @@ impl X11WindowState {
             )?;
 
             let reply = get_reply(|| "X11 GetGeometry failed.", xcb.get_geometry(x_window))?;
-            if reply.x == 0 && reply.y == 0 {
+            if reply.x == 0 && reply.y == 0 && !is_anchored_popup {
                 bounds.origin.x.0 += 2;
                 // Work around a bug where our rendered content appears
                 // outside the window bounds when opened at the default position
@@ -573,7 +597,23 @@ impl X11WindowState {
                 )?;
             }
 
-            if params.kind == WindowKind::Floating || params.kind == WindowKind::Dialog {
+            if is_anchored_popup {
+                check_reply(
+                    || "X11 ChangeProperty32 setting window type for popup menu failed.",
+                    xcb.change_property32(
+                        xproto::PropMode::REPLACE,
+                        x_window,
+                        atoms._NET_WM_WINDOW_TYPE,
+                        xproto::AtomEnum::ATOM,
+                        &[atoms._NET_WM_WINDOW_TYPE_POPUP_MENU],
+                    ),
+                )?;
+            }
+
+            if params.kind == WindowKind::Floating
+                || params.kind == WindowKind::Dialog
+                || is_anchored_popup
+            {
                 if let Some(parent_window) = parent_window.as_ref().map(|w| w.x_window) {
                     // WM_TRANSIENT_FOR hint indicating the main application window. For floating windows, we set
                     // a parent window (WM_TRANSIENT_FOR) such that the window manager knows where to
@@ -592,6 +632,10 @@ impl X11WindowState {
                 }
             }
 
+            if is_anchored_popup && let Some(parent_window) = parent_window.as_ref() {
+                parent_window.state.borrow_mut().popups.insert(x_window);
+            }
+
             let parent = if params.kind == WindowKind::Dialog
                 && let Some(parent) = parent_window
             {
@@ -701,6 +745,26 @@ impl X11WindowState {
                 ),
             )?;
 
+            // The GPU driver presents through the Present extension on its own connection, but
+            // completion events are delivered to every client that selected them on the window.
+            let presentation_feedback_supported = xcb
+                .extension_information(present::X11_EXTENSION_NAME)
+                .ok()
+                .flatten()
+                .is_some()
+                && xcb.generate_id().is_ok_and(|event_id| {
+                    check_reply(
+                        || "X11 PresentSelectInput failed.",
+                        xcb.present_select_input(
+                            event_id,
+                            x_window,
+                            present::EventMask::COMPLETE_NOTIFY,
+                        ),
+                    )
+                    .log_err()
+                    .is_some()
+                });
+
             xcb_flush(xcb);
 
             let mut renderer = {
@@ -770,13 +834,18 @@ impl X11WindowState {
                 )?;
             }
 
-            let display = Rc::new(X11Display::new(xcb, scale_factor, x_screen_index)?);
-
+            let display = X11Display::containing(
+                &displays.borrow(),
+                x_screen_index,
+                bounds.to_pixels(scale_factor),
+            );
             Ok(Self {
                 parent,
                 children: FxHashSet::default(),
+                popups: FxHashSet::default(),
                 client,
                 executor,
+                displays,
                 display,
                 x_root_window: visual_set.root,
                 x_screen_index,
@@ -789,6 +858,9 @@ impl X11WindowState {
                 active: false,
                 hovered: false,
                 force_render_after_recovery: false,
+                presentation_feedback_supported,
+                drawn_frames: 0,
+                pending_presentations: VecDeque::new(),
                 fullscreen: false,
                 maximized_vertical: false,
                 maximized_horizontal: false,
@@ -800,6 +872,9 @@ impl X11WindowState {
                 client_side_decorations_supported,
                 decorations: WindowDecorations::Server,
                 last_insets: [0, 0, 0, 0],
+                idle_inhibited: false,
+                pointer_constraint: None,
+                last_pointer_position: None,
                 edge_constraints: None,
                 counter_id: sync_request_counter,
                 last_sync_counter: None,
@@ -832,6 +907,14 @@ impl Drop for X11Window {
             parent.state.borrow_mut().children.remove(&self.0.x_window);
         }
 
+        if state.idle_inhibited {
+            check_reply(
+                || "X11 ScreenSaverSuspend failed.",
+                self.0.xcb.screensaver_suspend(0),
+            )
+            .log_err();
+        }
+
         state.renderer.destroy();
 
         let destroy_x_window = maybe!({
@@ -883,6 +966,7 @@ impl X11Window {
         x_window: xproto::Window,
         atoms: &XcbAtoms,
         scale_factor: f32,
+        displays: X11Displays,
         appearance: WindowAppearance,
         parent_window: Option<X11WindowStatePtr>,
         supports_xinput_gestures: bool,
@@ -902,6 +986,7 @@ impl X11Window {
                 x_window,
                 atoms,
                 scale_factor,
+                displays,
                 appearance,
                 parent_window,
                 supports_xinput_gestures,
@@ -1001,6 +1086,77 @@ impl X11Window {
 }
 
 impl X11WindowStatePtr {
+    /// Override-redirect windows are not placed by the window manager, so popups are positioned
+    /// relative to the parent's location on the root window.
+    pub fn popup_bounds(
+        &self,
+        options: &PopupOptions,
+        size: Size<Pixels>,
+    ) -> anyhow::Result<Bounds<Pixels>> {
+        let state = self.state.borrow();
+        let parent_origin = get_reply(
+            || "X11 TranslateCoordinates for popup parent failed.",
+            self.xcb
+                .translate_coordinates(self.x_window, state.x_root_window, 0, 0),
+        )?;
+        let parent_origin = point(
+            px(parent_origin.dst_x as f32 / state.scale_factor),
+            px(parent_origin.dst_y as f32 / state.scale_factor),
+        );
+        let parent_bounds = Bounds::new(parent_origin, state.bounds.size);
+        let displays = state.displays.borrow();
+        let display = X11Display::containing(&displays, state.x_screen_index, parent_bounds)
+            .or_else(|| X11Display::primary(&displays, state.x_screen_index))
+            .context("No display to place the popup on")?;
+        Ok(options.resolve_bounds(parent_origin, size, display.bounds()))
+    }
+
+    /// The bounds of the window on the root window. Unlike the origin reported by configure
+    /// events, this doesn't depend on how the window manager reparented the window.
+    fn root_bounds(&self) -> anyhow::Result<Bounds<Pixels>> {
+        let state = self.state.borrow();
+        let origin = get_reply(
+            || "X11 TranslateCoordinates failed.",
+            self.xcb
+                .translate_coordinates(self.x_window, state.x_root_window, 0, 0),
+        )?;
+        Ok(Bounds::new(
+            point(
+                px(origin.dst_x as f32 / state.scale_factor),
+                px(origin.dst_y as f32 / state.scale_factor),
+            ),
+            state.bounds.size,
+        ))
+    }
+
+    /// Looks up the monitor showing most of the window again, after it moved or the monitors
+    /// changed.
+    pub fn update_display(&self) {
+        let Some(bounds) = self.root_bounds().log_err() else {
+            return;
+        };
+        let mut state = self.state.borrow_mut();
+        let display =
+            X11Display::containing(&state.displays.borrow(), state.x_screen_index, bounds);
+        state.display = display;
+    }
+
+    /// Moves the window to the center of the given display bounds.
+    fn center_on(&self, display_bounds: Bounds<Pixels>) -> anyhow::Result<()> {
+        let state = self.state.borrow();
+        let size = state.bounds.size.min(&display_bounds.size);
+        let center = display_bounds.center();
+        let x = (f32::from(center.x - size.width / 2.) * state.scale_factor) as i32;
+        let y = (f32::from(center.y - size.height / 2.) * state.scale_factor) as i32;
+        check_reply(
+            || format!("X11 ConfigureWindow failed. x: {}, y: {}", x, y),
+            self.xcb
+                .configure_window(self.x_window, &xproto::ConfigureWindowAux::new().x(x).y(y)),
+        )?;
+        xcb_flush(&self.xcb);
+        Ok(())
+    }
+
     pub fn should_close(&self) -> bool {
         let mut cb = self.callbacks.borrow_mut();
         if let Some(mut should_close) = cb.should_close.take() {
@@ -1105,6 +1261,23 @@ impl X11WindowStatePtr {
         !state.children.is_empty()
     }
 
+    /// Closes the anchored popups opened from this window, e.g. after the user clicked into
+    /// another application.
+    pub fn close_popups(&self) {
+        let mut state = self.state.borrow_mut();
+        let client = state.client.clone();
+        let popups = mem::take(&mut state.popups);
+        drop(state);
+
+        if let Some(client) = client.get_client() {
+            for popup in popups {
+                if let Some(popup_window) = client.get_window(popup) {
+                    popup_window.close();
+                }
+            }
+        }
+    }
+
     pub fn close(&self) {
         let state = self.state.borrow();
         let client = state.client.clone();
@@ -1258,6 +1431,9 @@ impl X11WindowStatePtr {
             }
             result
         };
+        if !is_resize {
+            self.update_display();
+        }
 
         let mut callbacks = self.callbacks.borrow_mut();
         if let Some(ref mut fun) = callbacks.resize {
@@ -1271,7 +1447,38 @@ impl X11WindowStatePtr {
         Ok(())
     }
 
+    pub fn handle_present_complete(
+        &self,
+        event: &present::CompleteNotifyEvent,
+        refresh_interval: Option<Duration>,
+    ) {
+        if event.kind != present::CompleteKind::PIXMAP {
+            return;
+        }
+        let Some(frame) = self.state.borrow_mut().pending_presentations.pop_front() else {
+            return;
+        };
+        let presented_at = if event.mode == present::CompleteMode::SKIP {
+            None
+        } else {
+            instant_from_clock(libc::CLOCK_MONOTONIC, Duration::from_micros(event.ust))
+        };
+        let callback = self.callbacks.borrow_mut().presentation_feedback.take();
+        if let Some(mut fun) = callback {
+            fun(gpui::PresentationFeedback {
+                frame,
+                presented_at,
+                refresh_interval,
+            });
+            self.callbacks.borrow_mut().presentation_feedback = Some(fun);
+        }
+    }
+
     pub fn set_active(&self, focus: bool) {
+        if self.state.borrow().pointer_constraint.is_some() {
+            self.update_pointer_grab(focus);
+        }
+
         let callback = self.callbacks.borrow_mut().active_status_change.take();
         if let Some(mut fun) = callback {
             fun(focus);
@@ -1279,6 +1486,88 @@ impl X11WindowStatePtr {
         }
     }
 
+    /// X11 has no pointer constraints, so they're emulated with a pointer grab confined to the
+    /// window. The grab is released while the window is inactive so other windows stay usable.
+    fn update_pointer_grab(&self, grab: bool) {
+        if grab {
+            let reply = get_reply(
+                || "X11 GrabPointer failed.",
+                self.xcb.grab_pointer(
+                    true,
+                    self.x_window,
+                    xproto::EventMask::NO_EVENT,
+                    xproto::GrabMode::ASYNC,
+                    xproto::GrabMode::ASYNC,
+                    self.x_window,
+                    x11rb::NONE,
+                    x11rb::CURRENT_TIME,
+                ),
+            )
+            .log_err();
+            if let Some(reply) = reply
+                && reply.status != xproto::GrabStatus::SUCCESS
+            {
+                log::warn!("X11 GrabPointer was refused: {:?}", reply.status);
+            }
+        } else {
+            check_reply(
+                || "X11 UngrabPointer failed.",
+                self.xcb.ungrab_pointer(x11rb::CURRENT_TIME),
+            )
+            .log_err();
+        }
+        xcb_flush(&self.xcb);
+    }
+
+    /// Reports pointer motion as raw motion while the pointer is constrained. Returns true if the
+    /// pointer is locked, in which case the motion shouldn't move the cursor.
+    pub fn handle_constrained_motion(&self, position: Point<Pixels>, modifiers: Modifiers) -> bool {
+        let mut state = self.state.borrow_mut();
+        let Some(constraint) = state.pointer_constraint else {
+            return false;
+        };
+        let locked = constraint == PointerConstraint::Locked;
+        let Some(last_position) = state.last_pointer_position else {
+            state.last_pointer_position = Some(position);
+            return locked;
+        };
+
+        let delta = position - last_position;
+        if delta == Point::default() {
+            return locked;
+        }
+
+        if locked {
+            // Warp the pointer back to where it was locked. The motion caused by the warp has no
+            // delta, so it isn't reported.
+            check_reply(
+                || "X11 WarpPointer failed.",
+                self.xcb.warp_pointer(
+                    x11rb::NONE,
+                    self.x_window,
+                    0,
+                    0,
+                    0,
+                    0,
+                    (f32::from(last_position.x) * state.scale_factor) as i16,
+                    (f32::from(last_position.y) * state.scale_factor) as i16,
+                ),
+            )
+            .log_err();
+            xcb_flush(&self.xcb);
+        } else {
+            state.last_pointer_position = Some(position);
+        }
+        drop(state);
+
+        self.handle_input(PlatformInput::RawMouseMotion(RawMouseMotionEvent {
+            delta,
+            unaccelerated_delta: delta,
+            modifiers,
+        }));
+        locked
+    }
+
     pub fn set_hovered(&self, focus: bool) {
         let callback = self.callbacks.borrow_mut().hovered_status_change.take();
         if let Some(mut fun) = callback {
@@ -1308,6 +1597,31 @@ impl X11WindowStatePtr {
             self.callbacks.borrow_mut().button_layout_changed = Some(fun);
         }
     }
+
+    /// Switches to a new scale factor while keeping the window's size in device pixels.
+    pub fn set_scale_factor(&self, scale_factor: f32) {
+        let content_size = {
+            let mut state = self.state.borrow_mut();
+            let ratio = state.scale_factor / scale_factor;
+            state.bounds = state.bounds.map(|value| value * ratio);
+            state.scale_factor = scale_factor;
+            state.content_size()
+        };
+
+        let mut callbacks = self.callbacks.borrow_mut();
+        if let Some(ref mut fun) = callbacks.resize {
+            fun(content_size, scale_factor)
+        }
+    }
+
+    /// Drops rasterized glyphs after the desktop's font rendering settings changed.
+    pub fn set_font_rendering(&self, is_bgr: bool) {
+        let mut state = self.state.borrow_mut();
+        state.renderer.set_subpixel_layout(is_bgr);
+        state.renderer.sprite_atlas().clear();
+        // Cached views would otherwise replay references to the cleared atlas tiles.
+        state.force_render_after_recovery = true;
+    }
 }
 
 impl PlatformWindow for X11Window {
@@ -1395,7 +1709,12 @@ impl PlatformWindow for X11Window {
     }
 
     fn display(&self) -> Option<Rc<dyn PlatformDisplay>> {
-        Some(self.0.state.borrow().display.clone())
+        self.0
+            .state
+            .borrow()
+            .display
+            .clone()
+            .map(|display| display as Rc<dyn PlatformDisplay>)
     }
 
     fn mouse_position(&self) -> Point<Pixels> {
@@ -1658,8 +1977,17 @@ impl PlatformWindow for X11Window {
         self.0.callbacks.borrow_mut().button_layout_changed = Some(callback);
     }
 
+    fn on_presentation_feedback(
+        &self,
+        callback: Box<dyn FnMut(gpui::PresentationFeedback)>,
+    ) -> bool {
+        self.0.callbacks.borrow_mut().presentation_feedback = Some(callback);
+        self.0.state.borrow().presentation_feedback_supported
+    }
+
     fn draw(&self, scene: &Scene) {
         let mut inner = self.0.state.borrow_mut();
+        inner.drawn_frames += 1;
 
         if inner.renderer.device_lost() {
             let raw_window = RawWindow {
@@ -1681,7 +2009,10 @@ impl PlatformWindow for X11Window {
             return;
         }
 
-        inner.renderer.draw(scene);
+        if inner.renderer.draw(scene) && inner.presentation_feedback_supported {
+            let frame = inner.drawn_frames;
+            inner.pending_presentations.push_back(frame);
+        }
 
         if inner.renderer.needs_redraw() {
             inner.force_render_after_recovery = true;
@@ -1878,6 +2209,23 @@ impl PlatformWindow for X11Window {
         client.update_ime_position(bounds);
     }
 
+    fn move_to_display(&self, display_id: DisplayId) -> anyhow::Result<()> {
+        let display = {
+            let state = self.0.state.borrow();
+            state
+                .displays
+                .borrow()
+                .iter()
+                .find(|display| display.id() == display_id)
+                .cloned()
+        }
+        .with_context(|| format!("No display with id {display_id:?}"))?;
+        if X11Display::x_screen_index(display_id) != self.0.state.borrow().x_screen_index {
+            anyhow::bail!("Windows can't be moved to another X screen");
+        }
+        self.0.center_on(display.bounds())
+    }
+
     fn gpu_specs(&self) -> Option<GpuSpecs> {
         self.0.state.borrow().renderer.gpu_specs().into()
     }
@@ -1886,4 +2234,33 @@ impl PlatformWindow for X11Window {
         // Volume 0% means don't increase or decrease from system volume
         let _ = self.0.xcb.bell(0);
     }
+
+    fn set_idle_inhibited(&self, inhibited: bool) {
+        let mut state = self.0.state.borrow_mut();
+        if state.idle_inhibited == inhibited {
+            return;
+        }
+        state.idle_inhibited = inhibited;
+        drop(state);
+
+        // The server counts suspensions per client, so each window can hold one independently.
+        check_reply(
+            || "X11 ScreenSaverSuspend failed.",
+            self.0.xcb.screensaver_suspend(inhibited as u32),
+        )
+        .log_err();
+        xcb_flush(&self.0.xcb);
+    }
+
+    fn set_pointer_constraint(&self, constraint: Option<PointerConstraint>) {
+        let mut state = self.0.state.borrow_mut();
+        let was_constrained = state.pointer_constraint.is_some();
+        state.pointer_constraint = constraint;
+        state.last_pointer_position = None;
+        drop(state);
+
+        if constraint.is_some() || was_constrained {
+            self.0.update_pointer_grab(constraint.is_some());
+        }
+    }
 }
@@ -0,0 +1,282 @@
+//! Client for the XSETTINGS protocol, through which X11 desktops such as XFCE, MATE and Cinnamon
+//! publish user preferences like the DPI, font rendering and the cursor theme.
+//!
+//! See <https://specifications.freedesktop.org/xsettings-spec/0.5/>.
+
+use std::time::Duration;
+
+use anyhow::{Context as _, bail};
+use collections::HashMap;
+use x11rb::{
+    connection::Connection as _,
+    protocol::xproto::{self, ChangeWindowAttributesAux, ConnectionExt as _, EventMask},
+    xcb_ffi::XCBConnection,
+};
+
+/// The settings gpui acts on. Fields the settings manager doesn't provide stay `None`, and callers
+/// fall back to the X resource database for them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct XSettings {
+    /// `Xft/DPI`, in dots per inch.
+    pub dpi: Option<f32>,
+    /// `Xft/Antialias`.
+    pub antialias: Option<bool>,
+    /// `Xft/Hinting`, also turned off by an `Xft/HintStyle` of `hintnone`.
+    pub hinting: Option<bool>,
+    /// `Xft/RGBA`: one of `none`, `rgb`, `bgr`, `vrgb` or `vbgr`.
+    pub rgba: Option<String>,
+    /// `Gtk/CursorThemeName`.
+    pub cursor_theme: Option<String>,
+    /// `Gtk/CursorThemeSize`, in pixels.
+    pub cursor_size: Option<u32>,
+    /// `Net/DoubleClickTime`.
+    pub double_click_time: Option<Duration>,
+    /// `Net/DoubleClickDistance`, in pixels.
+    pub double_click_distance: Option<u32>,
+    /// `Net/DndDragThreshold`, in pixels.
+    pub drag_threshold: Option<u32>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum XSetting {
+    Integer(i32),
+    String(String),
+    Color,
+}
+
+impl XSettings {
+    pub fn parse(data: &[u8]) -> anyhow::Result<Self> {
+        let settings = parse_settings(data)?;
+        // Managers publish -1 or an empty string for settings the user hasn't changed.
+        let integer = |name: &str| match settings.get(name) {
+            Some(XSetting::Integer(value)) if *value >= 0 => Some(*value),
+            _ => None,
+        };
+        let string = |name: &str| match settings.get(name) {
+            Some(XSetting::String(value)) if !value.is_empty() => Some(value.clone()),
+            _ => None,
+        };
+
+        let hinting = match (integer("Xft/Hinting"), string("Xft/HintStyle").as_deref()) {
+            (Some(0), _) | (_, Some("hintnone")) => Some(false),
+            (Some(_), _) | (_, Some(_)) => Some(true),
+            (None, None) => None,
+        };
+
+        Ok(Self {
+            dpi: integer("Xft/DPI")
+                .filter(|dpi| *dpi > 0)
+                .map(|dpi| dpi as f32 / 1024.0),
+            antialias: integer("Xft/Antialias").map(|antialias| antialias != 0),
+            hinting,
+            rgba: string("Xft/RGBA"),
+            cursor_theme: string("Gtk/CursorThemeName"),
+            cursor_size: integer("Gtk/CursorThemeSize")
+                .filter(|size| *size > 0)
+                .map(|size| size as u32),
+            double_click_time: integer("Net/DoubleClickTime")
+                .filter(|time| *time > 0)
+                .map(|time| Duration::from_millis(time as u64)),
+            double_click_distance: integer("Net/DoubleClickDistance")
+                .map(|distance| distance as u32),
+            drag_threshold: integer("Net/DndDragThreshold").map(|threshold| threshold as u32),
+        })
+    }
+}
+
+/// Finds the running settings manager, subscribes to changes of its settings and reads them.
+/// Returns `None` when no settings manager is running.
+pub(crate) fn read_xsettings(
+    connection: &XCBConnection,
+    selection: xproto::Atom,
+    settings_atom: xproto::Atom,
+) -> anyhow::Result<Option<(xproto::Window, XSettings)>> {
+    let owner = connection.get_selection_owner(selection)?.reply()?.owner;
+    if owner == x11rb::NONE {
+        return Ok(None);
+    }
+
+    // Subscribe before reading so that a change in between isn't missed.
+    connection
+        .change_window_attributes(
+            owner,
+            &ChangeWindowAttributesAux::new()
+                .event_mask(EventMask::PROPERTY_CHANGE | EventMask::STRUCTURE_NOTIFY),
+        )?
+        .check()
+        .context("settings manager window went away")?;
+
+    let reply = connection
+        .get_property(false, owner, settings_atom, settings_atom, 0, u32::MAX)?
+        .reply()?;
+    let settings = XSettings::parse(&reply.value)?;
+    Ok(Some((owner, settings)))
+}
+
+/// Subscribes to the `MANAGER` client messages that announce a new settings manager.
+pub(crate) fn watch_for_xsettings_manager(
+    connection: &XCBConnection,
+    root: xproto::Window,
+) -> anyhow::Result<()> {
+    let event_mask = connection
+        .get_window_attributes(root)?
+        .reply()?
+        .your_event_mask;
+    connection
+        .change_window_attributes(
+            root,
+            &ChangeWindowAttributesAux::new().event_mask(event_mask | EventMask::STRUCTURE_NOTIFY),
+        )?
+        .check()?;
+    Ok(())
+}
+
+fn parse_settings(data: &[u8]) -> anyhow::Result<HashMap<String, XSetting>> {
+    let mut reader = Reader {
+        data,
+        big_endian: false,
+    };
+    reader.big_endian = match reader.u8()? {
+        0 => false,
+        1 => true,
+        byte_order => bail!("invalid XSETTINGS byte order {byte_order}"),
+    };
+    reader.skip(3)?;
+    let _serial = reader.u32()?;
+    let count = reader.u32()?;
+
+    let mut settings = HashMap::default();
+    for _ in 0..count {
+        let kind = reader.u8()?;
+        reader.skip(1)?;
+        let name_len = reader.u16()? as usize;
+        let name = reader.string(name_len)?;
+        let _last_change_serial = reader.u32()?;
+        let value = match kind {
+            0 => XSetting::Integer(reader.u32()? as i32),
+            1 => {
+                let len = reader.u32()? as usize;
+                XSetting::String(reader.string(len)?)
+            }
+            2 => {
+                reader.skip(8)?;
+                XSetting::Color
+            }
+            kind => bail!("unknown type {kind} for XSETTINGS setting {name:?}"),
+        };
+        settings.insert(name, value);
+    }
+    Ok(settings)
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    big_endian: bool,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> anyhow::Result<&'a [u8]> {
+        if self.data.len() < len {
+            bail!("XSETTINGS data is truncated");
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, len: usize) -> anyhow::Result<()> {
+        self.bytes(len).map(|_| ())
+    }
+
+    fn u8(&mut self) -> anyhow::Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> anyhow::Result<u16> {
+        let bytes = self.bytes(2)?.try_into()?;
+        Ok(if self.big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    }
+
+    fn u32(&mut self) -> anyhow::Result<u32> {
+        let bytes = self.bytes(4)?.try_into()?;
+        Ok(if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    }
+
+    /// Reads a string and the padding that aligns what follows to four bytes.
+    fn string(&mut self, len: usize) -> anyhow::Result<String> {
+        let string = String::from_utf8_lossy(self.bytes(len)?).into_owned();
+        self.skip((4 - len % 4) % 4)?;
+        Ok(string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setting(data: &mut Vec<u8>, kind: u8, name: &str, value: &[u8]) {
+        data.extend([kind, 0]);
+        data.extend((name.len() as u16).to_le_bytes());
+        data.extend(name.as_bytes());
+        data.resize(data.len().next_multiple_of(4), 0);
+        data.extend(0u32.to_le_bytes());
+        data.extend(value);
+    }
+
+    fn string_value(value: &str) -> Vec<u8> {
+        let mut data = (value.len() as u32).to_le_bytes().to_vec();
+        data.extend(value.as_bytes());
+        data.resize(data.len().next_multiple_of(4), 0);
+        data
+    }
+
+    #[test]
+    fn test_parse_xsettings() {
+        let mut data = vec![0, 0, 0, 0];
+        data.extend(7u32.to_le_bytes());
+        data.extend(8u32.to_le_bytes());
+        setting(&mut data, 0, "Xft/DPI", &(144 * 1024i32).to_le_bytes());
+        setting(&mut data, 0, "Xft/Antialias", &1i32.to_le_bytes());
+        setting(&mut data, 1, "Xft/HintStyle", &string_value("hintnone"));
+        setting(&mut data, 1, "Xft/RGBA", &string_value("bgr"));
+        setting(
+            &mut data,
+            1,
+            "Gtk/CursorThemeName",
+            &string_value("Adwaita"),
+        );
+        setting(&mut data, 0, "Net/DoubleClickTime", &(-1i32).to_le_bytes());
+        setting(&mut data, 0, "Net/DndDragThreshold", &8i32.to_le_bytes());
+        setting(&mut data, 2, "Gtk/Color", &[0xff; 8]);
+
+        let settings = XSettings::parse(&data).unwrap();
+        assert_eq!(
+            settings,
+            XSettings {
+                dpi: Some(144.0),
+                antialias: Some(true),
+                hinting: Some(false),
+                rgba: Some("bgr".into()),
+                cursor_theme: Some("Adwaita".into()),
+                drag_threshold: Some(8),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_truncated_xsettings() {
+        let mut data = vec![1, 0, 0, 0];
+        data.extend(0u32.to_be_bytes());
+        data.extend(1u32.to_be_bytes());
+        assert!(XSettings::parse(&data).is_err());
+    }
+}
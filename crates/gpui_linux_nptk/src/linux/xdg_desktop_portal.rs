@@ -2,15 +2,18 @@
 //!
 //! This module uses the [ashpd] crate
 
-use ashpd::desktop::settings::{ColorScheme, Settings};
+use ashpd::desktop::Color;
+use ashpd::desktop::settings::{ColorScheme, Contrast, Settings};
 use calloop::channel::Channel;
 use calloop::{EventSource, Poll, PostAction, Readiness, Token, TokenFactory};
 use smol::stream::StreamExt;
 
-use gpui::{BackgroundExecutor, WindowAppearance};
+use gpui::{AccessibilityPreferences, BackgroundExecutor, Hsla, Rgba, WindowAppearance};
 
 pub enum Event {
     WindowAppearance(WindowAppearance),
+    AccentColor(Option<Hsla>),
+    Accessibility(AccessibilitySetting),
     #[cfg_attr(feature = "x11", allow(dead_code))]
     CursorTheme(String),
     #[cfg_attr(feature = "x11", allow(dead_code))]
@@ -18,6 +21,27 @@ pub enum Event {
     ButtonLayout(String),
 }
 
+pub enum AccessibilitySetting {
+    HighContrast(bool),
+    /// GNOME's `enable-animations`, inverted.
+    ReduceMotion(bool),
+    TextScale(f32),
+}
+
+impl AccessibilitySetting {
+    pub fn apply(self, preferences: &mut AccessibilityPreferences) {
+        match self {
+            AccessibilitySetting::HighContrast(high_contrast) => {
+                preferences.high_contrast = high_contrast
+            }
+            AccessibilitySetting::ReduceMotion(reduce_motion) => {
+                preferences.reduce_motion = reduce_motion
+            }
+            AccessibilitySetting::TextScale(text_scale) => preferences.text_scale = text_scale,
+        }
+    }
+}
+
 pub struct XDPEventSource {
     channel: Channel<Event>,
 }
@@ -37,6 +61,33 @@ impl XDPEventSource {
                         window_appearance_from_color_scheme(initial_appearance),
                     ))?;
                 }
+                if let Ok(initial_accent_color) = settings.accent_color().await {
+                    sender.send(Event::AccentColor(accent_color_from_portal(
+                        initial_accent_color,
+                    )))?;
+                }
+                if let Ok(initial_contrast) = settings.contrast().await {
+                    sender.send(Event::Accessibility(AccessibilitySetting::HighContrast(
+                        initial_contrast == Contrast::High,
+                    )))?;
+                }
+                if let Ok(enable_animations) = settings
+                    .read::<bool>("org.gnome.desktop.interface", "enable-animations")
+                    .await
+                {
+                    sender.send(Event::Accessibility(AccessibilitySetting::ReduceMotion(
+                        !enable_animations,
+                    )))?;
+                }
+                if let Ok(initial_text_scale) = settings
+                    .read::<f64>("org.gnome.desktop.interface", "text-scaling-factor")
+                    .await
+                    && let Some(text_scale) = valid_text_scale(initial_text_scale)
+                {
+                    sender.send(Event::Accessibility(AccessibilitySetting::TextScale(
+                        text_scale,
+                    )))?;
+                }
                 if let Ok(initial_theme) = settings
                     .read::<String>("org.gnome.desktop.interface", "cursor-theme")
                     .await
@@ -59,6 +110,78 @@ impl XDPEventSource {
                     sender.send(Event::ButtonLayout(initial_layout))?;
                 }
 
+                if let Ok(mut accent_color_changed) = settings.receive_accent_color_changed().await
+                {
+                    let sender = sender.clone();
+                    background
+                        .spawn(async move {
+                            while let Some(color) = accent_color_changed.next().await {
+                                sender.send(Event::AccentColor(accent_color_from_portal(color)))?;
+                            }
+                            anyhow::Ok(())
+                        })
+                        .detach();
+                }
+
+                if let Ok(mut contrast_changed) = settings.receive_contrast_changed().await {
+                    let sender = sender.clone();
+                    background
+                        .spawn(async move {
+                            while let Some(contrast) = contrast_changed.next().await {
+                                sender.send(Event::Accessibility(
+                                    AccessibilitySetting::HighContrast(contrast == Contrast::High),
+                                ))?;
+                            }
+                            anyhow::Ok(())
+                        })
+                        .detach();
+                }
+
+                if let Ok(mut enable_animations_changed) = settings
+                    .receive_setting_changed_with_args::<bool>(
+                        "org.gnome.desktop.interface",
+                        "enable-animations",
+                    )
+                    .await
+                {
+                    let sender = sender.clone();
+                    background
+                        .spawn(async move {
+                            while let Some(enable_animations) =
+                                enable_animations_changed.next().await
+                            {
+                                let enable_animations = enable_animations?;
+                                sender.send(Event::Accessibility(
+                                    AccessibilitySetting::ReduceMotion(!enable_animations),
+                                ))?;
+                            }
+                            anyhow::Ok(())
+                        })
+                        .detach();
+                }
+
+                if let Ok(mut text_scale_changed) = settings
+                    .receive_setting_changed_with_args::<f64>(
+                        "org.gnome.desktop.interface",
+                        "text-scaling-factor",
+                    )
+                    .await
+                {
+                    let sender = sender.clone();
+                    background
+                        .spawn(async move {
+                            while let Some(text_scale) = text_scale_changed.next().await {
+                                if let Some(text_scale) = valid_text_scale(text_scale?) {
+                                    sender.send(Event::Accessibility(
+                                        AccessibilitySetting::TextScale(text_scale),
+                                    ))?;
+                                }
+                            }
+                            anyhow::Ok(())
+                        })
+                        .detach();
+                }
+
                 if let Ok(mut cursor_theme_changed) = settings
                     .receive_setting_changed_with_args(
                         "org.gnome.desktop.interface",
@@ -189,3 +312,62 @@ fn window_appearance_from_color_scheme(cs: ColorScheme) -> WindowAppearance {
         ColorScheme::NoPreference => WindowAppearance::Light,
     }
 }
+
+fn valid_text_scale(text_scale: f64) -> Option<f32> {
+    (text_scale.is_finite() && text_scale > 0.0).then_some(text_scale as f32)
+}
+
+/// The portal reports components outside of `0.0..=1.0` when the user hasn't picked an accent
+/// color.
+fn accent_color_from_portal(color: Color) -> Option<Hsla> {
+    let components = [color.red(), color.green(), color.blue()];
+    if components
+        .iter()
+        .any(|component| !(0.0..=1.0).contains(component))
+    {
+        return None;
+    }
+    Some(
+        Rgba {
+            r: color.red() as f32,
+            g: color.green() as f32,
+            b: color.blue() as f32,
+            a: 1.0,
+        }
+        .into(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_text_scale() {
+        assert_eq!(valid_text_scale(1.25), Some(1.25));
+        assert_eq!(valid_text_scale(0.0), None);
+        assert_eq!(valid_text_scale(-1.0), None);
+        assert_eq!(valid_text_scale(f64::NAN), None);
+        assert_eq!(valid_text_scale(f64::INFINITY), None);
+    }
+
+    #[test]
+    fn test_apply_accessibility_settings() {
+        let mut preferences = AccessibilityPreferences::default();
+        AccessibilitySetting::HighContrast(true).apply(&mut preferences);
+        AccessibilitySetting::ReduceMotion(true).apply(&mut preferences);
+        AccessibilitySetting::TextScale(1.5).apply(&mut preferences);
+        assert_eq!(
+            preferences,
+            AccessibilityPreferences {
+                reduce_motion: true,
+                high_contrast: true,
+                text_scale: 1.5,
+            }
+        );
+
+        AccessibilitySetting::ReduceMotion(false).apply(&mut preferences);
+        assert!(!preferences.reduce_motion);
+        assert!(preferences.high_contrast);
+    }
+}
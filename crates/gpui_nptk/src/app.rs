@@ -28,7 +28,7 @@ pub use bench_context::{BenchAppContext, BenchReport, BenchWindowContext, bench_
 use collections::{FxHashMap, FxHashSet, HashMap, TypeIdHashMap, TypeIdHashSet, VecDeque};
 pub use context::*;
 pub use entity_map::*;
-use gpui_util::{ResultExt, debug_panic};
+use gpui_util::{ResultExt, debug_panic, post_inc};
 #[cfg(any(test, feature = "test-support"))]
 pub use headless_app_context::*;
 use http_client::{HttpClient, Url};
@@ -45,16 +45,25 @@ use crate::InspectorElementRegistry;
 use crate::{
     Action, ActionBuildError, ActionRegistry, Any, AnyView, AnyWindowHandle, AppContext, Arena,
     ArenaBox, Asset, AssetSource, BackgroundExecutor, Bounds, ClipboardItem, CursorStyle,
-    DispatchPhase, DisplayId, EventEmitter, FocusHandle, FocusMap, ForegroundExecutor, Global,
-    KeyBinding, KeyContext, Keymap, Keystroke, LayoutId, Menu, MenuItem, OwnedMenu,
-    PathPromptOptions, Pixels, Platform, PlatformDisplay, PlatformKeyboardLayout,
-    PlatformKeyboardMapper, Point, Priority, PromptBuilder, PromptButton, PromptHandle,
-    PromptLevel, Render, RenderImage, RenderablePromptHandle, Reservation, ScreenCaptureSource,
-    SharedString, SubscriberSet, Subscription, SvgRenderer, Task, TextRenderingMode, TextSystem,
-    ThermalState, Window, WindowAppearance, WindowButtonLayout, WindowHandle, WindowId,
-    WindowInvalidator,
+    DispatchPhase, DisplayId, DisplayInfo, DisplaysChanged, EventEmitter, FocusHandle, FocusMap,
+    ForegroundExecutor, Global, Hsla, KeyBinding, KeyContext, Keymap, Keystroke, LayoutId, Menu,
+    MenuItem, OwnedMenu, PathPromptOptions, Pixels, Platform, PlatformDisplay,
+    PlatformKeyboardLayout, PlatformKeyboardMapper, Point, PowerState, Priority, PromptBuilder,
+    PromptButton, PromptHandle, PromptLevel, Render, RenderImage, RenderablePromptHandle,
+    Reservation, ScreenCaptureSource, SerializableView, Session, SessionRestorePolicy,
+    SharedString, SingleInstanceClaim, SubscriberSet, Subscription, SvgRenderer, Task,
+    TextRenderingMode, TextSystem, ThermalState, Window, WindowAppearance, WindowButtonLayout,
+    WindowHandle, WindowId, WindowInvalidator,
     colors::{Colors, GlobalColors},
-    hash, init_app_menus,
+    foreign_toplevel::{
+        ForeignToplevel, ForeignToplevelEvent, ForeignToplevelId, ForeignToplevelRequest,
+    },
+    global_shortcut::{GlobalShortcut, GlobalShortcutId, GlobalShortcutPhase},
+    hash,
+    inhibit::{InhibitFlags, Inhibitor, InhibitorId},
+    init_app_menus,
+    notification::{Notification, NotificationHandle, NotificationId, NotificationResponse},
+    tray::{TrayEvent, TrayIcon},
 };
 
 mod async_context;
@@ -195,6 +204,49 @@ impl Application {
         self
     }
 
+    /// Makes this the only running instance of the application identified by `app_id`, which
+    /// should be a reverse-DNS name matching the application's desktop entry.
+    ///
+    /// If another instance is already running, this launch's arguments and activation token are
+    /// forwarded to it and the process exits. Otherwise, later launches are delivered to this
+    /// instance through [`Application::on_open_urls`] and [`Application::on_reopen`].
+    pub fn with_single_instance(self, app_id: &str) -> Self {
+        let platform = self.0.borrow().platform.clone();
+        match platform.claim_single_instance(app_id) {
+            Ok(SingleInstanceClaim::Primary) => {}
+            Ok(SingleInstanceClaim::Forwarded) => std::process::exit(0),
+            Err(error) => log::error!("Failed to claim single instance for {app_id}: {error:#}"),
+        }
+        self
+    }
+
+    /// Saves the application's windows when it quits, and when the session manager asks for it
+    /// before the user logs out, so that they can be reopened with [`App::restore_session`] on the
+    /// next launch. `app_id` names the saved session and should be the reverse-DNS name matching
+    /// the application's desktop entry, i.e. `release_channel::app_id()`, the name its autostart
+    /// entry is written under as well.
+    ///
+    /// On X11 the session manager relaunches the application with `--sm-client-id=ID`, so its
+    /// argument parsing should accept and ignore that option.
+    pub fn with_session(self, app_id: &str) -> Self {
+        let mut cx = self.0.borrow_mut();
+        cx.session = Some(Session::new(app_id));
+        cx.on_session_end_query(|cx| {
+            cx.save_session().log_err();
+        })
+        .detach();
+        let platform = cx.platform.clone();
+        drop(cx);
+
+        let app = Rc::downgrade(&self.0);
+        platform.on_save_session(Box::new(move || {
+            if let Some(app) = app.upgrade() {
+                app.borrow_mut().save_session().log_err();
+            }
+        }));
+        self
+    }
+
     /// Start the application. The provided callback will be called once the
     /// app is fully launched.
     pub fn run<F>(self, on_finish_launching: F)
@@ -259,6 +311,11 @@ type Handler = Box<dyn FnMut(&mut App) -> bool + 'static>;
 type Listener = Box<dyn FnMut(&dyn Any, &mut App) -> bool + 'static>;
 pub(crate) type KeystrokeObserver =
     Box<dyn FnMut(&KeystrokeEvent, &mut Window, &mut App) -> bool + 'static>;
+type ForeignToplevelHandler = Box<dyn FnMut(&ForeignToplevelEvent, &mut App) -> bool + 'static>;
+type TrayEventHandler = Box<dyn FnMut(&TrayEvent, &mut App) -> bool + 'static>;
+type DisplaysChangedHandler = Box<dyn FnMut(&DisplaysChanged, &mut App) -> bool + 'static>;
+type GlobalShortcutHandler =
+    Box<dyn FnMut(&dyn Action, GlobalShortcutPhase, &mut App) -> bool + 'static>;
 type QuitHandler = Box<dyn FnOnce(&mut App) -> LocalBoxFuture<'static, ()> + 'static>;
 type WindowClosedHandler = Box<dyn FnMut(&mut App, WindowId)>;
 type ReleaseListener = Box<dyn FnOnce(&mut dyn Any, &mut App) + 'static>;
@@ -627,6 +684,10 @@ pub struct App {
     pub(crate) window_handles: FxHashMap<WindowId, AnyWindowHandle>,
     pub(crate) focus_handles: Arc<FocusMap>,
     pub(crate) keymap: Rc<RefCell<Keymap>>,
+    /// Bindings registered with the desktop rather than the keymap.
+    global_shortcuts: Vec<KeyBinding>,
+    global_shortcut_actions: FxHashMap<GlobalShortcutId, Box<dyn Action>>,
+    global_shortcuts_dirty: bool,
     pub(crate) keyboard_layout: Box<dyn PlatformKeyboardLayout>,
     pub(crate) keyboard_mapper: Rc<dyn PlatformKeyboardMapper>,
     pub(crate) global_action_listeners:
@@ -639,6 +700,14 @@ pub struct App {
     pub(crate) keystroke_interceptors: SubscriberSet<(), KeystrokeObserver>,
     pub(crate) keyboard_layout_observers: SubscriberSet<(), Handler>,
     pub(crate) thermal_state_observers: SubscriberSet<(), Handler>,
+    pub(crate) power_state_observers: SubscriberSet<(), Handler>,
+    pub(crate) accent_color_observers: SubscriberSet<(), Handler>,
+    pub(crate) session_end_query_observers: SubscriberSet<(), Handler>,
+    pub(crate) foreign_toplevel_observers: SubscriberSet<(), ForeignToplevelHandler>,
+    pub(crate) tray_event_observers: SubscriberSet<(), TrayEventHandler>,
+    pub(crate) global_shortcut_observers: SubscriberSet<(), GlobalShortcutHandler>,
+    pub(crate) display_observers: SubscriberSet<(), DisplaysChangedHandler>,
+    last_displays: Vec<DisplayInfo>,
     pub(crate) release_listeners: SubscriberSet<EntityId, ReleaseListener>,
     pub(crate) global_observers: SubscriberSet<TypeId, Handler>,
     pub(crate) quit_observers: SubscriberSet<(), QuitHandler>,
@@ -670,6 +739,10 @@ pub struct App {
     pub(crate) layout_id_buffer: Vec<LayoutId>, // We recycle this memory across layout requests.
     pub(crate) propagate_event: bool,
     pub(crate) prompt_builder: Option<PromptBuilder>,
+    shown_notifications: FxHashMap<NotificationId, Notification>,
+    next_notification_id: u64,
+    next_inhibitor_id: u64,
+    pub(crate) session: Option<Session>,
     pub(crate) window_invalidators_by_entity:
         FxHashMap<EntityId, FxHashMap<WindowId, WindowInvalidator>>,
     pub(crate) tracked_entities: FxHashMap<WindowId, FxHashSet<EntityId>>,
@@ -692,6 +765,7 @@ pub struct App {
     pending_updates: usize,
     quit_mode: QuitMode,
     quitting: bool,
+    throttle_in_low_power_mode: bool,
 
     // We need to ensure the leak detector drops last, after all tasks, callbacks and things have been dropped.
     // Otherwise it may report false positives.
@@ -746,6 +820,9 @@ impl App {
                 window_handles: FxHashMap::default(),
                 focus_handles: Arc::new(RwLock::new(SlotMap::with_key())),
                 keymap: Rc::new(RefCell::new(Keymap::default())),
+                global_shortcuts: Vec::new(),
+                global_shortcut_actions: FxHashMap::default(),
+                global_shortcuts_dirty: false,
                 keyboard_layout,
                 keyboard_mapper,
                 global_action_listeners: Default::default(),
@@ -762,6 +839,18 @@ impl App {
                 keystroke_interceptors: SubscriberSet::new(),
                 keyboard_layout_observers: SubscriberSet::new(),
                 thermal_state_observers: SubscriberSet::new(),
+                power_state_observers: SubscriberSet::new(),
+                accent_color_observers: SubscriberSet::new(),
+                session_end_query_observers: SubscriberSet::new(),
+                foreign_toplevel_observers: SubscriberSet::new(),
+                tray_event_observers: SubscriberSet::new(),
+                global_shortcut_observers: SubscriberSet::new(),
+                display_observers: SubscriberSet::new(),
+                last_displays: platform
+                    .displays()
+                    .iter()
+                    .map(|display| DisplayInfo::new(display.as_ref()))
+                    .collect(),
                 global_observers: SubscriberSet::new(),
                 quit_observers: SubscriberSet::new(),
                 restart_observers: SubscriberSet::new(),
@@ -770,12 +859,17 @@ impl App {
                 layout_id_buffer: Default::default(),
                 propagate_event: true,
                 prompt_builder: Some(PromptBuilder::Default),
+                shown_notifications: FxHashMap::default(),
+                next_notification_id: 0,
+                next_inhibitor_id: 0,
+                session: None,
                 #[cfg(any(feature = "inspector", debug_assertions))]
                 inspector_renderer: None,
                 #[cfg(any(feature = "inspector", debug_assertions))]
                 inspector_element_registry: InspectorElementRegistry::default(),
                 quit_mode: QuitMode::default(),
                 quitting: false,
+                throttle_in_low_power_mode: false,
                 cursor_hide_mode: CursorHideMode::default(),
                 accessibility_force_disabled: false,
 
@@ -791,6 +885,8 @@ impl App {
 
         init_app_menus(platform.as_ref(), &app.borrow());
         SystemWindowTabController::init(&mut app.borrow_mut());
+        app.borrow_mut()
+            .set_global(platform.accessibility_preferences());
 
         platform.on_keyboard_layout_change(Box::new({
             let app = Rc::downgrade(&app);
@@ -818,6 +914,135 @@ impl App {
             }
         }));
 
+        platform.on_power_state_change(Box::new({
+            let app = Rc::downgrade(&app);
+            move || {
+                if let Some(app) = app.upgrade() {
+                    let cx = &mut app.borrow_mut();
+                    if cx.throttle_in_low_power_mode {
+                        // Resume or pause animations that are waiting on the next frame.
+                        cx.refresh_windows();
+                    }
+                    cx.power_state_observers
+                        .clone()
+                        .retain(&(), move |callback| (callback)(cx));
+                }
+            }
+        }));
+
+        platform.on_accessibility_preferences_change(Box::new({
+            let app = Rc::downgrade(&app);
+            move || {
+                if let Some(app) = app.upgrade() {
+                    let cx = &mut app.borrow_mut();
+                    let preferences = cx.platform.accessibility_preferences();
+                    cx.set_global(preferences);
+                    cx.refresh_windows();
+                }
+            }
+        }));
+
+        platform.on_session_end_query(Box::new({
+            let app = Rc::downgrade(&app);
+            move || {
+                if let Some(app) = app.upgrade() {
+                    let cx = &mut app.borrow_mut();
+                    cx.session_end_query_observers
+                        .clone()
+                        .retain(&(), move |callback| (callback)(cx));
+                }
+            }
+        }));
+
+        platform.on_accent_color_change(Box::new({
+            let app = Rc::downgrade(&app);
+            move || {
+                if let Some(app) = app.upgrade() {
+                    let cx = &mut app.borrow_mut();
+                    cx.accent_color_observers
+                        .clone()
+                        .retain(&(), move |callback| (callback)(cx));
+                }
+            }
+        }));
+
+        platform.on_displays_changed(Box::new({
+            let app = Rc::downgrade(&app);
+            move || {
+                if let Some(app) = app.upgrade() {
+                    app.borrow_mut().displays_changed();
+                }
+            }
+        }));
+
+        platform.on_foreign_toplevel_event(Box::new({
+            let app = Rc::downgrade(&app);
+            move |event| {
+                if let Some(app) = app.upgrade() {
+                    let cx = &mut app.borrow_mut();
+                    cx.foreign_toplevel_observers
+                        .clone()
+                        .retain(&(), move |callback| (callback)(&event, cx));
+                }
+            }
+        }));
+
+        platform.on_tray_event(Box::new({
+            let app = Rc::downgrade(&app);
+            move |event| {
+                if let Some(app) = app.upgrade() {
+                    let cx = &mut app.borrow_mut();
+                    cx.tray_event_observers
+                        .clone()
+                        .retain(&(), move |callback| (callback)(&event, cx));
+                }
+            }
+        }));
+
+        platform.on_global_shortcut(Box::new({
+            let app = Rc::downgrade(&app);
+            move |id, phase| {
+                if let Some(app) = app.upgrade() {
+                    app.borrow_mut()
+                        .update(|cx| cx.handle_global_shortcut(id, phase));
+                }
+            }
+        }));
+
+        platform.on_tray_menu_action(Box::new({
+            let app = Rc::downgrade(&app);
+            move |action| {
+                if let Some(app) = app.upgrade() {
+                    app.borrow_mut().update(|cx| cx.dispatch_action(action));
+                }
+            }
+        }));
+
+        platform.on_notification_response(Box::new({
+            let app = Rc::downgrade(&app);
+            move |id, response| {
+                if let Some(app) = app.upgrade() {
+                    app.borrow_mut().update(|cx| {
+                        let Some(notification) = cx.shown_notifications.remove(&id) else {
+                            return;
+                        };
+                        let action = match response {
+                            NotificationResponse::Default => notification.default_action,
+                            NotificationResponse::Action(index) => notification
+                                .actions
+                                .into_iter()
+                                .nth(index)
+                                .map(|action| action.action),
+                            NotificationResponse::Dismissed => None,
+                        };
+                        if let Some(action) = action {
+                            cx.dispatch_action(action.as_ref());
+                        }
+                    });
+                }
+            }
+        }));
+
         platform.on_quit(Box::new({
             let cx = Rc::downgrade(&app);
             move || {
@@ -864,6 +1089,10 @@ impl App {
     /// Quit the application gracefully. Handlers registered with [`Context::on_app_quit`]
     /// will be given `SHUTDOWN_TIMEOUT` to complete before exiting.
     pub fn shutdown(&mut self) {
+        if self.session.is_some() {
+            self.save_session().log_err();
+        }
+
         let mut futures = Vec::new();
 
         for observer in self.quit_observers.remove(&()) {
@@ -1125,6 +1354,67 @@ impl App {
         self.platform.window_stack()
     }
 
+    /// Opens a window on every display and keeps that set in sync as displays are connected and
+    /// disconnected, e.g. for per-monitor bars built from layer shell windows.
+    ///
+    /// `options` builds the options for each display's window; its `display_id` is always set to
+    /// that display. Windows on disconnected displays are closed. Dropping the returned
+    /// subscription stops following display changes, but leaves the open windows alone.
+    pub fn open_window_per_display<V: 'static + Render>(
+        &mut self,
+        options: impl Fn(&Rc<dyn PlatformDisplay>) -> crate::WindowOptions + 'static,
+        build_root_view: impl Fn(DisplayId, &mut Window, &mut App) -> Entity<V> + 'static,
+    ) -> Subscription {
+        let windows: Rc<RefCell<HashMap<DisplayId, WindowHandle<V>>>> = Rc::default();
+        let mut sync_windows = move |cx: &mut App| {
+            let displays = cx.displays();
+            let disconnected = windows
+                .borrow()
+                .iter()
+                .filter(|(display_id, _)| {
+                    !displays.iter().any(|display| display.id() == **display_id)
+                })
+                .map(|(display_id, window)| (*display_id, *window))
+                .collect::<Vec<_>>();
+            for (display_id, window) in disconnected {
+                windows.borrow_mut().remove(&display_id);
+                window
+                    .update(cx, |_, window, _| window.remove_window())
+                    .log_err();
+            }
+
+            for display in displays {
+                let display_id = display.id();
+                if windows.borrow().contains_key(&display_id) {
+                    continue;
+                }
+                let mut window_options = options(&display);
+                window_options.display_id = Some(display_id);
+                match cx.open_window(window_options, |window, cx| {
+                    build_root_view(display_id, window, cx)
+                }) {
+                    Ok(window) => {
+                        windows.borrow_mut().insert(display_id, window);
+                    }
+                    Err(error) => {
+                        log::error!("Failed to open window on display {display_id:?}: {error:?}");
+                    }
+                }
+            }
+        };
+        sync_windows(self);
+
+        let (subscription, activate) = self.display_observers.insert(
+            (),
+            Box::new(move |_, cx| {
+                sync_windows(cx);
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
     /// Returns a handle to the window that is currently focused at the platform level, if one exists.
     pub fn active_window(&self) -> Option<AnyWindowHandle> {
         self.platform.active_window()
@@ -1218,6 +1508,75 @@ impl App {
             .cloned()
     }
 
+    /// Invokes a handler when displays are connected, disconnected or change, e.g. when the
+    /// laptop is docked or a monitor is rescaled.
+    ///
+    /// Windows on a disconnected display are moved to the primary display after the handlers ran,
+    /// where the platform allows it.
+    pub fn on_displays_changed<F>(&self, mut callback: F) -> Subscription
+    where
+        F: 'static + FnMut(&DisplaysChanged, &mut App),
+    {
+        let (subscription, activate) = self.display_observers.insert(
+            (),
+            Box::new(move |changes, cx| {
+                callback(changes, cx);
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
+    fn displays_changed(&mut self) {
+        let displays = self
+            .displays()
+            .iter()
+            .map(|display| DisplayInfo::new(display.as_ref()))
+            .collect::<Vec<_>>();
+        let changes = DisplaysChanged::between(&self.last_displays, &displays);
+        self.last_displays = displays;
+        if changes.is_empty() {
+            return;
+        }
+
+        self.display_observers
+            .clone()
+            .retain(&(), |callback| callback(&changes, self));
+
+        if !changes.removed.is_empty() {
+            self.move_windows_off_displays(&changes.removed);
+        }
+    }
+
+    /// Moves the windows on the given displays, which have been disconnected, to a connected one.
+    fn move_windows_off_displays(&mut self, removed: &[DisplayInfo]) {
+        let Some(target) = self
+            .primary_display()
+            .or_else(|| self.displays().into_iter().next())
+        else {
+            return;
+        };
+        let target = target.id();
+
+        for handle in self.windows() {
+            handle
+                .update(self, |_, window, _| {
+                    let Some(display_id) = window.display_id else {
+                        return;
+                    };
+                    if !removed.iter().any(|display| display.id == display_id) {
+                        return;
+                    }
+                    if let Err(error) = window.move_to_display(target) {
+                        // Wayland compositors rehome regular windows on their own.
+                        log::debug!("Couldn't move window off disconnected display: {error:?}");
+                    }
+                })
+                .ok();
+        }
+    }
+
     /// Returns the current thermal state of the system.
     pub fn thermal_state(&self) -> ThermalState {
         self.platform.thermal_state()
@@ -1239,16 +1598,137 @@ impl App {
         subscription
     }
 
+    /// Returns whether the system runs on battery, its battery level and whether it is trying to
+    /// save power.
+    pub fn power_state(&self) -> PowerState {
+        self.platform.power_state()
+    }
+
+    /// Invokes a handler when the power state changes, e.g. to defer background work while the
+    /// system is running on battery.
+    pub fn on_power_state_change<F>(&self, mut callback: F) -> Subscription
+    where
+        F: 'static + FnMut(&mut App),
+    {
+        let (subscription, activate) = self.power_state_observers.insert(
+            (),
+            Box::new(move |cx| {
+                callback(cx);
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
+    /// Configures whether windows should cap their frame rate and pause repeating [`crate::Animation`]s
+    /// while the system is in low power mode. Disabled by default.
+    pub fn set_throttle_in_low_power_mode(&mut self, throttle: bool) {
+        self.throttle_in_low_power_mode = throttle;
+        self.refresh_windows();
+    }
+
+    /// Whether rendering should currently be throttled to save power, see
+    /// [`App::set_throttle_in_low_power_mode`].
+    pub fn should_throttle_for_power(&self) -> bool {
+        self.throttle_in_low_power_mode && self.platform.power_state().low_power_mode
+    }
+
     /// Returns the appearance of the application's windows.
     pub fn window_appearance(&self) -> WindowAppearance {
         self.platform.window_appearance()
     }
 
+    /// Asks the session manager to hold off the given actions, e.g. logging out while there are
+    /// unsaved changes, until the returned [`Inhibitor`] is dropped. The reason may be shown to
+    /// the user.
+    pub fn inhibit(&mut self, flags: InhibitFlags, reason: &str) -> Inhibitor {
+        let id = InhibitorId(self.next_inhibitor_id);
+        self.next_inhibitor_id += 1;
+        self.platform.inhibit(id, flags, reason);
+        Inhibitor {
+            id,
+            platform: self.platform.clone(),
+        }
+    }
+
+    /// Invokes a handler when the session is about to end, e.g. because the user logs out.
+    /// Handlers that still have work to save can take an [`Inhibitor`] with
+    /// [`InhibitFlags::LOGOUT`], so that the session manager asks the user before going on.
+    pub fn on_session_end_query<F>(&self, mut callback: F) -> Subscription
+    where
+        F: 'static + FnMut(&mut App),
+    {
+        let (subscription, activate) = self.session_end_query_observers.insert(
+            (),
+            Box::new(move |cx| {
+                callback(cx);
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
+    /// Saves windows whose root view is of type `V` with the session set up by
+    /// [`Application::with_session`]. Views must be registered before [`App::restore_session`]
+    /// can reopen their windows.
+    pub fn register_serializable_view<V: SerializableView>(&mut self) {
+        match self.session.as_mut() {
+            Some(session) => session.register::<V>(),
+            None => log::error!(
+                "can't register {} without a session, see `Application::with_session`",
+                V::KIND
+            ),
+        }
+    }
+
+    /// Saves the windows of registered views, along with their bounds and displays. This happens
+    /// on its own when the application quits and when the session manager asks for it. Nothing is
+    /// saved until [`App::restore_session`] has been called.
+    pub fn save_session(&mut self) -> Result<()> {
+        Session::save(self)
+    }
+
+    /// Reopens the windows saved by the previous run, or discards them, according to `policy`.
+    /// Returns the reopened windows, so that the application can open one of its own when there
+    /// are none.
+    pub fn restore_session(&mut self, policy: SessionRestorePolicy) -> Vec<AnyWindowHandle> {
+        Session::restore(self, policy)
+    }
+
+    /// Returns the accent color the user picked in the desktop's settings, if the platform
+    /// exposes one.
+    pub fn system_accent_color(&self) -> Option<Hsla> {
+        self.platform.accent_color()
+    }
+
+    /// Invokes a handler when the user picks another accent color in the desktop's settings.
+    pub fn on_system_accent_color_change<F>(&self, mut callback: F) -> Subscription
+    where
+        F: 'static + FnMut(&mut App),
+    {
+        let (subscription, activate) = self.accent_color_observers.insert(
+            (),
+            Box::new(move |cx| {
+                callback(cx);
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
     /// Returns the window button layout configuration when supported.
     pub fn button_layout(&self) -> Option<WindowButtonLayout> {
         self.platform.button_layout()
     }
 
+    /// Returns how far the mouse has to move with the button held down before a drag starts.
+    pub fn drag_threshold(&self) -> Pixels {
+        self.platform.drag_threshold()
+    }
+
     /// Reads data from the platform clipboard.
     pub fn read_from_clipboard(&self) -> Option<ClipboardItem> {
         self.platform.read_from_clipboard()
@@ -1338,6 +1818,19 @@ impl App {
         self.platform.register_url_scheme(scheme)
     }
 
+    /// Sets whether the app is launched when the user logs in, with `args` as its command line
+    /// arguments.
+    ///
+    /// Sandboxed apps have to ask the user first, so this can fail even where it's supported.
+    pub fn set_autostart(&self, enabled: bool, args: &[String]) -> Task<Result<()>> {
+        self.platform.set_autostart(enabled, args)
+    }
+
+    /// Returns whether the app is launched when the user logs in.
+    pub fn autostart_enabled(&self) -> bool {
+        self.platform.autostart_enabled()
+    }
+
     /// Returns the full pathname of the current app bundle.
     ///
     /// Returns an error if the app is not being run from a bundle.
@@ -1352,6 +1845,111 @@ impl App {
         self.platform.compositor_name()
     }
 
+    /// Unlocks a session locked by opening [`WindowKind::SessionLock`](crate::WindowKind) windows,
+    /// closing them. Only call this once the user has been authenticated.
+    ///
+    /// Does nothing on platforms without session locking.
+    pub fn unlock_session(&self) {
+        self.platform.unlock_session()
+    }
+
+    /// Returns the toplevel windows of other applications, for building taskbars and docks.
+    ///
+    /// Only supported on Wayland compositors implementing `zwlr_foreign_toplevel_manager_v1` or
+    /// `ext_foreign_toplevel_list_v1`; returns an empty list elsewhere.
+    pub fn foreign_toplevels(&self) -> Vec<ForeignToplevel> {
+        self.platform.foreign_toplevels()
+    }
+
+    /// Invokes a handler when another application's toplevel window is opened, changed or closed.
+    pub fn on_foreign_toplevel_event<F>(&self, mut callback: F) -> Subscription
+    where
+        F: 'static + FnMut(&ForeignToplevelEvent, &mut App),
+    {
+        let (subscription, activate) = self.foreign_toplevel_observers.insert(
+            (),
+            Box::new(move |event, cx| {
+                callback(event, cx);
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
+    /// Asks the compositor to activate, minimize, maximize or close another application's
+    /// toplevel window.
+    ///
+    /// Fails when the compositor only lets us list toplevels, or doesn't support either protocol.
+    pub fn request_foreign_toplevel(
+        &self,
+        id: ForeignToplevelId,
+        request: ForeignToplevelRequest,
+    ) -> Result<()> {
+        self.platform.request_foreign_toplevel(id, request)
+    }
+
+    /// Shows an icon in the system tray, replacing the previous one, or removes it when given
+    /// `None`.
+    ///
+    /// Fails on platforms without a system tray, or when no tray host is running.
+    pub fn set_tray_icon(&self, tray_icon: Option<TrayIcon>) -> Result<()> {
+        self.platform
+            .set_tray_icon(tray_icon, &self.keymap.borrow())
+    }
+
+    /// Invokes a handler when the tray icon is clicked or scrolled over.
+    pub fn on_tray_event<F>(&self, mut callback: F) -> Subscription
+    where
+        F: 'static + FnMut(&TrayEvent, &mut App),
+    {
+        let (subscription, activate) = self.tray_event_observers.insert(
+            (),
+            Box::new(move |event, cx| {
+                callback(event, cx);
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
+    /// Shows a desktop notification, resolving to a handle for updating or withdrawing it once
+    /// the notification server has accepted it.
+    ///
+    /// Clicking the notification or one of its buttons dispatches the corresponding action as if
+    /// it had been dispatched from the active window.
+    pub fn show_notification(
+        &mut self,
+        notification: Notification,
+    ) -> Task<Result<NotificationHandle>> {
+        let id = NotificationId(post_inc(&mut self.next_notification_id));
+        let shown = self.post_notification(id, notification);
+        self.foreground_executor.spawn(async move {
+            shown.await?;
+            Ok(NotificationHandle { id })
+        })
+    }
+
+    pub(crate) fn post_notification(
+        &mut self,
+        id: NotificationId,
+        notification: Notification,
+    ) -> Task<Result<()>> {
+        let shown = self.platform.show_notification(id, &notification);
+        self.spawn(async move |cx| {
+            shown.await?;
+            cx.update(|cx| cx.shown_notifications.insert(id, notification));
+            Ok(())
+        })
+    }
+
+    pub(crate) fn withdraw_notification(&mut self, id: NotificationId) {
+        if self.shown_notifications.remove(&id).is_some() {
+            self.platform.withdraw_notification(id);
+        }
+    }
+
     /// Returns the file URL of the executable with the specified name in the application bundle
     pub fn path_for_auxiliary_executable(&self, name: &str) -> Result<PathBuf> {
         self.platform.path_for_auxiliary_executable(name)
@@ -2003,6 +2601,12 @@ impl App {
 
     /// Register key bindings.
     pub fn bind_keys(&mut self, bindings: impl IntoIterator<Item = KeyBinding>) {
+        let (global_shortcuts, bindings): (Vec<_>, Vec<_>) =
+            bindings.into_iter().partition(KeyBinding::is_global);
+        if !global_shortcuts.is_empty() {
+            self.global_shortcuts.extend(global_shortcuts);
+            self.update_global_shortcuts();
+        }
         self.keymap.borrow_mut().add_bindings(bindings);
         self.pending_effects.push_back(Effect::RefreshWindows);
     }
@@ -2010,9 +2614,81 @@ impl App {
     /// Clear all key bindings in the app.
     pub fn clear_key_bindings(&mut self) {
         self.keymap.borrow_mut().clear();
+        if !self.global_shortcuts.is_empty() {
+            self.global_shortcuts.clear();
+            self.update_global_shortcuts();
+        }
         self.pending_effects.push_back(Effect::RefreshWindows);
     }
 
+    /// Registers the global shortcuts with the platform once the current effect cycle is done,
+    /// as keymaps are usually reloaded by clearing all bindings and binding them again.
+    fn update_global_shortcuts(&mut self) {
+        if mem::replace(&mut self.global_shortcuts_dirty, true) {
+            return;
+        }
+        self.defer(|cx| {
+            cx.global_shortcuts_dirty = false;
+            cx.global_shortcut_actions.clear();
+            let mut shortcuts = Vec::<GlobalShortcut>::new();
+            for binding in &cx.global_shortcuts {
+                let [keystroke] = binding.keystrokes() else {
+                    log::error!("Global shortcuts must be a single keystroke: {binding:?}");
+                    continue;
+                };
+                let name = binding.action().name();
+                let id = GlobalShortcutId(match binding.action_input() {
+                    Some(input) => format!("{name} {input}").into(),
+                    None => name.into(),
+                });
+                // Later bindings for the same action take precedence, as they do in the keymap.
+                shortcuts.retain(|shortcut| shortcut.id != id);
+                shortcuts.push(GlobalShortcut {
+                    id: id.clone(),
+                    description: name.into(),
+                    keystroke: keystroke.inner().clone(),
+                });
+                cx.global_shortcut_actions
+                    .insert(id, binding.action().boxed_clone());
+            }
+            cx.platform.set_global_shortcuts(shortcuts);
+        });
+    }
+
+    fn handle_global_shortcut(&mut self, id: GlobalShortcutId, phase: GlobalShortcutPhase) {
+        let Some(action) = self
+            .global_shortcut_actions
+            .get(&id)
+            .map(|action| action.boxed_clone())
+        else {
+            return;
+        };
+        self.global_shortcut_observers
+            .clone()
+            .retain(&(), |callback| callback(action.as_ref(), phase, self));
+        if phase == GlobalShortcutPhase::Activated {
+            self.dispatch_action(action.as_ref());
+        }
+    }
+
+    /// Registers a callback for when a global shortcut is pressed or released, even while none
+    /// of the application's windows has focus. Pressing one also dispatches its action, so this
+    /// is only needed to react to releases, e.g. for push-to-talk.
+    pub fn on_global_shortcut<F>(&self, mut callback: F) -> Subscription
+    where
+        F: 'static + FnMut(&dyn Action, GlobalShortcutPhase, &mut App),
+    {
+        let (subscription, activate) = self.global_shortcut_observers.insert(
+            (),
+            Box::new(move |action, phase, cx| {
+                callback(action, phase, cx);
+                true
+            }),
+        );
+        activate();
+        subscription
+    }
+
     /// Get all key bindings in the app.
     pub fn key_bindings(&self) -> Rc<RefCell<Keymap>> {
         self.keymap.clone()
@@ -2207,6 +2883,22 @@ impl App {
         self.platform.perform_dock_menu_action(action);
     }
 
+    /// Shows a count badge on the application's icon in the dock, or hides it when `None`.
+    pub fn set_dock_badge(&self, badge: Option<u32>) {
+        self.platform.set_dock_badge(badge);
+    }
+
+    /// Shows a progress bar on the application's icon in the dock, or hides it when `None`.
+    /// The progress is clamped to `0.0..=1.0`.
+    pub fn set_dock_progress(&self, progress: Option<f32>) {
+        self.platform.set_dock_progress(progress);
+    }
+
+    /// Marks the application's icon in the dock as requesting the user's attention.
+    pub fn set_dock_urgent(&self, urgent: bool) {
+        self.platform.set_dock_urgent(urgent);
+    }
+
     /// Adds given path to the bottom of the list of recent paths for the application.
     /// The list is usually shown on the application icon's context menu in the dock,
     /// and allows to open the recent files via that context menu.
@@ -2215,6 +2907,15 @@ impl App {
         self.platform.add_recent_document(path);
     }
 
+    /// Returns the paths added with [`App::add_recent_document`] that still exist, most recently
+    /// opened first. On Linux the list is shared with other applications, so it reflects files
+    /// removed from the history elsewhere.
+    ///
+    /// Returns an empty list on platforms that don't expose the history to applications.
+    pub fn recent_documents(&self) -> Vec<PathBuf> {
+        self.platform.recent_documents()
+    }
+
     /// Updates the jump list with the updated list of recent paths for the application, only used on Windows for now.
     /// Note that this also sets the dock menu on Windows.
     pub fn update_jump_list(
@@ -2293,7 +2994,9 @@ impl App {
 
     /// Gets the cursor style of the currently active drag operation.
     pub fn active_drag_cursor_style(&self) -> Option<CursorStyle> {
-        self.active_drag.as_ref().and_then(|drag| drag.cursor_style)
+        self.active_drag
+            .as_ref()
+            .and_then(|drag| drag.cursor_style.clone())
     }
 
     /// Stops active drag and clears any related effects.
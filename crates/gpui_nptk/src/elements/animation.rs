@@ -2,7 +2,8 @@ use scheduler::Instant;
 use std::{rc::Rc, time::Duration};
 
 use crate::{
-    AnyElement, App, Element, ElementId, GlobalElementId, InspectorElementId, IntoElement, Window,
+    AccessibilityPreferences, AnyElement, App, Element, ElementId, GlobalElementId,
+    InspectorElementId, IntoElement, Window,
 };
 
 pub use easing::*;
@@ -141,10 +142,23 @@ impl<E: IntoElement + 'static> Element for AnimationElement<E> {
                 start: Instant::now(),
                 animation_ix: 0,
             });
+
+            let reduce_motion = cx.global::<AccessibilityPreferences>().reduce_motion;
+            if reduce_motion {
+                // Skip ahead to the end state instead of playing one-shot animations.
+                while state.animation_ix < self.animations.len() - 1
+                    && self.animations[state.animation_ix].oneshot
+                {
+                    state.animation_ix += 1;
+                }
+            }
             let animation_ix = state.animation_ix;
 
             let mut delta = state.start.elapsed().as_secs_f32()
                 / self.animations[animation_ix].duration.as_secs_f32();
+            if reduce_motion && self.animations[animation_ix].oneshot {
+                delta = f32::INFINITY;
+            }
 
             let mut done = false;
             if delta > 1.0 {
@@ -170,7 +184,12 @@ impl<E: IntoElement + 'static> Element for AnimationElement<E> {
             let element = self.element.take().expect("should only be called once");
             let mut element = (self.animator)(element, animation_ix, delta).into_any_element();
 
-            if !done {
+            // Repeating animations never finish on their own, so they hold still while the app
+            // saves power or the user asked for reduced motion. The next refresh picks them up
+            // again.
+            let paused = !self.animations[animation_ix].oneshot
+                && (reduce_motion || cx.should_throttle_for_power());
+            if !done && !paused {
                 window.request_animation_frame();
             }
 
@@ -259,3 +278,40 @@ mod easing {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc, time::Duration};
+
+    use crate::{
+        self as gpui, AccessibilityPreferences, Animation, AnimationExt as _, TestAppContext, div,
+        point, px, size,
+    };
+
+    #[gpui::test]
+    fn test_reduce_motion_skips_oneshot_animations(cx: &mut TestAppContext) {
+        cx.set_global(AccessibilityPreferences {
+            reduce_motion: true,
+            ..Default::default()
+        });
+        let cx = cx.add_empty_window();
+
+        let frames = Rc::new(RefCell::new(Vec::new()));
+        cx.draw(point(px(0.), px(0.)), size(px(100.), px(100.)), |_, _| {
+            let frames = frames.clone();
+            div().with_animations(
+                "fade-in",
+                vec![
+                    Animation::new(Duration::from_secs(1)),
+                    Animation::new(Duration::from_secs(1)),
+                ],
+                move |this, animation_ix, delta| {
+                    frames.borrow_mut().push((animation_ix, delta));
+                    this
+                },
+            )
+        });
+
+        assert_eq!(*frames.borrow(), [(1, 1.0)]);
+    }
+}
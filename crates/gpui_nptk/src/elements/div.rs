@@ -45,7 +45,6 @@ use std::{
 
 use super::ImageCacheProvider;
 
-const DRAG_THRESHOLD: f64 = 2.;
 const DEFAULT_TOOLTIP_SHOW_DELAY: Duration = Duration::from_millis(500);
 const HOVERABLE_TOOLTIP_HIDE_DELAY: Duration = Duration::from_millis(500);
 
@@ -2283,12 +2282,17 @@ impl Interactivity {
                                             );
 
                                             if let Some(drag) = cx.active_drag.as_ref() {
-                                                if let Some(mouse_cursor) = drag.cursor_style {
-                                                    window.set_window_cursor_style(mouse_cursor);
+                                                if let Some(mouse_cursor) = &drag.cursor_style {
+                                                    window.set_window_cursor_style(
+                                                        mouse_cursor.clone(),
+                                                    );
                                                 }
                                             } else {
-                                                if let Some(mouse_cursor) = style.mouse_cursor {
-                                                    window.set_cursor_style(mouse_cursor, hitbox);
+                                                if let Some(mouse_cursor) = &style.mouse_cursor {
+                                                    window.set_cursor_style(
+                                                        mouse_cursor.clone(),
+                                                        hitbox,
+                                                    );
                                                 }
                                             }
 
@@ -2595,7 +2599,7 @@ impl Interactivity {
             }
         }
 
-        let drag_cursor_style = self.base_style.as_ref().mouse_cursor;
+        let drag_cursor_style = self.base_style.as_ref().mouse_cursor.clone();
 
         let mut drag_listener = mem::take(&mut self.drag_listener);
         let drop_listeners = mem::take(&mut self.drop_listeners);
@@ -2682,7 +2686,8 @@ impl Interactivity {
                         let mut pending_mouse_down = pending_mouse_down.borrow_mut();
                         if let Some(mouse_down) = pending_mouse_down.clone()
                             && !cx.has_active_drag()
-                            && (event.position - mouse_down.position).magnitude() > DRAG_THRESHOLD
+                            && (event.position - mouse_down.position).magnitude()
+                                > f64::from(cx.drag_threshold())
                             && let Some((drag_value, drag_listener)) = drag_listener.take()
                             && mouse_down.button == MouseButton::Left
                         {
@@ -2694,7 +2699,7 @@ impl Interactivity {
                                 view: drag,
                                 value: drag_value,
                                 cursor_offset,
-                                cursor_style: drag_cursor_style,
+                                cursor_style: drag_cursor_style.clone(),
                             });
                             pending_mouse_down.take();
                             window.refresh();
@@ -3147,7 +3152,7 @@ impl Interactivity {
                     }
                 }
 
-                style.mouse_cursor = drag.cursor_style;
+                style.mouse_cursor = drag.cursor_style.clone();
                 cx.active_drag = Some(drag);
             }
         }
@@ -33,6 +33,7 @@ mod keymap;
 mod path_builder;
 mod platform;
 pub mod prelude;
+mod print;
 /// Profiling utilities for task, frame, and thread performance tracking.
 pub mod profiler;
 #[cfg(any(
@@ -45,6 +46,7 @@ pub mod profiler;
 #[expect(missing_docs)]
 pub mod queue;
 mod scene;
+mod session;
 mod shared_uri;
 mod style;
 mod styled;
@@ -136,11 +138,13 @@ use key_dispatch::*;
 pub use keymap::*;
 pub use path_builder::*;
 pub use platform::*;
+pub use print::*;
 pub use profiler::*;
 #[cfg(any(target_os = "windows", target_os = "linux", target_family = "wasm"))]
 pub use queue::{PriorityQueueReceiver, PriorityQueueSender};
 pub use refineable::*;
 pub use scene::*;
+pub use session::*;
 pub use shared_uri::*;
 use std::{any::Any, future::Future};
 pub use style::*;
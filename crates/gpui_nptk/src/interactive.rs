@@ -423,6 +423,40 @@ impl MouseMoveEvent {
     }
 }
 
+/// Relative pointer motion from the platform, reported while the pointer is locked or confined
+/// with [`Window::lock_pointer`](crate::Window::lock_pointer) or
+/// [`Window::confine_pointer`](crate::Window::confine_pointer).
+///
+/// Unlike [`MouseMoveEvent`], motion keeps being reported when the cursor can't move any further.
+#[derive(Clone, Debug, Default)]
+pub struct RawMouseMotionEvent {
+    /// How far the pointer moved, with pointer acceleration applied.
+    pub delta: Point<Pixels>,
+
+    /// How far the pointer moved, without pointer acceleration. Platforms that can't report
+    /// unaccelerated motion report the same value as `delta`.
+    pub unaccelerated_delta: Point<Pixels>,
+
+    /// The modifiers that were held down when the pointer was moved.
+    pub modifiers: Modifiers,
+}
+
+impl Sealed for RawMouseMotionEvent {}
+impl InputEvent for RawMouseMotionEvent {
+    fn to_platform_input(self) -> PlatformInput {
+        PlatformInput::RawMouseMotion(self)
+    }
+}
+impl MouseEvent for RawMouseMotionEvent {}
+
+impl Deref for RawMouseMotionEvent {
+    type Target = Modifiers;
+
+    fn deref(&self) -> &Self::Target {
+        &self.modifiers
+    }
+}
+
 /// A mouse wheel event from the platform.
 #[derive(Clone, Debug, Default)]
 pub struct ScrollWheelEvent {
@@ -662,6 +696,8 @@ pub enum PlatformInput {
     MousePressure(MousePressureEvent),
     /// The mouse was moved.
     MouseMove(MouseMoveEvent),
+    /// The pointer was moved while locked or confined.
+    RawMouseMotion(RawMouseMotionEvent),
     /// The mouse exited the window.
     MouseExited(MouseExitEvent),
     /// The scroll wheel was used.
@@ -681,6 +717,7 @@ impl PlatformInput {
             PlatformInput::MouseDown(event) => Some(event),
             PlatformInput::MouseUp(event) => Some(event),
             PlatformInput::MouseMove(event) => Some(event),
+            PlatformInput::RawMouseMotion(event) => Some(event),
             PlatformInput::MousePressure(event) => Some(event),
             PlatformInput::MouseExited(event) => Some(event),
             PlatformInput::ScrollWheel(event) => Some(event),
@@ -697,6 +734,7 @@ impl PlatformInput {
             PlatformInput::MouseDown(_) => None,
             PlatformInput::MouseUp(_) => None,
             PlatformInput::MouseMove(_) => None,
+            PlatformInput::RawMouseMotion(_) => None,
             PlatformInput::MousePressure(_) => None,
             PlatformInput::MouseExited(_) => None,
             PlatformInput::ScrollWheel(_) => None,
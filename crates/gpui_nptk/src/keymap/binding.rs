@@ -14,6 +14,7 @@ pub struct KeyBinding {
     pub(crate) meta: Option<KeyBindingMetaIndex>,
     /// The json input string used when building the keybinding, if any
     pub(crate) action_input: Option<SharedString>,
+    pub(crate) global: bool,
 }
 
 impl Clone for KeyBinding {
@@ -24,6 +25,7 @@ impl Clone for KeyBinding {
             context_predicate: self.context_predicate.clone(),
             meta: self.meta,
             action_input: self.action_input.clone(),
+            global: self.global,
         }
     }
 }
@@ -71,6 +73,7 @@ impl KeyBinding {
             context_predicate,
             meta: None,
             action_input,
+            global: false,
         })
     }
 
@@ -85,6 +88,17 @@ impl KeyBinding {
         self.meta = Some(meta);
     }
 
+    /// Make this binding a global shortcut, which is registered with the desktop instead of the
+    /// keymap and fires even while none of the application's windows has focus.
+    pub fn set_global(&mut self, global: bool) {
+        self.global = global;
+    }
+
+    /// Whether this binding is a global shortcut.
+    pub fn is_global(&self) -> bool {
+        self.global
+    }
+
     /// Check if the given keystrokes match this binding.
     pub fn match_keystrokes(&self, typed: &[impl AsKeystroke]) -> Option<bool> {
         if self.keystrokes.len() < typed.len() {
@@ -2,10 +2,25 @@ mod app_menu;
 mod keyboard;
 mod keystroke;
 
+pub mod foreign_toplevel;
+
+pub mod global_shortcut;
+
+pub mod inhibit;
+
 #[cfg(all(target_os = "linux", feature = "wayland"))]
 #[expect(missing_docs)]
 pub mod layer_shell;
 
+pub mod notification;
+
+pub mod popup;
+
+#[cfg(all(target_os = "linux", feature = "wayland"))]
+pub mod session_lock;
+
+pub mod tray;
+
 #[cfg(any(test, feature = "test-support"))]
 mod test;
 
@@ -31,17 +46,17 @@ pub(crate) type PlatformScreenCaptureFrame = core_video::image_buffer::CVImageBu
 use crate::{
     Action, AnyWindowHandle, App, AsyncWindowContext, BackgroundExecutor, Bounds,
     DEFAULT_WINDOW_SIZE, DevicePixels, DispatchEventResult, Font, FontId, FontMetrics, FontRun,
-    ForegroundExecutor, GlyphId, GpuSpecs, Hsla, ImageSource, Keymap, LineLayout, Pixels,
-    PlatformInput, Point, Priority, RenderGlyphParams, RenderImage, RenderImageParams,
-    RenderSvgParams, Scene, ShapedGlyph, ShapedRun, SharedString, Size, SvgRenderer,
-    SystemWindowTab, Task, ThreadTaskTimings, Window, WindowControlArea, hash, point, px, size,
+    ForegroundExecutor, Global, GlyphId, GpuSpecs, Hsla, ImageSource, Keymap, LineLayout, Pixels,
+    PlatformInput, Point, PrintOptions, Priority, RenderGlyphParams, RenderImage,
+    RenderImageParams, RenderSvgParams, Scene, ShapedGlyph, ShapedRun, SharedString, Size,
+    SvgRenderer, SystemWindowTab, Task, ThreadTaskTimings, Window, WindowControlArea, hash, point,
+    px, size,
 };
 use anyhow::Result;
 #[cfg(any(target_os = "linux", target_os = "freebsd"))]
 use anyhow::bail;
 use async_task::Runnable;
 use futures::channel::oneshot;
-#[cfg(any(test, feature = "test-support"))]
 use image::RgbaImage;
 use image::codecs::gif::GifDecoder;
 use image::{AnimationDecoder as _, Frame};
@@ -157,15 +172,39 @@ pub trait Platform: 'static {
 
     /// Returns the appearance of the application's windows.
     fn window_appearance(&self) -> WindowAppearance;
+    fn accent_color(&self) -> Option<Hsla> {
+        None
+    }
+    fn on_accent_color_change(&self, _callback: Box<dyn FnMut()>) {}
 
     /// Returns the window button layout configuration when supported.
     fn button_layout(&self) -> Option<WindowButtonLayout> {
         None
     }
 
+    /// Returns how far the mouse has to move with the button held down before a drag starts.
+    fn drag_threshold(&self) -> Pixels {
+        DEFAULT_DRAG_THRESHOLD
+    }
+
     fn open_url(&self, url: &str);
     fn on_open_urls(&self, callback: Box<dyn FnMut(Vec<String>)>);
     fn register_url_scheme(&self, url: &str) -> Task<Result<()>>;
+    fn set_autostart(&self, _enabled: bool, _args: &[String]) -> Task<Result<()>> {
+        Task::ready(Err(anyhow::anyhow!(
+            "Starting at login isn't supported on this platform"
+        )))
+    }
+    fn autostart_enabled(&self) -> bool {
+        false
+    }
+    /// Shows the print dialog and sends the PDF document to the chosen printer. Resolves once
+    /// the document has been handed over, or the user cancelled.
+    fn print_pdf(&self, _options: &PrintOptions, _pdf: Vec<u8>) -> Task<Result<()>> {
+        Task::ready(Err(anyhow::anyhow!(
+            "Printing isn't supported on this platform"
+        )))
+    }
 
     fn prompt_for_paths(
         &self,
@@ -190,7 +229,13 @@ pub trait Platform: 'static {
 
     fn set_dock_menu(&self, menu: Vec<MenuItem>, keymap: &Keymap);
     fn perform_dock_menu_action(&self, _action: usize) {}
+    fn set_dock_badge(&self, _badge: Option<u32>) {}
+    fn set_dock_progress(&self, _progress: Option<f32>) {}
+    fn set_dock_urgent(&self, _urgent: bool) {}
     fn add_recent_document(&self, _path: &Path) {}
+    fn recent_documents(&self) -> Vec<PathBuf> {
+        Vec::new()
+    }
     fn update_jump_list(
         &self,
         _menus: Vec<MenuItem>,
@@ -204,6 +249,21 @@ pub trait Platform: 'static {
 
     fn thermal_state(&self) -> ThermalState;
     fn on_thermal_state_change(&self, callback: Box<dyn FnMut()>);
+    fn power_state(&self) -> PowerState {
+        PowerState::default()
+    }
+    fn on_power_state_change(&self, _callback: Box<dyn FnMut()>) {}
+    fn accessibility_preferences(&self) -> AccessibilityPreferences {
+        AccessibilityPreferences::default()
+    }
+    fn on_accessibility_preferences_change(&self, _callback: Box<dyn FnMut()>) {}
+    fn inhibit(&self, _id: inhibit::InhibitorId, _flags: inhibit::InhibitFlags, _reason: &str) {}
+    fn uninhibit(&self, _id: inhibit::InhibitorId) {}
+    fn on_session_end_query(&self, _callback: Box<dyn FnMut()>) {}
+    fn on_save_session(&self, _callback: Box<dyn FnMut()>) {}
+    fn launched_by_session_manager(&self) -> bool {
+        false
+    }
 
     fn compositor_name(&self) -> &'static str {
         ""
@@ -242,6 +302,67 @@ pub trait Platform: 'static {
     fn keyboard_layout(&self) -> Box<dyn PlatformKeyboardLayout>;
     fn keyboard_mapper(&self) -> Rc<dyn PlatformKeyboardMapper>;
     fn on_keyboard_layout_change(&self, callback: Box<dyn FnMut()>);
+    fn on_displays_changed(&self, _callback: Box<dyn FnMut()>) {}
+
+    fn show_notification(
+        &self,
+        _id: notification::NotificationId,
+        _notification: &notification::Notification,
+    ) -> Task<Result<()>> {
+        Task::ready(Err(anyhow::anyhow!(
+            "Notifications aren't supported on this platform"
+        )))
+    }
+    fn withdraw_notification(&self, _id: notification::NotificationId) {}
+    fn on_notification_response(
+        &self,
+        _callback: Box<dyn FnMut(notification::NotificationId, notification::NotificationResponse)>,
+    ) {
+    }
+
+    fn set_tray_icon(&self, tray_icon: Option<tray::TrayIcon>, _keymap: &Keymap) -> Result<()> {
+        if tray_icon.is_some() {
+            return Err(anyhow::anyhow!(
+                "Tray icons aren't supported on this platform"
+            ));
+        }
+        Ok(())
+    }
+    fn on_tray_event(&self, _callback: Box<dyn FnMut(tray::TrayEvent)>) {}
+    fn on_tray_menu_action(&self, _callback: Box<dyn FnMut(&dyn Action)>) {}
+
+    fn set_global_shortcuts(&self, _shortcuts: Vec<global_shortcut::GlobalShortcut>) {}
+    fn on_global_shortcut(
+        &self,
+        _callback: Box<
+            dyn FnMut(global_shortcut::GlobalShortcutId, global_shortcut::GlobalShortcutPhase),
+        >,
+    ) {
+    }
+
+    fn claim_single_instance(&self, _app_id: &str) -> Result<SingleInstanceClaim> {
+        Ok(SingleInstanceClaim::Primary)
+    }
+
+    fn unlock_session(&self) {}
+
+    fn foreign_toplevels(&self) -> Vec<foreign_toplevel::ForeignToplevel> {
+        Vec::new()
+    }
+    fn on_foreign_toplevel_event(
+        &self,
+        _callback: Box<dyn FnMut(foreign_toplevel::ForeignToplevelEvent)>,
+    ) {
+    }
+    fn request_foreign_toplevel(
+        &self,
+        _id: foreign_toplevel::ForeignToplevelId,
+        _request: foreign_toplevel::ForeignToplevelRequest,
+    ) -> Result<()> {
+        Err(anyhow::anyhow!(
+            "Managing other applications' windows isn't supported on this platform"
+        ))
+    }
 }
 
 /// A handle to a platform's display, e.g. a monitor or laptop screen.
@@ -256,6 +377,21 @@ pub trait PlatformDisplay: Debug {
     /// Get the bounds for this display
     fn bounds(&self) -> Bounds<Pixels>;
 
+    /// Get the human readable name of this display, e.g. its connector like `DP-1`, if known.
+    fn name(&self) -> Option<SharedString> {
+        None
+    }
+
+    /// Get the scale factor windows on this display are rendered at.
+    fn scale_factor(&self) -> f32 {
+        1.0
+    }
+
+    /// Get the refresh rate of this display in Hz, if known.
+    fn refresh_rate(&self) -> Option<f32> {
+        None
+    }
+
     /// Get the visible bounds for this display, excluding taskbar/dock areas.
     /// This is the usable area where windows can be placed without being obscured.
     /// Defaults to the full display bounds if not overridden.
@@ -275,6 +411,87 @@ pub trait PlatformDisplay: Debug {
     }
 }
 
+/// A snapshot of a display's properties, as reported by [`crate::App::on_displays_changed`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DisplayInfo {
+    /// The ID of the display.
+    pub id: DisplayId,
+    /// The human readable name of the display, if known.
+    pub name: Option<SharedString>,
+    /// The bounds of the display in the global coordinate space.
+    pub bounds: Bounds<Pixels>,
+    /// The scale factor windows on the display are rendered at.
+    pub scale_factor: f32,
+    /// The refresh rate of the display in Hz, if known.
+    pub refresh_rate: Option<f32>,
+}
+
+impl DisplayInfo {
+    /// Takes a snapshot of the given display's properties.
+    pub fn new(display: &dyn PlatformDisplay) -> Self {
+        Self {
+            id: display.id(),
+            name: display.name(),
+            bounds: display.bounds(),
+            scale_factor: display.scale_factor(),
+            refresh_rate: display.refresh_rate(),
+        }
+    }
+}
+
+/// The displays that were connected, disconnected or changed, e.g. rescaled or moved, since the
+/// displays were last reported.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DisplaysChanged {
+    /// Displays that were connected.
+    pub added: Vec<DisplayInfo>,
+    /// Displays that were disconnected, as they were last seen.
+    pub removed: Vec<DisplayInfo>,
+    /// Displays whose properties changed, with their new properties.
+    pub changed: Vec<DisplayInfo>,
+}
+
+impl DisplaysChanged {
+    /// Compares two snapshots of the connected displays.
+    pub fn between(old: &[DisplayInfo], new: &[DisplayInfo]) -> Self {
+        let mut changes = Self::default();
+        for display in new {
+            match old.iter().find(|old_display| old_display.id == display.id) {
+                None => changes.added.push(display.clone()),
+                Some(old_display) if old_display != display => {
+                    changes.changed.push(display.clone())
+                }
+                Some(_) => {}
+            }
+        }
+        changes.removed = old
+            .iter()
+            .filter(|old_display| !new.iter().any(|display| display.id == old_display.id))
+            .cloned()
+            .collect();
+        changes
+    }
+
+    /// Whether no display was connected, disconnected or changed.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// The outcome of claiming single-instance mode with [`crate::Application::with_single_instance`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SingleInstanceClaim {
+    /// This process is the running instance and will receive later launches through
+    /// [`crate::Application::on_open_urls`] and [`crate::Application::on_reopen`].
+    Primary,
+    /// Another instance is already running and has been handed this launch's arguments.
+    Forwarded,
+}
+
+/// How far the mouse has to move with the button held down before a drag starts, on platforms
+/// that don't let the user configure it.
+pub const DEFAULT_DRAG_THRESHOLD: Pixels = px(2.);
+
 /// Thermal state of the system
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ThermalState {
@@ -288,6 +505,42 @@ pub enum ThermalState {
     Critical,
 }
 
+/// Power source and power saving state of the system
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PowerState {
+    /// Whether the system is running on battery rather than external power
+    pub on_battery: bool,
+    /// Whether the user asked the system to save power, e.g. by picking a power saver profile
+    pub low_power_mode: bool,
+    /// Charge of the system's batteries between 0 and 1, or `None` when there are none
+    pub battery_level: Option<f32>,
+}
+
+/// Accessibility settings the user picked for the whole desktop. Available as a global, which is
+/// updated when the user changes them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AccessibilityPreferences {
+    /// Whether the user asked for fewer animations. One-shot [`crate::Animation`]s jump to their
+    /// end and repeating ones hold still while this is set.
+    pub reduce_motion: bool,
+    /// Whether the user asked for interfaces with more contrast
+    pub high_contrast: bool,
+    /// The factor the user asked to scale text by, where 1.0 is the regular size
+    pub text_scale: f32,
+}
+
+impl Default for AccessibilityPreferences {
+    fn default() -> Self {
+        Self {
+            reduce_motion: false,
+            high_contrast: false,
+            text_scale: 1.0,
+        }
+    }
+}
+
+impl Global for AccessibilityPreferences {}
+
 /// Metadata for a given [ScreenCaptureSource]
 #[derive(Clone)]
 pub struct SourceMetadata {
@@ -610,6 +863,17 @@ pub struct RequestFrameOptions {
     pub force_render: bool,
 }
 
+/// Reports when a frame submitted through [`PlatformWindow::draw`] reached the screen.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct PresentationFeedback {
+    /// Which frame this is about, counting calls to [`PlatformWindow::draw`] from 1.
+    pub frame: u64,
+    /// When the frame started scanning out, or `None` if it was replaced before it was ever shown.
+    pub presented_at: Option<Instant>,
+    /// The refresh interval of the output the frame was shown on, if known.
+    pub refresh_interval: Option<Duration>,
+}
+
 #[expect(missing_docs)]
 pub trait PlatformWindow: HasWindowHandle + HasDisplayHandle {
     fn bounds(&self) -> Bounds<Pixels>;
@@ -655,6 +919,12 @@ pub trait PlatformWindow: HasWindowHandle + HasDisplayHandle {
     fn on_button_layout_changed(&self, _callback: Box<dyn FnMut()>) {}
     fn draw(&self, scene: &Scene);
     fn completed_frame(&self) {}
+    /// Registers a callback for when frames reach the screen, returning false if the platform
+    /// can't tell. Feedback is delivered in the order frames were drawn, but a frame that never
+    /// made it to the compositor may be skipped.
+    fn on_presentation_feedback(&self, _callback: Box<dyn FnMut(PresentationFeedback)>) -> bool {
+        false
+    }
     fn sprite_atlas(&self) -> Arc<dyn PlatformAtlas>;
     fn is_subpixel_rendering_supported(&self) -> bool;
 
@@ -704,6 +974,19 @@ pub trait PlatformWindow: HasWindowHandle + HasDisplayHandle {
         WindowControls::default()
     }
     fn set_client_inset(&self, _inset: Pixels) {}
+    fn set_idle_inhibited(&self, _inhibited: bool) {}
+    fn set_pointer_constraint(&self, _constraint: Option<PointerConstraint>) {}
+    #[cfg(all(target_os = "linux", feature = "wayland"))]
+    fn layer_shell_options(&self) -> Option<layer_shell::LayerShellOptions> {
+        None
+    }
+    #[cfg(all(target_os = "linux", feature = "wayland"))]
+    fn set_layer_shell_options(&self, _options: layer_shell::LayerShellOptions) {}
+    fn move_to_display(&self, _display_id: DisplayId) -> Result<()> {
+        Err(anyhow::anyhow!(
+            "Moving this window to another display isn't supported"
+        ))
+    }
     fn gpu_specs(&self) -> Option<GpuSpecs>;
 
     fn update_ime_position(&self, _bounds: Bounds<Pixels>);
@@ -727,10 +1010,14 @@ pub trait PlatformWindow: HasWindowHandle + HasDisplayHandle {
     /// Renders the given scene to a texture and returns the pixel data as an RGBA image.
     /// This does not present the frame to screen - useful for visual testing where we want
     /// to capture what would be rendered without displaying it or requiring the window to be visible.
-    #[cfg(any(test, feature = "test-support"))]
     fn render_to_image(&self, _scene: &Scene) -> Result<RgbaImage> {
         anyhow::bail!("render_to_image not implemented for this platform")
     }
+
+    /// Queues input to be handled as if the user had produced it.
+    fn inject_input(&self, _input: PlatformInput) -> Result<()> {
+        anyhow::bail!("inject_input not implemented for this platform")
+    }
 }
 
 /// A renderer for headless windows that can produce real rendered output.
@@ -1509,11 +1796,12 @@ pub struct WindowOptions {
     pub icon: Option<Arc<image::RgbaImage>>,
 
     /// Tab group name, allows opening the window as a native tab on macOS 10.12+. Windows with the same tabbing identifier will be grouped together.
+    /// On Linux, windows with the same tabbing identifier are hosted as tabs in one window.
     pub tabbing_identifier: Option<String>,
 }
 
 /// The variables that can be configured when creating a new window
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 #[cfg_attr(
     all(
         any(target_os = "linux", target_os = "freebsd"),
@@ -1562,12 +1850,12 @@ pub struct WindowParams {
     pub display_id: Option<DisplayId>,
 
     pub window_min_size: Option<Size<Pixels>>,
-    #[cfg(target_os = "macos")]
+    #[cfg(any(target_os = "macos", target_os = "linux", target_os = "freebsd"))]
     pub tabbing_identifier: Option<String>,
 }
 
 /// Represents the status of how a window should be opened.
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub enum WindowBounds {
     /// Indicates that the window should open in a windowed state with the given bounds.
     Windowed(Bounds<Pixels>),
@@ -1628,7 +1916,7 @@ impl Default for WindowOptions {
 }
 
 /// The options that can be configured for a window's titlebar
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct TitlebarOptions {
     /// The initial title of the window
     pub title: Option<SharedString>,
@@ -1659,9 +1947,22 @@ pub enum WindowKind {
     #[cfg(all(target_os = "linux", feature = "wayland"))]
     LayerShell(layer_shell::LayerShellOptions),
 
+    /// A Wayland lock screen surface, shown on the display given by
+    /// [`WindowOptions::display_id`] while the session is locked. Opening the first one locks the
+    /// session. GPUI doesn't cover displays by itself: open one of these per display, and another
+    /// when a display is connected while locked (see [`App::on_displays_changed`]), or the
+    /// compositor fills the uncovered displays with a solid color. The compositor gives these
+    /// windows keyboard focus until [`App::unlock_session`] is called.
+    #[cfg(all(target_os = "linux", feature = "wayland"))]
+    SessionLock,
+
     /// A window that appears on top of its parent window and blocks interaction with it
     /// until the modal window is closed
     Dialog,
+
+    /// A short-lived window positioned relative to a parent window, such as a menu or a
+    /// dropdown. Unlike [`WindowKind::PopUp`], it may extend past the edges of its parent.
+    AnchoredPopUp(popup::PopupOptions),
 }
 
 /// The appearance of the window, as defined by the operating system.
@@ -1717,6 +2018,17 @@ pub enum WindowBackgroundAppearance {
     MicaAltBackdrop,
 }
 
+/// How the pointer is held within a window, for views that track relative motion such as
+/// games or 3D viewports.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PointerConstraint {
+    /// The cursor stays where it is. Motion is only reported through
+    /// [`RawMouseMotionEvent`](crate::RawMouseMotionEvent).
+    Locked,
+    /// The cursor moves freely, but can't leave the window.
+    Confined,
+}
+
 /// The text rendering mode to use for drawing glyphs.
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub enum TextRenderingMode {
@@ -1809,7 +2121,7 @@ impl From<&str> for PromptButton {
 }
 
 /// The style of the cursor (pointer)
-#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Default, Debug, Serialize, Deserialize, JsonSchema)]
 pub enum CursorStyle {
     /// The default cursor
     #[default]
@@ -1894,6 +2206,99 @@ pub enum CursorStyle {
     /// A cursor indicating that the operation will result in a context menu
     /// corresponds to the CSS cursor value `context-menu`
     ContextualMenu,
+
+    /// A cursor drawn from an image, such as a brush outline
+    /// corresponds to the CSS cursor value `url(...)`
+    #[serde(skip)]
+    #[schemars(skip)]
+    Custom {
+        /// The cursor image at a scale factor of 1. Platforms resample it for
+        /// high-DPI displays.
+        image: Arc<RgbaImage>,
+        /// The point within the image that tracks the pointer position.
+        hotspot: Point<Pixels>,
+    },
+
+    /// A cursor that cycles through a list of images, starting over after the last
+    #[serde(skip)]
+    #[schemars(skip)]
+    Animated {
+        /// The frames of the animation, in the order they are shown.
+        frames: Arc<[CursorFrame]>,
+    },
+}
+
+impl CursorStyle {
+    /// Creates a cursor drawn from the given image, with the hotspot in the image's pixels.
+    pub fn custom(image: impl Into<Arc<RgbaImage>>, hotspot: Point<Pixels>) -> Self {
+        Self::Custom {
+            image: image.into(),
+            hotspot,
+        }
+    }
+
+    /// Creates a cursor that cycles through the given frames.
+    pub fn animated(frames: impl Into<Arc<[CursorFrame]>>) -> Self {
+        Self::Animated {
+            frames: frames.into(),
+        }
+    }
+
+    /// Whether this cursor is drawn from images supplied by the application rather than
+    /// taken from the system's cursor theme.
+    pub fn is_image(&self) -> bool {
+        matches!(self, Self::Custom { .. } | Self::Animated { .. })
+    }
+}
+
+// Image cursors are compared by identity rather than by their pixels, as the cursor style is
+// compared every time it's set.
+impl PartialEq for CursorStyle {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                Self::Custom { image, hotspot },
+                Self::Custom {
+                    image: other_image,
+                    hotspot: other_hotspot,
+                },
+            ) => Arc::ptr_eq(image, other_image) && hotspot == other_hotspot,
+            (
+                Self::Animated { frames },
+                Self::Animated {
+                    frames: other_frames,
+                },
+            ) => Arc::ptr_eq(frames, other_frames),
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+}
+
+impl Eq for CursorStyle {}
+
+impl Hash for CursorStyle {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Self::Custom { image, hotspot } => {
+                Arc::as_ptr(image).hash(state);
+                hotspot.hash(state);
+            }
+            Self::Animated { frames } => Arc::as_ptr(frames).cast::<()>().hash(state),
+            _ => {}
+        }
+    }
+}
+
+/// A single frame of an animated cursor.
+#[derive(Clone, Debug)]
+pub struct CursorFrame {
+    /// The frame's image at a scale factor of 1.
+    pub image: Arc<RgbaImage>,
+    /// The point within the image that tracks the pointer position.
+    pub hotspot: Point<Pixels>,
+    /// How long the frame stays on screen before the next one is shown.
+    pub duration: Duration,
 }
 
 /// A clipboard item that should be copied to the clipboard
@@ -2590,4 +2995,81 @@ mod tests {
     fn test_window_button_layout_parse_all_invalid() {
         assert!(WindowButtonLayout::parse("asdfghjkl").is_err());
     }
+
+    fn popup_options(anchor_rect: Bounds<Pixels>) -> popup::PopupOptions {
+        let parent = crate::WindowHandle::<crate::Empty>::new(crate::WindowId::from(1)).into();
+        popup::PopupOptions::new(parent, anchor_rect)
+    }
+
+    #[test]
+    fn test_popup_resolve_bounds_unconstrained() {
+        let options = popup_options(Bounds::new(point(px(10.), px(10.)), size(px(50.), px(20.))));
+        let display = Bounds::new(point(px(0.), px(0.)), size(px(1000.), px(1000.)));
+
+        let bounds =
+            options.resolve_bounds(point(px(100.), px(100.)), size(px(200.), px(300.)), display);
+        assert_eq!(
+            bounds,
+            Bounds::new(point(px(110.), px(130.)), size(px(200.), px(300.)))
+        );
+    }
+
+    #[test]
+    fn test_popup_resolve_bounds_flips() {
+        let options = popup_options(Bounds::new(point(px(10.), px(10.)), size(px(50.), px(20.))));
+        let display = Bounds::new(point(px(0.), px(0.)), size(px(1000.), px(1000.)));
+
+        // There's no room below or to the right of the anchor, so the popup flips to the other
+        // side along both axes.
+        let bounds =
+            options.resolve_bounds(point(px(900.), px(800.)), size(px(200.), px(300.)), display);
+        assert_eq!(
+            bounds,
+            Bounds::new(point(px(760.), px(510.)), size(px(200.), px(300.)))
+        );
+    }
+
+    #[test]
+    fn test_popup_resolve_bounds_resizes() {
+        let mut options = popup_options(Bounds::new(point(px(0.), px(0.)), size(px(10.), px(10.))));
+        options.constraint_adjustment = popup::PopupConstraintAdjustment {
+            flip_x: false,
+            flip_y: false,
+            slide_x: false,
+            slide_y: false,
+            resize_x: false,
+            resize_y: true,
+        };
+        let display = Bounds::new(point(px(0.), px(0.)), size(px(500.), px(500.)));
+
+        let bounds =
+            options.resolve_bounds(point(px(0.), px(390.)), size(px(100.), px(300.)), display);
+        assert_eq!(
+            bounds,
+            Bounds::new(point(px(0.), px(400.)), size(px(100.), px(100.)))
+        );
+    }
+
+    #[test]
+    fn test_displays_changed_between() {
+        let display = |id: u64, scale_factor: f32| DisplayInfo {
+            id: DisplayId(id),
+            name: Some(format!("DP-{id}").into()),
+            bounds: Bounds::new(
+                point(px(id as f32 * 1920.), px(0.)),
+                size(px(1920.), px(1080.)),
+            ),
+            scale_factor,
+            refresh_rate: Some(60.),
+        };
+
+        let old = [display(1, 1.), display(2, 1.), display(3, 1.)];
+        let new = [display(1, 1.), display(2, 2.), display(4, 1.)];
+        let changes = DisplaysChanged::between(&old, &new);
+        assert_eq!(changes.added, [display(4, 1.)]);
+        assert_eq!(changes.removed, [display(3, 1.)]);
+        assert_eq!(changes.changed, [display(2, 2.)]);
+
+        assert!(DisplaysChanged::between(&new, &new).is_empty());
+    }
 }
@@ -0,0 +1,64 @@
+//! Listing and controlling the windows of other applications, for taskbars and docks.
+
+use crate::DisplayId;
+
+/// Identifies a toplevel window of another application for as long as it stays open.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ForeignToplevelId(pub u64);
+
+/// The window manager state of a foreign toplevel.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ForeignToplevelState {
+    /// The toplevel is maximized.
+    pub maximized: bool,
+    /// The toplevel is minimized.
+    pub minimized: bool,
+    /// The toplevel has keyboard focus.
+    pub activated: bool,
+    /// The toplevel is fullscreen.
+    pub fullscreen: bool,
+}
+
+/// A toplevel window belonging to another application, as reported by the compositor.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ForeignToplevel {
+    /// The identifier used to refer to this toplevel in requests.
+    pub id: ForeignToplevelId,
+    /// The title of the toplevel.
+    pub title: String,
+    /// The application ID of the toplevel, usually the name of its desktop entry.
+    pub app_id: String,
+    /// The window manager state of the toplevel. Always the default when the compositor only
+    /// supports listing toplevels.
+    pub state: ForeignToplevelState,
+    /// The displays the toplevel is currently shown on.
+    pub displays: Vec<DisplayId>,
+}
+
+/// A change to the set of foreign toplevels.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ForeignToplevelEvent {
+    /// A toplevel was opened.
+    Added(ForeignToplevel),
+    /// The title, application ID, state or displays of a toplevel changed.
+    Changed(ForeignToplevel),
+    /// A toplevel was closed.
+    Removed(ForeignToplevelId),
+}
+
+/// A request to the compositor to change a foreign toplevel.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ForeignToplevelRequest {
+    /// Focus the toplevel and raise it, unminimizing it if necessary.
+    Activate,
+    /// Minimize the toplevel.
+    Minimize,
+    /// Restore the toplevel from being minimized.
+    Unminimize,
+    /// Maximize the toplevel.
+    Maximize,
+    /// Restore the toplevel from being maximized.
+    Unmaximize,
+    /// Ask the toplevel's application to close it.
+    Close,
+}
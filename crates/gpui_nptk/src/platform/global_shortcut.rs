@@ -0,0 +1,30 @@
+//! Keyboard shortcuts that reach the application while none of its windows has focus.
+
+use crate::{Keystroke, SharedString};
+
+/// Identifies a global shortcut. Derived from the bound action, so that desktops which let the
+/// user change the trigger can remember their choice across launches.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GlobalShortcutId(pub SharedString);
+
+/// A keystroke that's reported to the application even while none of its windows has focus.
+/// Bound from keymap sections marked `"global": true`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GlobalShortcut {
+    /// Identifies the shortcut in [`GlobalShortcutPhase`] reports.
+    pub id: GlobalShortcutId,
+    /// Shown by desktops that let the user review the shortcuts of each application.
+    pub description: SharedString,
+    /// The keystroke to bind. Desktops that ask the user to confirm global shortcuts only treat
+    /// this as a suggestion.
+    pub keystroke: Keystroke,
+}
+
+/// Whether a global shortcut was pressed or released.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GlobalShortcutPhase {
+    /// The shortcut was pressed, which also dispatches its action.
+    Activated,
+    /// The shortcut was released.
+    Deactivated,
+}
@@ -0,0 +1,56 @@
+//! Holding off logout, suspend and idle while the application is busy.
+
+use std::rc::Rc;
+
+use bitflags::bitflags;
+
+use crate::Platform;
+
+bitflags! {
+    /// The session actions an [`Inhibitor`] holds off.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct InhibitFlags: u32 {
+        /// Logging out, shutting down or rebooting.
+        const LOGOUT = 1;
+        /// Switching to another user.
+        const USER_SWITCH = 1 << 1;
+        /// Suspending the system.
+        const SUSPEND = 1 << 2;
+        /// Marking the session as idle, which may start the screensaver or lock the screen.
+        const IDLE = 1 << 3;
+    }
+}
+
+/// Identifies an [`Inhibitor`] for as long as the application is running.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct InhibitorId(pub u64);
+
+/// Holds off the session actions it was created with until it's dropped. Created with
+/// [`crate::App::inhibit`].
+///
+/// Session managers usually show the reason to the user and let them proceed anyway, so an
+/// inhibitor is a request rather than a guarantee.
+#[must_use = "the inhibitor is released when dropped"]
+pub struct Inhibitor {
+    pub(crate) id: InhibitorId,
+    pub(crate) platform: Rc<dyn Platform>,
+}
+
+impl Inhibitor {
+    /// Returns the identifier of this inhibitor.
+    pub fn id(&self) -> InhibitorId {
+        self.id
+    }
+}
+
+impl Drop for Inhibitor {
+    fn drop(&mut self) {
+        self.platform.uninhibit(self.id);
+    }
+}
+
+impl std::fmt::Debug for Inhibitor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Inhibitor").field("id", &self.id).finish()
+    }
+}
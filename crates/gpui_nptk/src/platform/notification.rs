@@ -0,0 +1,95 @@
+//! Desktop notifications, shown by the notification server outside of the application's
+//! windows.
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use crate::{Action, App, SharedString, Task};
+
+/// Identifies a notification for as long as the application is running.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NotificationId(pub u64);
+
+/// How urgently a notification should be brought to the user's attention.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum NotificationUrgency {
+    /// Informational, may be shown without interrupting the user.
+    Low,
+    /// The usual level for most notifications.
+    #[default]
+    Normal,
+    /// Requires the user's attention, and may stay visible until dismissed.
+    Critical,
+}
+
+/// The icon shown alongside a notification.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NotificationIcon {
+    /// An icon from the desktop's icon theme, e.g. `document-save`.
+    Named(SharedString),
+    /// An image file on disk.
+    File(PathBuf),
+}
+
+/// A button shown on a notification.
+#[derive(Debug)]
+pub struct NotificationAction {
+    /// The text on the button.
+    pub label: SharedString,
+    /// The action dispatched when the button is clicked.
+    pub action: Box<dyn Action>,
+}
+
+/// A desktop notification, shown with [`App::show_notification`].
+#[derive(Debug, Default)]
+pub struct Notification {
+    /// The summary line of the notification.
+    pub title: SharedString,
+    /// Additional text shown below the title.
+    pub body: Option<SharedString>,
+    /// The icon shown alongside the notification. Defaults to the application's icon.
+    pub icon: Option<NotificationIcon>,
+    /// How urgently the notification should be brought to the user's attention.
+    pub urgency: NotificationUrgency,
+    /// Buttons shown on the notification, if the notification server supports them.
+    pub actions: Vec<NotificationAction>,
+    /// The action dispatched when the notification itself is clicked.
+    pub default_action: Option<Box<dyn Action>>,
+}
+
+/// Which part of a notification the user clicked, or that it was closed without being clicked.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NotificationResponse {
+    /// The notification itself.
+    Default,
+    /// The button at this index in [`Notification::actions`].
+    Action(usize),
+    /// The notification was dismissed by the user or expired. Not reported on Linux when
+    /// notifications go through the XDG desktop portal, which doesn't tell applications about
+    /// dismissals.
+    Dismissed,
+}
+
+/// A notification that has been shown, which can be replaced or withdrawn.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct NotificationHandle {
+    pub(crate) id: NotificationId,
+}
+
+impl NotificationHandle {
+    /// Returns the identifier of this notification.
+    pub fn id(&self) -> NotificationId {
+        self.id
+    }
+
+    /// Replaces the contents of the notification, showing it again if it was dismissed.
+    pub fn update(&self, notification: Notification, cx: &mut App) -> Task<Result<()>> {
+        cx.post_notification(self.id, notification)
+    }
+
+    /// Removes the notification if it's still shown.
+    pub fn withdraw(&self, cx: &mut App) {
+        cx.withdraw_notification(self.id)
+    }
+}
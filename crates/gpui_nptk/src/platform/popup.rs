@@ -0,0 +1,178 @@
+//! Popup windows positioned relative to a parent window, such as menus and tooltips that
+//! may extend past the parent's edges.
+
+use crate::{Anchor, AnyWindowHandle, Axis, Bounds, Pixels, Point, Size, point};
+
+/// How a popup may be adjusted when its requested position would place it outside of the
+/// display it's shown on. Adjustments are attempted in order: flip, then slide, then resize.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PopupConstraintAdjustment {
+    /// Mirror the popup to the other side of the anchor rectangle horizontally.
+    pub flip_x: bool,
+    /// Mirror the popup to the other side of the anchor rectangle vertically.
+    pub flip_y: bool,
+    /// Move the popup horizontally until it fits.
+    pub slide_x: bool,
+    /// Move the popup vertically until it fits.
+    pub slide_y: bool,
+    /// Shrink the popup horizontally until it fits.
+    pub resize_x: bool,
+    /// Shrink the popup vertically until it fits.
+    pub resize_y: bool,
+}
+
+impl Default for PopupConstraintAdjustment {
+    fn default() -> Self {
+        Self {
+            flip_x: true,
+            flip_y: true,
+            slide_x: true,
+            slide_y: true,
+            resize_x: false,
+            resize_y: false,
+        }
+    }
+}
+
+/// Options for creating a popup window that is positioned relative to a parent window.
+///
+/// On Wayland this is backed by an `xdg_popup`, which lets the compositor place the popup
+/// outside of the parent's surface. On X11 an override-redirect window is placed by GPUI.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PopupOptions {
+    /// The window the popup is attached to.
+    pub parent: AnyWindowHandle,
+    /// The rectangle the popup is positioned against, relative to the parent's content area.
+    pub anchor_rect: Bounds<Pixels>,
+    /// Which point of the anchor rectangle the popup is attached to.
+    pub attach: Anchor,
+    /// Which point of the popup is placed at the attachment point.
+    pub anchor: Anchor,
+    /// Offsets the popup from the attachment point.
+    pub offset: Point<Pixels>,
+    /// How the popup may be moved or resized to stay on screen.
+    pub constraint_adjustment: PopupConstraintAdjustment,
+    /// Whether the popup should take an explicit input grab. Grabbing popups are dismissed
+    /// when the user clicks outside of them.
+    pub grab: bool,
+}
+
+impl PopupOptions {
+    /// Creates options for a grabbing popup that opens below the given rectangle.
+    pub fn new(parent: AnyWindowHandle, anchor_rect: Bounds<Pixels>) -> Self {
+        Self {
+            parent,
+            anchor_rect,
+            attach: Anchor::BottomLeft,
+            anchor: Anchor::TopLeft,
+            offset: Point::default(),
+            constraint_adjustment: PopupConstraintAdjustment::default(),
+            grab: true,
+        }
+    }
+
+    /// Returns the bounds of a popup of the given size, in the same coordinate space as
+    /// `constraint`. `parent_origin` is the position of the parent's content area in that space.
+    ///
+    /// This mirrors the placement rules of `xdg_positioner` and is used by platforms that have
+    /// to position popups themselves.
+    pub fn resolve_bounds(
+        &self,
+        parent_origin: Point<Pixels>,
+        size: Size<Pixels>,
+        constraint: Bounds<Pixels>,
+    ) -> Bounds<Pixels> {
+        let anchor_rect = Bounds::new(
+            parent_origin + self.anchor_rect.origin,
+            self.anchor_rect.size,
+        );
+        let mut bounds = place(anchor_rect, self.attach, self.anchor, self.offset, size);
+
+        if self.constraint_adjustment.flip_x && !fits_along(bounds, constraint, Axis::Horizontal) {
+            let flipped = place(
+                anchor_rect,
+                self.attach.other_side_along(Axis::Horizontal),
+                self.anchor.other_side_along(Axis::Horizontal),
+                point(-self.offset.x, self.offset.y),
+                size,
+            );
+            if fits_along(flipped, constraint, Axis::Horizontal) {
+                bounds.origin.x = flipped.origin.x;
+            }
+        }
+
+        if self.constraint_adjustment.flip_y && !fits_along(bounds, constraint, Axis::Vertical) {
+            let flipped = place(
+                anchor_rect,
+                self.attach.other_side_along(Axis::Vertical),
+                self.anchor.other_side_along(Axis::Vertical),
+                point(self.offset.x, -self.offset.y),
+                size,
+            );
+            if fits_along(flipped, constraint, Axis::Vertical) {
+                bounds.origin.y = flipped.origin.y;
+            }
+        }
+
+        if self.constraint_adjustment.slide_x {
+            if bounds.right() > constraint.right() {
+                bounds.origin.x = constraint.right() - bounds.size.width;
+            }
+            if bounds.left() < constraint.left() {
+                bounds.origin.x = constraint.left();
+            }
+        }
+
+        if self.constraint_adjustment.slide_y {
+            if bounds.bottom() > constraint.bottom() {
+                bounds.origin.y = constraint.bottom() - bounds.size.height;
+            }
+            if bounds.top() < constraint.top() {
+                bounds.origin.y = constraint.top();
+            }
+        }
+
+        if self.constraint_adjustment.resize_x {
+            let left = bounds.left().max(constraint.left());
+            let right = bounds.right().min(constraint.right());
+            if right > left {
+                bounds.origin.x = left;
+                bounds.size.width = right - left;
+            }
+        }
+
+        if self.constraint_adjustment.resize_y {
+            let top = bounds.top().max(constraint.top());
+            let bottom = bounds.bottom().min(constraint.bottom());
+            if bottom > top {
+                bounds.origin.y = top;
+                bounds.size.height = bottom - top;
+            }
+        }
+
+        bounds
+    }
+}
+
+fn place(
+    anchor_rect: Bounds<Pixels>,
+    attach: Anchor,
+    anchor: Anchor,
+    offset: Point<Pixels>,
+    size: Size<Pixels>,
+) -> Bounds<Pixels> {
+    let attach_point = anchor_rect.corner(attach) + offset;
+    let anchor_offset = Bounds::new(Point::default(), size).corner(anchor);
+    Bounds::new(attach_point - anchor_offset, size)
+}
+
+fn fits_along(bounds: Bounds<Pixels>, constraint: Bounds<Pixels>, axis: Axis) -> bool {
+    match axis {
+        Axis::Horizontal => {
+            bounds.left() >= constraint.left() && bounds.right() <= constraint.right()
+        }
+        Axis::Vertical => {
+            bounds.top() >= constraint.top() && bounds.bottom() <= constraint.bottom()
+        }
+    }
+}
@@ -0,0 +1,9 @@
+//! Windows that cover a display while the session is locked, for screen lockers.
+
+use thiserror::Error;
+
+/// An error indicating that a session lock window couldn't be opened because the compositor
+/// doesn't support the required protocol.
+#[derive(Debug, Error)]
+#[error("Compositor doesn't support ext_session_lock_manager_v1")]
+pub struct SessionLockNotSupportedError;
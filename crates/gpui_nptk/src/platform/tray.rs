@@ -0,0 +1,71 @@
+//! Icons in the system tray, also known as status notifier items.
+
+use crate::{Axis, MenuItem, Pixels, Point, SharedString};
+
+/// The image shown for a tray icon.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TrayIconImage {
+    /// An icon from the desktop's icon theme, e.g. `mail-unread`.
+    Named(SharedString),
+    /// A bitmap of non-premultiplied RGBA pixels, row by row.
+    Rgba {
+        /// The width of the bitmap in pixels.
+        width: u32,
+        /// The height of the bitmap in pixels.
+        height: u32,
+        /// Four bytes per pixel, `width * height * 4` in total.
+        bytes: Vec<u8>,
+    },
+}
+
+/// Whether a tray icon should currently be shown, and how prominently.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum TrayStatus {
+    /// Nothing interesting is happening; the tray may hide the icon.
+    Passive,
+    /// The icon is shown as usual.
+    #[default]
+    Active,
+    /// The application needs the user's attention, shown with [`TrayIcon::attention_icon`].
+    NeedsAttention,
+}
+
+/// An icon in the system tray, shown with [`App::set_tray_icon`](crate::App::set_tray_icon).
+#[derive(Default)]
+pub struct TrayIcon {
+    /// The icon shown in the tray.
+    pub icon: Option<TrayIconImage>,
+    /// The icon shown while the status is [`TrayStatus::NeedsAttention`]. Defaults to `icon`.
+    pub attention_icon: Option<TrayIconImage>,
+    /// A short name for the icon, used by trays that list their items.
+    pub title: SharedString,
+    /// Text shown when hovering the icon.
+    pub tooltip: Option<SharedString>,
+    /// Whether the icon should be shown, and how prominently.
+    pub status: TrayStatus,
+    /// The items of the menu shown when the icon is right-clicked. Their actions are dispatched
+    /// as if they had been dispatched from the active window.
+    pub menu: Vec<MenuItem>,
+}
+
+/// An interaction with the tray icon, delivered to [`App::on_tray_event`](crate::App::on_tray_event).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TrayEvent {
+    /// The icon was clicked, usually with the primary button.
+    Activate {
+        /// Where the icon was clicked, in screen coordinates. Zero when the tray doesn't know.
+        position: Point<Pixels>,
+    },
+    /// The icon was clicked with the middle button.
+    SecondaryActivate {
+        /// Where the icon was clicked, in screen coordinates. Zero when the tray doesn't know.
+        position: Point<Pixels>,
+    },
+    /// The mouse wheel was scrolled over the icon.
+    Scroll {
+        /// How far the wheel was scrolled, in tray-specific units.
+        delta: i32,
+        /// The direction the wheel was scrolled in.
+        axis: Axis,
+    },
+}
@@ -0,0 +1,330 @@
+mod pdf;
+
+pub(crate) use pdf::*;
+
+use std::{borrow::Cow, mem, path::PathBuf};
+
+use anyhow::Result;
+use collections::FxHashMap;
+use parking_lot::Mutex;
+
+use crate::{
+    AtlasKey, AtlasTextureId, AtlasTile, Bounds, DevicePixels, Edges, Pixels, PlatformAtlas, Point,
+    ScaledPixels, Scene, SharedString, Size, TileId, px, size,
+};
+
+const MILLIMETERS_PER_INCH: f32 = 25.4;
+const POINTS_PER_INCH: f32 = 72.;
+const PIXELS_PER_INCH: f32 = 96.;
+
+/// The resolution glyphs, SVGs and other sprites are rasterized at when printing.
+const PRINT_DOTS_PER_INCH: f32 = 300.;
+
+/// Page breaks are moved up to avoid cutting through a line of text or an image, unless that
+/// would leave less than this fraction of the page filled.
+const MIN_PAGE_FILL: f32 = 0.8;
+
+/// The size of a sheet of paper, in millimeters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PaperSize {
+    /// The width of the sheet, in millimeters.
+    pub width: f32,
+    /// The height of the sheet, in millimeters.
+    pub height: f32,
+}
+
+impl PaperSize {
+    /// ISO A3, 297 × 420 mm.
+    pub const A3: Self = Self::new(297., 420.);
+    /// ISO A4, 210 × 297 mm.
+    pub const A4: Self = Self::new(210., 297.);
+    /// ISO A5, 148 × 210 mm.
+    pub const A5: Self = Self::new(148., 210.);
+    /// US Letter, 8.5 × 11 in.
+    pub const LETTER: Self = Self::new(215.9, 279.4);
+    /// US Legal, 8.5 × 14 in.
+    pub const LEGAL: Self = Self::new(215.9, 355.6);
+
+    /// Creates a paper size from its width and height in millimeters.
+    pub const fn new(width: f32, height: f32) -> Self {
+        Self { width, height }
+    }
+
+    /// Returns this paper size turned sideways, so that it's wider than it's tall.
+    pub fn landscape(self) -> Self {
+        Self {
+            width: self.width.max(self.height),
+            height: self.width.min(self.height),
+        }
+    }
+
+    /// Whether the paper is wider than it's tall.
+    pub fn is_landscape(&self) -> bool {
+        self.width > self.height
+    }
+
+    /// The name of this paper size as defined by PWG 5101.1, if it's one of the common sizes.
+    pub fn pwg_name(&self) -> Option<&'static str> {
+        let portrait = Self::new(self.width.min(self.height), self.width.max(self.height));
+        [
+            (Self::A3, "iso_a3"),
+            (Self::A4, "iso_a4"),
+            (Self::A5, "iso_a5"),
+            (Self::LETTER, "na_letter"),
+            (Self::LEGAL, "na_legal"),
+        ]
+        .into_iter()
+        .find(|(size, _)| {
+            (size.width - portrait.width).abs() < 1. && (size.height - portrait.height).abs() < 1.
+        })
+        .map(|(_, name)| name)
+    }
+}
+
+impl Default for PaperSize {
+    fn default() -> Self {
+        Self::A4
+    }
+}
+
+/// Where [`crate::Window::print`] sends the document.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum PrintDestination {
+    /// Show the platform's print dialog and send the document to the printer the user picks.
+    #[default]
+    Printer,
+    /// Save the document as a PDF file at the given path, without showing a dialog.
+    Pdf(PathBuf),
+}
+
+/// Describes how to lay out a view onto pages when printing it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PrintOptions {
+    /// The title of the document, shown in the print dialog and the printer queue, and stored
+    /// in the PDF.
+    pub title: SharedString,
+    /// The size of each page.
+    pub paper_size: PaperSize,
+    /// The space left blank around the content of each page, in millimeters.
+    pub margins: Edges<f32>,
+    /// Where the document is sent.
+    pub destination: PrintDestination,
+}
+
+impl Default for PrintOptions {
+    fn default() -> Self {
+        Self {
+            title: SharedString::default(),
+            paper_size: PaperSize::default(),
+            margins: Edges::all(15.),
+            destination: PrintDestination::default(),
+        }
+    }
+}
+
+impl PrintOptions {
+    /// The size of the area inside the margins of each page, in the pixels views are laid
+    /// out in.
+    pub fn printable_size(&self) -> Size<Pixels> {
+        size(
+            millimeters_to_pixels(self.paper_size.width - self.margins.left - self.margins.right),
+            millimeters_to_pixels(self.paper_size.height - self.margins.top - self.margins.bottom),
+        )
+        .map(|length| length.max(px(1.)))
+    }
+
+    pub(crate) fn page_setup(&self) -> PdfPageSetup {
+        let points = |millimeters: f32| millimeters / MILLIMETERS_PER_INCH * POINTS_PER_INCH;
+        PdfPageSetup {
+            title: self.title.to_string(),
+            page_size: size(
+                points(self.paper_size.width),
+                points(self.paper_size.height),
+            ),
+            content_origin: Point::new(points(self.margins.left), points(self.margins.top)),
+            content_width: self.printable_size().width.0 / PIXELS_PER_INCH * POINTS_PER_INCH,
+            points_per_scaled_pixel: POINTS_PER_INCH / PIXELS_PER_INCH / print_scale_factor(),
+        }
+    }
+}
+
+fn millimeters_to_pixels(millimeters: f32) -> Pixels {
+    px(millimeters / MILLIMETERS_PER_INCH * PIXELS_PER_INCH)
+}
+
+/// The scale factor views are painted at when printing.
+pub(crate) fn print_scale_factor() -> f32 {
+    PRINT_DOTS_PER_INCH / PIXELS_PER_INCH
+}
+
+/// Returns where each page ends when breaking content of the given height into pages.
+/// Breaks are moved up to the top of any text or image they would otherwise cut through, as
+/// long as that keeps most of the page filled.
+pub(crate) fn page_breaks(
+    scene: &Scene,
+    page_height: ScaledPixels,
+    content_height: ScaledPixels,
+) -> Vec<ScaledPixels> {
+    let sprites = scene
+        .monochrome_sprites
+        .iter()
+        .map(|sprite| sprite.bounds)
+        .chain(scene.polychrome_sprites.iter().map(|sprite| sprite.bounds))
+        .collect::<Vec<_>>();
+
+    let mut breaks = Vec::new();
+    let mut page_top = 0.;
+    while page_top + page_height.0 < content_height.0 {
+        let min_page_bottom = page_top + page_height.0 * MIN_PAGE_FILL;
+        let mut page_bottom = page_top + page_height.0;
+        // Moving the break above one sprite can make it cut through another.
+        while let Some(top) = sprites
+            .iter()
+            .filter(|bounds| bounds.top().0 < page_bottom && bounds.bottom().0 > page_bottom)
+            .map(|bounds| bounds.top().0)
+            .reduce(f32::min)
+            && top >= min_page_bottom
+        {
+            page_bottom = top;
+        }
+        breaks.push(ScaledPixels(page_bottom));
+        page_top = page_bottom;
+    }
+    breaks.push(ScaledPixels(content_height.0.max(page_top)));
+    breaks
+}
+
+/// A texture holding a single sprite painted for a printout.
+pub(crate) struct PrintTexture {
+    pub size: Size<DevicePixels>,
+    /// One byte of coverage per pixel for monochrome sprites, BGRA for polychrome ones.
+    pub bytes: Vec<u8>,
+}
+
+#[derive(Default)]
+struct PrintAtlasState {
+    tiles: FxHashMap<AtlasKey, AtlasTile>,
+    textures: Vec<PrintTexture>,
+}
+
+/// Keeps every sprite painted for a printout in memory, so that it can be embedded in the
+/// document. Each sprite gets a texture of its own, indexed by its [`AtlasTextureId`].
+#[derive(Default)]
+pub(crate) struct PrintAtlas(Mutex<PrintAtlasState>);
+
+impl PrintAtlas {
+    pub fn take_textures(&self) -> Vec<PrintTexture> {
+        let mut state = self.0.lock();
+        state.tiles.clear();
+        mem::take(&mut state.textures)
+    }
+}
+
+impl PlatformAtlas for PrintAtlas {
+    fn get_or_insert_with<'a>(
+        &self,
+        key: &AtlasKey,
+        build: &mut dyn FnMut() -> Result<Option<(Size<DevicePixels>, Cow<'a, [u8]>)>>,
+    ) -> Result<Option<AtlasTile>> {
+        if let Some(tile) = self.0.lock().tiles.get(key) {
+            return Ok(Some(*tile));
+        }
+        let Some((size, bytes)) = build()? else {
+            return Ok(None);
+        };
+
+        let mut state = self.0.lock();
+        let tile = AtlasTile {
+            texture_id: AtlasTextureId {
+                index: state.textures.len() as u32,
+                kind: key.texture_kind(),
+            },
+            tile_id: TileId(0),
+            padding: 0,
+            bounds: Bounds {
+                origin: Point::default(),
+                size,
+            },
+        };
+        state.textures.push(PrintTexture {
+            size,
+            bytes: bytes.into_owned(),
+        });
+        state.tiles.insert(key.clone(), tile);
+        Ok(Some(tile))
+    }
+
+    fn remove(&self, key: &AtlasKey) {
+        self.0.lock().tiles.remove(key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ContentMask, MonochromeSprite, TransformationMatrix, bounds, point};
+
+    fn glyph(top: f32, height: f32) -> MonochromeSprite {
+        let bounds = bounds(
+            point(ScaledPixels(0.), ScaledPixels(top)),
+            size(ScaledPixels(10.), ScaledPixels(height)),
+        );
+        MonochromeSprite {
+            order: 0,
+            pad: 0,
+            bounds,
+            content_mask: ContentMask { bounds },
+            color: Default::default(),
+            tile: AtlasTile {
+                texture_id: AtlasTextureId {
+                    index: 0,
+                    kind: crate::AtlasTextureKind::Monochrome,
+                },
+                tile_id: TileId(0),
+                padding: 0,
+                bounds: Default::default(),
+            },
+            transformation: TransformationMatrix::unit(),
+        }
+    }
+
+    #[test]
+    fn test_page_breaks() {
+        let page_height = ScaledPixels(100.);
+        let mut scene = Scene::default();
+        assert_eq!(
+            page_breaks(&scene, page_height, ScaledPixels(250.)),
+            [ScaledPixels(100.), ScaledPixels(200.), ScaledPixels(250.)]
+        );
+        assert_eq!(
+            page_breaks(&scene, page_height, ScaledPixels(0.)),
+            [ScaledPixels(0.)]
+        );
+
+        // Lines of text straddling a break move it up to their top, one after another.
+        scene.insert_primitive(glyph(95., 10.));
+        scene.insert_primitive(glyph(88., 10.));
+        scene.finish();
+        assert_eq!(
+            page_breaks(&scene, page_height, ScaledPixels(150.)),
+            [ScaledPixels(88.), ScaledPixels(150.)]
+        );
+
+        // Content taller than most of a page is cut rather than leaving the page empty.
+        let mut scene = Scene::default();
+        scene.insert_primitive(glyph(50., 100.));
+        scene.finish();
+        assert_eq!(
+            page_breaks(&scene, page_height, ScaledPixels(150.)),
+            [ScaledPixels(100.), ScaledPixels(150.)]
+        );
+    }
+
+    #[test]
+    fn test_paper_sizes() {
+        assert_eq!(PaperSize::A4.pwg_name(), Some("iso_a4"));
+        assert_eq!(PaperSize::LETTER.landscape().pwg_name(), Some("na_letter"));
+        assert!(PaperSize::LETTER.landscape().is_landscape());
+        assert_eq!(PaperSize::new(100., 100.).pwg_name(), None);
+    }
+}
@@ -0,0 +1,668 @@
+//! Writes the scene painted for a printout as a PDF document. Quads, paths and underlines are
+//! written as vector shapes, while glyphs, SVGs and images are embedded as images at the
+//! resolution they were rasterized at.
+
+use collections::FxHashMap;
+
+use crate::{
+    Background, Bounds, ContentMask, Corners, Hsla, MonochromeSprite, Path, Point,
+    PolychromeSprite, Primitive, Quad, ScaledPixels, Scene, Size, TransformationMatrix, Underline,
+    color::BackgroundTag, print::PrintTexture, scene::PaintOperation,
+};
+
+/// How far the control points of the Bézier curves approximating a quarter circle are from its
+/// ends, relative to the radius.
+const KAPPA: f32 = 0.552_284_8;
+
+/// Where the content painted for a printout goes on each page.
+pub(crate) struct PdfPageSetup {
+    pub title: String,
+    /// The size of each page, in points.
+    pub page_size: Size<f32>,
+    /// The top left corner of the content on each page, in points from the top left corner of
+    /// the page.
+    pub content_origin: Point<f32>,
+    /// The width of the content on each page, in points.
+    pub content_width: f32,
+    pub points_per_scaled_pixel: f32,
+}
+
+/// Writes `scene` as a PDF document, with a page for each band of the scene ending at one of
+/// `page_breaks`. Sprites are looked up in `textures` by the index of their texture.
+pub(crate) fn write_pdf(
+    setup: &PdfPageSetup,
+    scene: &Scene,
+    textures: &[PrintTexture],
+    page_breaks: &[ScaledPixels],
+) -> Vec<u8> {
+    let mut document = PdfDocument::default();
+    let catalog = document.reserve();
+    let pages = document.reserve();
+    let resources_id = document.reserve();
+    let mut resources = Resources::default();
+
+    let scale = setup.points_per_scaled_pixel;
+    let content_width = setup.content_width / scale;
+    let mut page_ids = Vec::with_capacity(page_breaks.len());
+    let mut page_top = 0.;
+    for page_bottom in page_breaks.iter().map(|page_bottom| page_bottom.0) {
+        let mut painter = Painter {
+            document: &mut document,
+            resources: &mut resources,
+            textures,
+            content: String::new(),
+        };
+        // Map the band of the scene onto the page, flipping it to PDF's bottom-up coordinates.
+        painter.op(format_args!(
+            "q {scale:.5} 0 0 {:.5} {:.2} {:.2} cm",
+            -scale,
+            setup.content_origin.x,
+            setup.page_size.height - setup.content_origin.y + page_top * scale,
+        ));
+        painter.op(format_args!(
+            "0 {page_top:.2} {content_width:.2} {:.2} re W n",
+            page_bottom - page_top
+        ));
+        for operation in &scene.paint_operations {
+            let PaintOperation::Primitive(primitive) = operation else {
+                continue;
+            };
+            let bounds = primitive
+                .bounds()
+                .intersect(&primitive.content_mask().bounds);
+            if bounds.is_empty() || bounds.bottom().0 <= page_top || bounds.top().0 >= page_bottom {
+                continue;
+            }
+            match primitive {
+                Primitive::Quad(quad) => painter.quad(quad),
+                Primitive::Path(path) => painter.path(path),
+                Primitive::Underline(underline) => painter.underline(underline),
+                Primitive::MonochromeSprite(sprite) => painter.monochrome_sprite(sprite),
+                Primitive::PolychromeSprite(sprite) => painter.polychrome_sprite(sprite),
+                // Blurred shadows have no counterpart in PDF, subpixel glyphs aren't rendered
+                // when printing, and surfaces only exist on macOS.
+                Primitive::Shadow(_) | Primitive::SubpixelSprite(_) | Primitive::Surface(_) => {}
+            }
+        }
+        painter.op(format_args!("Q"));
+
+        let content = painter.content.into_bytes();
+        let content = document.add_stream("", &content);
+        page_ids.push(document.add(format!(
+            "<< /Type /Page /Parent {pages} 0 R /MediaBox [0 0 {:.2} {:.2}] /Resources {resources_id} 0 R /Contents {content} 0 R >>",
+            setup.page_size.width, setup.page_size.height
+        )));
+        page_top = page_bottom;
+    }
+
+    document.set(resources_id, resources.dictionary());
+    let kids = page_ids
+        .iter()
+        .map(|id| format!("{id} 0 R"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    document.set(
+        pages,
+        format!(
+            "<< /Type /Pages /Kids [{kids}] /Count {} >>",
+            page_ids.len()
+        ),
+    );
+    document.set(catalog, format!("<< /Type /Catalog /Pages {pages} 0 R >>"));
+    let info = document.add(format!(
+        "<< /Title {} /Producer (GPUI) >>",
+        text_string(&setup.title)
+    ));
+    document.finish(catalog, info)
+}
+
+#[derive(Default)]
+struct PdfDocument {
+    objects: Vec<Vec<u8>>,
+}
+
+impl PdfDocument {
+    /// Reserves an object number for an object that's added later with [`Self::set`].
+    fn reserve(&mut self) -> usize {
+        self.objects.push(Vec::new());
+        self.objects.len()
+    }
+
+    fn set(&mut self, id: usize, object: impl Into<Vec<u8>>) {
+        self.objects[id - 1] = object.into();
+    }
+
+    fn add(&mut self, object: impl Into<Vec<u8>>) -> usize {
+        self.objects.push(object.into());
+        self.objects.len()
+    }
+
+    fn add_stream(&mut self, dictionary: &str, data: &[u8]) -> usize {
+        let mut object =
+            format!("<< {dictionary} /Length {} >>\nstream\n", data.len()).into_bytes();
+        object.extend_from_slice(data);
+        object.extend_from_slice(b"\nendstream");
+        self.add(object)
+    }
+
+    fn finish(self, root: usize, info: usize) -> Vec<u8> {
+        let mut output = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+        let mut offsets = Vec::with_capacity(self.objects.len());
+        for (index, object) in self.objects.iter().enumerate() {
+            offsets.push(output.len());
+            output.extend_from_slice(format!("{} 0 obj\n", index + 1).as_bytes());
+            output.extend_from_slice(object);
+            output.extend_from_slice(b"\nendobj\n");
+        }
+
+        let xref = output.len();
+        let mut trailer = format!("xref\n0 {}\n0000000000 65535 f \n", self.objects.len() + 1);
+        for offset in offsets {
+            trailer.push_str(&format!("{offset:010} 00000 n \n"));
+        }
+        trailer.push_str(&format!(
+            "trailer\n<< /Size {} /Root {root} 0 R /Info {info} 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+            self.objects.len() + 1
+        ));
+        output.extend_from_slice(trailer.as_bytes());
+        output
+    }
+}
+
+/// The graphics states, images and shadings shared by every page.
+#[derive(Default)]
+struct Resources {
+    /// Graphics states setting the opacity, keyed by opacity out of 255.
+    opacities: FxHashMap<u8, usize>,
+    images: Vec<usize>,
+    shadings: Vec<usize>,
+    /// Coverage masks of monochrome sprites, keyed by texture index.
+    masks: FxHashMap<u32, usize>,
+    /// Monochrome sprites filled with an RGB color, keyed by texture index and color.
+    tinted_masks: FxHashMap<(u32, [u8; 3]), usize>,
+    /// Polychrome sprites, keyed by texture index and whether they're grayscale.
+    pictures: FxHashMap<(u32, bool), usize>,
+}
+
+impl Resources {
+    fn dictionary(&self) -> String {
+        let mut dictionary = String::from("<< /ExtGState <<");
+        for (opacity, id) in &self.opacities {
+            dictionary.push_str(&format!(" /Op{opacity} {id} 0 R"));
+        }
+        dictionary.push_str(" >> /XObject <<");
+        for (index, id) in self.images.iter().enumerate() {
+            dictionary.push_str(&format!(" /Im{index} {id} 0 R"));
+        }
+        dictionary.push_str(" >> /Shading <<");
+        for (index, id) in self.shadings.iter().enumerate() {
+            dictionary.push_str(&format!(" /Sh{index} {id} 0 R"));
+        }
+        dictionary.push_str(" >> >>");
+        dictionary
+    }
+}
+
+struct Painter<'a> {
+    document: &'a mut PdfDocument,
+    resources: &'a mut Resources,
+    textures: &'a [PrintTexture],
+    content: String,
+}
+
+impl Painter<'_> {
+    fn op(&mut self, operator: std::fmt::Arguments) {
+        self.content.push_str(&operator.to_string());
+        self.content.push('\n');
+    }
+
+    fn clip(&mut self, content_mask: &ContentMask<ScaledPixels>) {
+        let bounds = content_mask.bounds;
+        self.op(format_args!(
+            "q {:.2} {:.2} {:.2} {:.2} re W n",
+            bounds.origin.x.0, bounds.origin.y.0, bounds.size.width.0, bounds.size.height.0
+        ));
+    }
+
+    fn set_opacity(&mut self, opacity: f32) {
+        let opacity = (opacity.clamp(0., 1.) * 255.).round() as u8;
+        if !self.resources.opacities.contains_key(&opacity) {
+            let id = self.document.add(format!(
+                "<< /Type /ExtGState /ca {0:.3} /CA {0:.3} >>",
+                opacity as f32 / 255.
+            ));
+            self.resources.opacities.insert(opacity, id);
+        }
+        self.op(format_args!("/Op{opacity} gs"));
+    }
+
+    fn set_fill_color(&mut self, color: Hsla) {
+        let color = color.to_rgb();
+        self.op(format_args!(
+            "{:.3} {:.3} {:.3} rg",
+            color.r, color.g, color.b
+        ));
+        self.set_opacity(color.a);
+    }
+
+    /// Fills the path written by `path` with `background`.
+    fn fill(
+        &mut self,
+        background: &Background,
+        bounds: Bounds<ScaledPixels>,
+        path: impl FnOnce(&mut Self),
+    ) {
+        if background.tag != BackgroundTag::LinearGradient {
+            self.set_fill_color(background.solid);
+            path(self);
+            self.op(format_args!("f"));
+            return;
+        }
+
+        let [from, to] = background.colors;
+        // Angles follow CSS, where 0 points up and increasing values turn clockwise.
+        let angle = background.gradient_angle_or_pattern_height.to_radians();
+        let direction = Point::new(angle.sin(), -angle.cos());
+        let length =
+            (bounds.size.width.0 * direction.x).abs() + (bounds.size.height.0 * direction.y).abs();
+        let center = bounds.center();
+        let start = Point::new(
+            center.x.0 - direction.x * length / 2.,
+            center.y.0 - direction.y * length / 2.,
+        );
+        let point_at = |percentage: f32| {
+            Point::new(
+                start.x + direction.x * length * percentage,
+                start.y + direction.y * length * percentage,
+            )
+        };
+        let (from_point, to_point) = (point_at(from.percentage), point_at(to.percentage));
+        let (from_color, to_color) = (from.color.to_rgb(), to.color.to_rgb());
+        let shading = self.document.add(format!(
+            "<< /ShadingType 2 /ColorSpace /DeviceRGB /Coords [{:.2} {:.2} {:.2} {:.2}] /Function << /FunctionType 2 /Domain [0 1] /C0 [{:.3} {:.3} {:.3}] /C1 [{:.3} {:.3} {:.3}] /N 1 >> /Extend [true true] >>",
+            from_point.x,
+            from_point.y,
+            to_point.x,
+            to_point.y,
+            from_color.r,
+            from_color.g,
+            from_color.b,
+            to_color.r,
+            to_color.g,
+            to_color.b,
+        ));
+        self.resources.shadings.push(shading);
+        let name = self.resources.shadings.len() - 1;
+
+        self.op(format_args!("q"));
+        // Shadings can't vary their opacity, so use the more opaque end throughout.
+        self.set_opacity(from_color.a.max(to_color.a));
+        path(self);
+        self.op(format_args!("W n /Sh{name} sh Q"));
+    }
+
+    fn rounded_rect(&mut self, bounds: Bounds<ScaledPixels>, corner_radii: Corners<ScaledPixels>) {
+        let (left, top) = (bounds.left().0, bounds.top().0);
+        let (right, bottom) = (bounds.right().0, bounds.bottom().0);
+        let max_radius = (bounds.size.width.0.min(bounds.size.height.0) / 2.).max(0.);
+        let radius = |radius: ScaledPixels| radius.0.clamp(0., max_radius);
+        let (top_left, top_right) = (
+            radius(corner_radii.top_left),
+            radius(corner_radii.top_right),
+        );
+        let (bottom_right, bottom_left) = (
+            radius(corner_radii.bottom_right),
+            radius(corner_radii.bottom_left),
+        );
+        if top_left == 0. && top_right == 0. && bottom_right == 0. && bottom_left == 0. {
+            self.op(format_args!(
+                "{left:.2} {top:.2} {:.2} {:.2} re",
+                right - left,
+                bottom - top
+            ));
+            return;
+        }
+
+        // Each corner's control points lie along its edges, this far from where it ends.
+        let k = 1. - KAPPA;
+        self.op(format_args!(
+            "{:.2} {top:.2} m {:.2} {top:.2} l {:.2} {top:.2} {right:.2} {:.2} {right:.2} {:.2} c",
+            left + top_left,
+            right - top_right,
+            right - top_right * k,
+            top + top_right * k,
+            top + top_right,
+        ));
+        self.op(format_args!(
+            "{right:.2} {:.2} l {right:.2} {:.2} {:.2} {bottom:.2} {:.2} {bottom:.2} c",
+            bottom - bottom_right,
+            bottom - bottom_right * k,
+            right - bottom_right * k,
+            right - bottom_right,
+        ));
+        self.op(format_args!(
+            "{:.2} {bottom:.2} l {:.2} {bottom:.2} {left:.2} {:.2} {left:.2} {:.2} c",
+            left + bottom_left,
+            left + bottom_left * k,
+            bottom - bottom_left * k,
+            bottom - bottom_left,
+        ));
+        self.op(format_args!(
+            "{left:.2} {:.2} l {left:.2} {:.2} {:.2} {top:.2} {:.2} {top:.2} c h",
+            top + top_left,
+            top + top_left * k,
+            left + top_left * k,
+            left + top_left,
+        ));
+    }
+
+    fn quad(&mut self, quad: &Quad) {
+        self.clip(&quad.content_mask);
+        if !quad.background.is_transparent() {
+            self.fill(&quad.background, quad.bounds, |painter| {
+                painter.rounded_rect(quad.bounds, quad.corner_radii)
+            });
+        }
+
+        // Dashed borders are printed solid.
+        let widths = quad.border_widths;
+        let has_border = [widths.top, widths.right, widths.bottom, widths.left]
+            .iter()
+            .any(|width| width.0 > 0.);
+        if has_border && !quad.border_color.is_transparent() {
+            let inner_bounds = Bounds {
+                origin: Point::new(
+                    quad.bounds.origin.x + widths.left,
+                    quad.bounds.origin.y + widths.top,
+                ),
+                size: Size::new(
+                    (quad.bounds.size.width - widths.left - widths.right).max(ScaledPixels(0.)),
+                    (quad.bounds.size.height - widths.top - widths.bottom).max(ScaledPixels(0.)),
+                ),
+            };
+            let radii = quad.corner_radii;
+            let inner_radius = |radius: ScaledPixels, first: ScaledPixels, second: ScaledPixels| {
+                (radius - first.max(second)).max(ScaledPixels(0.))
+            };
+            let inner_radii = Corners {
+                top_left: inner_radius(radii.top_left, widths.top, widths.left),
+                top_right: inner_radius(radii.top_right, widths.top, widths.right),
+                bottom_right: inner_radius(radii.bottom_right, widths.bottom, widths.right),
+                bottom_left: inner_radius(radii.bottom_left, widths.bottom, widths.left),
+            };
+            self.set_fill_color(quad.border_color);
+            self.rounded_rect(quad.bounds, radii);
+            self.rounded_rect(inner_bounds, inner_radii);
+            self.op(format_args!("f*"));
+        }
+        self.op(format_args!("Q"));
+    }
+
+    /// Paths are made of triangles, some of which fill the inside of a quadratic curve between
+    /// their first and last vertices. Each is written as a contour of its own, all running the
+    /// same way so that overlapping ones don't cancel out.
+    fn path(&mut self, path: &Path<ScaledPixels>) {
+        self.clip(&path.content_mask);
+        self.fill(&path.color, path.bounds, |painter| {
+            for triangle in path.vertices.chunks_exact(3) {
+                let [a, b, c] = [0, 1, 2].map(|index| {
+                    let position = triangle[index].xy_position;
+                    Point::new(position.x.0, position.y.0)
+                });
+                let is_curve = triangle
+                    .iter()
+                    .any(|vertex| vertex.st_position != Point::new(0., 1.));
+                let clockwise = (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x) > 0.;
+                let (start, end) = if clockwise { (a, c) } else { (c, a) };
+                if is_curve {
+                    // Raise the quadratic curve with control point `b` to a cubic one.
+                    let control_1 = Point::new(
+                        start.x + (b.x - start.x) * 2. / 3.,
+                        start.y + (b.y - start.y) * 2. / 3.,
+                    );
+                    let control_2 = Point::new(
+                        end.x + (b.x - end.x) * 2. / 3.,
+                        end.y + (b.y - end.y) * 2. / 3.,
+                    );
+                    painter.op(format_args!(
+                        "{:.2} {:.2} m {:.2} {:.2} {:.2} {:.2} {:.2} {:.2} c h",
+                        start.x,
+                        start.y,
+                        control_1.x,
+                        control_1.y,
+                        control_2.x,
+                        control_2.y,
+                        end.x,
+                        end.y
+                    ));
+                } else {
+                    painter.op(format_args!(
+                        "{:.2} {:.2} m {:.2} {:.2} l {:.2} {:.2} l h",
+                        start.x, start.y, b.x, b.y, end.x, end.y
+                    ));
+                }
+            }
+        });
+        self.op(format_args!("Q"));
+    }
+
+    /// Wavy underlines are printed straight, through the middle of the wave.
+    fn underline(&mut self, underline: &Underline) {
+        let bounds = underline.bounds;
+        let thickness = underline.thickness.0.min(bounds.size.height.0);
+        let top = if underline.wavy == 0 {
+            bounds.top().0
+        } else {
+            bounds.center().y.0 - thickness / 2.
+        };
+        self.clip(&underline.content_mask);
+        self.set_fill_color(underline.color);
+        self.op(format_args!(
+            "{:.2} {top:.2} {:.2} {thickness:.2} re f Q",
+            bounds.origin.x.0, bounds.size.width.0
+        ));
+    }
+
+    fn monochrome_sprite(&mut self, sprite: &MonochromeSprite) {
+        let index = sprite.tile.texture_id.index;
+        let Some(texture) = self.textures.get(index as usize) else {
+            return;
+        };
+        let color = sprite.color.to_rgb();
+        let rgb = [color.r, color.g, color.b].map(|channel| (channel * 255.).round() as u8);
+
+        let image = match self.resources.tinted_masks.get(&(index, rgb)) {
+            Some(image) => *image,
+            None => {
+                let mask = match self.resources.masks.get(&index) {
+                    Some(mask) => *mask,
+                    None => {
+                        let mask = self.document.add_stream(
+                            &format!(
+                                "/Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceGray /BitsPerComponent 8",
+                                texture.size.width.0, texture.size.height.0
+                            ),
+                            &texture.bytes,
+                        );
+                        self.resources.masks.insert(index, mask);
+                        mask
+                    }
+                };
+                // A single pixel of the fill color, stretched over the sprite's coverage.
+                let id = self.document.add_stream(
+                    &format!(
+                        "/Type /XObject /Subtype /Image /Width 1 /Height 1 /ColorSpace /DeviceRGB /BitsPerComponent 8 /SMask {mask} 0 R"
+                    ),
+                    &rgb,
+                );
+                self.resources.images.push(id);
+                let image = self.resources.images.len() - 1;
+                self.resources.tinted_masks.insert((index, rgb), image);
+                image
+            }
+        };
+
+        self.clip(&sprite.content_mask);
+        self.set_opacity(color.a);
+        if sprite.transformation != TransformationMatrix::unit() {
+            let matrix = sprite.transformation;
+            self.op(format_args!(
+                "{:.5} {:.5} {:.5} {:.5} {:.2} {:.2} cm",
+                matrix.rotation_scale[0][0],
+                matrix.rotation_scale[1][0],
+                matrix.rotation_scale[0][1],
+                matrix.rotation_scale[1][1],
+                matrix.translation[0],
+                matrix.translation[1]
+            ));
+        }
+        self.image(sprite.bounds, image);
+        self.op(format_args!("Q"));
+    }
+
+    fn polychrome_sprite(&mut self, sprite: &PolychromeSprite) {
+        let index = sprite.tile.texture_id.index;
+        let Some(texture) = self.textures.get(index as usize) else {
+            return;
+        };
+
+        let image = match self.resources.pictures.get(&(index, sprite.grayscale)) {
+            Some(image) => *image,
+            None => {
+                let pixels = texture.bytes.chunks_exact(4);
+                let alpha = pixels.clone().map(|bgra| bgra[3]).collect::<Vec<_>>();
+                let (color_space, colors) = if sprite.grayscale {
+                    let luminance = pixels
+                        .map(|bgra| {
+                            (0.2126 * bgra[2] as f32
+                                + 0.7152 * bgra[1] as f32
+                                + 0.0722 * bgra[0] as f32)
+                                .round() as u8
+                        })
+                        .collect::<Vec<_>>();
+                    ("DeviceGray", luminance)
+                } else {
+                    let rgb = pixels
+                        .flat_map(|bgra| [bgra[2], bgra[1], bgra[0]])
+                        .collect::<Vec<_>>();
+                    ("DeviceRGB", rgb)
+                };
+                let dimensions = format!(
+                    "/Type /XObject /Subtype /Image /Width {} /Height {} /BitsPerComponent 8",
+                    texture.size.width.0, texture.size.height.0
+                );
+                let mask = self
+                    .document
+                    .add_stream(&format!("{dimensions} /ColorSpace /DeviceGray"), &alpha);
+                let id = self.document.add_stream(
+                    &format!("{dimensions} /ColorSpace /{color_space} /SMask {mask} 0 R"),
+                    &colors,
+                );
+                self.resources.images.push(id);
+                let image = self.resources.images.len() - 1;
+                self.resources
+                    .pictures
+                    .insert((index, sprite.grayscale), image);
+                image
+            }
+        };
+
+        self.clip(&sprite.content_mask);
+        if sprite.corner_radii != Corners::default() {
+            self.rounded_rect(sprite.bounds, sprite.corner_radii);
+            self.op(format_args!("W n"));
+        }
+        self.set_opacity(sprite.opacity);
+        self.image(sprite.bounds, image);
+        self.op(format_args!("Q"));
+    }
+
+    /// Draws an image, whose first row is at the top of the unit square, over `bounds`.
+    fn image(&mut self, bounds: Bounds<ScaledPixels>, image: usize) {
+        self.op(format_args!(
+            "q {:.2} 0 0 {:.2} {:.2} {:.2} cm /Im{image} Do Q",
+            bounds.size.width.0,
+            -bounds.size.height.0,
+            bounds.origin.x.0,
+            bounds.bottom().0
+        ));
+    }
+}
+
+/// Encodes text as a PDF text string, in UTF-16 so that it isn't limited to Latin-1.
+fn text_string(text: &str) -> String {
+    let mut encoded = String::from("<FEFF");
+    for unit in text.encode_utf16() {
+        encoded.push_str(&format!("{unit:04X}"));
+    }
+    encoded.push('>');
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Edges, black, bounds, point, red, size};
+
+    #[test]
+    fn test_write_pdf() {
+        let setup = PdfPageSetup {
+            title: "Invoice №1".into(),
+            page_size: size(200., 300.),
+            content_origin: point(10., 20.),
+            content_width: 180.,
+            points_per_scaled_pixel: 0.5,
+        };
+        let mut scene = Scene::default();
+        let bounds = bounds(
+            point(ScaledPixels(0.), ScaledPixels(0.)),
+            size(ScaledPixels(100.), ScaledPixels(100.)),
+        );
+        scene.insert_primitive(Quad {
+            order: 0,
+            border_style: Default::default(),
+            bounds,
+            content_mask: ContentMask { bounds },
+            background: red().into(),
+            border_color: black(),
+            corner_radii: Corners::all(ScaledPixels(4.)),
+            border_widths: Edges::all(ScaledPixels(1.)),
+        });
+        scene.finish();
+
+        let pdf = write_pdf(
+            &setup,
+            &scene,
+            &[],
+            &[ScaledPixels(80.), ScaledPixels(100.)],
+        );
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.starts_with("%PDF-1.4\n"));
+        assert!(text.ends_with("%%EOF\n"));
+        assert_eq!(text.matches("/Type /Page ").count(), 2);
+        assert!(text.contains("/Count 2"));
+        assert!(text.contains(&text_string("Invoice №1")));
+
+        // Every entry of the cross-reference table points at the object it's numbered after.
+        let xref = pdf
+            .windows(6)
+            .position(|window| window == b"\nxref\n")
+            .unwrap();
+        let table = String::from_utf8_lossy(&pdf[xref..]);
+        let entries = table
+            .lines()
+            .skip(4)
+            .take_while(|line| line.ends_with(" n "))
+            .collect::<Vec<_>>();
+        assert_eq!(text.matches(" 0 obj\n").count(), entries.len());
+        for (index, entry) in entries.into_iter().enumerate() {
+            let offset = entry[..10].parse::<usize>().unwrap();
+            assert!(pdf[offset..].starts_with(format!("{} 0 obj\n", index + 1).as_bytes()));
+        }
+    }
+
+    #[test]
+    fn test_text_strings() {
+        assert_eq!(text_string("A€"), "<FEFF004120AC>");
+    }
+}
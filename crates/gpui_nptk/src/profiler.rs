@@ -18,7 +18,7 @@ pub(crate) use actions::{save_action_timing, update_running_action};
 
 use serde::{Deserialize, Serialize};
 
-use crate::{SharedString, TasksIncluded, WindowId};
+use crate::{PresentationFeedback, SharedString, TasksIncluded, WindowId};
 
 #[cfg(feature = "profiler")]
 #[doc(hidden)]
@@ -710,6 +710,9 @@ pub struct FrameTiming {
     pub draw_start: Instant,
     /// When `Window::draw` finished.
     pub draw_end: Instant,
+    /// When the frame reached the screen, for platforms that report it. Frames are recorded
+    /// once this is known, which can be a few refresh cycles after they were drawn.
+    pub presentation: Option<PresentationFeedback>,
 }
 
 impl FrameTiming {
@@ -724,6 +727,19 @@ impl FrameTiming {
         self.dirty_at
             .map(|dirty_at| self.draw_end.duration_since(dirty_at))
     }
+
+    /// Time from the frame's first invalidation until it started scanning out, if both are known.
+    pub fn dirty_to_presented_duration(&self) -> Option<Duration> {
+        let presented_at = self.presentation?.presented_at?;
+        self.dirty_at
+            .map(|dirty_at| presented_at.saturating_duration_since(dirty_at))
+    }
+
+    /// Whether the platform reported that this frame was never shown.
+    pub fn was_dropped(&self) -> bool {
+        self.presentation
+            .is_some_and(|presentation| presentation.presented_at.is_none())
+    }
 }
 
 // Allow 16MiB of frame timing entries.
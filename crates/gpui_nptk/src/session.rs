@@ -0,0 +1,348 @@
+use std::{any::TypeId, fs, path::PathBuf};
+
+use anyhow::{Context as _, Result};
+use collections::FxHashMap;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use uuid::Uuid;
+
+use crate::{
+    AnyView, AnyWindowHandle, App, AppContext as _, Bounds, Context, Render, Window, WindowBounds,
+    WindowOptions,
+};
+
+/// A root view that is saved with the session and reopened on the next launch.
+///
+/// Views opt in by being registered with [`App::register_serializable_view`]. When the session is
+/// saved, every window whose root view is of a registered type is written out along with its
+/// bounds, whether it was maximized or fullscreen, and the display it was on.
+pub trait SerializableView: Render + Sized {
+    /// Identifies this kind of view in saved sessions, so it must not change between releases.
+    const KIND: &'static str;
+
+    /// The part of the view's state that is saved. It's stored as JSON.
+    type State: Serialize + DeserializeOwned;
+
+    /// Returns the state to save for this view.
+    fn serialize(&self, window: &Window, cx: &App) -> Self::State;
+
+    /// Recreates the view from saved state when its window is reopened.
+    fn deserialize(state: Self::State, window: &mut Window, cx: &mut Context<Self>) -> Self;
+
+    /// Returns the options reopened windows are created with. The saved bounds and display
+    /// replace the ones set here.
+    fn window_options(_cx: &App) -> WindowOptions {
+        WindowOptions::default()
+    }
+}
+
+/// What [`App::restore_session`] does with the windows saved by the previous run.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SessionRestorePolicy {
+    /// Reopen the saved windows.
+    #[default]
+    Restore,
+    /// Reopen the saved windows only when the session manager relaunched the application, e.g.
+    /// after the user logged out and back in, and discard them otherwise.
+    RestoreAfterLogout,
+    /// Discard the saved windows.
+    Discard,
+}
+
+#[derive(Clone, Copy)]
+struct SerializableViewEntry {
+    kind: &'static str,
+    serialize: fn(AnyView, &Window, &App) -> Option<serde_json::Value>,
+    restore: fn(SavedWindow, &mut App) -> Result<AnyWindowHandle>,
+}
+
+/// Where an application's session is saved and the views it's made of.
+pub(crate) struct Session {
+    path: PathBuf,
+    views: FxHashMap<TypeId, SerializableViewEntry>,
+    /// Saving is held off until the previous session has been restored or discarded, so that it
+    /// isn't overwritten before the application had a chance to read it.
+    restored: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SavedSession {
+    windows: Vec<SavedWindow>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedWindow {
+    kind: String,
+    bounds: WindowBounds,
+    /// The UUID of the display the window was on, if the platform reports one.
+    display: Option<Uuid>,
+    state: serde_json::Value,
+}
+
+impl Session {
+    /// Sessions are saved per application, as all NPTK applications share a state directory.
+    pub(crate) fn new(app_id: &str) -> Self {
+        Self::at(
+            paths::state_dir()
+                .join("sessions")
+                .join(format!("{app_id}.json")),
+        )
+    }
+
+    fn at(path: PathBuf) -> Self {
+        Self {
+            path,
+            views: FxHashMap::default(),
+            restored: false,
+        }
+    }
+
+    pub(crate) fn register<V: SerializableView>(&mut self) {
+        self.views.insert(
+            TypeId::of::<V>(),
+            SerializableViewEntry {
+                kind: V::KIND,
+                serialize: serialize_view::<V>,
+                restore: restore_window::<V>,
+            },
+        );
+    }
+
+    fn view_for_kind(&self, kind: &str) -> Option<SerializableViewEntry> {
+        self.views
+            .values()
+            .find(|entry| entry.kind == kind)
+            .copied()
+    }
+
+    fn load(&self) -> Result<SavedSession> {
+        let contents = match fs::read(&self.path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(SavedSession::default());
+            }
+            Err(error) => {
+                return Err(error)
+                    .with_context(|| format!("failed to read {}", self.path.display()));
+            }
+        };
+        serde_json::from_slice(&contents)
+            .with_context(|| format!("failed to parse {}", self.path.display()))
+    }
+
+    /// Writes to a temporary file first, so that quitting halfway doesn't lose the session.
+    fn write(&self, session: &SavedSession) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create {}", dir.display()))?;
+        }
+        let temp_path = self.path.with_extension("json.tmp");
+        fs::write(&temp_path, serde_json::to_vec_pretty(session)?)
+            .with_context(|| format!("failed to write {}", temp_path.display()))?;
+        fs::rename(&temp_path, &self.path)
+            .with_context(|| format!("failed to write {}", self.path.display()))
+    }
+
+    fn discard(&self) -> Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(error) if error.kind() != std::io::ErrorKind::NotFound => {
+                Err(error).with_context(|| format!("failed to remove {}", self.path.display()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Saves the windows of registered views, see [`App::save_session`].
+    pub(crate) fn save(cx: &mut App) -> Result<()> {
+        let Some(session) = cx.session.as_ref() else {
+            anyhow::bail!("the application has no session, see `Application::with_session`");
+        };
+        if !session.restored {
+            log::debug!("not saving the session before the previous one was restored");
+            return Ok(());
+        }
+        let views = session.views.clone();
+
+        let mut windows = Vec::new();
+        for handle in cx.windows() {
+            let saved = handle.update(cx, |root, window, cx| {
+                let entry = views.get(&root.entity_type())?;
+                Some(SavedWindow {
+                    kind: entry.kind.to_string(),
+                    bounds: window.window_bounds(),
+                    display: window.display(cx).and_then(|display| display.uuid().ok()),
+                    state: (entry.serialize)(root, window, cx)?,
+                })
+            });
+            windows.extend(saved.ok().flatten());
+        }
+
+        let Some(session) = cx.session.as_ref() else {
+            return Ok(());
+        };
+        session.write(&SavedSession { windows })
+    }
+
+    /// Reopens or discards the windows saved by the previous run, see [`App::restore_session`].
+    pub(crate) fn restore(cx: &mut App, policy: SessionRestorePolicy) -> Vec<AnyWindowHandle> {
+        let Some(session) = cx.session.as_mut() else {
+            log::error!("can't restore the session without one, see `Application::with_session`");
+            return Vec::new();
+        };
+        session.restored = true;
+        let session = &*session;
+        let restore = match policy {
+            SessionRestorePolicy::Restore => true,
+            SessionRestorePolicy::RestoreAfterLogout => cx.platform.launched_by_session_manager(),
+            SessionRestorePolicy::Discard => false,
+        };
+        if !restore {
+            if let Err(error) = session.discard() {
+                log::error!("{error:#}");
+            }
+            return Vec::new();
+        }
+
+        let saved = match session.load() {
+            Ok(saved) => saved,
+            Err(error) => {
+                log::error!("failed to restore the session: {error:#}");
+                return Vec::new();
+            }
+        };
+        let windows = saved
+            .windows
+            .into_iter()
+            .filter_map(|window| match session.view_for_kind(&window.kind) {
+                Some(entry) => Some((entry, window)),
+                None => {
+                    log::warn!(
+                        "not restoring a window of unregistered view {}",
+                        window.kind
+                    );
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        windows
+            .into_iter()
+            .filter_map(|(entry, window)| {
+                let kind = window.kind.clone();
+                (entry.restore)(window, cx)
+                    .map_err(|error| log::error!("failed to restore a {kind} window: {error:#}"))
+                    .ok()
+            })
+            .collect()
+    }
+}
+
+fn serialize_view<V: SerializableView>(
+    root: AnyView,
+    window: &Window,
+    cx: &App,
+) -> Option<serde_json::Value> {
+    let view = root.downcast::<V>().ok()?;
+    match serde_json::to_value(view.read(cx).serialize(window, cx)) {
+        Ok(state) => Some(state),
+        Err(error) => {
+            log::error!("failed to save a {} window: {error}", V::KIND);
+            None
+        }
+    }
+}
+
+fn restore_window<V: SerializableView>(
+    saved: SavedWindow,
+    cx: &mut App,
+) -> Result<AnyWindowHandle> {
+    let state: V::State = serde_json::from_value(saved.state)?;
+    let display = saved.display.and_then(|uuid| {
+        cx.displays()
+            .into_iter()
+            .find(|display| display.uuid().ok() == Some(uuid))
+    });
+
+    let mut options = V::window_options(cx);
+    options.display_id = display.as_ref().map(|display| display.id());
+    options.window_bounds = Some(if display.is_some() || saved.display.is_none() {
+        saved.bounds
+    } else {
+        // The display the window was on is gone, so keep its size but center it on the primary
+        // display rather than leaving it where that display used to be.
+        let size = saved.bounds.get_bounds().size;
+        let bounds = Bounds::centered(None, size, cx);
+        match saved.bounds {
+            WindowBounds::Windowed(_) => WindowBounds::Windowed(bounds),
+            WindowBounds::Maximized(_) => WindowBounds::Maximized(bounds),
+            WindowBounds::Fullscreen(_) => WindowBounds::Fullscreen(bounds),
+        }
+    });
+
+    let handle = cx.open_window(options, |window, cx| {
+        cx.new(|cx| V::deserialize(state, window, cx))
+    })?;
+    Ok(handle.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{self as gpui, IntoElement, TestAppContext, div};
+
+    struct Counter {
+        count: usize,
+    }
+
+    impl Render for Counter {
+        fn render(&mut self, _: &mut Window, _: &mut Context<Self>) -> impl IntoElement {
+            div()
+        }
+    }
+
+    impl SerializableView for Counter {
+        const KIND: &'static str = "Counter";
+        type State = usize;
+
+        fn serialize(&self, _: &Window, _: &App) -> usize {
+            self.count
+        }
+
+        fn deserialize(count: usize, _: &mut Window, _: &mut Context<Self>) -> Self {
+            Self { count }
+        }
+    }
+
+    #[gpui::test]
+    fn test_save_and_restore_session(cx: &mut TestAppContext) {
+        let path = std::env::temp_dir().join(format!(
+            "gpui-session-test-{}-{}.json",
+            std::process::id(),
+            Uuid::new_v4()
+        ));
+        cx.update(|cx| {
+            cx.session = Some(Session::at(path.clone()));
+            cx.register_serializable_view::<Counter>();
+        });
+        cx.add_window(|_, _| Counter { count: 3 });
+
+        // Nothing is saved until the previous session has been restored.
+        cx.update(|cx| cx.save_session()).unwrap();
+        assert!(!path.exists());
+        let restored = cx.update(|cx| cx.restore_session(SessionRestorePolicy::Restore));
+        assert!(restored.is_empty());
+        cx.update(|cx| cx.save_session()).unwrap();
+
+        let restored = cx.update(|cx| cx.restore_session(SessionRestorePolicy::Restore));
+        assert_eq!(restored.len(), 1);
+        let counter = restored[0].downcast::<Counter>().unwrap();
+        let count = counter.read_with(cx, |counter, _| counter.count).unwrap();
+        assert_eq!(count, 3);
+
+        cx.update(|cx| {
+            cx.restore_session(SessionRestorePolicy::Discard);
+            // Keep the session from being saved again when the test app shuts down.
+            cx.session = None;
+        });
+        assert!(!path.exists());
+    }
+}
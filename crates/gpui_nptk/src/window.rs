@@ -9,18 +9,21 @@ use crate::{
     Hsla, InputHandler, IsZero, KeyBinding, KeyContext, KeyDownEvent, KeyEvent, Keystroke,
     KeystrokeEvent, LayoutId, LineLayoutIndex, Modifiers, ModifiersChangedEvent, MonochromeSprite,
     MouseButton, MouseEvent, MouseMoveEvent, MouseUpEvent, Path, Pixels, PlatformAtlas,
-    PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow, Point, PolychromeSprite,
-    Priority, PromptButton, PromptLevel, Quad, Render, RenderGlyphParams, RenderImage,
-    RenderImageParams, RenderSvgParams, Replay, ResizeEdge, SMOOTH_SVG_SCALE_FACTOR,
-    SUBPIXEL_VARIANTS_X, SUBPIXEL_VARIANTS_Y, ScaledPixels, Scene, Shadow, SharedString, Size,
-    StrikethroughStyle, Style, SubpixelSprite, SubscriberSet, Subscription, SystemWindowTab,
-    SystemWindowTabController, TabStopMap, TaffyLayoutEngine, Task, TextRenderingMode, TextStyle,
-    TextStyleRefinement, ThermalState, TransformationMatrix, Underline, UnderlineStyle,
-    WindowAppearance, WindowBackgroundAppearance, WindowBounds, WindowControls, WindowDecorations,
-    WindowOptions, WindowParams, WindowTextSystem, point, prelude::*, profiler, px, rems, size,
-    transparent_black,
+    PlatformDisplay, PlatformInput, PlatformInputHandler, PlatformWindow, Point, PointerConstraint,
+    PolychromeSprite, PresentationFeedback, PrintAtlas, PrintDestination, PrintOptions, Priority,
+    PromptButton, PromptLevel, Quad, Render, RenderGlyphParams, RenderImage, RenderImageParams,
+    RenderSvgParams, Replay, ResizeEdge, SMOOTH_SVG_SCALE_FACTOR, SUBPIXEL_VARIANTS_X,
+    SUBPIXEL_VARIANTS_Y, ScaledPixels, Scene, Shadow, SharedString, Size, StrikethroughStyle,
+    Style, SubpixelSprite, SubscriberSet, Subscription, SystemWindowTab, SystemWindowTabController,
+    TabStopMap, TaffyLayoutEngine, Task, TextRenderingMode, TextStyle, TextStyleRefinement,
+    ThermalState, TransformationMatrix, Underline, UnderlineStyle, WindowAppearance,
+    WindowBackgroundAppearance, WindowBounds, WindowControls, WindowDecorations, WindowOptions,
+    WindowParams, WindowTextSystem, page_breaks, point, prelude::*, print_scale_factor, profiler,
+    px, rems, size, transparent_black, write_pdf,
 };
 
+#[cfg(all(target_os = "linux", feature = "wayland"))]
+use crate::layer_shell;
 use anyhow::{Context as _, Result, anyhow};
 use collections::{FxHashMap, FxHashSet};
 #[cfg(target_os = "macos")]
@@ -45,6 +48,7 @@ use std::{
     borrow::Cow,
     cell::{Cell, RefCell},
     cmp,
+    collections::VecDeque,
     fmt::{Debug, Display},
     hash::{Hash, Hasher},
     marker::PhantomData,
@@ -925,11 +929,11 @@ impl Frame {
             .iter()
             .rev()
             .fold_while(None, |style, request| match request.hitbox_id {
-                None => Done(Some(request.style)),
+                None => Done(Some(request.style.clone())),
                 Some(hitbox_id) => Continue(style.or_else(|| {
                     hitbox_id
                         .is_hovered_ignoring_last_input(window)
-                        .then_some(request.style)
+                        .then(|| request.style.clone())
                 })),
             })
             .into_inner()
@@ -990,7 +994,7 @@ pub struct Window {
     pub(crate) invalidator: WindowInvalidator,
     pub(crate) removed: bool,
     pub(crate) platform_window: Box<dyn PlatformWindow>,
-    display_id: Option<DisplayId>,
+    pub(crate) display_id: Option<DisplayId>,
     sprite_atlas: Arc<dyn PlatformAtlas>,
     text_system: Arc<WindowTextSystem>,
     text_rendering_mode: Rc<Cell<TextRenderingMode>>,
@@ -1038,6 +1042,13 @@ pub struct Window {
     pub(crate) input_rate_tracker: Rc<RefCell<InputRateTracker>>,
     #[cfg(feature = "input-latency-histogram")]
     input_latency_tracker: InputLatencyTracker,
+    /// Whether the platform reports when frames reach the screen.
+    presentation_feedback: bool,
+    /// Number of frames handed to the platform, used to match up presentation feedback.
+    submitted_frames: u64,
+    pending_presentations: VecDeque<SubmittedFrame>,
+    /// Timing of the most recently drawn frame, recorded once it's presented.
+    drawn_frame_timing: Option<profiler::FrameTiming>,
     last_input_modality: InputModality,
     pub(crate) refreshing: bool,
     pub(crate) activation_observers: SubscriberSet<(), AnyObserver>,
@@ -1121,6 +1132,13 @@ pub struct InputLatencySnapshot {
     /// Count of input events that arrived mid-draw and were excluded from
     /// latency recording.
     pub mid_draw_events_dropped: u64,
+    /// Whether latency samples end when frames started scanning out, rather
+    /// than when they were submitted to the GPU.
+    pub measured_to_scanout: bool,
+    /// Count of refresh cycles that showed a stale frame even though a newer
+    /// one had been submitted, plus frames that were never shown at all. Only
+    /// tracked when `measured_to_scanout` is set.
+    pub dropped_frames: u64,
 }
 
 /// Records the time between when the first input event in a frame is dispatched
@@ -1140,11 +1158,17 @@ struct InputLatencyTracker {
     /// Count of input events that arrived mid-draw and were excluded from
     /// latency recording because their effects won't appear until the next frame.
     mid_draw_events_dropped: u64,
+    /// Whether frames are recorded when they reach the screen.
+    measured_to_scanout: bool,
+    /// When the most recently presented frame started scanning out.
+    last_presented_at: Option<Instant>,
+    /// Count of missed refresh cycles and frames that were never shown.
+    dropped_frames: u64,
 }
 
 #[cfg(feature = "input-latency-histogram")]
 impl InputLatencyTracker {
-    fn new() -> Result<Self> {
+    fn new(measured_to_scanout: bool) -> Result<Self> {
         Ok(Self {
             first_input_at: None,
             pending_input_count: 0,
@@ -1153,6 +1177,9 @@ impl InputLatencyTracker {
             events_per_frame_histogram: Histogram::new(3)
                 .map_err(|e| anyhow!("Failed to create events per frame histogram: {e}"))?,
             mid_draw_events_dropped: 0,
+            measured_to_scanout,
+            last_presented_at: None,
+            dropped_frames: 0,
         })
     }
 
@@ -1169,18 +1196,63 @@ impl InputLatencyTracker {
         self.mid_draw_events_dropped += 1;
     }
 
-    /// Record that a frame was presented, flushing pending latency and coalescing samples.
-    fn record_frame_presented(&mut self) {
-        if let Some(first_input_at) = self.first_input_at.take() {
-            let latency_nanos = first_input_at.elapsed().as_nanos() as u64;
-            self.latency_histogram.record(latency_nanos).ok();
-        }
+    /// Record that a frame was submitted to the platform, flushing coalescing
+    /// samples and returning the time of the first input event it reflects.
+    fn take_frame_input(&mut self) -> Option<Instant> {
         if self.pending_input_count > 0 {
             self.events_per_frame_histogram
                 .record(self.pending_input_count)
                 .ok();
             self.pending_input_count = 0;
         }
+        self.first_input_at.take()
+    }
+
+    /// Record a frame's latency, measured to when it reached the screen if the
+    /// platform reported that, or to when it was submitted otherwise.
+    fn record_frame(
+        &mut self,
+        first_input_at: Option<Instant>,
+        submitted_at: Instant,
+        presentation: Option<PresentationFeedback>,
+    ) {
+        let shown_at = match presentation {
+            Some(PresentationFeedback {
+                presented_at: Some(presented_at),
+                refresh_interval,
+                ..
+            }) => {
+                // A frame submitted before the previous one reached the screen
+                // was ready in time for the next refresh, so any further
+                // refreshes before it was shown were missed.
+                if let Some(last_presented_at) = self.last_presented_at
+                    && let Some(refresh_interval) = refresh_interval
+                    && !refresh_interval.is_zero()
+                    && submitted_at < last_presented_at
+                {
+                    let refreshes = presented_at
+                        .saturating_duration_since(last_presented_at)
+                        .as_secs_f64()
+                        / refresh_interval.as_secs_f64();
+                    self.dropped_frames += (refreshes.round() as u64).saturating_sub(1);
+                }
+                self.last_presented_at = Some(presented_at);
+                Some(presented_at)
+            }
+            Some(_) => {
+                self.dropped_frames += 1;
+                None
+            }
+            None => Some(submitted_at),
+        };
+        if let Some(first_input_at) = first_input_at
+            && let Some(shown_at) = shown_at
+        {
+            let latency_nanos = shown_at
+                .saturating_duration_since(first_input_at)
+                .as_nanos() as u64;
+            self.latency_histogram.record(latency_nanos).ok();
+        }
     }
 
     fn snapshot(&self) -> InputLatencySnapshot {
@@ -1188,10 +1260,26 @@ impl InputLatencyTracker {
             latency_histogram: self.latency_histogram.clone(),
             events_per_frame_histogram: self.events_per_frame_histogram.clone(),
             mid_draw_events_dropped: self.mid_draw_events_dropped,
+            measured_to_scanout: self.measured_to_scanout,
+            dropped_frames: self.dropped_frames,
         }
     }
 }
 
+/// Most frames reach the screen within a couple of refresh cycles. If feedback
+/// falls this far behind, the platform has stopped reporting it and waiting
+/// frames are recorded without it.
+const MAX_PENDING_PRESENTATIONS: usize = 16;
+
+/// A frame handed to the platform that hasn't been reported on screen yet.
+struct SubmittedFrame {
+    frame: u64,
+    submitted_at: Instant,
+    timing: Option<profiler::FrameTiming>,
+    #[cfg(feature = "input-latency-histogram")]
+    first_input_at: Option<Instant>,
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub(crate) enum DrawPhase {
     None,
@@ -1304,7 +1392,10 @@ impl Window {
                 allow(unused_variables)
             )]
             icon,
-            #[cfg_attr(not(target_os = "macos"), allow(unused_variables))]
+            #[cfg_attr(
+                not(any(target_os = "macos", target_os = "linux", target_os = "freebsd")),
+                allow(unused_variables)
+            )]
             tabbing_identifier,
         } = options;
 
@@ -1327,7 +1418,7 @@ impl Window {
                 display_id,
                 window_min_size,
                 icon,
-                #[cfg(target_os = "macos")]
+                #[cfg(any(target_os = "macos", target_os = "linux", target_os = "freebsd"))]
                 tabbing_identifier,
             },
         )?;
@@ -1467,19 +1558,23 @@ impl Window {
             let next_frame_callbacks = next_frame_callbacks.clone();
             let input_rate_tracker = input_rate_tracker.clone();
             move |request_frame_options| {
-                let thermal_state = handle
-                    .update(&mut cx, |_, _, cx| cx.thermal_state())
-                    .log_err();
+                let (thermal_state, throttle_for_power) = handle
+                    .update(&mut cx, |_, _, cx| {
+                        (cx.thermal_state(), cx.should_throttle_for_power())
+                    })
+                    .log_err()
+                    .unzip();
 
                 // Throttle frame rate based on conditions:
                 // - Thermal pressure (Serious/Critical): cap to ~60fps
-                // - Inactive window (not focused): cap to ~30fps to save energy
+                // - Inactive window (not focused) or opted-in low power mode: cap to ~30fps to
+                //   save energy
                 let min_frame_interval = if !request_frame_options.force_render
                     && !request_frame_options.require_presentation
                     && next_frame_callbacks.borrow().is_empty()
                 {
                     None
-                } else if !active.get() {
+                } else if !active.get() || throttle_for_power == Some(true) {
                     Some(Duration::from_micros(33333))
                 } else if let Some(ThermalState::Critical | ThermalState::Serious) = thermal_state {
                     Some(Duration::from_micros(16667))
@@ -1690,6 +1785,16 @@ impl Window {
                     .log_err();
             })
         });
+        let presentation_feedback = platform_window.on_presentation_feedback(Box::new({
+            let mut cx = cx.to_async();
+            move |feedback| {
+                handle
+                    .update(&mut cx, |_, window, _| {
+                        window.frame_presented(feedback);
+                    })
+                    .log_err();
+            }
+        }));
 
         if let Some(app_id) = app_id {
             platform_window.set_app_id(&app_id);
@@ -1742,7 +1847,11 @@ impl Window {
             needs_present,
             input_rate_tracker,
             #[cfg(feature = "input-latency-histogram")]
-            input_latency_tracker: InputLatencyTracker::new()?,
+            input_latency_tracker: InputLatencyTracker::new(presentation_feedback)?,
+            presentation_feedback,
+            submitted_frames: 0,
+            pending_presentations: VecDeque::new(),
+            drawn_frame_timing: None,
             last_input_modality: InputModality::Mouse,
             refreshing: false,
             activation_observers: SubscriberSet::new(),
@@ -2255,12 +2364,136 @@ impl Window {
     /// Renders the current frame's scene to a texture and returns the pixel data as an RGBA image.
     /// This does not present the frame to screen - useful for visual testing where we want
     /// to capture what would be rendered without displaying it or requiring the window to be visible.
-    #[cfg(any(test, feature = "test-support"))]
     pub fn render_to_image(&self) -> anyhow::Result<image::RgbaImage> {
         self.platform_window
             .render_to_image(&self.rendered_frame.scene)
     }
 
+    /// Prints `view`, laid out at the width of the printable area of the page and broken into as
+    /// many pages as it needs. Depending on [`PrintOptions::destination`], the document is
+    /// either sent to a printer chosen in the platform's print dialog or saved as a PDF file.
+    ///
+    /// See [`Self::print_to_pdf`] for how the view is laid out.
+    pub fn print(
+        &mut self,
+        view: impl Into<AnyView>,
+        options: PrintOptions,
+        cx: &mut App,
+    ) -> Task<Result<()>> {
+        let pdf = match self.print_to_pdf(view, &options, cx) {
+            Ok(pdf) => pdf,
+            Err(error) => return Task::ready(Err(error)),
+        };
+        match options.destination.clone() {
+            PrintDestination::Printer => cx.platform.print_pdf(&options, pdf),
+            PrintDestination::Pdf(path) => cx.background_spawn(async move {
+                std::fs::write(&path, pdf)
+                    .with_context(|| format!("Failed to save PDF to {}", path.display()))
+            }),
+        }
+    }
+
+    /// Renders `view` to a PDF document laid out as described by `options`.
+    ///
+    /// The view is given the width of the printable area and as much height as it asks for, so
+    /// its content shouldn't scroll. Pages are broken between lines of text where possible.
+    /// Images that haven't finished loading are left out, and shadows aren't printed.
+    pub fn print_to_pdf(
+        &mut self,
+        view: impl Into<AnyView>,
+        options: &PrintOptions,
+        cx: &mut App,
+    ) -> Result<Vec<u8>> {
+        if !self.invalidator.not_drawing() {
+            return Err(anyhow!("Can't print while the window is being drawn"));
+        }
+
+        let scale_factor = print_scale_factor();
+        let printable_size = options.printable_size();
+        let atlas = Arc::new(PrintAtlas::default());
+        let (scene, content_height) = self.paint_offscreen(
+            view.into(),
+            printable_size.width,
+            scale_factor,
+            atlas.clone(),
+            cx,
+        );
+        let page_breaks = page_breaks(
+            &scene,
+            printable_size.height.scale(scale_factor),
+            content_height.scale(scale_factor),
+        );
+        Ok(write_pdf(
+            &options.page_setup(),
+            &scene,
+            &atlas.take_textures(),
+            &page_breaks,
+        ))
+    }
+
+    /// Lays out and paints `view` at the given width and scale factor, without touching the
+    /// frames drawn for the window. Returns the painted scene and the height of the view.
+    fn paint_offscreen(
+        &mut self,
+        view: AnyView,
+        width: Pixels,
+        scale_factor: f32,
+        sprite_atlas: Arc<dyn PlatformAtlas>,
+        cx: &mut App,
+    ) -> (Scene, Pixels) {
+        let _arena_scope = ElementArenaScope::enter(&cx.element_arena);
+        let window_sprite_atlas = mem::replace(&mut self.sprite_atlas, sprite_atlas);
+        let window_scale_factor = mem::replace(&mut self.scale_factor, scale_factor);
+        let viewport_size = self.viewport_size;
+        // Subpixel glyphs can't be embedded in a document.
+        let text_rendering_mode = self
+            .text_rendering_mode
+            .replace(TextRenderingMode::Grayscale);
+        // Views must be painted from scratch rather than reusing what they painted for the window.
+        let refreshing = mem::replace(&mut self.refreshing, true);
+        let rendered_frame = mem::replace(
+            &mut self.rendered_frame,
+            Frame::new(DispatchTree::new(cx.keymap.clone(), cx.actions.clone())),
+        );
+        let next_frame = mem::replace(
+            &mut self.next_frame,
+            Frame::new(DispatchTree::new(cx.keymap.clone(), cx.actions.clone())),
+        );
+        self.a11y.deactivate_until_next_frame();
+
+        self.invalidator.set_phase(DrawPhase::Prepaint);
+        let mut element = view.into_any();
+        let content_size = element.layout_as_root(
+            size(AvailableSpace::Definite(width), AvailableSpace::MinContent),
+            self,
+            cx,
+        );
+        self.viewport_size = content_size;
+        element.prepaint_at(Point::default(), self, cx);
+        self.prepaint_deferred_draws(cx);
+        self.invalidator.set_phase(DrawPhase::Paint);
+        element.paint(self, cx);
+        self.paint_deferred_draws(cx);
+        self.invalidator.set_phase(DrawPhase::None);
+        self.layout_engine.as_mut().unwrap().clear();
+
+        let mut frame = mem::replace(&mut self.next_frame, next_frame);
+        frame.scene.finish();
+        self.rendered_frame = rendered_frame;
+        self.refreshing = refreshing;
+        self.text_rendering_mode.set(text_rendering_mode);
+        self.viewport_size = viewport_size;
+        self.scale_factor = window_scale_factor;
+        self.sprite_atlas = window_sprite_atlas;
+        (frame.scene, content_size.height)
+    }
+
+    /// Feeds input to the window through the platform as if the user had produced it, once the
+    /// current update completes. Only supported by headless windows.
+    pub fn inject_input(&self, input: PlatformInput) -> anyhow::Result<()> {
+        self.platform_window.inject_input(input)
+    }
+
     /// Set the content size of the window.
     pub fn resize(&mut self, size: Size<Pixels>) {
         self.platform_window.resize(size);
@@ -2344,6 +2577,93 @@ impl Window {
         self.client_inset
     }
 
+    /// Keeps the screen from dimming, locking or going to sleep while this window is shown,
+    /// e.g. while a video plays, until [`Self::uninhibit_idle`] is called (Wayland and X11)
+    pub fn inhibit_idle(&self) {
+        self.platform_window.set_idle_inhibited(true);
+    }
+
+    /// Releases an inhibitor set with [`Self::inhibit_idle`]
+    pub fn uninhibit_idle(&self) {
+        self.platform_window.set_idle_inhibited(false);
+    }
+
+    /// Locks the cursor in place while it's over this window. Pointer motion is reported as
+    /// [`RawMouseMotionEvent`](crate::RawMouseMotionEvent)s until [`Self::release_pointer`]
+    /// is called (Wayland and X11)
+    pub fn lock_pointer(&self) {
+        self.platform_window
+            .set_pointer_constraint(Some(PointerConstraint::Locked));
+    }
+
+    /// Keeps the cursor within this window until [`Self::release_pointer`] is called (Wayland and X11)
+    pub fn confine_pointer(&self) {
+        self.platform_window
+            .set_pointer_constraint(Some(PointerConstraint::Confined));
+    }
+
+    /// Releases a pointer lock or confinement
+    pub fn release_pointer(&self) {
+        self.platform_window.set_pointer_constraint(None);
+    }
+
+    /// Returns the current options of a layer shell window, or `None` for other kinds of windows.
+    #[cfg(all(target_os = "linux", feature = "wayland"))]
+    pub fn layer_shell_options(&self) -> Option<layer_shell::LayerShellOptions> {
+        self.platform_window.layer_shell_options()
+    }
+
+    /// Moves a layer shell window to another layer.
+    #[cfg(all(target_os = "linux", feature = "wayland"))]
+    pub fn set_layer(&self, layer: layer_shell::Layer) {
+        self.update_layer_shell_options(|options| options.layer = layer);
+    }
+
+    /// Changes the screen edges a layer shell window is anchored to.
+    #[cfg(all(target_os = "linux", feature = "wayland"))]
+    pub fn set_layer_anchor(&self, anchor: layer_shell::Anchor) {
+        self.update_layer_shell_options(|options| options.anchor = anchor);
+    }
+
+    /// Changes the margins between a layer shell window and its anchor edges, in CSS order: top,
+    /// right, bottom, left.
+    #[cfg(all(target_os = "linux", feature = "wayland"))]
+    pub fn set_layer_margin(&self, margin: Option<(Pixels, Pixels, Pixels, Pixels)>) {
+        self.update_layer_shell_options(|options| options.margin = margin);
+    }
+
+    /// Changes the area a layer shell window asks other surfaces not to occlude, e.g. to let
+    /// maximized windows cover an auto-hidden panel.
+    #[cfg(all(target_os = "linux", feature = "wayland"))]
+    pub fn set_layer_exclusive_zone(&self, exclusive_zone: Option<Pixels>) {
+        self.update_layer_shell_options(|options| options.exclusive_zone = exclusive_zone);
+    }
+
+    /// Changes how keyboard events are delivered to a layer shell window.
+    #[cfg(all(target_os = "linux", feature = "wayland"))]
+    pub fn set_layer_keyboard_interactivity(
+        &self,
+        keyboard_interactivity: layer_shell::KeyboardInteractivity,
+    ) {
+        self.update_layer_shell_options(|options| {
+            options.keyboard_interactivity = keyboard_interactivity
+        });
+    }
+
+    #[cfg(all(target_os = "linux", feature = "wayland"))]
+    fn update_layer_shell_options(&self, update: impl FnOnce(&mut layer_shell::LayerShellOptions)) {
+        if let Some(mut options) = self.platform_window.layer_shell_options() {
+            update(&mut options);
+            self.platform_window.set_layer_shell_options(options);
+        }
+    }
+
+    /// Moves the window to another display. On Wayland only layer shell and fullscreen windows can
+    /// be moved, as the compositor places all other windows; this fails for those.
+    pub fn move_to_display(&self, display_id: DisplayId) -> Result<()> {
+        self.platform_window.move_to_display(display_id)
+    }
+
     /// Returns whether the title bar window controls need to be rendered by the application (Wayland and X11)
     pub fn window_decorations(&self) -> Decorations {
         self.platform_window.window_decorations()
@@ -2723,13 +3043,18 @@ impl Window {
         self.needs_present.set(true);
 
         if let Some(draw_start) = draw_started_at {
-            profiler::record_frame_timing(profiler::FrameTiming {
+            let timing = profiler::FrameTiming {
                 window_id: self.handle.window_id(),
                 dirty_at: frame_dirty.dirty_at,
                 invalidations: frame_dirty.invalidations,
                 draw_start,
                 draw_end: Instant::now(),
-            });
+                presentation: None,
+            };
+            // A frame that's replaced before being presented is recorded as is.
+            if let Some(replaced) = self.drawn_frame_timing.replace(timing) {
+                profiler::record_frame_timing(replaced);
+            }
         }
 
         ArenaClearNeeded::new(&cx.element_arena)
@@ -2760,12 +3085,65 @@ impl Window {
     #[profiling::function]
     fn present(&mut self) {
         self.platform_window.draw(&self.rendered_frame.scene);
-        #[cfg(feature = "input-latency-histogram")]
-        self.input_latency_tracker.record_frame_presented();
+        self.submitted_frames += 1;
+        let submitted = SubmittedFrame {
+            frame: self.submitted_frames,
+            submitted_at: Instant::now(),
+            timing: self.drawn_frame_timing.take(),
+            #[cfg(feature = "input-latency-histogram")]
+            first_input_at: self.input_latency_tracker.take_frame_input(),
+        };
+        if self.presentation_feedback {
+            self.pending_presentations.push_back(submitted);
+            if self.pending_presentations.len() > MAX_PENDING_PRESENTATIONS
+                && let Some(stale) = self.pending_presentations.pop_front()
+            {
+                self.record_submitted_frame(stale, None);
+            }
+        } else {
+            self.record_submitted_frame(submitted, None);
+        }
         self.needs_present.set(false);
         profiling::finish_frame!();
     }
 
+    fn frame_presented(&mut self, feedback: PresentationFeedback) {
+        while let Some(submitted) = self.pending_presentations.pop_front() {
+            if submitted.frame > feedback.frame {
+                self.pending_presentations.push_front(submitted);
+                break;
+            }
+            // Platforms skip frames that never made it to the compositor.
+            let feedback = if submitted.frame == feedback.frame {
+                feedback
+            } else {
+                PresentationFeedback {
+                    frame: submitted.frame,
+                    presented_at: None,
+                    refresh_interval: None,
+                }
+            };
+            self.record_submitted_frame(submitted, Some(feedback));
+        }
+    }
+
+    fn record_submitted_frame(
+        &mut self,
+        submitted: SubmittedFrame,
+        presentation: Option<PresentationFeedback>,
+    ) {
+        if let Some(mut timing) = submitted.timing {
+            timing.presentation = presentation;
+            profiler::record_frame_timing(timing);
+        }
+        #[cfg(feature = "input-latency-histogram")]
+        self.input_latency_tracker.record_frame(
+            submitted.first_input_at,
+            submitted.submitted_at,
+            presentation,
+        );
+    }
+
     /// Presents the most recently drawn frame if it hasn't been presented yet.
     ///
     /// Benchmarks drive drawing synchronously rather than through a platform
@@ -4561,6 +4939,10 @@ impl Window {
             PlatformInput::MousePressure(mouse_pressure) => {
                 PlatformInput::MousePressure(mouse_pressure)
             }
+            PlatformInput::RawMouseMotion(raw_mouse_motion) => {
+                self.modifiers = raw_mouse_motion.modifiers;
+                PlatformInput::RawMouseMotion(raw_mouse_motion)
+            }
             PlatformInput::MouseExited(mouse_exited) => {
                 self.modifiers = mouse_exited.modifiers;
                 PlatformInput::MouseExited(mouse_exited)
@@ -5455,25 +5837,25 @@ impl Window {
     }
 
     /// Returns a list of all tabbed windows and their titles.
-    /// This is macOS specific.
+    /// This is supported on macOS and Linux.
     pub fn tabbed_windows(&self) -> Option<Vec<SystemWindowTab>> {
         self.platform_window.tabbed_windows()
     }
 
     /// Returns the tab bar visibility.
-    /// This is macOS specific.
+    /// This is supported on macOS and Linux.
     pub fn tab_bar_visible(&self) -> bool {
         self.platform_window.tab_bar_visible()
     }
 
     /// Merges all open windows into a single tabbed window.
-    /// This is macOS specific.
+    /// This is supported on macOS and Linux.
     pub fn merge_all_windows(&self) {
         self.platform_window.merge_all_windows()
     }
 
     /// Moves the tab to a new containing window.
-    /// This is macOS specific.
+    /// This is supported on macOS and Linux.
     pub fn move_tab_to_new_window(&self) {
         self.platform_window.move_tab_to_new_window()
     }
@@ -5485,7 +5867,7 @@ impl Window {
     }
 
     /// Sets the tabbing identifier for the window.
-    /// This is macOS specific.
+    /// This is supported on macOS and Linux.
     pub fn set_tabbing_identifier(&self, tabbing_identifier: Option<String>) {
         self.platform_window
             .set_tabbing_identifier(tabbing_identifier)
@@ -186,6 +186,12 @@ impl A11y {
         self.active_this_frame
     }
 
+    /// Stops building the tree until the next frame starts, for drawing that never reaches the
+    /// screen.
+    pub(crate) fn deactivate_until_next_frame(&mut self) {
+        self.active_this_frame = false;
+    }
+
     pub(crate) fn set_focusable(&mut self, node_id: NodeId, focus_id: FocusId) {
         self.focus_ids.insert(node_id, focus_id);
     }
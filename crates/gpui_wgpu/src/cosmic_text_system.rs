@@ -23,6 +23,109 @@ use unicode_segmentation::UnicodeSegmentation;
 
 pub struct CosmicTextSystem(RwLock<CosmicTextSystemState>);
 
+/// How glyphs are rasterized, usually taken from the desktop's font settings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FontRenderingOptions {
+    /// Smooth glyph edges. Without antialiasing, glyphs are drawn with hard edges.
+    pub antialias: bool,
+    /// Fit glyph outlines to the pixel grid.
+    pub hinting: bool,
+    /// Prefer subpixel antialiasing. Turned off for displays whose subpixels are laid out
+    /// vertically or are unknown.
+    pub subpixel: bool,
+}
+
+impl Default for FontRenderingOptions {
+    fn default() -> Self {
+        Self {
+            antialias: true,
+            hinting: true,
+            subpixel: true,
+        }
+    }
+}
+
+/// How the glyphs of one font are rasterized. Starts out from the desktop's
+/// `FontRenderingOptions`; with the `fontconfig` feature, the user's fontconfig rules for the font
+/// are applied on top.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct GlyphRenderingSettings {
+    pub(crate) antialias: bool,
+    pub(crate) hint_style: HintStyle,
+    pub(crate) subpixel_order: SubpixelOrder,
+    pub(crate) lcd_filter: LcdFilter,
+    /// Use the bitmaps embedded in a font instead of its outlines at sizes it has them for.
+    pub(crate) embedded_bitmaps: bool,
+}
+
+impl GlyphRenderingSettings {
+    pub(crate) fn new(options: FontRenderingOptions) -> Self {
+        Self {
+            antialias: options.antialias,
+            hint_style: if options.hinting {
+                HintStyle::Full
+            } else {
+                HintStyle::None
+            },
+            subpixel_order: if options.subpixel {
+                SubpixelOrder::Rgb
+            } else {
+                SubpixelOrder::None
+            },
+            lcd_filter: LcdFilter::None,
+            embedded_bitmaps: true,
+        }
+    }
+
+    fn subpixel(&self) -> bool {
+        self.antialias && matches!(self.subpixel_order, SubpixelOrder::Rgb | SubpixelOrder::Bgr)
+    }
+}
+
+/// How strongly glyph outlines are fitted to the pixel grid, matching fontconfig's `hintstyle`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(not(feature = "fontconfig"), allow(dead_code))]
+pub(crate) enum HintStyle {
+    None,
+    /// Only keeps glyphs on whole pixels vertically, preserving their shapes.
+    Slight,
+    Medium,
+    Full,
+}
+
+/// The layout of the display's subpixels, matching fontconfig's `rgba`. Subpixel antialiasing is
+/// only used for horizontal layouts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(not(feature = "fontconfig"), allow(dead_code))]
+pub(crate) enum SubpixelOrder {
+    None,
+    Rgb,
+    Bgr,
+    VerticalRgb,
+    VerticalBgr,
+}
+
+/// The filter applied to subpixel antialiased glyphs to reduce color fringes, matching
+/// fontconfig's `lcdfilter`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(not(feature = "fontconfig"), allow(dead_code))]
+pub(crate) enum LcdFilter {
+    None,
+    Default,
+    Light,
+}
+
+impl LcdFilter {
+    /// The weights of FreeType's FIR filters, out of 256.
+    fn weights(self) -> Option<[u32; 5]> {
+        match self {
+            LcdFilter::None => None,
+            LcdFilter::Default => Some([8, 77, 86, 77, 8]),
+            LcdFilter::Light => Some([0, 85, 86, 85, 0]),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct FontKey {
     family: SharedString,
@@ -50,6 +153,12 @@ struct CosmicTextSystemState {
     /// for every font face in a family.
     font_ids_by_family_cache: HashMap<FontKey, SmallVec<[FontId; 4]>>,
     system_font_fallback: String,
+    rendering_options: FontRenderingOptions,
+    /// The settings each font is rasterized with, queried when it is first rendered.
+    glyph_rendering_settings: HashMap<FontId, GlyphRenderingSettings>,
+    /// Whether fontconfig was loaded, so that it can be asked how fonts should be rendered.
+    #[cfg(feature = "fontconfig")]
+    fontconfig: bool,
 }
 
 struct LoadedFont {
@@ -72,6 +181,10 @@ impl CosmicTextSystem {
             loaded_fonts: Vec::new(),
             font_ids_by_family_cache: HashMap::default(),
             system_font_fallback: system_font_fallback.to_string(),
+            rendering_options: FontRenderingOptions::default(),
+            glyph_rendering_settings: HashMap::default(),
+            #[cfg(feature = "fontconfig")]
+            fontconfig: crate::fontconfig::init(),
         }))
     }
 
@@ -88,8 +201,20 @@ impl CosmicTextSystem {
             loaded_fonts: Vec::new(),
             font_ids_by_family_cache: HashMap::default(),
             system_font_fallback: system_font_fallback.to_string(),
+            rendering_options: FontRenderingOptions::default(),
+            glyph_rendering_settings: HashMap::default(),
+            #[cfg(feature = "fontconfig")]
+            fontconfig: false,
         }))
     }
+
+    /// Changes how glyphs are rasterized from now on. Glyphs that were already rasterized into a
+    /// sprite atlas keep their old rendering until the atlas is cleared.
+    pub fn set_rendering_options(&self, options: FontRenderingOptions) {
+        let mut state = self.0.write();
+        state.rendering_options = options;
+        state.glyph_rendering_settings.clear();
+    }
 }
 
 impl PlatformTextSystem for CosmicTextSystem {
@@ -194,12 +319,21 @@ impl PlatformTextSystem for CosmicTextSystem {
         self.0.write().layout_line(text, font_size, runs)
     }
 
-    fn recommended_rendering_mode(
-        &self,
-        _font_id: FontId,
-        _font_size: Pixels,
-    ) -> TextRenderingMode {
-        TextRenderingMode::Subpixel
+    fn recommended_rendering_mode(&self, font_id: FontId, _font_size: Pixels) -> TextRenderingMode {
+        // This is called for every glyph, so only take the write lock when the settings for the
+        // font haven't been queried yet.
+        let settings = self
+            .0
+            .read()
+            .glyph_rendering_settings
+            .get(&font_id)
+            .copied();
+        let settings = settings.unwrap_or_else(|| self.0.write().glyph_rendering_settings(font_id));
+        if settings.subpixel() {
+            TextRenderingMode::Subpixel
+        } else {
+            TextRenderingMode::Grayscale
+        }
     }
 }
 
@@ -268,13 +402,12 @@ impl CosmicTextSystemState {
 
         let name = gpui::font_name_with_fallbacks(name, &self.system_font_fallback);
 
-        let families = self
-            .font_system
-            .db()
-            .faces()
-            .filter(|face| face.families.iter().any(|family| *name == family.0))
-            .map(|face| (face.id, face.post_script_name.clone()))
-            .collect::<SmallVec<[_; 4]>>();
+        let mut families = self.family_faces(name);
+        if families.is_empty()
+            && let Some(resolved_name) = self.resolve_generic_family(name)
+        {
+            families = self.family_faces(&resolved_name);
+        }
 
         let cosmic_features = cosmic_font_features(features)?;
 
@@ -311,6 +444,69 @@ impl CosmicTextSystemState {
         Ok(loaded_font_ids)
     }
 
+    fn family_faces(&self, name: &str) -> SmallVec<[(cosmic_text::fontdb::ID, String); 4]> {
+        self.font_system
+            .db()
+            .faces()
+            .filter(|face| face.families.iter().any(|family| *name == family.0))
+            .map(|face| (face.id, face.post_script_name.clone()))
+            .collect()
+    }
+
+    /// Resolves generic families such as `monospace` to the family the rest of the desktop uses
+    /// for them.
+    #[cfg(feature = "fontconfig")]
+    fn resolve_generic_family(&self, name: &str) -> Option<String> {
+        if !self.fontconfig {
+            return None;
+        }
+        crate::fontconfig::resolve_generic_family(name)
+    }
+
+    #[cfg(not(feature = "fontconfig"))]
+    fn resolve_generic_family(&self, _name: &str) -> Option<String> {
+        None
+    }
+
+    fn glyph_rendering_settings(&mut self, font_id: FontId) -> GlyphRenderingSettings {
+        if let Some(settings) = self.glyph_rendering_settings.get(&font_id) {
+            return *settings;
+        }
+        let settings = self
+            .query_glyph_rendering_settings(font_id)
+            .unwrap_or_else(|| GlyphRenderingSettings::new(self.rendering_options));
+        self.glyph_rendering_settings.insert(font_id, settings);
+        settings
+    }
+
+    #[cfg(feature = "fontconfig")]
+    fn query_glyph_rendering_settings(&self, font_id: FontId) -> Option<GlyphRenderingSettings> {
+        if !self.fontconfig {
+            return None;
+        }
+        let face = self
+            .font_system
+            .db()
+            .face(self.loaded_font(font_id).font.id())?;
+        let family = face.families.first()?;
+        let style = match face.style {
+            cosmic_text::Style::Normal => gpui::FontStyle::Normal,
+            cosmic_text::Style::Italic => gpui::FontStyle::Italic,
+            cosmic_text::Style::Oblique => gpui::FontStyle::Oblique,
+        };
+        crate::fontconfig::rendering_settings(
+            &family.0,
+            gpui::FontWeight(face.weight.0 as f32),
+            style,
+            self.rendering_options,
+        )
+    }
+
+    #[cfg(not(feature = "fontconfig"))]
+    fn query_glyph_rendering_settings(&self, _font_id: FontId) -> Option<GlyphRenderingSettings> {
+        None
+    }
+
     fn advance(&self, font_id: FontId, glyph_id: GlyphId) -> Result<Size<f32>> {
         let glyph_metrics = self.loaded_font(font_id).font.as_swash().glyph_metrics(&[]);
         Ok(Size {
@@ -346,16 +542,35 @@ impl CosmicTextSystemState {
             anyhow::bail!("glyph bounds are empty");
         }
 
+        let settings = self.glyph_rendering_settings(params.font_id);
         let mut image = self.render_glyph_image(params)?;
+        if !settings.antialias && matches!(image.content, swash::scale::image::Content::Mask) {
+            for alpha in &mut image.data {
+                *alpha = if *alpha >= 128 { 255 } else { 0 };
+            }
+        }
         let bitmap_size = glyph_bounds.size;
         match image.content {
-            swash::scale::image::Content::Color | swash::scale::image::Content::SubpixelMask => {
+            swash::scale::image::Content::Color => {
                 // Convert from RGBA to BGRA.
                 for pixel in image.data.chunks_exact_mut(4) {
                     pixel.swap(0, 2);
                 }
                 Ok((bitmap_size, image.data))
             }
+            swash::scale::image::Content::SubpixelMask => {
+                if let Some(weights) = settings.lcd_filter.weights() {
+                    apply_lcd_filter(&mut image.data, image.placement.width as usize, weights);
+                }
+                // Convert from RGBA to BGRA. On BGR displays the leftmost subpixel is blue, so
+                // the coverage is already in the order the atlas expects.
+                if settings.subpixel_order != SubpixelOrder::Bgr {
+                    for pixel in image.data.chunks_exact_mut(4) {
+                        pixel.swap(0, 2);
+                    }
+                }
+                Ok((bitmap_size, image.data))
+            }
             swash::scale::image::Content::Mask => {
                 if params.subpixel_rendering {
                     // We must always return RGBA data when subpixel rendering is requested.
@@ -372,20 +587,31 @@ impl CosmicTextSystemState {
         &mut self,
         params: &RenderGlyphParams,
     ) -> Result<swash::scale::image::Image> {
+        let settings = self.glyph_rendering_settings(params.font_id);
         let loaded_font = &self.loaded_fonts[params.font_id.0];
         let font_ref = loaded_font.font.as_swash();
         let pixel_size = f32::from(params.font_size);
 
+        // Slight hinting keeps glyphs on whole pixels vertically without distorting their
+        // outlines, which swash's hinter would.
+        let subpixel_offset_y = if settings.hint_style == HintStyle::Slight {
+            0.
+        } else {
+            params.subpixel_variant.y as f32 / SUBPIXEL_VARIANTS_Y as f32 / params.scale_factor
+        };
         let subpixel_offset = Vector::new(
             params.subpixel_variant.x as f32 / SUBPIXEL_VARIANTS_X as f32 / params.scale_factor,
-            params.subpixel_variant.y as f32 / SUBPIXEL_VARIANTS_Y as f32 / params.scale_factor,
+            subpixel_offset_y,
         );
 
         let mut scaler = self
             .swash_scale_context
             .builder(font_ref)
             .size(pixel_size * params.scale_factor)
-            .hint(true)
+            .hint(matches!(
+                settings.hint_style,
+                HintStyle::Medium | HintStyle::Full
+            ))
             .build();
 
         let sources: &[Source] = if params.is_emoji {
@@ -394,8 +620,10 @@ impl CosmicTextSystemState {
                 Source::ColorBitmap(StrikeWith::BestFit),
                 Source::Outline,
             ]
-        } else {
+        } else if settings.embedded_bitmaps {
             &[Source::Bitmap(StrikeWith::ExactSize), Source::Outline]
+        } else {
+            &[Source::Outline]
         };
 
         let mut renderer = Render::new(sources);
@@ -857,6 +1085,36 @@ fn face_info_into_properties(
     }
 }
 
+/// Spreads the coverage of each subpixel over its neighbours, like FreeType's LCD filters. `data`
+/// holds rows of RGBA pixels whose channels are in the order the subpixels appear on screen.
+fn apply_lcd_filter(data: &mut [u8], width: usize, weights: [u32; 5]) {
+    if width == 0 {
+        return;
+    }
+    let mut subpixels = Vec::with_capacity(width * 3);
+    for row in data.chunks_exact_mut(width * 4) {
+        subpixels.clear();
+        subpixels.extend(
+            row.chunks_exact(4)
+                .flat_map(|pixel| [pixel[0], pixel[1], pixel[2]]),
+        );
+        for (ix, pixel) in row.chunks_exact_mut(4).enumerate() {
+            for (channel, value) in pixel[..3].iter_mut().enumerate() {
+                let center = ix * 3 + channel;
+                let sum: u32 = weights
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(tap, weight)| {
+                        let subpixel = (center + tap).checked_sub(2)?;
+                        Some(*subpixels.get(subpixel)? as u32 * weight)
+                    })
+                    .sum();
+                *value = (sum / 256).min(255) as u8;
+            }
+        }
+    }
+}
+
 fn check_is_known_emoji_font(postscript_name: &str) -> bool {
     // TODO: Include other common emoji fonts
     postscript_name == "NotoColorEmoji"
@@ -1051,6 +1309,20 @@ mod tests {
         assert_eq!(spans.as_slice(), &[span(0, text.len(), Some(0), fid(1))]);
     }
 
+    #[test]
+    fn lcd_filter_spreads_subpixel_coverage() {
+        let weights = LcdFilter::Default.weights().unwrap();
+        let mut data = vec![0; 3 * 4];
+        // The green subpixel of the middle pixel.
+        data[5] = 255;
+        apply_lcd_filter(&mut data, 3, weights);
+        assert_eq!(data, [0, 0, 7, 0, 76, 85, 76, 0, 7, 0, 0, 0]);
+
+        let mut data = vec![255; 3 * 4];
+        apply_lcd_filter(&mut data, 3, weights);
+        assert_eq!(&data[4..8], &[255, 255, 255, 255]);
+    }
+
     #[test]
     fn run_spans_empty_run_returns_no_spans() {
         let primary = fid(0);
@@ -0,0 +1,273 @@
+//! Queries fontconfig for the settings the rest of the desktop uses to render a font: the user's
+//! per-family rules in `fonts.conf`, family aliases such as `sans-serif`, and so on.
+
+use std::{
+    ffi::{CStr, CString, c_char, c_int},
+    ptr,
+};
+
+use fontconfig_sys::{
+    FcBool, FcChar8, FcConfigSubstitute, FcDefaultSubstitute, FcFontMatch, FcInit, FcMatchPattern,
+    FcPattern, FcPatternAddBool, FcPatternAddInteger, FcPatternAddString, FcPatternCreate,
+    FcPatternDestroy, FcPatternGetBool, FcPatternGetInteger, FcPatternGetString, FcResultMatch,
+};
+use gpui::{FontStyle, FontWeight};
+
+use crate::cosmic_text_system::{
+    FontRenderingOptions, GlyphRenderingSettings, HintStyle, LcdFilter, SubpixelOrder,
+};
+
+const FC_FAMILY: &CStr = c"family";
+const FC_WEIGHT: &CStr = c"weight";
+const FC_SLANT: &CStr = c"slant";
+const FC_ANTIALIAS: &CStr = c"antialias";
+const FC_HINTING: &CStr = c"hinting";
+const FC_HINT_STYLE: &CStr = c"hintstyle";
+const FC_RGBA: &CStr = c"rgba";
+const FC_LCD_FILTER: &CStr = c"lcdfilter";
+const FC_EMBEDDED_BITMAP: &CStr = c"embeddedbitmap";
+
+const FC_SLANT_ROMAN: c_int = 0;
+const FC_SLANT_ITALIC: c_int = 100;
+const FC_SLANT_OBLIQUE: c_int = 110;
+
+const FC_RGBA_NONE: c_int = 5;
+
+/// Loads the default fontconfig configuration. Returns false when it can't be loaded, in which
+/// case text is rendered with the desktop settings alone.
+pub(crate) fn init() -> bool {
+    // SAFETY: `FcInit` may be called any number of times.
+    let initialized = unsafe { FcInit() } != 0;
+    if !initialized {
+        log::warn!("failed to load the fontconfig configuration");
+    }
+    initialized
+}
+
+/// The generic families fontconfig's default configuration defines aliases for.
+const GENERIC_FAMILIES: &[&str] = &[
+    "sans-serif",
+    "sans",
+    "serif",
+    "monospace",
+    "mono",
+    "cursive",
+    "fantasy",
+    "system-ui",
+    "emoji",
+    "math",
+];
+
+/// Returns the family fontconfig picks for a generic family such as `monospace`, or `None` if the
+/// given family isn't generic. Other families are left alone, as fontconfig would substitute any
+/// font for one that isn't installed.
+pub(crate) fn resolve_generic_family(family: &str) -> Option<String> {
+    if !GENERIC_FAMILIES
+        .iter()
+        .any(|generic| generic.eq_ignore_ascii_case(family))
+    {
+        return None;
+    }
+    let pattern = Pattern::new(family)?;
+    let matched = pattern.font_match()?;
+    matched.get_string(FC_FAMILY)
+}
+
+/// Returns how the given font should be rendered. The desktop settings are used for anything the
+/// user's fontconfig configuration doesn't set, the same way cairo merges them.
+pub(crate) fn rendering_settings(
+    family: &str,
+    weight: FontWeight,
+    style: FontStyle,
+    options: FontRenderingOptions,
+) -> Option<GlyphRenderingSettings> {
+    let pattern = Pattern::new(family)?;
+    pattern.add_integer(FC_WEIGHT, fc_weight(weight.0));
+    pattern.add_integer(
+        FC_SLANT,
+        match style {
+            FontStyle::Normal => FC_SLANT_ROMAN,
+            FontStyle::Italic => FC_SLANT_ITALIC,
+            FontStyle::Oblique => FC_SLANT_OBLIQUE,
+        },
+    );
+    let matched = pattern.font_match_with(|pattern| {
+        if pattern.get_bool(FC_ANTIALIAS).is_none() {
+            pattern.add_bool(FC_ANTIALIAS, options.antialias);
+        }
+        if pattern.get_bool(FC_HINTING).is_none() {
+            pattern.add_bool(FC_HINTING, options.hinting);
+        }
+        if pattern.get_integer(FC_RGBA).is_none() && !options.subpixel {
+            pattern.add_integer(FC_RGBA, FC_RGBA_NONE);
+        }
+    })?;
+
+    let defaults = GlyphRenderingSettings::new(options);
+    let hinting = matched.get_bool(FC_HINTING).unwrap_or(options.hinting);
+    Some(GlyphRenderingSettings {
+        antialias: matched.get_bool(FC_ANTIALIAS).unwrap_or(defaults.antialias),
+        hint_style: if hinting {
+            match matched.get_integer(FC_HINT_STYLE) {
+                Some(0) => HintStyle::None,
+                Some(1) => HintStyle::Slight,
+                Some(2) => HintStyle::Medium,
+                Some(3) => HintStyle::Full,
+                _ => defaults.hint_style,
+            }
+        } else {
+            HintStyle::None
+        },
+        subpixel_order: match matched.get_integer(FC_RGBA) {
+            Some(1) => SubpixelOrder::Rgb,
+            Some(2) => SubpixelOrder::Bgr,
+            Some(3) => SubpixelOrder::VerticalRgb,
+            Some(4) => SubpixelOrder::VerticalBgr,
+            Some(5) => SubpixelOrder::None,
+            _ => defaults.subpixel_order,
+        },
+        lcd_filter: match matched.get_integer(FC_LCD_FILTER) {
+            Some(0) => LcdFilter::None,
+            Some(2) => LcdFilter::Light,
+            // FreeType's legacy filter is only kept for compatibility and looks close to the
+            // default one.
+            Some(1 | 3) => LcdFilter::Default,
+            _ => defaults.lcd_filter,
+        },
+        embedded_bitmaps: matched
+            .get_bool(FC_EMBEDDED_BITMAP)
+            .unwrap_or(defaults.embedded_bitmaps),
+    })
+}
+
+/// Converts an OpenType weight to fontconfig's weight scale, like `FcWeightFromOpenType`.
+fn fc_weight(weight: f32) -> c_int {
+    const WEIGHTS: [(f32, f32); 12] = [
+        (100., 0.),
+        (200., 40.),
+        (300., 50.),
+        (350., 55.),
+        (380., 75.),
+        (400., 80.),
+        (500., 100.),
+        (600., 180.),
+        (700., 200.),
+        (800., 205.),
+        (900., 210.),
+        (1000., 215.),
+    ];
+    let weight = weight.clamp(100., 1000.);
+    let ix = WEIGHTS
+        .iter()
+        .position(|(open_type, _)| weight <= *open_type)
+        .unwrap_or(WEIGHTS.len() - 1)
+        .max(1);
+    let (ot_low, fc_low) = WEIGHTS[ix - 1];
+    let (ot_high, fc_high) = WEIGHTS[ix];
+    (fc_low + (weight - ot_low) * (fc_high - fc_low) / (ot_high - ot_low)).round() as c_int
+}
+
+/// An owned `FcPattern`.
+struct Pattern(*mut FcPattern);
+
+impl Pattern {
+    fn new(family: &str) -> Option<Self> {
+        let family = CString::new(family).ok()?;
+        // SAFETY: the pattern is destroyed when dropped.
+        let pattern = Self(unsafe { FcPatternCreate() });
+        if pattern.0.is_null() {
+            return None;
+        }
+        // SAFETY: fontconfig copies the string.
+        unsafe {
+            FcPatternAddString(
+                pattern.0,
+                FC_FAMILY.as_ptr(),
+                family.as_ptr() as *const FcChar8,
+            );
+        }
+        Some(pattern)
+    }
+
+    /// Returns the best font for this pattern, with the configuration's rendering settings
+    /// applied.
+    fn font_match(&self) -> Option<Self> {
+        self.font_match_with(|_| {})
+    }
+
+    /// Like `font_match`, but lets the caller add properties after the user's configuration has
+    /// been applied and before fontconfig's defaults are.
+    fn font_match_with(&self, substitute: impl FnOnce(&Self)) -> Option<Self> {
+        let mut result = FcResultMatch;
+        // SAFETY: a null config refers to the default one, loaded by `FcInit`.
+        let matched = unsafe {
+            FcConfigSubstitute(ptr::null_mut(), self.0, FcMatchPattern);
+            substitute(self);
+            FcDefaultSubstitute(self.0);
+            FcFontMatch(ptr::null_mut(), self.0, &mut result)
+        };
+        (!matched.is_null()).then_some(Self(matched))
+    }
+
+    fn add_bool(&self, object: &CStr, value: bool) {
+        // SAFETY: `object` is a valid C string.
+        unsafe { FcPatternAddBool(self.0, object.as_ptr(), value as FcBool) };
+    }
+
+    fn add_integer(&self, object: &CStr, value: c_int) {
+        // SAFETY: `object` is a valid C string.
+        unsafe { FcPatternAddInteger(self.0, object.as_ptr(), value) };
+    }
+
+    fn get_bool(&self, object: &CStr) -> Option<bool> {
+        let mut value: FcBool = 0;
+        // SAFETY: `object` is a valid C string and `value` outlives the call.
+        let result = unsafe { FcPatternGetBool(self.0, object.as_ptr(), 0, &mut value) };
+        (result == FcResultMatch).then_some(value != 0)
+    }
+
+    fn get_integer(&self, object: &CStr) -> Option<c_int> {
+        let mut value: c_int = 0;
+        // SAFETY: `object` is a valid C string and `value` outlives the call.
+        let result = unsafe { FcPatternGetInteger(self.0, object.as_ptr(), 0, &mut value) };
+        (result == FcResultMatch).then_some(value)
+    }
+
+    fn get_string(&self, object: &CStr) -> Option<String> {
+        let mut value: *mut FcChar8 = ptr::null_mut();
+        // SAFETY: the returned string is owned by the pattern and copied before it is dropped.
+        unsafe {
+            let result = FcPatternGetString(self.0, object.as_ptr(), 0, &mut value);
+            if result != FcResultMatch || value.is_null() {
+                return None;
+            }
+            Some(
+                CStr::from_ptr(value as *const c_char)
+                    .to_string_lossy()
+                    .into_owned(),
+            )
+        }
+    }
+}
+
+impl Drop for Pattern {
+    fn drop(&mut self) {
+        // SAFETY: the pattern is owned and not used after this.
+        unsafe { FcPatternDestroy(self.0) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fc_weight;
+
+    #[test]
+    fn converts_open_type_weights() {
+        assert_eq!(fc_weight(100.), 0);
+        assert_eq!(fc_weight(400.), 80);
+        assert_eq!(fc_weight(450.), 90);
+        assert_eq!(fc_weight(700.), 200);
+        assert_eq!(fc_weight(1000.), 215);
+        assert_eq!(fc_weight(50.), 0);
+    }
+}
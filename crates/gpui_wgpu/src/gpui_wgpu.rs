@@ -1,4 +1,6 @@
 mod cosmic_text_system;
+#[cfg(feature = "fontconfig")]
+mod fontconfig;
 mod wgpu_atlas;
 mod wgpu_context;
 mod wgpu_renderer;
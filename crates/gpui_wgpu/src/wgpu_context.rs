@@ -71,16 +71,7 @@ impl WgpuContext {
                 reject_software,
             ))?;
 
-        let device_lost = Arc::new(AtomicBool::new(false));
-        device.set_device_lost_callback({
-            let device_lost = Arc::clone(&device_lost);
-            move |reason, message| {
-                log::error!("wgpu device lost: reason={reason:?}, message={message}");
-                if reason != wgpu::DeviceLostReason::Destroyed {
-                    device_lost.store(true, Ordering::Relaxed);
-                }
-            }
-        });
+        let device_lost = Self::watch_device_lost(&device);
 
         log::info!(
             "Selected GPU adapter: {:?} ({:?})",
@@ -99,6 +90,64 @@ impl WgpuContext {
         })
     }
 
+    /// Creates a context for rendering into offscreen textures, without a display connection.
+    /// Software adapters such as llvmpipe are accepted, since headless machines rarely have
+    /// anything else.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn new_offscreen() -> anyhow::Result<Self> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::VULKAN | wgpu::Backends::GL,
+            flags: wgpu::InstanceFlags::default(),
+            backend_options: wgpu::BackendOptions::default(),
+            memory_budget_thresholds: wgpu::MemoryBudgetThresholds::default(),
+            display: None,
+        });
+
+        let adapter = gpui::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::LowPower,
+            compatible_surface: None,
+            force_fallback_adapter: false,
+        }))
+        .map_err(|e| {
+            anyhow::anyhow!("Failed to request a GPU adapter for offscreen rendering: {e}")
+        })?;
+
+        let (device, queue, dual_source_blending, color_texture_format) =
+            gpui::block_on(Self::create_device(&adapter))?;
+        let device_lost = Self::watch_device_lost(&device);
+
+        log::info!(
+            "Selected GPU adapter for offscreen rendering: {:?} ({:?})",
+            adapter.get_info().name,
+            adapter.get_info().backend
+        );
+
+        Ok(Self {
+            instance,
+            adapter,
+            device: Arc::new(device),
+            queue: Arc::new(queue),
+            dual_source_blending,
+            color_texture_format,
+            device_lost,
+        })
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    fn watch_device_lost(device: &wgpu::Device) -> Arc<AtomicBool> {
+        let device_lost = Arc::new(AtomicBool::new(false));
+        device.set_device_lost_callback({
+            let device_lost = Arc::clone(&device_lost);
+            move |reason, message| {
+                log::error!("wgpu device lost: reason={reason:?}, message={message}");
+                if reason != wgpu::DeviceLostReason::Destroyed {
+                    device_lost.store(true, Ordering::Relaxed);
+                }
+            }
+        });
+        device_lost
+    }
+
     #[cfg(target_family = "wasm")]
     pub async fn new_web() -> anyhow::Result<Self> {
         let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
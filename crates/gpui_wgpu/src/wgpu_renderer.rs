@@ -108,7 +108,7 @@ pub type GpuContext = Rc<RefCell<Option<WgpuContext>>>;
 struct WgpuResources {
     device: Arc<wgpu::Device>,
     queue: Arc<wgpu::Queue>,
-    surface: wgpu::Surface<'static>,
+    target: RenderTarget,
     pipelines: WgpuPipelines,
     bind_group_layouts: WgpuBindGroupLayouts,
     atlas_sampler: wgpu::Sampler,
@@ -129,6 +129,33 @@ impl WgpuResources {
         self.path_msaa_texture = None;
         self.path_msaa_view = None;
     }
+
+    fn offscreen_texture(&self) -> Option<&wgpu::Texture> {
+        match &self.target {
+            RenderTarget::Surface(_) => None,
+            RenderTarget::Offscreen(texture) => Some(texture),
+        }
+    }
+}
+
+/// What frames are drawn into.
+enum RenderTarget {
+    /// A window's surface, which frames are presented to.
+    Surface(wgpu::Surface<'static>),
+    /// A texture that holds the last frame until it is read back, for windows without a display.
+    Offscreen(wgpu::Texture),
+}
+
+impl RenderTarget {
+    fn configure(&mut self, device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) {
+        match self {
+            Self::Surface(surface) => surface.configure(device, config),
+            Self::Offscreen(texture) => {
+                texture.destroy();
+                *texture = create_offscreen_texture(device, config);
+            }
+        }
+    }
 }
 
 pub struct WgpuRenderer {
@@ -234,13 +261,38 @@ impl WgpuRenderer {
         Self::new_internal(
             Some(Rc::clone(&gpu_context)),
             context,
-            surface,
+            Some(surface),
             config,
             compositor_gpu,
             atlas,
         )
     }
 
+    /// Creates a renderer that draws into an offscreen texture rather than a window's surface.
+    /// Frames are retrieved with [`Self::read_pixels`].
+    #[cfg(not(target_family = "wasm"))]
+    pub fn new_offscreen(
+        gpu_context: GpuContext,
+        config: WgpuSurfaceConfig,
+    ) -> anyhow::Result<Self> {
+        let mut ctx_ref = gpu_context.borrow_mut();
+        let context = match ctx_ref.as_mut() {
+            Some(context) => context,
+            None => ctx_ref.insert(WgpuContext::new_offscreen()?),
+        };
+
+        let atlas = Arc::new(WgpuAtlas::from_context(context));
+
+        Self::new_internal(
+            Some(Rc::clone(&gpu_context)),
+            context,
+            None,
+            config,
+            None,
+            atlas,
+        )
+    }
+
     #[cfg(target_family = "wasm")]
     pub fn new_from_canvas(
         context: &WgpuContext,
@@ -254,18 +306,30 @@ impl WgpuRenderer {
 
         let atlas = Arc::new(WgpuAtlas::from_context(context));
 
-        Self::new_internal(None, context, surface, config, None, atlas)
+        Self::new_internal(None, context, Some(surface), config, None, atlas)
     }
 
     fn new_internal(
         gpu_context: Option<GpuContext>,
         context: &WgpuContext,
-        surface: wgpu::Surface<'static>,
+        surface: Option<wgpu::Surface<'static>>,
         config: WgpuSurfaceConfig,
         compositor_gpu: Option<CompositorGpuHint>,
         atlas: Arc<WgpuAtlas>,
     ) -> anyhow::Result<Self> {
-        let surface_caps = surface.get_capabilities(&context.adapter);
+        let surface_caps = match &surface {
+            Some(surface) => surface.get_capabilities(&context.adapter),
+            // Offscreen textures are never presented, so any alpha mode works.
+            None => wgpu::SurfaceCapabilities {
+                formats: vec![wgpu::TextureFormat::Rgba8Unorm],
+                present_modes: vec![wgpu::PresentMode::Fifo],
+                alpha_modes: vec![
+                    wgpu::CompositeAlphaMode::Opaque,
+                    wgpu::CompositeAlphaMode::PreMultiplied,
+                ],
+                usages: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            },
+        };
         let preferred_formats = [
             wgpu::TextureFormat::Bgra8Unorm,
             wgpu::TextureFormat::Rgba8Unorm,
@@ -345,7 +409,15 @@ impl WgpuRenderer {
         };
         // Configure the surface immediately. The adapter selection process already validated
         // that this adapter can successfully configure this surface.
-        surface.configure(&context.device, &surface_config);
+        let target = match surface {
+            Some(surface) => {
+                surface.configure(&context.device, &surface_config);
+                RenderTarget::Surface(surface)
+            }
+            None => {
+                RenderTarget::Offscreen(create_offscreen_texture(&context.device, &surface_config))
+            }
+        };
 
         let queue = Arc::clone(&context.queue);
         let dual_source_blending = context.supports_dual_source_blending();
@@ -449,7 +521,7 @@ impl WgpuRenderer {
         let resources = WgpuResources {
             device,
             queue,
-            surface,
+            target,
             pipelines,
             bind_group_layouts,
             atlas_sampler,
@@ -981,7 +1053,7 @@ impl WgpuRenderer {
             }
 
             resources
-                .surface
+                .target
                 .configure(&resources.device, &surface_config);
 
             // Invalidate intermediate textures - they will be lazily recreated
@@ -1037,7 +1109,7 @@ impl WgpuRenderer {
             let dual_source_blending = self.dual_source_blending;
             let resources = self.resources_mut();
             resources
-                .surface
+                .target
                 .configure(&resources.device, &surface_config);
             resources.pipelines = Self::create_pipelines(
                 &resources.device,
@@ -1114,30 +1186,36 @@ impl WgpuRenderer {
 
         self.atlas.before_frame();
 
-        let frame = match self.resources().surface.get_current_texture() {
-            wgpu::CurrentSurfaceTexture::Success(frame) => frame,
-            wgpu::CurrentSurfaceTexture::Suboptimal(frame) => {
+        // Offscreen renderers always draw into the same texture, there is nothing to acquire.
+        let surface_texture = match &self.resources().target {
+            RenderTarget::Surface(surface) => Some(surface.get_current_texture()),
+            RenderTarget::Offscreen(_) => None,
+        };
+        let frame = match surface_texture {
+            None => None,
+            Some(wgpu::CurrentSurfaceTexture::Success(frame)) => Some(frame),
+            Some(wgpu::CurrentSurfaceTexture::Suboptimal(frame)) => {
                 // Textures must be destroyed before the surface can be reconfigured.
                 drop(frame);
                 let surface_config = self.surface_config.clone();
                 let resources = self.resources_mut();
                 resources
-                    .surface
+                    .target
                     .configure(&resources.device, &surface_config);
                 return false;
             }
-            wgpu::CurrentSurfaceTexture::Lost | wgpu::CurrentSurfaceTexture::Outdated => {
+            Some(wgpu::CurrentSurfaceTexture::Lost | wgpu::CurrentSurfaceTexture::Outdated) => {
                 let surface_config = self.surface_config.clone();
                 let resources = self.resources_mut();
                 resources
-                    .surface
+                    .target
                     .configure(&resources.device, &surface_config);
                 return false;
             }
-            wgpu::CurrentSurfaceTexture::Timeout | wgpu::CurrentSurfaceTexture::Occluded => {
+            Some(wgpu::CurrentSurfaceTexture::Timeout | wgpu::CurrentSurfaceTexture::Occluded) => {
                 return false;
             }
-            wgpu::CurrentSurfaceTexture::Validation => {
+            Some(wgpu::CurrentSurfaceTexture::Validation) => {
                 *self.last_error.lock().unwrap() =
                     Some("Surface texture validation error".to_string());
                 return false;
@@ -1147,9 +1225,16 @@ impl WgpuRenderer {
         // Now that we know the surface is healthy, ensure intermediate textures exist
         self.ensure_intermediate_textures();
 
-        let frame_view = frame
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
+        let frame_view = match &frame {
+            Some(frame) => frame
+                .texture
+                .create_view(&wgpu::TextureViewDescriptor::default()),
+            None => self
+                .resources()
+                .offscreen_texture()
+                .expect("renderers without a surface draw offscreen")
+                .create_view(&wgpu::TextureViewDescriptor::default()),
+        };
 
         let gamma_params = GammaParams {
             gamma_ratios: self.rendering_params.gamma_ratios,
@@ -1320,7 +1405,9 @@ impl WgpuRenderer {
                         "instance buffer size grew too large: {}",
                         self.instance_buffer_capacity
                     );
-                    frame.present();
+                    if let Some(frame) = frame {
+                        frame.present();
+                    }
                     return true;
                 }
                 self.grow_instance_buffer();
@@ -1330,7 +1417,9 @@ impl WgpuRenderer {
             self.resources()
                 .queue
                 .submit(std::iter::once(encoder.finish()));
-            frame.present();
+            if let Some(frame) = frame {
+                frame.present();
+            }
             return true;
         }
     }
@@ -1741,7 +1830,7 @@ impl WgpuRenderer {
                 .as_mut()
                 .expect("GPU resources not available");
             surface.configure(&res.device, &self.surface_config);
-            res.surface = surface;
+            res.target = RenderTarget::Surface(surface);
 
             // Invalidate intermediate textures — they'll be recreated lazily.
             res.invalidate_intermediate_textures();
@@ -1758,6 +1847,71 @@ impl WgpuRenderer {
         self.resources.take();
     }
 
+    /// Reads back the last frame drawn by an offscreen renderer as tightly packed RGBA rows.
+    pub fn read_pixels(&self) -> anyhow::Result<Vec<u8>> {
+        let resources = self.resources();
+        let texture = resources
+            .offscreen_texture()
+            .ok_or_else(|| anyhow::anyhow!("Only offscreen renderers can be read back"))?;
+        let width = self.surface_config.width;
+        let height = self.surface_config.height;
+        let bytes_per_row = width * 4;
+        let padded_bytes_per_row =
+            bytes_per_row.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+
+        let buffer = resources.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("read_pixels_buffer"),
+            size: padded_bytes_per_row as u64 * height as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder =
+            resources
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("read_pixels_encoder"),
+                });
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            texture.size(),
+        );
+        resources.queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            sender.send(result).ok();
+        });
+        resources
+            .device
+            .poll(wgpu::PollType::Wait {
+                submission_index: None,
+                timeout: None,
+            })
+            .map_err(|e| anyhow::anyhow!("Failed to wait for the frame to be copied: {e:?}"))?;
+        receiver
+            .recv()?
+            .map_err(|e| anyhow::anyhow!("Failed to map the frame: {e}"))?;
+
+        let mut pixels = Vec::with_capacity((bytes_per_row * height) as usize);
+        for row in slice
+            .get_mapped_range()
+            .chunks_exact(padded_bytes_per_row as usize)
+        {
+            pixels.extend_from_slice(&row[..bytes_per_row as usize]);
+        }
+        buffer.unmap();
+        Ok(pixels)
+    }
+
     /// Returns true if the GPU device was lost and recovery is needed.
     pub fn device_lost(&self) -> bool {
         self.device_lost.load(std::sync::atomic::Ordering::SeqCst)
@@ -1836,7 +1990,7 @@ impl WgpuRenderer {
         *self = Self::new_internal(
             Some(gpu_context.clone()),
             context,
-            surface,
+            Some(surface),
             config,
             self.compositor_gpu,
             self.atlas.clone(),
@@ -1847,6 +2001,26 @@ impl WgpuRenderer {
     }
 }
 
+fn create_offscreen_texture(
+    device: &wgpu::Device,
+    config: &wgpu::SurfaceConfiguration,
+) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("offscreen_target"),
+        size: wgpu::Extent3d {
+            width: config.width,
+            height: config.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: config.format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    })
+}
+
 #[cfg(not(target_family = "wasm"))]
 fn create_surface(
     instance: &wgpu::Instance,
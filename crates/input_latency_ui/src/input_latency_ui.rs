@@ -11,7 +11,7 @@ actions!(
     ]
 );
 
-/// Generates a formatted text report of the input-to-frame latency histogram
+/// Generates a formatted text report of the input latency histogram
 /// for the given window. If a previous report was generated (tracked via a
 /// global on the `App`), includes a delta section showing changes since that
 /// report.
@@ -72,7 +72,7 @@ pub fn report_input_latency_telemetry(window: &Window, cx: &mut App) {
     let state = cx.default_global::<TelemetryReporterState>();
     let now = Instant::now();
 
-    let (delta_latency, delta_coalesce, report_window_seconds) =
+    let (delta_latency, delta_coalesce, dropped_frames, report_window_seconds) =
         if let Some((prev_instant, prev_snapshot)) = state.previous.get(&window_id) {
             let mut delta_latency = current.latency_histogram.clone();
             delta_latency
@@ -82,8 +82,11 @@ pub fn report_input_latency_telemetry(window: &Window, cx: &mut App) {
             delta_coalesce
                 .subtract(&prev_snapshot.events_per_frame_histogram)
                 .ok();
+            let dropped_frames = current
+                .dropped_frames
+                .saturating_sub(prev_snapshot.dropped_frames);
             let elapsed = now.duration_since(*prev_instant).as_secs();
-            (delta_latency, delta_coalesce, elapsed)
+            (delta_latency, delta_coalesce, dropped_frames, elapsed)
         } else {
             // First report for this window: the full cumulative histogram is the
             // delta from the empty starting state. We don't know how long the
@@ -92,6 +95,7 @@ pub fn report_input_latency_telemetry(window: &Window, cx: &mut App) {
             (
                 current.latency_histogram.clone(),
                 current.events_per_frame_histogram.clone(),
+                current.dropped_frames,
                 0u64,
             )
         };
@@ -101,6 +105,7 @@ pub fn report_input_latency_telemetry(window: &Window, cx: &mut App) {
         return;
     }
 
+    let measured_to_scanout = current.measured_to_scanout;
     state.previous.insert(window_id, (now, current));
 
     let frames_sub4 = count_frames_in_range(&delta_latency, 0, MS4_NS);
@@ -126,6 +131,8 @@ pub fn report_input_latency_telemetry(window: &Window, cx: &mut App) {
         frames_with_1_event = frames_with_1_event,
         frames_with_2_events = frames_with_2_events,
         frames_with_3_events = frames_with_3_events,
+        dropped_frames = dropped_frames,
+        measured_to_scanout = measured_to_scanout,
         report_window_seconds = report_window_seconds,
     );
 }
@@ -166,6 +173,12 @@ fn format_report(snapshot: &InputLatencySnapshot, previous: &ReporterState) -> S
     let timestamp = now.format("%Y-%m-%d %H:%M:%S %Z");
     report.push_str(&format!("Timestamp: {timestamp}\n"));
     report.push_str(&format!("Samples: {total}\n"));
+    if snapshot.measured_to_scanout {
+        report.push_str("Measured to: scanout\n");
+        report.push_str(&format!("Dropped frames: {}\n", snapshot.dropped_frames));
+    } else {
+        report.push_str("Measured to: frame submission\n");
+    }
     if snapshot.mid_draw_events_dropped > 0 {
         report.push_str(&format!(
             "Mid-draw events excluded: {}\n",
@@ -475,6 +475,10 @@ pub struct ThemeStyleContent {
     #[serde(default)]
     pub accents: Vec<AccentContent>,
 
+    /// Set to `"system"` to derive the focus, selection and accent colors from the accent color
+    /// picked in the desktop's settings.
+    pub accent: Option<AccentSourceContent>,
+
     #[serde(flatten, default)]
     pub colors: ThemeColorsContent,
 
@@ -492,6 +496,15 @@ pub struct ThemeStyleContent {
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, MergeFrom, PartialEq)]
 pub struct AccentContent(pub Option<String>);
 
+/// Where a theme takes its accent color from.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize, JsonSchema, MergeFrom)]
+#[serde(rename_all = "snake_case")]
+pub enum AccentSourceContent {
+    /// The accent color picked in the desktop's settings. Themes keep their own colors on
+    /// platforms that don't expose one.
+    System,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, MergeFrom, PartialEq)]
 pub struct PlayerColorContent {
     pub cursor: Option<String>,
@@ -74,6 +74,11 @@ pub struct KeymapSection {
     /// on macOS. See the documentation for more details.
     #[serde(default)]
     use_key_equivalents: bool,
+    /// Registers this section's bindings with the desktop as global shortcuts, which trigger
+    /// their actions even while none of the application's windows has focus. Each binding must be
+    /// a single keystroke, and the `context` of the section is ignored.
+    #[serde(default)]
+    global: bool,
     /// This keymap section's unbindings, as a JSON object mapping keystrokes to actions. These are
     /// parsed before `bindings`, so bindings later in the same section can still take precedence.
     #[serde(default)]
@@ -274,6 +279,7 @@ impl KeymapFile {
         for KeymapSection {
             context,
             use_key_equivalents,
+            global,
             unbind,
             bindings,
             unrecognized_fields,
@@ -349,7 +355,8 @@ impl KeymapFile {
                         cx,
                     );
                     match result {
-                        Ok(key_binding) => {
+                        Ok(mut key_binding) => {
+                            key_binding.set_global(*global);
                             key_bindings.push(key_binding);
                         }
                         Err(err) => {
@@ -1605,6 +1612,35 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    fn keymap_global_sections_load_global_bindings(cx: &mut App) {
+        let key_bindings = match KeymapFile::load(
+            indoc::indoc! {r#"
+                [
+                    {
+                        "bindings": {
+                            "ctrl-a": "test_keymap_file::StringAction"
+                        }
+                    },
+                    {
+                        "global": true,
+                        "bindings": {
+                            "ctrl-alt-space": "test_keymap_file::StringAction"
+                        }
+                    }
+                ]
+            "#},
+            cx,
+        ) {
+            crate::keymap_file::KeymapFileLoadResult::Success { key_bindings } => key_bindings,
+            other => panic!("expected Success, got {other:?}"),
+        };
+
+        assert_eq!(key_bindings.len(), 2);
+        assert!(!key_bindings[0].is_global());
+        assert!(key_bindings[1].is_global());
+    }
+
     #[gpui::test]
     fn keymap_unbind_loads_valid_target_action_with_input(cx: &mut App) {
         let key_bindings = match KeymapFile::load(
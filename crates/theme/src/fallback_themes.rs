@@ -108,6 +108,7 @@ pub(crate) fn zed_default_dark() -> Theme {
         id: "one_dark".to_string(),
         name: DEFAULT_DARK_THEME.into(),
         appearance: Appearance::Dark,
+        follows_system_accent: false,
         styles: ThemeStyles {
             window_background_appearance: WindowBackgroundAppearance::Opaque,
             system: SystemColors::default(),
@@ -237,6 +237,9 @@ pub struct Theme {
     pub appearance: Appearance,
     /// The colors and other styles for the theme.
     pub styles: ThemeStyles,
+    /// Whether the focus, selection and accent colors should follow the desktop's accent color,
+    /// see [`Theme::with_system_accent`].
+    pub follows_system_accent: bool,
 }
 
 impl Theme {
@@ -288,6 +291,41 @@ impl Theme {
         self.styles.window_background_appearance
     }
 
+    /// Returns a copy of the theme with its focus, selection and accent colors derived from
+    /// the given accent color.
+    pub fn with_system_accent(&self, accent: Hsla) -> Theme {
+        let mut theme = self.clone();
+        let styles = &mut theme.styles;
+
+        // Keep accented text and icons legible against the theme's backgrounds.
+        let mut legible_accent = accent;
+        legible_accent.l = match theme.appearance {
+            Appearance::Light => accent.l.min(0.45),
+            Appearance::Dark => accent.l.max(0.65),
+        };
+        let selection = accent.alpha(0.25);
+
+        let mut accents = styles.accents.0.to_vec();
+        match accents.first_mut() {
+            Some(first) => *first = accent,
+            None => accents.push(accent),
+        }
+        styles.accents = AccentColors(accents.into());
+
+        styles.colors.border_focused = accent;
+        styles.colors.element_selection_background = selection;
+        styles.colors.text_accent = legible_accent;
+        styles.colors.icon_accent = legible_accent;
+        // Accent tinted buttons are drawn with the info colors.
+        styles.status.info_background = accent;
+        styles.status.info_border = accent;
+        if let Some(local) = styles.player.0.first_mut() {
+            local.selection = selection;
+        }
+
+        theme
+    }
+
     /// Darkens the color by reducing its lightness.
     /// The resulting lightness is clamped to ensure it doesn't go below 0.0.
     ///
@@ -345,3 +383,29 @@ impl GlobalTheme {
         &cx.global::<Self>().icon_theme
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use gpui::rgb;
+
+    use super::*;
+
+    #[test]
+    fn derive_colors_from_system_accent() {
+        let theme = crate::fallback_themes::zed_default_dark();
+        let accent: Hsla = rgb(0x3584e4).into();
+
+        let accented = theme.with_system_accent(accent);
+
+        assert_eq!(accented.colors().border_focused, accent);
+        assert_eq!(
+            accented.colors().element_selection_background,
+            accent.alpha(0.25)
+        );
+        assert_eq!(accented.accents().0[0], accent);
+        assert_eq!(accented.accents().0.len(), theme.accents().0.len());
+        assert_eq!(accented.status().info_border, accent);
+        assert!(accented.colors().text_accent.l >= 0.65);
+        assert_eq!(accented.colors().text, theme.colors().text);
+    }
+}
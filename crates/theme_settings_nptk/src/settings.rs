@@ -4,14 +4,14 @@ use crate::schema::{status_colors_refinement, syntax_overrides, theme_colors_ref
 use crate::{merge_accent_colors, merge_player_colors};
 use collections::HashMap;
 use gpui::{
-    App, Context, Font, FontFallbacks, FontStyle, Global, Pixels, SharedString, Subscription,
-    Window, px,
+    AccessibilityPreferences, App, Context, Font, FontFallbacks, FontStyle, Global, Pixels,
+    SharedString, Subscription, Window, px,
 };
 use refineable::Refineable;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use settings::{AccentSourceContent, IntoGpui, RegisterSetting, Settings, SettingsContent};
 pub use settings::{FontFamilyName, IconThemeName, ThemeAppearanceMode, ThemeName};
-use settings::{IntoGpui, RegisterSetting, Settings, SettingsContent};
 use std::sync::Arc;
 use theme::{Appearance, DEFAULT_ICON_THEME_NAME, SyntaxTheme, Theme, UiDensity};
 
@@ -388,7 +388,14 @@ impl ThemeSettings {
         let font_size = cx
             .try_global::<UiFontSize>()
             .map(|size| size.0)
-            .unwrap_or(self.ui_font_size);
+            .unwrap_or_else(|| {
+                // The desktop's text scale applies to the configured size, not to sizes the
+                // user adjusted in the app.
+                let text_scale = cx
+                    .try_global::<AccessibilityPreferences>()
+                    .map_or(1.0, |preferences| preferences.text_scale);
+                self.ui_font_size * text_scale
+            });
         clamp_font_size(font_size)
     }
 
@@ -496,6 +503,9 @@ impl ThemeSettings {
         base_theme.styles.status.refine(&status_color_refinement);
         merge_player_colors(&mut base_theme.styles.player, &theme_overrides.players);
         merge_accent_colors(&mut base_theme.styles.accents, &theme_overrides.accents);
+        if let Some(accent) = theme_overrides.accent {
+            base_theme.follows_system_accent = accent == AccentSourceContent::System;
+        }
         base_theme.styles.syntax = SyntaxTheme::merge(
             base_theme.styles.syntax.clone(),
             syntax_overrides(theme_overrides),
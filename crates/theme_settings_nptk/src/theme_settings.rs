@@ -10,9 +10,9 @@ mod settings;
 
 use std::sync::Arc;
 
-use ::settings::{IntoGpui, Settings, SettingsStore};
+use ::settings::{AccentSourceContent, IntoGpui, Settings, SettingsStore};
 use anyhow::{Context as _, Result};
-use gpui::{App, Font, HighlightStyle, Pixels, Refineable, px};
+use gpui::{AccessibilityPreferences, App, Font, HighlightStyle, Pixels, Refineable, px};
 use gpui_util::ResultExt;
 use theme::{
     AccentColors, Appearance, AppearanceContent, DEFAULT_DARK_THEME, GlobalTheme, LoadThemes,
@@ -139,9 +139,36 @@ pub fn init(themes_to_load: LoadThemes, cx: &mut App) {
         }
     })
     .detach();
+
+    let mut prev_high_contrast = prefers_high_contrast(cx);
+    cx.observe_global::<AccessibilityPreferences>(move |cx| {
+        let high_contrast = prefers_high_contrast(cx);
+        if high_contrast != prev_high_contrast {
+            prev_high_contrast = high_contrast;
+            reload_theme(cx);
+        }
+    })
+    .detach();
+
+    cx.on_system_accent_color_change(|cx| {
+        if GlobalTheme::theme(cx).follows_system_accent {
+            reload_theme(cx);
+        }
+    })
+    .detach();
+}
+
+/// Suffixes of the theme variants used in place of the configured theme when the user asked for
+/// more contrast, in order of preference.
+const HIGH_CONTRAST_VARIANT_SUFFIXES: [&str; 2] = [" High Contrast", " Hard"];
+
+fn prefers_high_contrast(cx: &App) -> bool {
+    cx.try_global::<AccessibilityPreferences>()
+        .is_some_and(|preferences| preferences.high_contrast)
 }
 
 fn configured_theme(cx: &mut App) -> Arc<Theme> {
+    let high_contrast = prefers_high_contrast(cx);
     let themes = ThemeRegistry::default_global(cx);
     let theme_settings = ThemeSettings::get_global(cx);
     let system_appearance = SystemAppearance::global(cx);
@@ -159,7 +186,19 @@ fn configured_theme(cx: &mut App) -> Arc<Theme> {
                 .unwrap_or_else(|_| themes.get(DEFAULT_DARK_THEME).unwrap())
         }
     };
-    theme_settings.apply_theme_overrides(theme)
+    let theme = if high_contrast {
+        HIGH_CONTRAST_VARIANT_SUFFIXES
+            .iter()
+            .find_map(|suffix| themes.get(&format!("{}{suffix}", theme.name)).ok())
+            .unwrap_or(theme)
+    } else {
+        theme
+    };
+    let theme = theme_settings.apply_theme_overrides(theme);
+    match cx.system_accent_color() {
+        Some(accent) if theme.follows_system_accent => Arc::new(theme.with_system_accent(accent)),
+        _ => theme,
+    }
 }
 
 fn configured_xdg_icon_theme_name(cx: &App) -> String {
@@ -330,6 +369,7 @@ pub fn refine_theme(theme: &ThemeContent) -> Theme {
         id: uuid::Uuid::new_v4().to_string(),
         name: theme.name.clone().into(),
         appearance,
+        follows_system_accent: theme.style.accent == Some(AccentSourceContent::System),
         styles: ThemeStyles {
             system: SystemColors::default(),
             window_background_appearance,
@@ -769,7 +769,7 @@ impl RenderOnce for ButtonLike {
                 if self.cursor_style == CursorStyle::PointingHand {
                     this.cursor_not_allowed()
                 } else {
-                    this.cursor(self.cursor_style)
+                    this.cursor(self.cursor_style.clone())
                 }
             })
             .when(!self.disabled, |this| {
@@ -777,7 +777,7 @@ impl RenderOnce for ButtonLike {
                 let focus_color =
                     |refinement: StyleRefinement| refinement.bg(hovered_style.background);
 
-                this.cursor(self.cursor_style)
+                this.cursor(self.cursor_style.clone())
                     .hover(focus_color)
                     .map(|this| {
                         if is_outlined {
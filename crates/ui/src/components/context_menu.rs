@@ -1,3 +1,4 @@
+use super::popover_menu::{MenuPopupWindow, close_menu_popup_window, sync_menu_popup_window};
 use crate::{
     ButtonCommon, ButtonStyle, IconButtonShape, KeyBinding, List, ListItem, ListSeparator,
     ListSubHeader, Tooltip, prelude::*, utils::WithRemSize,
@@ -27,6 +28,7 @@ struct OpenSubmenu {
     trigger_bounds: Option<Bounds<Pixels>>,
     offset: Option<Pixels>,
     flip_left: bool,
+    popup_window: Rc<RefCell<Option<MenuPopupWindow>>>,
     _dismiss_subscription: Subscription,
 }
 
@@ -233,6 +235,7 @@ pub struct ContextMenu {
     submenu_trigger_bounds: Rc<Cell<Option<Bounds<Pixels>>>>,
     submenu_trigger_mouse_down: bool,
     ignore_blur_until: Option<Instant>,
+    popup_window: bool,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq)]
@@ -284,6 +287,10 @@ impl ContextMenu {
 
                 if this.main_menu.is_none() {
                     if let SubmenuState::Open(open_submenu) = &this.submenu_state {
+                        // Focus has moved to the submenu's own window.
+                        if open_submenu.popup_window.borrow().is_some() {
+                            return;
+                        }
                         let submenu_focus = open_submenu.entity.read(cx).focus_handle.clone();
                         if submenu_focus.contains_focused(window, cx) {
                             return;
@@ -320,6 +327,7 @@ impl ContextMenu {
                 submenu_trigger_bounds: Rc::new(Cell::new(None)),
                 submenu_trigger_mouse_down: false,
                 ignore_blur_until: None,
+                popup_window: false,
             },
             window,
             cx,
@@ -361,6 +369,10 @@ impl ContextMenu {
 
                     if this.main_menu.is_none() {
                         if let SubmenuState::Open(open_submenu) = &this.submenu_state {
+                            // Focus has moved to the submenu's own window.
+                            if open_submenu.popup_window.borrow().is_some() {
+                                return;
+                            }
                             let submenu_focus = open_submenu.entity.read(cx).focus_handle.clone();
                             if submenu_focus.contains_focused(window, cx) {
                                 return;
@@ -397,6 +409,7 @@ impl ContextMenu {
                     submenu_trigger_bounds: Rc::new(Cell::new(None)),
                     submenu_trigger_mouse_down: false,
                     ignore_blur_until: None,
+                    popup_window: false,
                 },
                 window,
                 cx,
@@ -443,6 +456,10 @@ impl ContextMenu {
 
                         if this.main_menu.is_none() {
                             if let SubmenuState::Open(open_submenu) = &this.submenu_state {
+                                // Focus has moved to the submenu's own window.
+                                if open_submenu.popup_window.borrow().is_some() {
+                                    return;
+                                }
                                 let submenu_focus =
                                     open_submenu.entity.read(cx).focus_handle.clone();
                                 if submenu_focus.contains_focused(window, cx) {
@@ -466,6 +483,7 @@ impl ContextMenu {
                 submenu_trigger_bounds: Rc::new(Cell::new(None)),
                 submenu_trigger_mouse_down: false,
                 ignore_blur_until: None,
+                popup_window: false,
             },
             window,
             cx,
@@ -866,6 +884,13 @@ impl ContextMenu {
         self
     }
 
+    /// Opens submenus in their own popup windows. Use this when the menu is itself shown in a
+    /// popup window, where in-window submenus would be clipped.
+    pub fn popup_window(mut self, popup_window: bool) -> Self {
+        self.popup_window = popup_window;
+        self
+    }
+
     pub fn trigger_end_slot_handler(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         let Some(entry) = self.selected_index.and_then(|ix| self.items.get(ix)) else {
             return;
@@ -1206,10 +1231,11 @@ impl ContextMenu {
     fn create_submenu(
         builder: Rc<dyn Fn(ContextMenu, &mut Window, &mut Context<ContextMenu>) -> ContextMenu>,
         parent_entity: Entity<ContextMenu>,
+        popup_window: bool,
         window: &mut Window,
         cx: &mut Context<Self>,
     ) -> (Entity<ContextMenu>, Subscription) {
-        let submenu = Self::build_submenu(builder, parent_entity, window, cx);
+        let submenu = Self::build_submenu(builder, parent_entity, popup_window, window, cx);
 
         let dismiss_subscription = cx.subscribe(&submenu, |this, submenu, _: &DismissEvent, cx| {
             let should_dismiss_parent = submenu.read(cx).clicked;
@@ -1227,6 +1253,7 @@ impl ContextMenu {
     fn build_submenu(
         builder: Rc<dyn Fn(ContextMenu, &mut Window, &mut Context<ContextMenu>) -> ContextMenu>,
         parent_entity: Entity<ContextMenu>,
+        popup_window: bool,
         window: &mut Window,
         cx: &mut App,
     ) -> Entity<ContextMenu> {
@@ -1262,6 +1289,7 @@ impl ContextMenu {
                 submenu_trigger_bounds: Rc::new(Cell::new(None)),
                 submenu_trigger_mouse_down: false,
                 ignore_blur_until: None,
+                popup_window,
             };
 
             menu = (builder)(menu, window, cx);
@@ -1269,7 +1297,14 @@ impl ContextMenu {
         })
     }
 
+    fn close_submenu_popup_window(&self, cx: &mut App) {
+        if let SubmenuState::Open(open_submenu) = &self.submenu_state {
+            close_menu_popup_window(&open_submenu.popup_window, cx);
+        }
+    }
+
     fn close_submenu(&mut self, clear_selection: bool, cx: &mut Context<Self>) {
+        self.close_submenu_popup_window(cx);
         self.submenu_state = SubmenuState::Closed;
         self.hover_target = HoverTarget::MainMenu;
         self.submenu_safety_threshold_x = None;
@@ -1300,7 +1335,7 @@ impl ContextMenu {
         }
 
         let (submenu, dismiss_subscription) =
-            Self::create_submenu(builder, cx.entity(), window, cx);
+            Self::create_submenu(builder, cx.entity(), self.popup_window, window, cx);
 
         let flip_left = self
             .main_menu_observed_bounds
@@ -1323,12 +1358,14 @@ impl ContextMenu {
 
         let trigger_bounds = self.submenu_trigger_bounds.get();
 
+        self.close_submenu_popup_window(cx);
         self.submenu_state = SubmenuState::Open(OpenSubmenu {
             item_index,
             entity: submenu,
             trigger_bounds,
             offset: None,
             flip_left,
+            popup_window: Rc::default(),
             _dismiss_subscription: dismiss_subscription,
         });
 
@@ -1694,7 +1731,32 @@ impl ContextMenu {
         offset: Pixels,
         flip_left: bool,
         cx: &mut Context<Self>,
-    ) -> impl IntoElement {
+    ) -> AnyElement {
+        if self.popup_window
+            && let SubmenuState::Open(open_submenu) = &self.submenu_state
+            && let Some(trigger_bounds) = open_submenu
+                .trigger_bounds
+                .or_else(|| self.submenu_trigger_bounds.get())
+        {
+            let popup_window = open_submenu.popup_window.clone();
+            return canvas(
+                move |_bounds, window, cx| {
+                    sync_menu_popup_window(
+                        &popup_window,
+                        Some(submenu),
+                        trigger_bounds,
+                        Anchor::TopRight,
+                        Anchor::TopLeft,
+                        Point::default(),
+                        window,
+                        cx,
+                    );
+                },
+                |_bounds, _state, _window, _cx| {},
+            )
+            .into_any_element();
+        }
+
         let bounds_cell = self.main_menu_observed_bounds.clone();
         let canvas = canvas(
             {
@@ -2132,7 +2194,10 @@ impl Render for ContextMenu {
                         open_submenu.offset = Some(offset);
                     }
 
-                    focus_submenu = Some(open_submenu.entity.read(cx).focus_handle.clone());
+                    // Popup windows focus their menu when they open.
+                    if !self.popup_window {
+                        focus_submenu = Some(open_submenu.entity.read(cx).focus_handle.clone());
+                    }
                     Some((
                         open_submenu.item_index,
                         open_submenu.entity.clone(),
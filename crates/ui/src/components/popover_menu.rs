@@ -1,11 +1,14 @@
 use std::{cell::RefCell, rc::Rc};
 
 use gpui::{
-    Anchor, AnyElement, AnyView, App, Bounds, DismissEvent, DispatchPhase, Element, ElementId,
-    Entity, Focusable as _, GlobalElementId, HitboxBehavior, HitboxId, InteractiveElement,
-    IntoElement, LayoutId, Length, ManagedView, MouseDownEvent, ParentElement, Pixels, Point,
-    Style, Window, anchored, deferred, div, point, prelude::FluentBuilder, px, size,
+    Anchor, AnyElement, AnyView, AnyWindowHandle, App, AsyncApp, AvailableSpace, Bounds,
+    DismissEvent, DispatchPhase, Element, ElementId, Entity, Focusable as _, GlobalElementId,
+    HitboxBehavior, HitboxId, InteractiveElement, IntoElement, LayoutId, Length, ManagedView,
+    MouseDownEvent, ParentElement, Pixels, Point, Size, Style, Subscription, Window,
+    WindowBackgroundAppearance, WindowBounds, WindowKind, WindowOptions, anchored, deferred, div,
+    point, popup::PopupOptions, prelude::FluentBuilder, px, size,
 };
+use gpui_util::ResultExt as _;
 
 use crate::prelude::*;
 
@@ -143,6 +146,7 @@ pub struct PopoverMenu<M: ManagedView> {
     trigger_handle: Option<PopoverMenuHandle<M>>,
     on_open: Option<Rc<dyn Fn(&mut Window, &mut App)>>,
     full_width: bool,
+    popup_window: bool,
 }
 
 impl<M: ManagedView> PopoverMenu<M> {
@@ -158,6 +162,7 @@ impl<M: ManagedView> PopoverMenu<M> {
             trigger_handle: None,
             on_open: None,
             full_width: false,
+            popup_window: false,
         }
     }
 
@@ -242,6 +247,13 @@ impl<M: ManagedView> PopoverMenu<M> {
         self
     }
 
+    /// Shows the menu in its own popup window, so it isn't clipped by the edges of the window
+    /// containing the trigger.
+    pub fn popup_window(mut self, popup_window: bool) -> Self {
+        self.popup_window = popup_window;
+        self
+    }
+
     fn resolved_attach(&self) -> Anchor {
         self.attach
             .unwrap_or(self.attach.unwrap_or(match self.anchor {
@@ -316,9 +328,139 @@ fn show_menu<M: ManagedView>(
     }
 }
 
+/// A popup window showing a menu outside of the window that opened it.
+pub(crate) struct MenuPopupWindow {
+    handle: Option<AnyWindowHandle>,
+    _window_closed: Option<Subscription>,
+    cx: AsyncApp,
+}
+
+impl Drop for MenuPopupWindow {
+    /// Closes the popup window when the menu element owning it goes away without dismissing the
+    /// menu first.
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            let mut cx = self.cx.clone();
+            self.cx
+                .foreground_executor()
+                .spawn(async move {
+                    handle
+                        .update(&mut cx, |_, window, _| window.remove_window())
+                        .ok();
+                })
+                .detach();
+        }
+    }
+}
+
+/// Opens `menu` in a popup window positioned against `anchor_rect`, or closes the popup window
+/// once the menu has been dismissed. Must be called during prepaint, as the menu is measured in
+/// the parent window to size the popup.
+pub(crate) fn sync_menu_popup_window<M: ManagedView>(
+    popup_window: &Rc<RefCell<Option<MenuPopupWindow>>>,
+    menu: Option<Entity<M>>,
+    anchor_rect: Bounds<Pixels>,
+    attach: Anchor,
+    anchor: Anchor,
+    offset: Point<Pixels>,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let Some(menu) = menu else {
+        close_menu_popup_window(popup_window, cx);
+        return;
+    };
+
+    if popup_window.borrow().is_some() {
+        return;
+    }
+
+    let mut menu_element = menu.clone().into_any_element();
+    let menu_size = menu_element.layout_as_root(AvailableSpace::min_size(), window, cx);
+    let options = PopupOptions {
+        attach,
+        anchor,
+        offset,
+        ..PopupOptions::new(window.window_handle(), anchor_rect)
+    };
+
+    *popup_window.borrow_mut() = Some(MenuPopupWindow {
+        handle: None,
+        _window_closed: None,
+        cx: cx.to_async(),
+    });
+
+    let popup_window = popup_window.clone();
+    cx.defer(move |cx| {
+        let window_options = WindowOptions {
+            window_bounds: Some(WindowBounds::Windowed(Bounds::new(
+                Point::default(),
+                menu_size,
+            ))),
+            titlebar: None,
+            kind: WindowKind::AnchoredPopUp(options),
+            is_movable: false,
+            is_resizable: false,
+            is_minimizable: false,
+            window_background: WindowBackgroundAppearance::Transparent,
+            ..Default::default()
+        };
+        let handle = cx
+            .open_window(window_options, {
+                let menu = menu.clone();
+                move |window, cx| {
+                    window.focus(&menu.focus_handle(cx), cx);
+                    menu
+                }
+            })
+            .log_err()
+            .map(AnyWindowHandle::from);
+
+        let Some(handle) = handle else {
+            menu.update(cx, |_, cx| cx.emit(DismissEvent));
+            return;
+        };
+
+        // The platform may close the popup on its own, e.g. when the user clicks outside of it.
+        let window_closed = cx.on_window_closed(move |cx, window_id| {
+            if window_id == handle.window_id() {
+                menu.update(cx, |_, cx| cx.emit(DismissEvent));
+            }
+        });
+
+        if let Some(popup_window) = popup_window.borrow_mut().as_mut() {
+            popup_window.handle = Some(handle);
+            popup_window._window_closed = Some(window_closed);
+        } else {
+            // The menu was dismissed before its window finished opening.
+            handle
+                .update(cx, |_, window, _| window.remove_window())
+                .ok();
+        }
+    });
+}
+
+pub(crate) fn close_menu_popup_window(
+    popup_window: &Rc<RefCell<Option<MenuPopupWindow>>>,
+    cx: &mut App,
+) {
+    if let Some(handle) = popup_window
+        .borrow_mut()
+        .take()
+        .and_then(|mut popup_window| popup_window.handle.take())
+    {
+        cx.defer(move |cx| {
+            handle
+                .update(cx, |_, window, _| window.remove_window())
+                .ok();
+        });
+    }
+}
+
 pub struct PopoverMenuElementState<M> {
     menu: Rc<RefCell<Option<Entity<M>>>>,
     child_bounds: Option<Bounds<Pixels>>,
+    popup_window: Rc<RefCell<Option<MenuPopupWindow>>>,
 }
 
 impl<M> Clone for PopoverMenuElementState<M> {
@@ -326,6 +468,7 @@ impl<M> Clone for PopoverMenuElementState<M> {
         Self {
             menu: Rc::clone(&self.menu),
             child_bounds: self.child_bounds,
+            popup_window: Rc::clone(&self.popup_window),
         }
     }
 }
@@ -335,6 +478,7 @@ impl<M> Default for PopoverMenuElementState<M> {
         Self {
             menu: Rc::default(),
             child_bounds: None,
+            popup_window: Rc::default(),
         }
     }
 }
@@ -344,6 +488,7 @@ pub struct PopoverMenuFrameState<M: ManagedView> {
     child_element: Option<AnyElement>,
     menu_element: Option<AnyElement>,
     menu_handle: Rc<RefCell<Option<Entity<M>>>>,
+    popup_window: Rc<RefCell<Option<MenuPopupWindow>>>,
 }
 
 impl<M: ManagedView> Element for PopoverMenu<M> {
@@ -371,23 +516,29 @@ impl<M: ManagedView> Element for PopoverMenu<M> {
                 let element_state = element_state.unwrap_or_default();
                 let mut menu_layout_id = None;
 
-                let menu_element = element_state.menu.borrow_mut().as_mut().map(|menu| {
-                    let offset = self.resolved_offset(window);
-                    let mut anchored = anchored()
-                        .snap_to_window_with_margin(px(8.))
-                        .anchor(self.anchor)
-                        .offset(offset);
-                    if let Some(child_bounds) = element_state.child_bounds {
-                        anchored =
-                            anchored.position(child_bounds.corner(self.resolved_attach()) + offset);
-                    }
-                    let mut element = deferred(anchored.child(div().occlude().child(menu.clone())))
-                        .with_priority(1)
-                        .into_any();
+                let menu_element = element_state
+                    .menu
+                    .borrow_mut()
+                    .as_mut()
+                    .filter(|_| !self.popup_window)
+                    .map(|menu| {
+                        let offset = self.resolved_offset(window);
+                        let mut anchored = anchored()
+                            .snap_to_window_with_margin(px(8.))
+                            .anchor(self.anchor)
+                            .offset(offset);
+                        if let Some(child_bounds) = element_state.child_bounds {
+                            anchored = anchored
+                                .position(child_bounds.corner(self.resolved_attach()) + offset);
+                        }
+                        let mut element =
+                            deferred(anchored.child(div().occlude().child(menu.clone())))
+                                .with_priority(1)
+                                .into_any();
 
-                    menu_layout_id = Some(element.request_layout(window, cx));
-                    element
-                });
+                        menu_layout_id = Some(element.request_layout(window, cx));
+                        element
+                    });
 
                 let mut child_element = self.child_builder.take().map(|child_builder| {
                     (child_builder)(element_state.menu.clone(), self.menu_builder.clone())
@@ -426,6 +577,7 @@ impl<M: ManagedView> Element for PopoverMenu<M> {
                             child_layout_id,
                             menu_element,
                             menu_handle: element_state.menu.clone(),
+                            popup_window: element_state.popup_window.clone(),
                         },
                     ),
                     element_state,
@@ -451,8 +603,28 @@ impl<M: ManagedView> Element for PopoverMenu<M> {
             menu.prepaint(window, cx);
         }
 
-        request_layout.child_layout_id.map(|layout_id| {
-            let bounds = window.layout_bounds(layout_id);
+        let child_bounds = request_layout
+            .child_layout_id
+            .map(|layout_id| window.layout_bounds(layout_id));
+
+        if self.popup_window {
+            let anchor_rect = child_bounds
+                .unwrap_or_else(|| Bounds::new(window.mouse_position(), Size::default()));
+            let offset = self.resolved_offset(window);
+            let menu = request_layout.menu_handle.borrow().clone();
+            sync_menu_popup_window(
+                &request_layout.popup_window,
+                menu,
+                anchor_rect,
+                self.resolved_attach(),
+                self.anchor,
+                offset,
+                window,
+                cx,
+            );
+        }
+
+        child_bounds.map(|bounds| {
             window.with_element_state(global_id.unwrap(), |element_state, _cx| {
                 let mut element_state: PopoverMenuElementState<M> = element_state.unwrap();
                 element_state.child_bounds = Some(bounds);
@@ -477,6 +649,25 @@ impl<M: ManagedView> Element for PopoverMenu<M> {
             child.paint(window, cx);
         }
 
+        if self.popup_window && request_layout.menu_handle.borrow().is_some() {
+            let menu_handle = request_layout.menu_handle.clone();
+            let child_hitbox = *child_hitbox;
+            // Popup windows don't always hold an input grab, so clicks back in this window
+            // dismiss the menu.
+            window.on_mouse_event(move |_: &MouseDownEvent, phase, window, cx| {
+                if phase == DispatchPhase::Bubble {
+                    if let Some(menu) = menu_handle.borrow().as_ref() {
+                        menu.update(cx, |_, cx| {
+                            cx.emit(DismissEvent);
+                        });
+                    }
+                    if child_hitbox.is_some_and(|child_hitbox| child_hitbox.is_hovered(window)) {
+                        cx.stop_propagation();
+                    }
+                }
+            })
+        }
+
         if let Some(mut menu) = request_layout.menu_element.take() {
             menu.paint(window, cx);
 
@@ -3,16 +3,19 @@ use std::{cell::RefCell, rc::Rc};
 use gpui::{
     Anchor, AnyElement, App, Bounds, DismissEvent, DispatchPhase, Element, ElementId, Entity,
     Focusable as _, GlobalElementId, Hitbox, HitboxBehavior, InteractiveElement, IntoElement,
-    LayoutId, ManagedView, MouseButton, MouseDownEvent, ParentElement, Pixels, Point, Window,
+    LayoutId, ManagedView, MouseButton, MouseDownEvent, ParentElement, Pixels, Point, Size, Window,
     anchored, deferred, div, px,
 };
 
+use super::popover_menu::{MenuPopupWindow, sync_menu_popup_window};
+
 pub struct RightClickMenu<M: ManagedView> {
     id: ElementId,
     child_builder: Option<Box<dyn FnOnce(bool, &mut Window, &mut App) -> AnyElement + 'static>>,
     menu_builder: Option<Rc<dyn Fn(&mut Window, &mut App) -> Entity<M> + 'static>>,
     anchor: Option<Anchor>,
     attach: Option<Anchor>,
+    popup_window: bool,
 }
 
 impl<M: ManagedView> RightClickMenu<M> {
@@ -45,6 +48,13 @@ impl<M: ManagedView> RightClickMenu<M> {
         self
     }
 
+    /// Shows the menu in its own popup window, so it isn't clipped by the edges of the window
+    /// that was right-clicked.
+    pub fn popup_window(mut self, popup_window: bool) -> Self {
+        self.popup_window = popup_window;
+        self
+    }
+
     fn with_element_state<R>(
         &mut self,
         global_id: &GlobalElementId,
@@ -71,12 +81,14 @@ pub fn right_click_menu<M: ManagedView>(id: impl Into<ElementId>) -> RightClickM
         menu_builder: None,
         anchor: None,
         attach: None,
+        popup_window: false,
     }
 }
 
 pub struct MenuHandleElementState<M> {
     menu: Rc<RefCell<Option<Entity<M>>>>,
     position: Rc<RefCell<Point<Pixels>>>,
+    popup_window: Rc<RefCell<Option<MenuPopupWindow>>>,
 }
 
 impl<M> Clone for MenuHandleElementState<M> {
@@ -84,6 +96,7 @@ impl<M> Clone for MenuHandleElementState<M> {
         Self {
             menu: Rc::clone(&self.menu),
             position: Rc::clone(&self.position),
+            popup_window: Rc::clone(&self.popup_window),
         }
     }
 }
@@ -93,6 +106,7 @@ impl<M> Default for MenuHandleElementState<M> {
         Self {
             menu: Rc::default(),
             position: Rc::default(),
+            popup_window: Rc::default(),
         }
     }
 }
@@ -134,20 +148,26 @@ impl<M: ManagedView> Element for RightClickMenu<M> {
             |this, element_state, window, cx| {
                 let mut menu_layout_id = None;
 
-                let menu_element = element_state.menu.borrow_mut().as_mut().map(|menu| {
-                    let mut anchored = anchored().snap_to_window_with_margin(px(8.));
-                    if let Some(anchor) = this.anchor {
-                        anchored = anchored.anchor(anchor);
-                    }
-                    anchored = anchored.position(*element_state.position.borrow());
-
-                    let mut element = deferred(anchored.child(div().occlude().child(menu.clone())))
-                        .with_priority(1)
-                        .into_any();
-
-                    menu_layout_id = Some(element.request_layout(window, cx));
-                    element
-                });
+                let menu_element = element_state
+                    .menu
+                    .borrow_mut()
+                    .as_mut()
+                    .filter(|_| !this.popup_window)
+                    .map(|menu| {
+                        let mut anchored = anchored().snap_to_window_with_margin(px(8.));
+                        if let Some(anchor) = this.anchor {
+                            anchored = anchored.anchor(anchor);
+                        }
+                        anchored = anchored.position(*element_state.position.borrow());
+
+                        let mut element =
+                            deferred(anchored.child(div().occlude().child(menu.clone())))
+                                .with_priority(1)
+                                .into_any();
+
+                        menu_layout_id = Some(element.request_layout(window, cx));
+                        element
+                    });
 
                 let mut child_element = this.child_builder.take().map(|child_builder| {
                     (child_builder)(element_state.menu.borrow().is_some(), window, cx)
@@ -177,7 +197,7 @@ impl<M: ManagedView> Element for RightClickMenu<M> {
 
     fn prepaint(
         &mut self,
-        _id: Option<&GlobalElementId>,
+        id: Option<&GlobalElementId>,
         _inspector_id: Option<&gpui::InspectorElementId>,
         bounds: Bounds<Pixels>,
         request_layout: &mut Self::RequestLayoutState,
@@ -194,6 +214,28 @@ impl<M: ManagedView> Element for RightClickMenu<M> {
             menu.prepaint(window, cx);
         }
 
+        if self.popup_window {
+            self.with_element_state(
+                id.unwrap(),
+                window,
+                cx,
+                |this, element_state, window, cx| {
+                    let position = *element_state.position.borrow();
+                    let menu = element_state.menu.borrow().clone();
+                    sync_menu_popup_window(
+                        &element_state.popup_window,
+                        menu,
+                        Bounds::new(position, Size::default()),
+                        Anchor::TopLeft,
+                        this.anchor.unwrap_or(Anchor::TopLeft),
+                        Point::default(),
+                        window,
+                        cx,
+                    );
+                },
+            );
+        }
+
         PrepaintState {
             hitbox,
             child_bounds: request_layout
@@ -225,6 +267,21 @@ impl<M: ManagedView> Element for RightClickMenu<M> {
                     menu.paint(window, cx);
                 }
 
+                if this.popup_window && element_state.menu.borrow().is_some() {
+                    let menu = element_state.menu.clone();
+                    // Popup windows don't always hold an input grab, so clicks back in this
+                    // window dismiss the menu.
+                    window.on_mouse_event(move |_: &MouseDownEvent, phase, _window, cx| {
+                        if phase == DispatchPhase::Bubble
+                            && let Some(menu) = menu.borrow().as_ref()
+                        {
+                            menu.update(cx, |_, cx| {
+                                cx.emit(DismissEvent);
+                            });
+                        }
+                    });
+                }
+
                 let Some(builder) = this.menu_builder.take() else {
                     return;
                 };